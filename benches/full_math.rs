@@ -0,0 +1,74 @@
+use alloy_primitives::{keccak256, U256};
+use alloy_sol_types::SolValue;
+use criterion::{criterion_group, criterion_main, Criterion};
+use uniswap_v3_math::full_math;
+use uniswap_v3_sdk::prelude::*;
+
+fn pseudo_random(seed: u64) -> U256 {
+    keccak256(seed.abi_encode()).into()
+}
+
+fn generate_inputs() -> Vec<(U256, U256, U256)> {
+    (0u64..100)
+        .map(|i| {
+            (
+                pseudo_random(i),
+                pseudo_random(i.pow(2)) >> 128,
+                pseudo_random(i.pow(3)),
+            )
+        })
+        .collect()
+}
+
+fn mul_div_benchmark(c: &mut Criterion) {
+    let inputs = generate_inputs();
+    c.bench_function("mul_div", |b| {
+        b.iter(|| {
+            for (a, b, denominator) in &inputs {
+                let _ = mul_div(*a, *b, *denominator);
+            }
+        })
+    });
+}
+
+fn mul_div_benchmark_ref(c: &mut Criterion) {
+    let inputs = generate_inputs();
+    c.bench_function("mul_div_ref", |b| {
+        b.iter(|| {
+            for (a, b, denominator) in &inputs {
+                let _ = full_math::mul_div(*a, *b, *denominator);
+            }
+        })
+    });
+}
+
+fn mul_div_rounding_up_benchmark(c: &mut Criterion) {
+    let inputs = generate_inputs();
+    c.bench_function("mul_div_rounding_up", |b| {
+        b.iter(|| {
+            for (a, b, denominator) in &inputs {
+                let _ = mul_div_rounding_up(*a, *b, *denominator);
+            }
+        })
+    });
+}
+
+fn mul_div_rounding_up_benchmark_ref(c: &mut Criterion) {
+    let inputs = generate_inputs();
+    c.bench_function("mul_div_rounding_up_ref", |b| {
+        b.iter(|| {
+            for (a, b, denominator) in &inputs {
+                let _ = full_math::mul_div_rounding_up(*a, *b, *denominator);
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    mul_div_benchmark,
+    mul_div_benchmark_ref,
+    mul_div_rounding_up_benchmark,
+    mul_div_rounding_up_benchmark_ref
+);
+criterion_main!(benches);
@@ -157,6 +157,12 @@ sol! {
         ) external payable;
     }
 
+    interface IPeripheryPaymentsExtended {
+        function wrapETH(uint256 value) external payable;
+
+        function pull(address token, uint256 value) external payable;
+    }
+
     interface IUniswapV3Staker {
         struct IncentiveKey {
             address rewardToken;
@@ -308,4 +314,264 @@ sol! {
 
         function exactOutput(ExactOutputParams calldata params) external payable returns (uint256 amountIn);
     }
+
+    interface IMulticallExtended {
+        function multicall(uint256 deadline, bytes[] calldata data) external payable returns (bytes[] memory results);
+    }
+
+    interface IV3SwapRouter {
+        struct ExactInputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256 amountOut);
+
+        struct ExactInputParams {
+            bytes path;
+            address recipient;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+        }
+
+        function exactInput(ExactInputParams calldata params) external payable returns (uint256 amountOut);
+
+        struct ExactOutputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        function exactOutputSingle(ExactOutputSingleParams calldata params) external payable returns (uint256 amountIn);
+
+        struct ExactOutputParams {
+            bytes path;
+            address recipient;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+        }
+
+        function exactOutput(ExactOutputParams calldata params) external payable returns (uint256 amountIn);
+    }
+
+    interface IUniswapV3Factory {
+        function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address pool);
+
+        function feeAmountTickSpacing(uint24 fee) external view returns (int24);
+
+        event PoolCreated(
+            address indexed token0,
+            address indexed token1,
+            uint24 indexed fee,
+            int24 tickSpacing,
+            address pool
+        );
+    }
+
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+
+        function getBlockNumber() external view returns (uint256 blockNumber);
+
+        function getCurrentBlockTimestamp() external view returns (uint256 timestamp);
+    }
+
+    interface ITickLens {
+        struct PopulatedTick {
+            int24 tick;
+            int128 liquidityNet;
+            uint128 liquidityGross;
+        }
+
+        function getPopulatedTicksInWord(address pool, int16 tickBitmapIndex)
+            external
+            view
+            returns (PopulatedTick[] memory populatedTicks);
+    }
+
+    interface IGasPriceOracle {
+        function getL1Fee(bytes memory _data) external view returns (uint256);
+    }
+
+    interface INodeInterface {
+        function gasEstimateL1Component(
+            address to,
+            bool contractCreation,
+            bytes calldata data
+        ) external payable returns (uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate);
+    }
+
+    interface AggregatorV3Interface {
+        function decimals() external view returns (uint8);
+
+        function description() external view returns (string memory);
+
+        function latestRoundData()
+            external
+            view
+            returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound);
+    }
+}
+
+// `all_derives` only applies at the macro level, so the interfaces whose types are compared and
+// debug printed are declared in their own block
+sol! {
+    #![sol(all_derives)]
+
+    interface INonfungiblePositionManagerEvents {
+        event IncreaseLiquidity(uint256 indexed tokenId, uint128 liquidity, uint256 amount0, uint256 amount1);
+
+        event DecreaseLiquidity(uint256 indexed tokenId, uint128 liquidity, uint256 amount0, uint256 amount1);
+
+        event Collect(uint256 indexed tokenId, address recipient, uint256 amount0, uint256 amount1);
+
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    }
+
+    interface IUniswapV3PoolEvents {
+        event Initialize(uint160 sqrtPriceX96, int24 tick);
+
+        event Mint(
+            address sender,
+            address indexed owner,
+            int24 indexed tickLower,
+            int24 indexed tickUpper,
+            uint128 amount,
+            uint256 amount0,
+            uint256 amount1
+        );
+
+        event Burn(
+            address indexed owner,
+            int24 indexed tickLower,
+            int24 indexed tickUpper,
+            uint128 amount,
+            uint256 amount0,
+            uint256 amount1
+        );
+
+        event Swap(
+            address indexed sender,
+            address indexed recipient,
+            int256 amount0,
+            int256 amount1,
+            uint160 sqrtPriceX96,
+            uint128 liquidity,
+            int24 tick
+        );
+
+        event Collect(
+            address indexed owner,
+            address recipient,
+            int24 indexed tickLower,
+            int24 indexed tickUpper,
+            uint128 amount0,
+            uint128 amount1
+        );
+
+        event Flash(
+            address indexed sender,
+            address indexed recipient,
+            uint256 amount0,
+            uint256 amount1,
+            uint256 paid0,
+            uint256 paid1
+        );
+
+        event IncreaseObservationCardinalityNext(
+            uint16 observationCardinalityNextOld,
+            uint16 observationCardinalityNextNew
+        );
+
+        event SetFeeProtocol(
+            uint8 feeProtocol0Old,
+            uint8 feeProtocol1Old,
+            uint8 feeProtocol0New,
+            uint8 feeProtocol1New
+        );
+
+        event CollectProtocol(
+            address indexed sender,
+            address indexed recipient,
+            uint128 amount0,
+            uint128 amount1
+        );
+    }
+
+    interface IAllowanceTransfer {
+        struct PermitDetails {
+            address token;
+            uint160 amount;
+            uint48 expiration;
+            uint48 nonce;
+        }
+
+        struct PermitSingle {
+            PermitDetails details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        struct PermitBatch {
+            PermitDetails[] details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        function allowance(address user, address token, address spender)
+            external
+            view
+            returns (uint160 amount, uint48 expiration, uint48 nonce);
+
+        function permit(address owner, PermitSingle memory permitSingle, bytes calldata signature) external;
+
+        function permit(address owner, PermitBatch memory permitBatch, bytes calldata signature) external;
+
+        error AllowanceExpired(uint256 deadline);
+        error InsufficientAllowance(uint256 amount);
+        error ExcessiveInvalidation();
+        error InvalidNonce();
+        error SignatureExpired(uint256 signatureDeadline);
+        error InvalidSignature();
+        error InvalidSigner();
+        error InvalidSignatureLength();
+    }
+
+    interface IUniversalRouter {
+        function execute(bytes calldata commands, bytes[] calldata inputs, uint256 deadline) external payable;
+
+        error ExecutionFailed(uint256 commandIndex, bytes message);
+        error ETHNotAccepted();
+        error TransactionDeadlinePassed();
+        error LengthMismatch();
+        error InvalidCommandType(uint256 commandType);
+        error InsufficientETH();
+        error InsufficientToken();
+        error InvalidBips();
+        error V3InvalidSwap();
+        error V3TooLittleReceived();
+        error V3TooMuchRequested();
+        error V3InvalidAmountOut();
+        error V3InvalidCaller();
+    }
 }
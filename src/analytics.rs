@@ -0,0 +1,1123 @@
+//! ## Analytics
+//! Functions to measure the performance of a liquidity position, such as its impermanent loss
+//! against holding the deposited tokens and its profit and loss in a quote token, the depth of the
+//! liquidity of a pool, candles and realized volatility of its swaps, and the price moves and time
+//! for fees to break even with impermanent loss, the delta and gamma of a position to size hedges,
+//! and the changes of a position between two snapshots.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{aliases::I24, I256, U160, U256};
+#[cfg(feature = "std")]
+use num_traits::{FromPrimitive, ToPrimitive};
+use uniswap_sdk_core::prelude::*;
+
+/// Calculates the impermanent loss of a position in a tick range when the price moves from
+/// `entry_price` to `current_price`, i.e. the value of the position relative to holding the tokens
+/// deposited at `entry_price`, minus 1.
+///
+/// ## Arguments
+///
+/// * `entry_price`: The price ratio of token1/token0 when the position was entered, which must be
+///   positive
+/// * `current_price`: The current price ratio of token1/token0, which must be positive
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+///
+/// ## Returns
+///
+/// The impermanent loss as a [`BigDecimal`] between -1 and 0, inclusive, e.g. -0.2 if the position
+/// is worth 20% less than holding.
+#[inline]
+pub fn impermanent_loss(
+    entry_price: &BigDecimal,
+    current_price: &BigDecimal,
+    tick_lower: I24,
+    tick_upper: I24,
+) -> Result<BigDecimal, Error> {
+    if tick_upper <= tick_lower {
+        return Err(Error::InvalidRange);
+    }
+    if *entry_price <= BigDecimal::zero() || *current_price <= BigDecimal::zero() {
+        return Err(Error::InvalidPrice);
+    }
+    let q96 = Q96.to_big_decimal();
+    let sqrt_lower = get_sqrt_ratio_at_tick(tick_lower)?.to_big_decimal() / &q96;
+    let sqrt_upper = get_sqrt_ratio_at_tick(tick_upper)?.to_big_decimal() / &q96;
+    // the token amounts of a unit of liquidity at a price
+    let amounts = |price: &BigDecimal| -> Result<(BigDecimal, BigDecimal), Error> {
+        let sqrt_price = price
+            .sqrt()
+            .ok_or(Error::InvalidPrice)?
+            .clamp(sqrt_lower.clone(), sqrt_upper.clone());
+        Ok((
+            BigDecimal::from(1) / &sqrt_price - BigDecimal::from(1) / &sqrt_upper,
+            sqrt_price - &sqrt_lower,
+        ))
+    };
+    let (entry0, entry1) = amounts(entry_price)?;
+    let (current0, current1) = amounts(current_price)?;
+    let value_held = entry0 * current_price + entry1;
+    let value_lp = current0 * current_price + current1;
+    Ok(value_lp / value_held - BigDecimal::from(1))
+}
+
+/// The exposure of a position to the price at a point of a price grid, see [`position_greeks`].
+///
+/// Prices are raw price ratios of token1/token0 and amounts are raw amounts, not adjusted for
+/// decimals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionGreeks {
+    /// The price ratio of token1/token0
+    pub price: BigDecimal,
+    /// The amount of token0 held by the position at the price
+    pub amount0: BigDecimal,
+    /// The amount of token1 held by the position at the price
+    pub amount1: BigDecimal,
+    /// The value of the position in token1
+    pub value: BigDecimal,
+    /// The sensitivity of the value to the price, i.e. the amount of token0 to short to hedge
+    pub delta: BigDecimal,
+    /// The delta valued in token1, i.e. the notional of the hedge
+    pub delta_quote: BigDecimal,
+    /// The sensitivity of the delta to the price, which is negative in range
+    pub gamma: BigDecimal,
+    /// The gamma times the price squared, i.e. the change in `delta_quote` for a relative price
+    /// move, in token1
+    pub gamma_quote: BigDecimal,
+}
+
+/// Computes the delta and gamma of a position across a grid of prices, e.g. to size a perpetual
+/// hedge.
+///
+/// A position of liquidity `L` between sqrt prices `sa` and `sb` holds `L (1 / s - 1 / sb)` token0
+/// and `L (s - sa)` token1 at a sqrt price `s` clamped to the range, so its delta is the amount of
+/// token0 it holds and its gamma is `-L / (2 s^3)` in range and zero out of range.
+///
+/// ## Arguments
+///
+/// * `position`: The position
+/// * `prices`: The price ratios of token1/token0 to evaluate, which must be positive
+#[inline]
+pub fn position_greeks<TP: TickDataProvider>(
+    position: &Position<TP>,
+    prices: &[BigDecimal],
+) -> Result<Vec<PositionGreeks>, Error> {
+    let q96 = Q96.to_big_decimal();
+    let sqrt_lower = get_sqrt_ratio_at_tick(position.tick_lower.to_i24())?.to_big_decimal() / &q96;
+    let sqrt_upper = get_sqrt_ratio_at_tick(position.tick_upper.to_i24())?.to_big_decimal() / &q96;
+    let liquidity = BigDecimal::from(BigInt::from(position.liquidity));
+    let one = BigDecimal::from(1);
+    prices
+        .iter()
+        .map(|price| {
+            if *price <= BigDecimal::zero() {
+                return Err(Error::InvalidPrice);
+            }
+            let sqrt_price = price.sqrt().ok_or(Error::InvalidPrice)?;
+            let in_range = sqrt_lower < sqrt_price && sqrt_price < sqrt_upper;
+            let sqrt_price = sqrt_price.clamp(sqrt_lower.clone(), sqrt_upper.clone());
+            let amount0 = &liquidity * (&one / &sqrt_price - &one / &sqrt_upper);
+            let amount1 = &liquidity * (&sqrt_price - &sqrt_lower);
+            let gamma = if in_range {
+                -&liquidity / (BigDecimal::from(2) * &sqrt_price * &sqrt_price * &sqrt_price)
+            } else {
+                BigDecimal::zero()
+            };
+            Ok(PositionGreeks {
+                price: price.clone(),
+                value: &amount0 * price + &amount1,
+                delta: amount0.clone(),
+                delta_quote: &amount0 * price,
+                gamma_quote: &gamma * price * price,
+                gamma,
+                amount0,
+                amount1,
+            })
+        })
+        .collect()
+}
+
+/// The state of a position when it was entered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionEntry {
+    /// The amount of token0 deposited
+    pub amount0: U256,
+    /// The amount of token1 deposited
+    pub amount1: U256,
+    /// The price of token0 in token1 when the position was entered
+    pub token0_price: Price<Token, Token>,
+}
+
+/// The profit and loss of a position, with all values in a quote token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionPnL {
+    /// The value of the deposited tokens at the entry price
+    pub entry_value: CurrencyAmount<Token>,
+    /// The value of the deposited tokens at the current price, i.e. if they were held instead
+    pub held_value: CurrencyAmount<Token>,
+    /// The value of the current token amounts of the position
+    pub principal_value: CurrencyAmount<Token>,
+    /// The value of the fees collected so far
+    pub collected_fees_value: CurrencyAmount<Token>,
+    /// The value of the fees owed to the position that are not yet collected
+    pub uncollected_fees_value: CurrencyAmount<Token>,
+}
+
+impl PositionPnL {
+    /// The change in value of the principal relative to holding the deposited tokens, in raw
+    /// units of the quote token. Negative when the position suffers impermanent loss.
+    #[inline]
+    #[must_use]
+    pub fn principal_drift(&self) -> BigInt {
+        self.principal_value.quotient() - self.held_value.quotient()
+    }
+
+    /// The value of all fees earned, in raw units of the quote token.
+    #[inline]
+    #[must_use]
+    pub fn fees(&self) -> BigInt {
+        self.collected_fees_value.quotient() + self.uncollected_fees_value.quotient()
+    }
+
+    /// The profit and loss of the position relative to the entry value, in raw units of the quote
+    /// token.
+    #[inline]
+    #[must_use]
+    pub fn pnl(&self) -> BigInt {
+        self.principal_value.quotient() + self.fees() - self.entry_value.quotient()
+    }
+}
+
+/// Values raw amounts of token0 and token1 in `quote_token`, which must be one of the two tokens.
+pub(crate) fn value_in_quote(
+    amount0: BigInt,
+    amount1: BigInt,
+    token0_price: &Price<Token, Token>,
+    quote_token: &Token,
+) -> Result<CurrencyAmount<Token>, Error> {
+    let token0 = &token0_price.base_currency;
+    let token1 = &token0_price.quote_currency;
+    if quote_token.equals(token1) {
+        Ok(token0_price
+            .quote(&CurrencyAmount::from_raw_amount(token0.clone(), amount0)?)?
+            .add(&CurrencyAmount::from_raw_amount(token1.clone(), amount1)?)?)
+    } else if quote_token.equals(token0) {
+        Ok(token0_price
+            .invert()
+            .quote(&CurrencyAmount::from_raw_amount(token1.clone(), amount1)?)?
+            .add(&CurrencyAmount::from_raw_amount(token0.clone(), amount0)?)?)
+    } else {
+        Err(Error::InvalidToken)
+    }
+}
+
+/// Calculates the profit and loss of a position since it was entered, combining the drift of the
+/// principal, the collected fees and the uncollected fees, all valued in `quote_token`.
+///
+/// ## Arguments
+///
+/// * `position`: The position with the current pool state
+/// * `entry`: The state of the position when it was entered
+/// * `collected_fees`: The amounts of token0 and token1 collected so far
+/// * `uncollected_fees`: The amounts of token0 and token1 owed to the position, e.g. from
+///   `get_collectable_token_amounts`
+/// * `quote_token`: The token to value the position in, one of the tokens in the pool
+#[inline]
+pub fn position_pnl<TP: TickDataProvider>(
+    position: &Position<TP>,
+    entry: &PositionEntry,
+    collected_fees: (U256, U256),
+    uncollected_fees: (U256, U256),
+    quote_token: &Token,
+) -> Result<PositionPnL, Error> {
+    let price = position.pool.token0_price();
+    let value = |amount0: U256, amount1: U256| {
+        value_in_quote(
+            amount0.to_big_int(),
+            amount1.to_big_int(),
+            &price,
+            quote_token,
+        )
+    };
+    Ok(PositionPnL {
+        entry_value: value_in_quote(
+            entry.amount0.to_big_int(),
+            entry.amount1.to_big_int(),
+            &entry.token0_price,
+            quote_token,
+        )?,
+        held_value: value(entry.amount0, entry.amount1)?,
+        principal_value: position.value_in(quote_token, &price, (U256::ZERO, U256::ZERO))?,
+        collected_fees_value: value(collected_fees.0, collected_fees.1)?,
+        uncollected_fees_value: value(uncollected_fees.0, uncollected_fees.1)?,
+    })
+}
+
+/// The state of a position at a block, which can be compared to a later snapshot with
+/// [`PositionSnapshot::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionSnapshot {
+    /// The block of the snapshot
+    pub block_number: u64,
+    /// The lower tick of the position
+    pub tick_lower: I24,
+    /// The upper tick of the position
+    pub tick_upper: I24,
+    /// The liquidity of the position
+    pub liquidity: u128,
+    /// The amount of token0 the liquidity could be burned for
+    pub amount0: U256,
+    /// The amount of token1 the liquidity could be burned for
+    pub amount1: U256,
+    /// The fees of token0 owed to the position that are not yet collected
+    pub uncollected_fees0: U256,
+    /// The fees of token1 owed to the position that are not yet collected
+    pub uncollected_fees1: U256,
+    /// The sqrt price of the pool
+    pub sqrt_price_x96: U160,
+}
+
+/// The changes of a position between two snapshots, with values in raw units of token1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionDiff {
+    /// The change in liquidity from minting or burning
+    pub liquidity_delta: i128,
+    /// The change in uncollected fees of token0, which excludes fees collected in between
+    pub fee_income0: BigInt,
+    /// The change in uncollected fees of token1, which excludes fees collected in between
+    pub fee_income1: BigInt,
+    /// The change in value of the liquidity of the earlier snapshot caused by the price move
+    pub price_value_change: BigInt,
+    /// The change in value caused by the change in liquidity, at the later price
+    pub liquidity_value_change: BigInt,
+}
+
+impl PositionDiff {
+    /// The total change in value of the principal, in raw units of token1.
+    #[inline]
+    #[must_use]
+    pub fn value_change(&self) -> BigInt {
+        &self.price_value_change + &self.liquidity_value_change
+    }
+}
+
+/// Values raw amounts of token0 and token1 in token1 at a sqrt price, rounded down.
+fn value_at_sqrt_price(amount0: U256, amount1: U256, sqrt_price_x96: U160) -> BigInt {
+    ((amount0.to_big_int() * sqrt_price_x96.to_big_int().pow(2)) >> 192) + amount1.to_big_int()
+}
+
+impl PositionSnapshot {
+    /// Takes a snapshot of a position with the current pool state.
+    ///
+    /// ## Arguments
+    ///
+    /// * `position`: The position with the pool state at the block
+    /// * `block_number`: The block of the snapshot
+    /// * `uncollected_fees`: The amounts of token0 and token1 owed to the position, e.g. from
+    ///   `get_collectable_token_amounts`
+    #[inline]
+    pub fn from_position<TP: TickDataProvider>(
+        position: &Position<TP>,
+        block_number: u64,
+        uncollected_fees: (U256, U256),
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            block_number,
+            tick_lower: position.tick_lower.to_i24(),
+            tick_upper: position.tick_upper.to_i24(),
+            liquidity: position.liquidity,
+            amount0: U256::from_big_int(position.amount0()?.quotient()),
+            amount1: U256::from_big_int(position.amount1()?.quotient()),
+            uncollected_fees0: uncollected_fees.0,
+            uncollected_fees1: uncollected_fees.1,
+            sqrt_price_x96: position.pool.sqrt_ratio_x96,
+        })
+    }
+
+    /// The value of the amounts of the position in raw units of token1 at the pool price.
+    #[inline]
+    #[must_use]
+    pub fn value(&self) -> BigInt {
+        value_at_sqrt_price(self.amount0, self.amount1, self.sqrt_price_x96)
+    }
+
+    /// Computes the fee income, the change in liquidity and the changes in value caused by the
+    /// price move and by the change in liquidity from this snapshot to a later one.
+    ///
+    /// ## Arguments
+    ///
+    /// * `later`: A later snapshot of the same position
+    ///
+    /// ## Returns
+    ///
+    /// The diff, or [`Error::InvalidRange`] if the snapshots are of different tick ranges
+    #[inline]
+    pub fn diff(&self, later: &Self) -> Result<PositionDiff, Error> {
+        if self.tick_lower != later.tick_lower || self.tick_upper != later.tick_upper {
+            return Err(Error::InvalidRange);
+        }
+        // the liquidity of this snapshot valued at the later price
+        let (amount0, amount1) = get_amounts_for_liquidity(
+            later.sqrt_price_x96,
+            get_sqrt_ratio_at_tick(self.tick_lower)?,
+            get_sqrt_ratio_at_tick(self.tick_upper)?,
+            self.liquidity,
+        )?;
+        let repriced_value = value_at_sqrt_price(amount0, amount1, later.sqrt_price_x96);
+        Ok(PositionDiff {
+            liquidity_delta: later.liquidity.wrapping_sub(self.liquidity) as i128,
+            fee_income0: later.uncollected_fees0.to_big_int() - self.uncollected_fees0.to_big_int(),
+            fee_income1: later.uncollected_fees1.to_big_int() - self.uncollected_fees1.to_big_int(),
+            price_value_change: &repriced_value - self.value(),
+            liquidity_value_change: later.value() - repriced_value,
+        })
+    }
+}
+
+/// A swap of a given size on a [`DepthCurve`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthPoint {
+    /// The amount swapped into the pool
+    pub amount_in: CurrencyAmount<Token>,
+    /// The amount received from the pool
+    pub amount_out: CurrencyAmount<Token>,
+    /// The percent difference between the mid price and the execution price
+    pub price_impact: Percent,
+}
+
+/// The output amounts and price impacts of swaps of increasing sizes in both directions, like the
+/// two sides of an orderbook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthCurve {
+    /// Swaps of the quote token for the base token
+    pub buy: Vec<DepthPoint>,
+    /// Swaps of the base token for the quote token
+    pub sell: Vec<DepthPoint>,
+}
+
+/// Simulates swapping `amount_in` through `pool`, returning [`None`] if the pool doesn't have
+/// enough liquidity.
+fn depth_point<TP: Clone + TickDataProvider>(
+    pool: &Pool<TP>,
+    amount_in: CurrencyAmount<Token>,
+) -> Result<Option<DepthPoint>, Error> {
+    let amount_out = match pool.get_output_amount(&amount_in, None) {
+        Ok((amount_out, _)) => {
+            CurrencyAmount::from_raw_amount(amount_out.currency.clone(), amount_out.quotient())?
+        }
+        Err(Error::InsufficientLiquidity) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let spot_amount_out = pool.price_of(&amount_in.currency)?.quote(&amount_in)?;
+    let price_impact = spot_amount_out
+        .subtract(&amount_out)?
+        .divide(&spot_amount_out)?;
+    Ok(Some(DepthPoint {
+        amount_in,
+        amount_out,
+        price_impact: Percent::new(price_impact.numerator, price_impact.denominator),
+    }))
+}
+
+/// Computes the depth curve of a pool for a ladder of swap sizes denominated in `quote_token`.
+///
+/// Buys swap each size of the quote token for the base token, while sells swap the amount of the
+/// base token worth each size at the mid price. Sizes that exceed the liquidity of the pool are
+/// omitted.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool with tick data
+/// * `quote_token`: The token to denominate the sizes in, one of the tokens in the pool
+/// * `sizes`: The raw amounts of the quote token to swap, e.g. 1k, 10k and 100k quote units
+#[inline]
+pub fn depth_curve<TP: Clone + TickDataProvider>(
+    pool: &Pool<TP>,
+    quote_token: &Token,
+    sizes: &[BigInt],
+) -> Result<DepthCurve, Error> {
+    let quote_price = pool.price_of(quote_token)?;
+    let mut buy = Vec::with_capacity(sizes.len());
+    let mut sell = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        let quote_amount = CurrencyAmount::from_raw_amount(quote_token.clone(), size.clone())?;
+        let base_amount = quote_price.quote(&quote_amount)?;
+        if let Some(point) = depth_point(pool, quote_amount)? {
+            buy.push(point);
+        }
+        if let Some(point) = depth_point(pool, base_amount)? {
+            sell.push(point);
+        }
+    }
+    Ok(DepthCurve { buy, sell })
+}
+
+/// A swap of a pool at a point in time, the input of [`ohlc_candles`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapSample {
+    /// The unix timestamp of the swap, e.g. of its block
+    pub timestamp: u64,
+    /// The sqrt price of the pool after the swap
+    pub sqrt_price_x96: U160,
+    /// The change of the balance of token0 of the pool
+    pub amount0: I256,
+    /// The change of the balance of token1 of the pool
+    pub amount1: I256,
+    /// The active liquidity of the pool after the swap
+    pub liquidity: u128,
+}
+
+impl SwapSample {
+    /// Creates a sample from a decoded `Swap` event of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `swap`: The event
+    /// * `timestamp`: The unix timestamp of the block of the event
+    #[inline]
+    #[must_use]
+    pub const fn from_event(swap: &IUniswapV3PoolEvents::Swap, timestamp: u64) -> Self {
+        Self {
+            timestamp,
+            sqrt_price_x96: swap.sqrtPriceX96,
+            amount0: swap.amount0,
+            amount1: swap.amount1,
+            liquidity: swap.liquidity,
+        }
+    }
+}
+
+/// The open, high, low and close prices and the volumes of the swaps in a time bucket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candle {
+    /// The unix timestamp of the start of the bucket
+    pub start: u64,
+    /// The price after the first swap
+    pub open: BigDecimal,
+    /// The highest price after a swap
+    pub high: BigDecimal,
+    /// The lowest price after a swap
+    pub low: BigDecimal,
+    /// The price after the last swap
+    pub close: BigDecimal,
+    /// The volume of token0 swapped in either direction
+    pub volume0: U256,
+    /// The volume of token1 swapped in either direction
+    pub volume1: U256,
+    /// The number of swaps
+    pub swaps: usize,
+}
+
+/// Aggregates swaps into OHLCV candles of `interval` seconds, aligned to multiples of `interval`.
+///
+/// Prices are raw price ratios of token1/token0 after each swap, not adjusted for decimals, or of
+/// token0/token1 if `invert` is set. Buckets without swaps are omitted.
+///
+/// ## Arguments
+///
+/// * `swaps`: The swaps, which are sorted by timestamp keeping the order of the swaps with the same
+///   timestamp, so they should be in the order they were executed within a block
+/// * `interval`: The length of a bucket in seconds
+/// * `invert`: Whether to price token0 in token1 instead
+///
+/// ## Returns
+///
+/// The candles in ascending order, or [`Error::InvalidWindow`] if `interval` is zero
+#[inline]
+pub fn ohlc_candles(
+    swaps: &[SwapSample],
+    interval: u64,
+    invert: bool,
+) -> Result<Vec<Candle>, Error> {
+    if interval == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let mut swaps: Vec<&SwapSample> = swaps.iter().collect();
+    swaps.sort_by_key(|swap| swap.timestamp);
+    let q192 = Q192.to_big_decimal();
+    let mut candles: Vec<Candle> = Vec::new();
+    for swap in swaps {
+        let ratio = BigDecimal::from(swap.sqrt_price_x96.to_big_int().pow(2));
+        let price = if invert { &q192 / ratio } else { ratio / &q192 };
+        let start = swap.timestamp - swap.timestamp % interval;
+        let volume0 = swap.amount0.unsigned_abs();
+        let volume1 = swap.amount1.unsigned_abs();
+        match candles.last_mut() {
+            Some(candle) if candle.start == start => {
+                if price > candle.high {
+                    candle.high = price.clone();
+                }
+                if price < candle.low {
+                    candle.low = price.clone();
+                }
+                candle.close = price;
+                candle.volume0 += volume0;
+                candle.volume1 += volume1;
+                candle.swaps += 1;
+            }
+            _ => candles.push(Candle {
+                start,
+                open: price.clone(),
+                high: price.clone(),
+                low: price.clone(),
+                close: price,
+                volume0,
+                volume1,
+                swaps: 1,
+            }),
+        }
+    }
+    Ok(candles)
+}
+
+/// Annualizes the volatility of a series of consecutive log price changes over `seconds` seconds.
+///
+/// ## Arguments
+///
+/// * `log_returns`: The natural logarithms of the ratios of consecutive prices
+/// * `seconds`: The time spanned by the price changes in seconds
+///
+/// ## Returns
+///
+/// The annualized volatility, or [`Error::InvalidWindow`] if `seconds` is zero
+#[cfg(feature = "std")]
+#[inline]
+pub fn annualized_volatility(log_returns: &[f64], seconds: u64) -> Result<f64, Error> {
+    if seconds == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let variance: f64 = log_returns.iter().map(|r| r * r).sum();
+    Ok((variance * SECONDS_PER_YEAR.to_f64().unwrap() / seconds.to_f64().unwrap()).sqrt())
+}
+
+/// Estimates the annualized realized volatility of the price of a pool from its swaps in the last
+/// `window` seconds before the last swap.
+///
+/// The log price changes between consecutive swaps are summed in squares, starting from the last
+/// swap before the window if there is one, and annualized over the window. Since the price only
+/// moves on swaps, the estimate is independent of how often the pool is traded.
+///
+/// ## Arguments
+///
+/// * `swaps`: The swaps in the order they were executed
+/// * `window`: The length of the window in seconds
+///
+/// ## Returns
+///
+/// The annualized volatility, zero without swaps, or [`Error::InvalidWindow`] if `window` is zero
+#[cfg(feature = "std")]
+#[inline]
+pub fn realized_volatility(swaps: &[SwapSample], window: u64) -> Result<f64, Error> {
+    if window == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let Some(last) = swaps.last() else {
+        return Ok(0.0);
+    };
+    let start = last.timestamp.saturating_sub(window);
+    // include the last swap at or before the start of the window as the opening price
+    let first = swaps
+        .iter()
+        .rposition(|swap| swap.timestamp <= start)
+        .unwrap_or_default();
+    let log_returns: Vec<f64> = swaps[first..]
+        .windows(2)
+        .map(|w| {
+            let ratio = w[1].sqrt_price_x96.to_big_decimal() / w[0].sqrt_price_x96.to_big_decimal();
+            2.0 * ratio.to_f64().unwrap().ln()
+        })
+        .collect();
+    annualized_volatility(&log_returns, window)
+}
+
+/// The price moves from the entry price at which the fees of a position over a horizon no longer
+/// cover its impermanent loss, see [`breakeven_price_range`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BreakevenRange {
+    /// The fees earned over the horizon as a fraction of the value of the position
+    pub fees: f64,
+    /// The ratio of the price to the entry price below which the position loses against holding,
+    /// or `None` if the fees cover any drop
+    pub price_ratio_lower: Option<f64>,
+    /// The ratio of the price to the entry price above which the position loses against holding,
+    /// or `None` if the fees cover any rise
+    pub price_ratio_upper: Option<f64>,
+}
+
+/// Computes the price moves at which the fees of a position accumulated over a horizon are equal
+/// to its impermanent loss.
+///
+/// The position is assumed to earn `fee_apr` over the whole horizon, i.e. to stay in range.
+///
+/// ## Arguments
+///
+/// * `tick_current`: The tick when the position is entered
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `fee_apr`: The estimated fee APR of the position, e.g. 0.2 for 20%
+/// * `horizon`: The horizon in seconds
+#[cfg(feature = "std")]
+#[inline]
+pub fn breakeven_price_range(
+    tick_current: I24,
+    tick_lower: I24,
+    tick_upper: I24,
+    fee_apr: f64,
+    horizon: u64,
+) -> Result<BreakevenRange, Error> {
+    if tick_upper <= tick_lower {
+        return Err(Error::InvalidRange);
+    }
+    let fees = fee_apr * horizon.to_f64().unwrap() / SECONDS_PER_YEAR.to_f64().unwrap();
+    // the impermanent loss at a log price ratio, relative to an entry price of 1
+    let tick_lower = (tick_lower - tick_current).max(MIN_TICK);
+    let tick_upper = (tick_upper - tick_current).min(MAX_TICK);
+    let entry_price = BigDecimal::from(1);
+    let loss = |log_ratio: f64| -> Result<f64, Error> {
+        let price = BigDecimal::from_f64(log_ratio.exp()).ok_or(Error::InvalidPrice)?;
+        Ok(
+            impermanent_loss(&entry_price, &price, tick_lower, tick_upper)?
+                .to_f64()
+                .unwrap(),
+        )
+    };
+    // the log price ratio of the widest move within the tick range
+    let max_log_ratio = f64::from(MAX_TICK_I32) * 1.0001_f64.ln();
+    let threshold = |direction: f64| -> Result<Option<f64>, Error> {
+        if loss(direction * max_log_ratio)? + fees >= 0.0 {
+            return Ok(None);
+        }
+        // the impermanent loss grows monotonically with the size of the move
+        let (mut covered, mut uncovered) = (0.0, max_log_ratio);
+        for _ in 0..100 {
+            let mid = (covered + uncovered) / 2.0;
+            if loss(direction * mid)? + fees >= 0.0 {
+                covered = mid;
+            } else {
+                uncovered = mid;
+            }
+        }
+        Ok(Some((direction * covered).exp()))
+    };
+    Ok(BreakevenRange {
+        fees,
+        price_ratio_lower: threshold(-1.0)?,
+        price_ratio_upper: threshold(1.0)?,
+    })
+}
+
+/// Computes the time it takes for the fees of a position to cover its impermanent loss at the
+/// current price.
+///
+/// ## Arguments
+///
+/// * `entry_price`: The price ratio of token1/token0 when the position was entered
+/// * `current_price`: The current price ratio of token1/token0
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `fee_apr`: The fee APR of the position at the current volume, e.g. 0.2 for 20%
+///
+/// ## Returns
+///
+/// The time to break even in seconds, or `None` if the position earns no fees
+#[cfg(feature = "std")]
+#[inline]
+pub fn breakeven_time(
+    entry_price: &BigDecimal,
+    current_price: &BigDecimal,
+    tick_lower: I24,
+    tick_upper: I24,
+    fee_apr: f64,
+) -> Result<Option<u64>, Error> {
+    let loss = impermanent_loss(entry_price, current_price, tick_lower, tick_upper)?
+        .to_f64()
+        .unwrap();
+    if fee_apr <= 0.0 {
+        return Ok(None);
+    }
+    Ok((-loss / fee_apr * SECONDS_PER_YEAR.to_f64().unwrap())
+        .ceil()
+        .to_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use core::str::FromStr;
+
+    fn assert_close(actual: BigDecimal, expected: &str) {
+        let diff = (actual - BigDecimal::from_str(expected).unwrap()).abs();
+        assert!(diff < BigDecimal::from_str("1e-9").unwrap());
+    }
+
+    #[test]
+    fn test_impermanent_loss_full_range() {
+        let il = impermanent_loss(
+            &BigDecimal::from(1),
+            &BigDecimal::from(4),
+            MIN_TICK,
+            MAX_TICK,
+        )
+        .unwrap();
+        // 2 * sqrt(4) / (1 + 4) - 1
+        assert_close(il, "-0.2");
+    }
+
+    #[test]
+    fn test_impermanent_loss_unchanged_price() {
+        let il = impermanent_loss(
+            &BigDecimal::from(1),
+            &BigDecimal::from(1),
+            I24::try_from(-600).unwrap(),
+            I24::try_from(600).unwrap(),
+        )
+        .unwrap();
+        assert!(il.is_zero());
+    }
+
+    #[test]
+    fn test_impermanent_loss_concentrated() {
+        let tick_lower = I24::try_from(-600).unwrap();
+        let tick_upper = I24::try_from(600).unwrap();
+        let full_range = impermanent_loss(
+            &BigDecimal::from(1),
+            &BigDecimal::from_str("1.05").unwrap(),
+            MIN_TICK,
+            MAX_TICK,
+        )
+        .unwrap();
+        let concentrated = impermanent_loss(
+            &BigDecimal::from(1),
+            &BigDecimal::from_str("1.05").unwrap(),
+            tick_lower,
+            tick_upper,
+        )
+        .unwrap();
+        assert!(concentrated < full_range);
+        assert!(concentrated < BigDecimal::zero());
+    }
+
+    #[test]
+    fn test_impermanent_loss_invalid_range() {
+        assert!(matches!(
+            impermanent_loss(
+                &BigDecimal::from(1),
+                &BigDecimal::from(1),
+                MAX_TICK,
+                MIN_TICK
+            ),
+            Err(Error::InvalidRange)
+        ));
+    }
+
+    #[test]
+    fn test_impermanent_loss_zero_price() {
+        // an entry below the range holds no token1, which made the held value zero
+        let tick_lower = I24::try_from(600).unwrap();
+        let tick_upper = I24::try_from(1200).unwrap();
+        assert!(matches!(
+            impermanent_loss(
+                &BigDecimal::from(1),
+                &BigDecimal::zero(),
+                tick_lower,
+                tick_upper
+            ),
+            Err(Error::InvalidPrice)
+        ));
+        assert!(matches!(
+            impermanent_loss(
+                &BigDecimal::zero(),
+                &BigDecimal::from(1),
+                MIN_TICK,
+                MAX_TICK
+            ),
+            Err(Error::InvalidPrice)
+        ));
+    }
+
+    #[test]
+    fn test_impermanent_loss_negative_price() {
+        assert!(matches!(
+            impermanent_loss(
+                &BigDecimal::from(1),
+                &BigDecimal::from(-1),
+                MIN_TICK,
+                MAX_TICK
+            ),
+            Err(Error::InvalidPrice)
+        ));
+        assert!(matches!(
+            impermanent_loss(
+                &BigDecimal::from(-1),
+                &BigDecimal::from(1),
+                MIN_TICK,
+                MAX_TICK
+            ),
+            Err(Error::InvalidPrice)
+        ));
+    }
+
+    #[test]
+    fn test_position_greeks() {
+        let position = Position::new(POOL_0_1.clone(), 1_000_000_000_000, -600, 600);
+        let prices = ["0.5", "0.99", "1", "1.01", "2"].map(|p| BigDecimal::from_str(p).unwrap());
+        let greeks = position_greeks(&position, &prices).unwrap();
+        // below the range the position holds only token0
+        assert!(greeks[0].amount1.is_zero());
+        assert_eq!(greeks[0].delta, greeks[0].amount0);
+        assert!(greeks[0].gamma.is_zero());
+        // above the range the position holds only token1
+        assert!(greeks[4].delta.is_zero());
+        assert!(greeks[4].gamma.is_zero());
+        // the delta is the derivative of the value and decreases in range
+        let slope = (&greeks[3].value - &greeks[1].value) / BigDecimal::from_str("0.02").unwrap();
+        assert!(
+            ((slope - &greeks[2].delta) / &greeks[2].delta).abs()
+                < BigDecimal::from_str("1e-3").unwrap()
+        );
+        assert!(greeks[2].gamma < BigDecimal::zero());
+        assert!(greeks[1].delta > greeks[3].delta);
+        assert_eq!(greeks[2].delta_quote, greeks[2].delta);
+        assert!(matches!(
+            position_greeks(&position, &[BigDecimal::zero()]),
+            Err(Error::InvalidPrice)
+        ));
+    }
+
+    #[test]
+    fn test_position_pnl() {
+        let position = Position::new(POOL_0_1.clone(), 1_000_000, -60, 60);
+        let entry = PositionEntry {
+            amount0: U256::from_big_int(position.amount0().unwrap().quotient()),
+            amount1: U256::from_big_int(position.amount1().unwrap().quotient()),
+            token0_price: position.pool.token0_price(),
+        };
+        let pnl = position_pnl(
+            &position,
+            &entry,
+            (U256::from(10), U256::from(20)),
+            (U256::from(1), U256::from(2)),
+            &TOKEN1,
+        )
+        .unwrap();
+        assert_eq!(pnl.principal_drift(), BigInt::zero());
+        assert_eq!(pnl.fees(), BigInt::from(33));
+        assert_eq!(pnl.pnl(), BigInt::from(33));
+        assert!(matches!(
+            position_pnl(
+                &position,
+                &entry,
+                (U256::ZERO, U256::ZERO),
+                (U256::ZERO, U256::ZERO),
+                &TOKEN2,
+            ),
+            Err(Error::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn test_position_snapshot_diff() {
+        let position = Position::new(POOL_0_1.clone(), 1_000_000_000, -600, 600);
+        let before =
+            PositionSnapshot::from_position(&position, 1, (U256::from(10), U256::ZERO)).unwrap();
+        // the price rises by 5% and half of the liquidity is burned
+        let pool = Pool::new(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FeeAmount::MEDIUM,
+            encode_sqrt_ratio_x96(105, 100),
+            0,
+        )
+        .unwrap();
+        let position = Position::new(pool, 500_000_000, -600, 600);
+        let after =
+            PositionSnapshot::from_position(&position, 2, (U256::from(15), U256::from(7))).unwrap();
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(diff.liquidity_delta, -500_000_000);
+        assert_eq!(diff.fee_income0, BigInt::from(5));
+        assert_eq!(diff.fee_income1, BigInt::from(7));
+        assert!(diff.price_value_change > BigInt::zero());
+        assert!(diff.liquidity_value_change < BigInt::zero());
+        assert_eq!(diff.value_change(), after.value() - before.value());
+        assert!(before.diff(&before).unwrap().value_change().is_zero());
+        let other = PositionSnapshot {
+            tick_lower: I24::ZERO,
+            ..after
+        };
+        assert!(matches!(before.diff(&other), Err(Error::InvalidRange)));
+    }
+
+    #[test]
+    fn test_depth_curve() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let mut sizes = [1000, 10000, 100000].map(BigInt::from).to_vec();
+        sizes.push(BigInt::from(10).pow(30));
+        let curve = depth_curve(&pool, &TOKEN1, &sizes).unwrap();
+        // the largest size exceeds the liquidity of the pool
+        assert_eq!(curve.buy.len(), 3);
+        assert_eq!(curve.sell.len(), 3);
+        for side in [&curve.buy, &curve.sell] {
+            for w in side.windows(2) {
+                assert!(w[0].amount_out.quotient() < w[1].amount_out.quotient());
+                assert!(w[0].price_impact < w[1].price_impact);
+            }
+            assert!(side[0].price_impact > Percent::default());
+        }
+        assert!(curve.buy[0].amount_in.currency.equals(&*TOKEN1));
+        assert!(curve.buy[0].amount_out.currency.equals(&*TOKEN0));
+        assert!(curve.sell[0].amount_in.currency.equals(&*TOKEN0));
+        assert_eq!(curve.buy[0].amount_in.quotient(), BigInt::from(1000));
+        assert_eq!(curve.sell[0].amount_in.quotient(), BigInt::from(1000));
+        assert!(matches!(
+            depth_curve(&pool, &TOKEN2, &sizes),
+            Err(Error::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn test_ohlc_candles() {
+        let sample = |timestamp: u64, price: u64, amount0: i64, amount1: i64| SwapSample {
+            timestamp,
+            sqrt_price_x96: encode_sqrt_ratio_x96(price * price, 1),
+            amount0: I256::try_from(amount0).unwrap(),
+            amount1: I256::try_from(amount1).unwrap(),
+            liquidity: 0,
+        };
+        let swaps = [
+            sample(0, 2, 10, -20),
+            sample(30, 4, -5, 20),
+            sample(59, 1, 40, -40),
+            sample(60, 3, -10, 30),
+            sample(200, 5, -1, 5),
+        ];
+        let candles = ohlc_candles(&swaps, 60, false).unwrap();
+        assert_eq!(candles.len(), 3);
+        let candle = &candles[0];
+        assert_eq!(candle.start, 0);
+        assert_eq!(candle.open, BigDecimal::from(4));
+        assert_eq!(candle.high, BigDecimal::from(16));
+        assert_eq!(candle.low, BigDecimal::from(1));
+        assert_eq!(candle.close, BigDecimal::from(1));
+        assert_eq!(candle.volume0, U256::from(55));
+        assert_eq!(candle.volume1, U256::from(80));
+        assert_eq!(candle.swaps, 3);
+        assert_eq!(candles[1].start, 60);
+        assert_eq!(candles[1].swaps, 1);
+        // empty buckets are omitted
+        assert_eq!(candles[2].start, 180);
+
+        let inverted = ohlc_candles(&swaps, 60, true).unwrap();
+        assert_eq!(inverted[0].high, BigDecimal::from(1));
+        assert_eq!(inverted[0].low, BigDecimal::from_str("0.0625").unwrap());
+
+        // out of order samples, e.g. from merged log queries, are sorted by timestamp
+        let mut shuffled = swaps;
+        shuffled.swap(0, 4);
+        shuffled.swap(1, 3);
+        assert_eq!(ohlc_candles(&shuffled, 60, false).unwrap(), candles);
+        assert!(matches!(
+            ohlc_candles(&swaps, 0, false),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_annualized_volatility() {
+        assert_eq!(annualized_volatility(&[], 60).unwrap(), 0.0);
+        // a 1% move every day for a year
+        let log_returns = vec![0.01; 365];
+        let volatility = annualized_volatility(&log_returns, SECONDS_PER_YEAR).unwrap();
+        assert!((volatility - 0.01 * 365_f64.sqrt()).abs() < 1e-9);
+        assert!(matches!(
+            annualized_volatility(&log_returns, 0),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_realized_volatility() {
+        let sample = |timestamp: u64, price: u64| SwapSample {
+            timestamp,
+            sqrt_price_x96: encode_sqrt_ratio_x96(price, 1),
+            amount0: I256::ZERO,
+            amount1: I256::ZERO,
+            liquidity: 0,
+        };
+        assert_eq!(realized_volatility(&[], 60).unwrap(), 0.0);
+        assert_eq!(realized_volatility(&[sample(0, 1)], 60).unwrap(), 0.0);
+        let swaps = [sample(0, 100), sample(30, 1), sample(50, 2), sample(130, 4)];
+        // the first swap is outside the window, the second opens it
+        let expected = annualized_volatility(&[2_f64.ln(), 2_f64.ln()], 100).unwrap();
+        assert!((realized_volatility(&swaps, 100).unwrap() - expected).abs() < 1e-6);
+        assert!(matches!(
+            realized_volatility(&swaps, 0),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_breakeven_price_range() {
+        // the impermanent loss of a full range position is 2 sqrt(r) / (1 + r) - 1
+        let full_range_loss = |r: f64| 2.0 * r.sqrt() / (1.0 + r) - 1.0;
+        let range = breakeven_price_range(
+            I24::ZERO,
+            nearest_usable_tick(MIN_TICK, FeeAmount::MEDIUM.tick_spacing()),
+            nearest_usable_tick(MAX_TICK, FeeAmount::MEDIUM.tick_spacing()),
+            0.2,
+            SECONDS_PER_YEAR / 2,
+        )
+        .unwrap();
+        assert!((range.fees - 0.1).abs() < 1e-12);
+        let lower = range.price_ratio_lower.unwrap();
+        let upper = range.price_ratio_upper.unwrap();
+        assert!(lower < 1.0 && upper > 1.0);
+        assert!((full_range_loss(lower) + 0.1).abs() < 1e-4);
+        assert!((full_range_loss(upper) + 0.1).abs() < 1e-4);
+        // a narrower range breaks even on smaller moves
+        let narrow = breakeven_price_range(
+            I24::ZERO,
+            I24::try_from(-600).unwrap(),
+            I24::try_from(600).unwrap(),
+            0.2,
+            SECONDS_PER_YEAR / 2,
+        )
+        .unwrap();
+        assert!(narrow.price_ratio_lower.unwrap() > lower);
+        assert!(narrow.price_ratio_upper.unwrap() < upper);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_breakeven_time() {
+        let tick_lower = nearest_usable_tick(MIN_TICK, FeeAmount::MEDIUM.tick_spacing());
+        let tick_upper = nearest_usable_tick(MAX_TICK, FeeAmount::MEDIUM.tick_spacing());
+        // a 4x move loses 20% of the value of a full range position
+        let time = breakeven_time(
+            &BigDecimal::from(1),
+            &BigDecimal::from(4),
+            tick_lower,
+            tick_upper,
+            0.2,
+        )
+        .unwrap()
+        .unwrap();
+        let year = SECONDS_PER_YEAR.to_f64().unwrap();
+        assert!((time.to_f64().unwrap() - year).abs() < year * 1e-3);
+        assert_eq!(
+            breakeven_time(
+                &BigDecimal::from(1),
+                &BigDecimal::from(4),
+                tick_lower,
+                tick_upper,
+                0.0
+            )
+            .unwrap(),
+            None
+        );
+    }
+}
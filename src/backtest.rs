@@ -0,0 +1,555 @@
+//! ## Backtest
+//! Replays the historical swaps of a pool against a liquidity providing [`Strategy`], which decides
+//! the range of the position and when to rebalance it, and reports the fees earned, the impermanent
+//! loss and the costs of rebalancing.
+//!
+//! Amounts are raw token amounts and values are in raw units of token1, as `f64`. The position is
+//! assumed to be small enough not to move the price, and the swap that rebalances it into a new
+//! range is executed at the current price, paying the fee of the pool.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{aliases::I24, I256};
+use num_traits::ToPrimitive;
+
+/// The state of the position passed to [`Strategy::rebalance`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrategyState {
+    /// The unix timestamp of the last price update
+    pub timestamp: u64,
+    /// The current tick of the pool
+    pub tick: I24,
+    /// The lower tick of the position
+    pub tick_lower: I24,
+    /// The upper tick of the position
+    pub tick_upper: I24,
+}
+
+impl StrategyState {
+    /// Whether the current tick is in the range of the position.
+    #[inline]
+    #[must_use]
+    pub fn in_range(&self) -> bool {
+        self.tick_lower <= self.tick && self.tick < self.tick_upper
+    }
+}
+
+/// A liquidity providing strategy, which chooses the range of a position and when to move it.
+pub trait Strategy {
+    /// Returns the range to open the position in.
+    ///
+    /// ## Arguments
+    ///
+    /// * `timestamp`: The unix timestamp of the start
+    /// * `tick`: The current tick of the pool
+    fn initial_range(&mut self, timestamp: u64, tick: I24) -> (I24, I24);
+
+    /// Called after each price update, returns the range to move the position to, if any.
+    ///
+    /// ## Arguments
+    ///
+    /// * `state`: The state of the position after the price update
+    fn rebalance(&mut self, state: &StrategyState) -> Option<(I24, I24)>;
+}
+
+/// A strategy that holds a position in a fixed range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PassiveStrategy {
+    /// The lower tick of the range
+    pub tick_lower: I24,
+    /// The upper tick of the range
+    pub tick_upper: I24,
+}
+
+impl Strategy for PassiveStrategy {
+    #[inline]
+    fn initial_range(&mut self, _timestamp: u64, _tick: I24) -> (I24, I24) {
+        (self.tick_lower, self.tick_upper)
+    }
+
+    #[inline]
+    fn rebalance(&mut self, _state: &StrategyState) -> Option<(I24, I24)> {
+        None
+    }
+}
+
+/// A strategy that centers a range of a fixed width around the current tick, and recenters it
+/// whenever the price leaves the range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecenterStrategy {
+    /// The distance from the current tick to each bound of the range
+    pub half_width: I24,
+    /// The tick spacing of the pool
+    pub tick_spacing: I24,
+}
+
+impl RecenterStrategy {
+    fn range(&self, tick: I24) -> (I24, I24) {
+        let tick_lower =
+            nearest_usable_tick((tick - self.half_width).max(MIN_TICK), self.tick_spacing);
+        let tick_upper =
+            nearest_usable_tick((tick + self.half_width).min(MAX_TICK), self.tick_spacing);
+        if tick_lower < tick_upper {
+            (tick_lower, tick_upper)
+        } else {
+            (tick_lower, tick_lower + self.tick_spacing)
+        }
+    }
+}
+
+impl Strategy for RecenterStrategy {
+    #[inline]
+    fn initial_range(&mut self, _timestamp: u64, tick: I24) -> (I24, I24) {
+        self.range(tick)
+    }
+
+    #[inline]
+    fn rebalance(&mut self, state: &StrategyState) -> Option<(I24, I24)> {
+        if state.in_range() {
+            None
+        } else {
+            Some(self.range(state.tick))
+        }
+    }
+}
+
+/// The inputs of a backtest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BacktestConfig {
+    /// The fee tier of the pool
+    pub fee: FeeAmount,
+    /// The raw amount of token0 to start with
+    pub amount0: f64,
+    /// The raw amount of token1 to start with
+    pub amount1: f64,
+    /// The gas cost of each rebalance in raw units of token1
+    pub gas_cost: f64,
+}
+
+/// The performance of a strategy over a backtest, with values in raw units of token1 at the final
+/// price unless stated otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BacktestReport {
+    /// The unix timestamp of the start
+    pub start: u64,
+    /// The unix timestamp of the end
+    pub end: u64,
+    /// The value of the starting amounts at the initial price
+    pub initial_value: f64,
+    /// The value of the starting amounts if they were held instead
+    pub held_value: f64,
+    /// The value of the position at the end
+    pub principal_value: f64,
+    /// The raw amount of token0 earned in fees
+    pub fees0: f64,
+    /// The raw amount of token1 earned in fees
+    pub fees1: f64,
+    /// The value of the fees earned
+    pub fees_value: f64,
+    /// The change of value of the position relative to holding, excluding the costs of
+    /// rebalancing
+    pub impermanent_loss: f64,
+    /// The total gas cost of rebalancing, valued when paid
+    pub gas_costs: f64,
+    /// The total fees paid by the swaps that rebalanced the position, valued when paid
+    pub swap_costs: f64,
+    /// The number of rebalances
+    pub rebalances: usize,
+    /// The fraction of the time the price was in the range of the position
+    pub time_in_range: f64,
+}
+
+impl BacktestReport {
+    /// The profit and loss relative to the initial value.
+    #[inline]
+    #[must_use]
+    pub fn pnl(&self) -> f64 {
+        self.principal_value + self.fees_value - self.initial_value
+    }
+
+    /// The profit and loss relative to holding the starting amounts.
+    #[inline]
+    #[must_use]
+    pub fn pnl_vs_hold(&self) -> f64 {
+        self.principal_value + self.fees_value - self.held_value
+    }
+}
+
+/// Runs a [`Strategy`] over a sequence of price updates, see [`backtest`] to replay swaps.
+#[derive(Clone, Debug)]
+pub struct Backtester<S> {
+    /// The strategy
+    pub strategy: S,
+    config: BacktestConfig,
+    state: StrategyState,
+    sqrt_price: f64,
+    liquidity: f64,
+    start: u64,
+    initial_value: f64,
+    fees0: f64,
+    fees1: f64,
+    gas_costs: f64,
+    swap_costs: f64,
+    rebalances: usize,
+    seconds_in_range: u64,
+}
+
+impl<S: Strategy> Backtester<S> {
+    /// Opens the initial position of a strategy.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config`: The inputs of the backtest
+    /// * `strategy`: The strategy
+    /// * `timestamp`: The unix timestamp of the start
+    /// * `sqrt_price`: The square root of the raw price of token0 in token1
+    /// * `tick`: The current tick of the pool
+    #[inline]
+    pub fn new(
+        config: BacktestConfig,
+        mut strategy: S,
+        timestamp: u64,
+        sqrt_price: f64,
+        tick: I24,
+    ) -> Result<Self, Error> {
+        let (tick_lower, tick_upper) = strategy.initial_range(timestamp, tick);
+        let price = sqrt_price * sqrt_price;
+        let initial_value = config.amount0 * price + config.amount1;
+        let mut backtester = Self {
+            strategy,
+            config,
+            state: StrategyState {
+                timestamp,
+                tick,
+                tick_lower,
+                tick_upper,
+            },
+            sqrt_price,
+            liquidity: 0.0,
+            start: timestamp,
+            initial_value,
+            fees0: 0.0,
+            fees1: 0.0,
+            gas_costs: 0.0,
+            swap_costs: 0.0,
+            rebalances: 0,
+            seconds_in_range: 0,
+        };
+        backtester.open(tick_lower, tick_upper, (config.amount0, config.amount1))?;
+        Ok(backtester)
+    }
+
+    /// The current state of the position.
+    #[inline]
+    #[must_use]
+    pub const fn state(&self) -> &StrategyState {
+        &self.state
+    }
+
+    /// The liquidity of the position.
+    #[inline]
+    #[must_use]
+    pub const fn liquidity(&self) -> f64 {
+        self.liquidity
+    }
+
+    /// Moves the price, credits the position with its share of the fees paid in the range, then
+    /// lets the strategy rebalance.
+    ///
+    /// ## Arguments
+    ///
+    /// * `timestamp`: The unix timestamp of the update
+    /// * `sqrt_price`: The square root of the raw price of token0 in token1 after the update
+    /// * `tick`: The tick of the pool after the update
+    /// * `fees`: The raw amounts of token0 and token1 paid in fees to all liquidity providers in
+    ///   range since the last update
+    /// * `pool_liquidity`: The active liquidity of the pool excluding the position
+    #[inline]
+    pub fn step(
+        &mut self,
+        timestamp: u64,
+        sqrt_price: f64,
+        tick: I24,
+        fees: (f64, f64),
+        pool_liquidity: f64,
+    ) -> Result<(), Error> {
+        if self.state.in_range() {
+            self.seconds_in_range += timestamp.saturating_sub(self.state.timestamp);
+            let share = self.liquidity / (self.liquidity + pool_liquidity);
+            if share.is_finite() {
+                self.fees0 += fees.0 * share;
+                self.fees1 += fees.1 * share;
+            }
+        }
+        self.state.timestamp = timestamp;
+        self.state.tick = tick;
+        self.sqrt_price = sqrt_price;
+        if let Some((tick_lower, tick_upper)) = self.strategy.rebalance(&self.state) {
+            if (tick_lower, tick_upper) != (self.state.tick_lower, self.state.tick_upper) {
+                let amounts = self.amounts();
+                let price = sqrt_price * sqrt_price;
+                self.gas_costs += self.config.gas_cost;
+                // pay the gas in token1, or in token0 if there is not enough token1
+                let amounts = if amounts.1 >= self.config.gas_cost {
+                    (amounts.0, amounts.1 - self.config.gas_cost)
+                } else {
+                    (
+                        (amounts.0 - (self.config.gas_cost - amounts.1) / price).max(0.0),
+                        0.0,
+                    )
+                };
+                self.open(tick_lower, tick_upper, amounts)?;
+                self.rebalances += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports the performance of the strategy so far.
+    #[inline]
+    #[must_use]
+    pub fn report(&self) -> BacktestReport {
+        let price = self.sqrt_price * self.sqrt_price;
+        let (amount0, amount1) = self.amounts();
+        let principal_value = amount0 * price + amount1;
+        let held_value = self.config.amount0 * price + self.config.amount1;
+        // out of order samples may step back before the start
+        let elapsed = self.state.timestamp.saturating_sub(self.start);
+        let time_in_range = if elapsed == 0 {
+            if self.state.in_range() {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            self.seconds_in_range.to_f64().unwrap() / elapsed.to_f64().unwrap()
+        };
+        BacktestReport {
+            start: self.start,
+            end: self.state.timestamp,
+            initial_value: self.initial_value,
+            held_value,
+            principal_value,
+            fees0: self.fees0,
+            fees1: self.fees1,
+            fees_value: self.fees0 * price + self.fees1,
+            impermanent_loss: principal_value - held_value + self.gas_costs + self.swap_costs,
+            gas_costs: self.gas_costs,
+            swap_costs: self.swap_costs,
+            rebalances: self.rebalances,
+            time_in_range,
+        }
+    }
+
+    /// The amounts of token0 and token1 of the position at the current price.
+    fn amounts(&self) -> (f64, f64) {
+        let (amount0, amount1) = unit_amounts(
+            self.sqrt_price,
+            self.state.tick_lower,
+            self.state.tick_upper,
+        );
+        (amount0 * self.liquidity, amount1 * self.liquidity)
+    }
+
+    /// Swaps `amounts` into the ratio of a range at the current price and adds them as liquidity.
+    fn open(&mut self, tick_lower: I24, tick_upper: I24, amounts: (f64, f64)) -> Result<(), Error> {
+        if tick_lower >= tick_upper
+            || CheckedTick::from_i24(tick_lower).is_err()
+            || CheckedTick::from_i24(tick_upper).is_err()
+        {
+            return Err(Error::InvalidRange);
+        }
+        let price = self.sqrt_price * self.sqrt_price;
+        let value = amounts.0 * price + amounts.1;
+        let (unit0, unit1) = unit_amounts(self.sqrt_price, tick_lower, tick_upper);
+        let unit_value = unit0 * price + unit1;
+        // the value of token0 to swap, and the fee paid on it
+        let swapped = (amounts.0 * price - value * unit0 * price / unit_value).abs();
+        let swap_cost = swapped * f64::from(self.config.fee.fee()) / 1e6;
+        self.swap_costs += swap_cost;
+        self.liquidity = (value - swap_cost) / unit_value;
+        self.state.tick_lower = tick_lower;
+        self.state.tick_upper = tick_upper;
+        Ok(())
+    }
+}
+
+/// Replays the swaps of a pool against a strategy.
+///
+/// The position is opened at the price after the first swap. The fees of each later swap are paid
+/// on its input amount, and shared with the active liquidity of the pool before the swap if the
+/// price was in the range of the position.
+///
+/// ## Arguments
+///
+/// * `swaps`: The swaps in the order they were executed, e.g. from [`SwapSample::from_event`]
+/// * `config`: The inputs of the backtest
+/// * `strategy`: The strategy
+///
+/// ## Returns
+///
+/// The report of the strategy, or [`Error::NoSwaps`] if `swaps` is empty
+#[inline]
+pub fn backtest<S: Strategy>(
+    swaps: &[SwapSample],
+    config: BacktestConfig,
+    strategy: S,
+) -> Result<BacktestReport, Error> {
+    let Some(first) = swaps.first() else {
+        return Err(Error::NoSwaps);
+    };
+    let q96 = Q96.to_big_decimal();
+    let sqrt_price = |swap: &SwapSample| {
+        (swap.sqrt_price_x96.to_big_decimal() / &q96)
+            .to_f64()
+            .unwrap()
+    };
+    let to_f64 = |amount: I256| amount.unsigned_abs().to_big_decimal().to_f64().unwrap();
+    let fee_rate = f64::from(config.fee.fee()) / 1e6;
+    let mut backtester = Backtester::new(
+        config,
+        strategy,
+        first.timestamp,
+        sqrt_price(first),
+        get_tick_at_sqrt_ratio(first.sqrt_price_x96)?,
+    )?;
+    for w in swaps.windows(2) {
+        let (previous, swap) = (&w[0], &w[1]);
+        let fees = if swap.amount0.is_positive() {
+            (to_f64(swap.amount0) * fee_rate, 0.0)
+        } else if swap.amount1.is_positive() {
+            (0.0, to_f64(swap.amount1) * fee_rate)
+        } else {
+            (0.0, 0.0)
+        };
+        backtester.step(
+            swap.timestamp,
+            sqrt_price(swap),
+            get_tick_at_sqrt_ratio(swap.sqrt_price_x96)?,
+            fees,
+            previous.liquidity.to_f64().unwrap(),
+        )?;
+    }
+    Ok(backtester.report())
+}
+
+/// The amounts of token0 and token1 of a unit of liquidity in a range at a sqrt price.
+pub(crate) fn unit_amounts(sqrt_price: f64, tick_lower: I24, tick_upper: I24) -> (f64, f64) {
+    let log_sqrt_tick = 1.0001_f64.ln() / 2.0;
+    let sqrt_lower = (f64::from(tick_lower.as_i32()) * log_sqrt_tick).exp();
+    let sqrt_upper = (f64::from(tick_upper.as_i32()) * log_sqrt_tick).exp();
+    let sqrt_price = sqrt_price.clamp(sqrt_lower, sqrt_upper);
+    (1.0 / sqrt_price - 1.0 / sqrt_upper, sqrt_price - sqrt_lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BacktestConfig = BacktestConfig {
+        fee: FeeAmount::MEDIUM,
+        amount0: 1e18,
+        amount1: 1e18,
+        gas_cost: 1e15,
+    };
+
+    fn swap(timestamp: u64, tick: i32, amount0: i128, amount1: i128) -> SwapSample {
+        SwapSample {
+            timestamp,
+            sqrt_price_x96: get_sqrt_ratio_at_tick(I24::try_from(tick).unwrap()).unwrap(),
+            amount0: I256::try_from(amount0).unwrap(),
+            amount1: I256::try_from(amount1).unwrap(),
+            liquidity: 1_000_000_000_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_backtest_no_price_change() {
+        let swaps = [
+            swap(0, 0, 0, 0),
+            swap(60, 0, 1_000_000_000_000_000, -1_000_000_000_000_000),
+            swap(120, 0, -1_000_000_000_000_000, 1_000_000_000_000_000),
+        ];
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        let report = backtest(&swaps, CONFIG, strategy).unwrap();
+        assert_eq!(report.start, 0);
+        assert_eq!(report.end, 120);
+        assert_eq!(report.rebalances, 0);
+        assert!((report.time_in_range - 1.0).abs() < 1e-12);
+        assert!(report.impermanent_loss.abs() < 1e6);
+        assert!(report.fees0 > 0.0 && report.fees1 > 0.0);
+        assert!(report.pnl() > 0.0);
+    }
+
+    #[test]
+    fn test_backtest_price_move() {
+        let swaps = [swap(0, 0, 0, 0), swap(100, -1200, 1_000_000_000_000, 0)];
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        let report = backtest(&swaps, CONFIG, strategy).unwrap();
+        assert!(report.impermanent_loss < 0.0);
+        // the position is entirely in token0 below the range
+        assert!(report.principal_value < report.held_value);
+        assert_eq!(report.rebalances, 0);
+    }
+
+    #[test]
+    fn test_backtest_recenter() {
+        let swaps = [
+            swap(0, 0, 0, 0),
+            swap(100, 1000, 0, 1_000_000_000_000),
+            swap(200, 1100, 0, 1_000_000_000_000),
+            swap(300, -50, 1_000_000_000_000, 0),
+        ];
+        let strategy = RecenterStrategy {
+            half_width: I24::try_from(600).unwrap(),
+            tick_spacing: I24::try_from(60).unwrap(),
+        };
+        let report = backtest(&swaps, CONFIG, strategy).unwrap();
+        assert_eq!(report.rebalances, 2);
+        assert!((report.gas_costs - 2.0 * CONFIG.gas_cost).abs() < 1.0);
+        assert!(report.swap_costs > 0.0);
+        // the position is recentered as soon as the price leaves the range
+        assert!((report.time_in_range - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_backtest_invalid_range() {
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(600).unwrap(),
+            tick_upper: I24::try_from(-600).unwrap(),
+        };
+        assert!(matches!(
+            backtest(&[swap(0, 0, 0, 0)], CONFIG, strategy),
+            Err(Error::InvalidRange)
+        ));
+    }
+
+    #[test]
+    fn test_backtest_no_swaps() {
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        assert!(matches!(
+            backtest(&[], CONFIG, strategy),
+            Err(Error::NoSwaps)
+        ));
+    }
+
+    #[test]
+    fn test_backtest_out_of_order_swaps() {
+        let swaps = [swap(100, 0, 0, 0), swap(0, 60, 1_000_000_000_000, 0)];
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        let report = backtest(&swaps, CONFIG, strategy).unwrap();
+        assert_eq!(report.end, 0);
+        assert!((report.time_in_range - 1.0).abs() < 1e-12);
+    }
+}
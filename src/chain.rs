@@ -0,0 +1,232 @@
+//! ## Chain
+//! The addresses of the Uniswap V3 deployments on each chain, and a [`ChainRegistry`] to look them
+//! up by chain id and register custom deployments.
+
+use crate::utils::PoolDeployment;
+use alloc::collections::BTreeMap;
+use alloy_primitives::{address, Address, ChainId};
+
+/// The addresses of a Uniswap V3 deployment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChainConfig {
+    /// The chain id
+    pub chain_id: ChainId,
+    /// The Uniswap V3 factory
+    pub factory: Address,
+    /// The nonfungible position manager
+    pub nonfungible_position_manager: Address,
+    /// SwapRouter02
+    pub swap_router02: Address,
+    /// The Universal Router
+    pub universal_router: Address,
+    /// QuoterV2
+    pub quoter_v2: Address,
+    /// TickLens
+    pub tick_lens: Address,
+    /// The wrapped native currency, e.g. WETH on Ethereum and WMATIC on Polygon
+    pub weth: Address,
+}
+
+impl ChainConfig {
+    /// Ethereum mainnet.
+    pub const MAINNET: Self = Self {
+        chain_id: 1,
+        factory: address!("1F98431c8aD98523631AE4a59f267346ea31F984"),
+        nonfungible_position_manager: address!("C36442b4a4522E871399CD717aBDD847Ab11FE88"),
+        swap_router02: address!("68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"),
+        universal_router: address!("3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"),
+        quoter_v2: address!("61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+        tick_lens: address!("bfd8137f7d1516D3ea5cA83523914859ec47F573"),
+        weth: address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+    };
+
+    /// OP Mainnet.
+    pub const OPTIMISM: Self = Self {
+        chain_id: 10,
+        universal_router: address!("Cb1355ff08Ab38bBCE60111F1bb2B784bE25D7e8"),
+        weth: address!("4200000000000000000000000000000000000006"),
+        ..Self::MAINNET
+    };
+
+    /// BNB Smart Chain.
+    pub const BNB: Self = Self {
+        chain_id: 56,
+        factory: address!("dB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7"),
+        nonfungible_position_manager: address!("7b8A01B39D58278b5DE7e48c8449c9f4F5170613"),
+        swap_router02: address!("B971eF87ede563556b2ED4b1C0b0019111Dd85d2"),
+        universal_router: address!("4Dae2f939ACf50408e13d58534Ff8c2776d45265"),
+        quoter_v2: address!("78D78E420Da98ad378D7799bE8f4AF69033EB077"),
+        tick_lens: address!("D9270014D396281579760619CCf4c3af0501A47C"),
+        weth: address!("bb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c"),
+    };
+
+    /// Polygon PoS.
+    pub const POLYGON: Self = Self {
+        chain_id: 137,
+        universal_router: address!("ec7BE89e9d109e7e3Fec59c222CF297125FEFda2"),
+        weth: address!("0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270"),
+        ..Self::MAINNET
+    };
+
+    /// Base.
+    pub const BASE: Self = Self {
+        chain_id: 8453,
+        factory: address!("33128a8fC17869897dcE68Ed026d694621f6FDfD"),
+        nonfungible_position_manager: address!("03a520b32C04BF3bEEf7BEb72E919cf822Ed34f1"),
+        swap_router02: address!("2626664c2603336E57B271c5C0b26F421741e481"),
+        universal_router: address!("3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"),
+        quoter_v2: address!("3d4e44Eb1374240CE5F1B871ab261CD16335B76a"),
+        tick_lens: address!("0CdeE061c75D43c82520eD998C23ac2991c9ac6d"),
+        weth: address!("4200000000000000000000000000000000000006"),
+    };
+
+    /// Arbitrum One.
+    pub const ARBITRUM: Self = Self {
+        chain_id: 42161,
+        universal_router: address!("5E325eDA8064b456f4781070C0738d849c824258"),
+        weth: address!("82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+        ..Self::MAINNET
+    };
+
+    /// Celo.
+    pub const CELO: Self = Self {
+        chain_id: 42220,
+        factory: address!("AfE208a311B21f13EF87E33A90049fC17A7acDEc"),
+        nonfungible_position_manager: address!("3d79EdAaBC0EaB6F08ED885C05Fc0B014290D95A"),
+        swap_router02: address!("5615CDAb10dc425a742d643d949a7F474C01abc4"),
+        universal_router: address!("643770E279d5D0733F21d6DC03A8efbABf3255B4"),
+        quoter_v2: address!("82825d0554fA07f7FC52Ab63c961F330fdEFa8E8"),
+        tick_lens: address!("5f115D9113F88e0a0Db1b5033D90D4a9690AcD3D"),
+        weth: address!("471EcE3750Da237f93B8E339c536989b8978a438"),
+    };
+
+    /// Avalanche C-Chain.
+    pub const AVALANCHE: Self = Self {
+        chain_id: 43114,
+        factory: address!("740b1c1de25031C31FF4fC9A62f554A55cdC1baD"),
+        nonfungible_position_manager: address!("655C406EBFa14EE2006250925e54ec43AD184f8B"),
+        swap_router02: address!("bb00FF08d01D300023C629E8fFfFcb65A5a578cE"),
+        universal_router: address!("4Dae2f939ACf50408e13d58534Ff8c2776d45265"),
+        quoter_v2: address!("be0F5544EC67e9B3b2D979aaA43f18Fd87E6257F"),
+        tick_lens: address!("EB9fFC8bf81b4fFd11fb6A63a6B0f098c6e21950"),
+        weth: address!("B31f66AA3C1e785363F0875A1B74E27b85FD66c7"),
+    };
+
+    /// The configs of the official deployments built into this crate.
+    pub const BUILTIN: [Self; 8] = [
+        Self::MAINNET,
+        Self::OPTIMISM,
+        Self::BNB,
+        Self::POLYGON,
+        Self::BASE,
+        Self::ARBITRUM,
+        Self::CELO,
+        Self::AVALANCHE,
+    ];
+
+    /// The deployment of the pools created by the factory.
+    #[inline]
+    #[must_use]
+    pub const fn pool_deployment(&self) -> PoolDeployment {
+        PoolDeployment::uniswap_v3(self.factory)
+    }
+
+    /// Returns the config of the official deployment on a chain, if built in.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    #[inline]
+    #[must_use]
+    pub const fn builtin(chain_id: ChainId) -> Option<Self> {
+        let mut i = 0;
+        while i < Self::BUILTIN.len() {
+            if Self::BUILTIN[i].chain_id == chain_id {
+                return Some(Self::BUILTIN[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+/// A registry of the deployments on each chain, starting with [`ChainConfig::BUILTIN`].
+#[derive(Clone, Debug)]
+pub struct ChainRegistry {
+    configs: BTreeMap<ChainId, ChainConfig>,
+}
+
+impl Default for ChainRegistry {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            configs: ChainConfig::BUILTIN
+                .into_iter()
+                .map(|config| (config.chain_id, config))
+                .collect(),
+        }
+    }
+}
+
+impl ChainRegistry {
+    /// Registers a custom deployment, replacing the config of its chain if any.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config`: The addresses of the deployment
+    #[inline]
+    pub fn register(&mut self, config: ChainConfig) -> Option<ChainConfig> {
+        self.configs.insert(config.chain_id, config)
+    }
+
+    /// Returns the config of a chain, if registered.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    #[inline]
+    #[must_use]
+    pub fn get(&self, chain_id: ChainId) -> Option<&ChainConfig> {
+        self.configs.get(&chain_id)
+    }
+
+    /// The chain ids of all registered deployments, in ascending order.
+    #[inline]
+    pub fn chain_ids(&self) -> impl Iterator<Item = ChainId> + '_ {
+        self.configs.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::FACTORY_ADDRESS;
+
+    #[test]
+    fn test_builtin() {
+        assert_eq!(ChainConfig::builtin(1), Some(ChainConfig::MAINNET));
+        assert_eq!(ChainConfig::MAINNET.factory, FACTORY_ADDRESS);
+        assert_eq!(
+            ChainConfig::builtin(42161).unwrap().factory,
+            FACTORY_ADDRESS
+        );
+        assert_eq!(ChainConfig::builtin(31337), None);
+        for config in ChainConfig::BUILTIN {
+            assert_eq!(ChainConfig::builtin(config.chain_id), Some(config));
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = ChainRegistry::default();
+        assert_eq!(registry.get(8453), Some(&ChainConfig::BASE));
+        assert_eq!(registry.get(31337), None);
+        let devnet = ChainConfig {
+            chain_id: 31337,
+            ..ChainConfig::MAINNET
+        };
+        assert_eq!(registry.register(devnet), None);
+        assert_eq!(registry.get(31337), Some(&devnet));
+        assert_eq!(registry.chain_ids().count(), ChainConfig::BUILTIN.len() + 1);
+    }
+}
@@ -1,30 +1,122 @@
+use crate::{error::Error, utils::FeePips};
 use alloy_primitives::{
     address,
     aliases::{I24, U24},
     b256, Address, B256,
 };
+use core::hash::{Hash, Hasher};
 
 pub const FACTORY_ADDRESS: Address = address!("1F98431c8aD98523631AE4a59f267346ea31F984");
 
 pub const POOL_INIT_CODE_HASH: B256 =
     b256!("e34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54");
 
-/// The default factory enabled fee amounts, denominated in hundredths of bips.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[repr(u32)]
+/// Used by SwapRouter02 as a recipient placeholder for `msg.sender`.
+pub const MSG_SENDER: Address = address!("0000000000000000000000000000000000000001");
+
+/// Used by SwapRouter02 as a recipient placeholder for the router itself.
+pub const ADDRESS_THIS: Address = address!("0000000000000000000000000000000000000002");
+
+/// The number of seconds in a year of 365 days.
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// The fee amounts enabled in the factory, denominated in hundredths of bips. Tiers other than the
+/// default ones, e.g. those added by governance or by forks, are represented by
+/// [`FeeAmount::Custom`].
+///
+/// Fee amounts are compared and hashed by their fee and tick spacing, so that a
+/// [`FeeAmount::Custom`] matching a default tier equals it.
+#[derive(Debug, Clone, Copy)]
 #[allow(non_camel_case_types)]
 pub enum FeeAmount {
-    LOWEST = 100,
-    LOW_200 = 200,
-    LOW_300 = 300,
-    LOW_400 = 400,
-    LOW = 500,
-    MEDIUM = 3000,
-    HIGH = 10000,
+    LOWEST,
+    LOW_200,
+    LOW_300,
+    LOW_400,
+    LOW,
+    MEDIUM,
+    HIGH,
+    /// A fee tier with a non-default fee or tick spacing
+    Custom {
+        /// The fee in hundredths of bips, validated to fit the factory's 24 bit fee
+        fee: FeePips,
+        /// The tick spacing
+        tick_spacing: I24,
+    },
 }
 
 impl FeeAmount {
-    /// The default factory tick spacings by fee amount.
+    /// All known fee amounts, in ascending order. Only `LOWEST`, `LOW`, `MEDIUM` and `HIGH` are
+    /// enabled on every chain.
+    pub const ALL: [Self; 7] = [
+        Self::LOWEST,
+        Self::LOW_200,
+        Self::LOW_300,
+        Self::LOW_400,
+        Self::LOW,
+        Self::MEDIUM,
+        Self::HIGH,
+    ];
+
+    /// Creates a fee amount from a fee and tick spacing, which is one of the default tiers if
+    /// both match it and [`FeeAmount::Custom`] otherwise.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fee`: The fee in hundredths of bips
+    /// * `tick_spacing`: The tick spacing
+    #[inline]
+    #[must_use]
+    pub fn new(fee: FeePips, tick_spacing: I24) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|tier| tier.fee() == fee.as_u32() && tier.tick_spacing() == tick_spacing)
+            .unwrap_or(Self::Custom { fee, tick_spacing })
+    }
+
+    /// Creates a fee amount like [`FeeAmount::new`], validating the fee and tick spacing as the
+    /// factory does when enabling a tier, e.g. for tiers read from untrusted input.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fee`: The fee in hundredths of bips, below 1,000,000
+    /// * `tick_spacing`: The tick spacing, between 1 and 16383
+    #[inline]
+    pub fn try_new(fee: u32, tick_spacing: I24) -> Result<Self, Error> {
+        let fee = FeePips::new(fee)?;
+        if tick_spacing <= I24::ZERO || tick_spacing >= I24::from_limbs([16384]) {
+            return Err(Error::InvalidTickSpacing(tick_spacing));
+        }
+        Ok(Self::new(fee, tick_spacing))
+    }
+
+    /// The fee in hundredths of bips, validated to be below 100%.
+    #[inline]
+    #[must_use]
+    pub fn fee_pips(&self) -> FeePips {
+        match self {
+            Self::Custom { fee, .. } => *fee,
+            _ => FeePips::new(self.fee()).unwrap(),
+        }
+    }
+
+    /// The fee in hundredths of bips.
+    #[inline]
+    #[must_use]
+    pub const fn fee(&self) -> u32 {
+        match self {
+            Self::LOWEST => 100,
+            Self::LOW_200 => 200,
+            Self::LOW_300 => 300,
+            Self::LOW_400 => 400,
+            Self::LOW => 500,
+            Self::MEDIUM => 3000,
+            Self::HIGH => 10000,
+            Self::Custom { fee, .. } => fee.as_u32(),
+        }
+    }
+
+    /// The default factory tick spacings by fee amount, or the tick spacing of a custom tier.
     #[inline]
     #[must_use]
     pub const fn tick_spacing(&self) -> I24 {
@@ -36,10 +128,30 @@ impl FeeAmount {
             Self::LOW => I24::from_limbs([10]),
             Self::MEDIUM => I24::from_limbs([60]),
             Self::HIGH => I24::from_limbs([200]),
+            Self::Custom { tick_spacing, .. } => *tick_spacing,
         }
     }
 }
 
+impl PartialEq for FeeAmount {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.fee() == other.fee() && self.tick_spacing() == other.tick_spacing()
+    }
+}
+
+impl Eq for FeeAmount {}
+
+impl Hash for FeeAmount {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fee().hash(state);
+        self.tick_spacing().hash(state);
+    }
+}
+
+/// Panics if `fee` is not one of the default tiers, whose tick spacing is known. Use
+/// [`FeeAmount::new`] for other tiers.
 impl From<u32> for FeeAmount {
     #[inline]
     fn from(fee: u32) -> Self {
@@ -75,13 +187,54 @@ impl From<i32> for FeeAmount {
 impl From<FeeAmount> for U24 {
     #[inline]
     fn from(fee: FeeAmount) -> Self {
-        Self::from_limbs([fee as u64])
+        fee.fee_pips().get()
+    }
+}
+
+/// Fails with [`Error::InvalidFee`] if `fee` is not one of the default tiers, whose tick spacing is
+/// known. Use [`FeeAmount::try_new`] for other tiers.
+impl TryFrom<U24> for FeeAmount {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(fee: U24) -> Result<Self, Self::Error> {
+        let fee = FeePips::try_from(fee)?;
+        Self::ALL
+            .into_iter()
+            .find(|tier| tier.fee() == fee.as_u32())
+            .ok_or(Error::InvalidFee(fee.as_u32()))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FeeAmountRepr {
+    fee: u32,
+    tick_spacing: i32,
+}
+
+/// Serializes as the fee and tick spacing, so that custom tiers round trip.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FeeAmount {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(
+            &FeeAmountRepr {
+                fee: self.fee(),
+                tick_spacing: self.tick_spacing().as_i32(),
+            },
+            serializer,
+        )
     }
 }
 
-impl From<U24> for FeeAmount {
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FeeAmount {
     #[inline]
-    fn from(fee: U24) -> Self {
-        (fee.into_limbs()[0] as u32).into()
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: FeeAmountRepr = serde::Deserialize::deserialize(deserializer)?;
+        let tick_spacing = I24::try_from(repr.tick_spacing).map_err(serde::de::Error::custom)?;
+        let fee = FeePips::new(repr.fee).map_err(serde::de::Error::custom)?;
+        Ok(Self::new(fee, tick_spacing))
     }
 }
@@ -1,25 +1,54 @@
+use super::tick_list_data_provider::{read_array, read_tick_index};
 use crate::prelude::{Error, *};
-use alloy_primitives::{ChainId, B256, I256, U160};
-use once_cell::sync::Lazy;
+use alloc::boxed::Box;
+use alloy_primitives::{aliases::I24, Address, ChainId, B256, I256, U160, U256};
+use once_cell::race::OnceBox;
 use uniswap_sdk_core::prelude::*;
 
-static _Q192: Lazy<BigUint> = Lazy::new(|| Q192.to_big_uint());
+static _Q192: OnceBox<BigUint> = OnceBox::new();
+
+/// [`Q192`] as a [`BigUint`], converted once.
+fn q192() -> BigUint {
+    _Q192.get_or_init(|| Box::new(Q192.to_big_uint())).clone()
+}
 
 /// Represents a V3 pool
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pool<TP = NoTickDataProvider>
 where
     TP: TickDataProvider,
 {
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::token"))]
     pub token0: Token,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::token"))]
     pub token1: Token,
     pub fee: FeeAmount,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::decimal"))]
     pub sqrt_ratio_x96: U160,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::decimal"))]
     pub liquidity: u128,
     pub tick_current: TP::Index,
     pub tick_data_provider: TP,
 }
 
+/// The result of a swap simulated off-chain against the pool state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapResult<I = i32> {
+    /// The amount of the input token spent, including fees
+    pub amount_in: U256,
+    /// The amount of the output token received
+    pub amount_out: U256,
+    /// The fees paid in the input token
+    pub fee_amount: U256,
+    /// The sqrt price of the pool after the swap
+    pub sqrt_price_x96_after: U160,
+    /// The tick of the pool after the swap, as stored in `slot0` on-chain
+    pub tick_after: I,
+    /// The in range liquidity of the pool after the swap
+    pub liquidity_after: u128,
+}
+
 impl<TP> PartialEq for Pool<TP>
 where
     TP: TickDataProvider<Index: PartialEq>,
@@ -130,6 +159,16 @@ impl<TP: TickDataProvider> Pool<TP> {
         )
     }
 
+    /// Returns the address of the pool in a deployment of Uniswap V3 or a fork of it
+    ///
+    /// ## Arguments
+    ///
+    /// * `deployment`: The deployment the pool belongs to
+    #[inline]
+    pub fn deployment_address(&self, deployment: &PoolDeployment) -> Address {
+        deployment.pool_address(self.token0.address(), self.token1.address(), self.fee)
+    }
+
     #[inline]
     pub fn chain_id(&self) -> ChainId {
         self.token0.chain_id()
@@ -160,7 +199,7 @@ impl<TP: TickDataProvider> Pool<TP> {
         Price::new(
             self.token0.clone(),
             self.token1.clone(),
-            _Q192.clone(),
+            q192(),
             &sqrt_ratio_x96 * &sqrt_ratio_x96,
         )
     }
@@ -174,7 +213,7 @@ impl<TP: TickDataProvider> Pool<TP> {
             self.token1.clone(),
             self.token0.clone(),
             &sqrt_ratio_x96 * &sqrt_ratio_x96,
-            _Q192.clone(),
+            q192(),
         )
     }
 
@@ -251,6 +290,159 @@ impl<TP: TickDataProvider> Pool<TP> {
             sqrt_price_limit_x96,
         )
     }
+
+    /// Simulates an exact input swap off-chain, stepping through initialized ticks exactly as the
+    /// pool contract does
+    ///
+    /// If the price limit is reached before the whole input is consumed, the swap is partially
+    /// filled and [`SwapResult::amount_in`] is less than `amount_in`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `zero_for_one`: Whether the swap is from token0 to token1
+    /// * `amount_in`: The exact amount of the input token to swap, including fees
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit
+    #[inline]
+    pub fn simulate_swap_exact_in(
+        &self,
+        zero_for_one: bool,
+        amount_in: U256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapResult<TP::Index>, Error> {
+        let amount_specified = I256::try_from(amount_in).map_err(|_| Error::InvalidAmount)?;
+        let state = self._swap(zero_for_one, amount_specified, sqrt_price_limit_x96)?;
+        Ok(SwapResult {
+            amount_in: (amount_specified - state.amount_specified_remaining).into_raw(),
+            amount_out: (-state.amount_calculated).into_raw(),
+            fee_amount: state.fee_amount,
+            sqrt_price_x96_after: state.sqrt_price_x96,
+            tick_after: state.tick_current,
+            liquidity_after: state.liquidity,
+        })
+    }
+
+    /// Simulates an exact output swap off-chain, stepping through initialized ticks exactly as the
+    /// pool contract does
+    ///
+    /// If the price limit is reached before the whole output is received, the swap is partially
+    /// filled and [`SwapResult::amount_out`] is less than `amount_out`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `zero_for_one`: Whether the swap is from token0 to token1
+    /// * `amount_out`: The exact amount of the output token to receive
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit
+    #[inline]
+    pub fn simulate_swap_exact_out(
+        &self,
+        zero_for_one: bool,
+        amount_out: U256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapResult<TP::Index>, Error> {
+        let amount_specified = -I256::try_from(amount_out).map_err(|_| Error::InvalidAmount)?;
+        let state = self._swap(zero_for_one, amount_specified, sqrt_price_limit_x96)?;
+        Ok(SwapResult {
+            amount_in: state.amount_calculated.into_raw(),
+            amount_out: (state.amount_specified_remaining - amount_specified).into_raw(),
+            fee_amount: state.fee_amount,
+            sqrt_price_x96_after: state.sqrt_price_x96,
+            tick_after: state.tick_current,
+            liquidity_after: state.liquidity,
+        })
+    }
+
+    async fn _swap_async<ATP: AsyncTickDataProvider<Index = TP::Index>>(
+        &self,
+        tick_data_provider: &mut ATP,
+        zero_for_one: bool,
+        amount_specified: I256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapState<TP::Index>, Error> {
+        v3_swap_async(
+            self.fee.into(),
+            self.sqrt_ratio_x96,
+            self.tick_current,
+            self.liquidity,
+            self.tick_spacing(),
+            tick_data_provider,
+            zero_for_one,
+            amount_specified,
+            sqrt_price_limit_x96,
+        )
+        .await
+    }
+
+    /// Simulates an exact input swap off-chain like [`Self::simulate_swap_exact_in`], loading
+    /// ticks from an [`AsyncTickDataProvider`] instead of the tick data provider of the pool
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick_data_provider`: The provider of the ticks of the pool, which may fetch them lazily
+    /// * `zero_for_one`: Whether the swap is from token0 to token1
+    /// * `amount_in`: The exact amount of the input token to swap, including fees
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit
+    #[inline]
+    pub async fn simulate_swap_exact_in_async<ATP: AsyncTickDataProvider<Index = TP::Index>>(
+        &self,
+        tick_data_provider: &mut ATP,
+        zero_for_one: bool,
+        amount_in: U256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapResult<TP::Index>, Error> {
+        let amount_specified = I256::try_from(amount_in).map_err(|_| Error::InvalidAmount)?;
+        let state = self
+            ._swap_async(
+                tick_data_provider,
+                zero_for_one,
+                amount_specified,
+                sqrt_price_limit_x96,
+            )
+            .await?;
+        Ok(SwapResult {
+            amount_in: (amount_specified - state.amount_specified_remaining).into_raw(),
+            amount_out: (-state.amount_calculated).into_raw(),
+            fee_amount: state.fee_amount,
+            sqrt_price_x96_after: state.sqrt_price_x96,
+            tick_after: state.tick_current,
+            liquidity_after: state.liquidity,
+        })
+    }
+
+    /// Simulates an exact output swap off-chain like [`Self::simulate_swap_exact_out`], loading
+    /// ticks from an [`AsyncTickDataProvider`] instead of the tick data provider of the pool
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick_data_provider`: The provider of the ticks of the pool, which may fetch them lazily
+    /// * `zero_for_one`: Whether the swap is from token0 to token1
+    /// * `amount_out`: The exact amount of the output token to receive
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit
+    #[inline]
+    pub async fn simulate_swap_exact_out_async<ATP: AsyncTickDataProvider<Index = TP::Index>>(
+        &self,
+        tick_data_provider: &mut ATP,
+        zero_for_one: bool,
+        amount_out: U256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapResult<TP::Index>, Error> {
+        let amount_specified = -I256::try_from(amount_out).map_err(|_| Error::InvalidAmount)?;
+        let state = self
+            ._swap_async(
+                tick_data_provider,
+                zero_for_one,
+                amount_specified,
+                sqrt_price_limit_x96,
+            )
+            .await?;
+        Ok(SwapResult {
+            amount_in: state.amount_calculated.into_raw(),
+            amount_out: (state.amount_specified_remaining - amount_specified).into_raw(),
+            fee_amount: state.fee_amount,
+            sqrt_price_x96_after: state.sqrt_price_x96,
+            tick_after: state.tick_current,
+            liquidity_after: state.liquidity,
+        })
+    }
 }
 
 impl<TP: Clone + TickDataProvider> Pool<TP> {
@@ -363,6 +555,117 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
     }
 }
 
+/// The magic bytes and version of pool snapshots
+const SNAPSHOT_MAGIC: [u8; 4] = *b"UV3P";
+const SNAPSHOT_VERSION: u8 = 1;
+
+impl<I: TickIndex> Pool<TickListDataProvider<I>> {
+    /// Encodes the pool and its ticks into a compact binary snapshot, so that the pool can be
+    /// checkpointed to disk and [restored](Self::restore) without fetching the ticks again.
+    ///
+    /// Integers are encoded as fixed-width big-endian bytes and the symbol and name of each token
+    /// as length-prefixed UTF-8 strings.
+    #[inline]
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(160 + 36 * self.tick_data_provider.len());
+        bytes.extend_from_slice(&SNAPSHOT_MAGIC);
+        bytes.push(SNAPSHOT_VERSION);
+        for token in [&self.token0, &self.token1] {
+            bytes.extend_from_slice(&token.chain_id().to_be_bytes());
+            bytes.extend_from_slice(token.address().as_slice());
+            bytes.push(token.decimals);
+            for text in [&token.symbol, &token.name] {
+                match text {
+                    Some(text) => {
+                        let len = text.len().min(u16::MAX as usize - 1);
+                        bytes.extend_from_slice(&(len as u16 + 1).to_be_bytes());
+                        bytes.extend_from_slice(&text.as_bytes()[..len]);
+                    }
+                    None => bytes.extend_from_slice(&0_u16.to_be_bytes()),
+                }
+            }
+        }
+        bytes.extend_from_slice(&self.fee.fee().to_be_bytes());
+        bytes.extend_from_slice(&self.fee.tick_spacing().as_i32().to_be_bytes());
+        bytes.extend_from_slice(&self.sqrt_ratio_x96.to_be_bytes::<20>());
+        bytes.extend_from_slice(&self.liquidity.to_be_bytes());
+        bytes.extend_from_slice(&self.tick_current.to_i24().as_i32().to_be_bytes());
+        self.tick_data_provider.encode(&mut bytes);
+        bytes
+    }
+
+    /// Restores a pool from a snapshot produced by [`Self::snapshot`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes`: The snapshot
+    ///
+    /// ## Returns
+    ///
+    /// The pool, or [`Error::InvalidSnapshot`] if the bytes are truncated, corrupt or of another
+    /// version
+    #[inline]
+    pub fn restore(mut bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = &mut bytes;
+        if read_array::<4>(bytes)? != SNAPSHOT_MAGIC
+            || read_array::<1>(bytes)? != [SNAPSHOT_VERSION]
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+        let mut tokens = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let chain_id = u64::from_be_bytes(read_array(bytes)?);
+            if chain_id == 0 {
+                return Err(Error::InvalidSnapshot);
+            }
+            let address = Address::from(read_array::<20>(bytes)?);
+            let [decimals] = read_array(bytes)?;
+            let mut texts = [None, None];
+            for text in &mut texts {
+                let len = u16::from_be_bytes(read_array(bytes)?) as usize;
+                if len == 0 {
+                    continue;
+                }
+                let (head, tail) = bytes
+                    .split_at_checked(len - 1)
+                    .ok_or(Error::InvalidSnapshot)?;
+                *text = Some(String::from_utf8(head.to_vec()).map_err(|_| Error::InvalidSnapshot)?);
+                *bytes = tail;
+            }
+            let [symbol, name] = texts;
+            tokens.push(Token::new(
+                chain_id, address, decimals, symbol, name, None, None,
+            ));
+        }
+        let fee = FeePips::new(u32::from_be_bytes(read_array(bytes)?))
+            .map_err(|_| Error::InvalidSnapshot)?;
+        let tick_spacing = I24::try_from(i32::from_be_bytes(read_array(bytes)?))
+            .map_err(|_| Error::InvalidSnapshot)?;
+        if tick_spacing <= I24::ZERO {
+            return Err(Error::InvalidSnapshot);
+        }
+        let sqrt_ratio_x96 = U160::from_be_bytes(read_array::<20>(bytes)?);
+        let liquidity = u128::from_be_bytes(read_array(bytes)?);
+        let tick_current = read_tick_index(bytes)?;
+        let tick_data_provider = TickListDataProvider::decode(bytes)?;
+        if !bytes.is_empty() {
+            return Err(Error::InvalidSnapshot);
+        }
+        let token1 = tokens.pop().unwrap();
+        let token0 = tokens.pop().unwrap();
+        Ok(Self {
+            token0,
+            token1,
+            fee: FeeAmount::new(fee, tick_spacing),
+            sqrt_ratio_x96,
+            liquidity,
+            tick_current,
+            tick_data_provider,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,6 +675,7 @@ mod tests {
 
     mod constructor {
         use super::*;
+        use alloy_primitives::aliases::{I24, U24};
 
         #[test]
         #[should_panic(expected = "CHAIN_IDS")]
@@ -410,6 +714,40 @@ mod tests {
             let weth9 = WETH9::default().get(1).unwrap().clone();
             Pool::new(USDC.clone(), weth9, FeeAmount::HIGH, ONE_ETHER, 0).unwrap();
         }
+
+        #[test]
+        fn works_with_a_custom_fee_tier() {
+            let fee = FeeAmount::try_new(2500, I24::from_limbs([50])).unwrap();
+            assert_eq!(
+                fee,
+                FeeAmount::Custom {
+                    fee: FeePips::new(2500).unwrap(),
+                    tick_spacing: I24::from_limbs([50])
+                }
+            );
+            let pool = Pool::new(USDC.clone(), DAI.clone(), fee, ONE_ETHER, 0).unwrap();
+            assert_eq!(pool.tick_spacing().to_i24(), I24::from_limbs([50]));
+            assert_eq!(pool.fee.fee_pips().get(), U24::from_limbs([2500]));
+            assert_eq!(
+                pool.address(None, None),
+                compute_pool_address(
+                    FACTORY_ADDRESS,
+                    USDC.address(),
+                    DAI.address(),
+                    fee,
+                    None,
+                    None
+                )
+            );
+            assert_ne!(
+                pool.address(None, None),
+                Pool::get_address(&USDC, &DAI, FeeAmount::MEDIUM, None, None)
+            );
+            assert_eq!(
+                FeeAmount::try_new(500, I24::from_limbs([10])).unwrap(),
+                FeeAmount::LOW
+            );
+        }
     }
 
     #[test]
@@ -581,6 +919,7 @@ mod tests {
     mod swaps {
         use super::*;
         use crate::utils::tick_math::{MAX_TICK, MIN_TICK};
+        use once_cell::sync::Lazy;
 
         static POOL: Lazy<Pool<TickListDataProvider>> = Lazy::new(|| {
             Pool::new_with_tick_data_provider(
@@ -632,6 +971,103 @@ mod tests {
             assert_eq!(output_amount.quotient(), 98.into());
         }
 
+        #[test]
+        fn simulate_swap_exact_in_usdc_to_dai() {
+            let zero_for_one = POOL.token0.equals(&USDC.clone());
+            let result = POOL
+                .simulate_swap_exact_in(zero_for_one, U256::from(100), None)
+                .unwrap();
+            assert_eq!(result.amount_in, U256::from(100));
+            assert_eq!(result.amount_out, U256::from(98));
+            assert_eq!(result.fee_amount, U256::from(1));
+            assert_eq!(result.liquidity_after, POOL.liquidity);
+            let (output_amount, pool_after) = POOL
+                .get_output_amount(
+                    &CurrencyAmount::from_raw_amount(USDC.clone(), 100).unwrap(),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(output_amount.quotient(), 98.into());
+            assert_eq!(result.sqrt_price_x96_after, pool_after.sqrt_ratio_x96);
+        }
+
+        #[test]
+        fn simulate_swap_exact_out_usdc_to_dai() {
+            let zero_for_one = POOL.token0.equals(&USDC.clone());
+            let result = POOL
+                .simulate_swap_exact_out(zero_for_one, U256::from(98), None)
+                .unwrap();
+            assert_eq!(result.amount_in, U256::from(100));
+            assert_eq!(result.amount_out, U256::from(98));
+            assert_eq!(result.fee_amount, U256::from(1));
+        }
+
+        #[test]
+        fn simulate_swap_exact_in_partial_fill_at_price_limit() {
+            let zero_for_one = POOL.token0.equals(&USDC.clone());
+            let sqrt_price_limit_x96 = if zero_for_one {
+                encode_sqrt_ratio_x96(99, 100)
+            } else {
+                encode_sqrt_ratio_x96(101, 100)
+            };
+            let result = POOL
+                .simulate_swap_exact_in(
+                    zero_for_one,
+                    U256::from(ONE_ETHER),
+                    Some(sqrt_price_limit_x96),
+                )
+                .unwrap();
+            assert!(result.amount_in < U256::from(ONE_ETHER));
+            assert_eq!(result.sqrt_price_x96_after, sqrt_price_limit_x96);
+        }
+
+        /// Serves the ticks of [`POOL`] through the async interface
+        struct AsyncTicks<'a>(&'a TickListDataProvider);
+
+        impl AsyncTickDataProvider for AsyncTicks<'_> {
+            type Index = i32;
+
+            async fn get_tick(&mut self, tick: i32) -> Result<Tick, Error> {
+                self.0.get_tick(tick).copied()
+            }
+
+            async fn next_initialized_tick_within_one_word(
+                &mut self,
+                tick: i32,
+                lte: bool,
+                tick_spacing: i32,
+            ) -> Result<(i32, bool), Error> {
+                self.0
+                    .next_initialized_tick_within_one_word(tick, lte, tick_spacing)
+            }
+        }
+
+        #[tokio::test]
+        async fn simulate_swap_async_matches_sync() {
+            let mut ticks = AsyncTicks(&POOL.tick_data_provider);
+            for zero_for_one in [true, false] {
+                let amount = U256::from(ONE_ETHER) * U256::from(3);
+                let result = POOL
+                    .simulate_swap_exact_in_async(&mut ticks, zero_for_one, amount, None)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    result,
+                    POOL.simulate_swap_exact_in(zero_for_one, amount, None)
+                        .unwrap()
+                );
+                let result = POOL
+                    .simulate_swap_exact_out_async(&mut ticks, zero_for_one, U256::from(98), None)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    result,
+                    POOL.simulate_swap_exact_out(zero_for_one, U256::from(98), None)
+                        .unwrap()
+                );
+            }
+        }
+
         #[test]
         fn get_input_amount_usdc_to_dai() {
             let (input_amount, _) = POOL
@@ -656,4 +1092,37 @@ mod tests {
             assert_eq!(input_amount.quotient(), 100.into());
         }
     }
+
+    mod snapshot {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let pool = make_pool(USDC.clone(), WETH.clone());
+            let bytes = pool.snapshot();
+            let restored = Pool::<TickListDataProvider>::restore(&bytes).unwrap();
+            assert_eq!(restored, pool);
+            assert_eq!(restored.tick_data_provider, pool.tick_data_provider);
+            assert_eq!(restored.token0.symbol, pool.token0.symbol);
+            assert_eq!(restored.token1.name, pool.token1.name);
+            assert_eq!(restored.snapshot(), bytes);
+        }
+
+        #[test]
+        fn rejects_invalid_snapshots() {
+            let bytes = make_pool(USDC.clone(), WETH.clone()).snapshot();
+            for invalid in [&bytes[..bytes.len() - 1], &bytes[1..], &bytes[..0]] {
+                assert!(matches!(
+                    Pool::<TickListDataProvider>::restore(invalid),
+                    Err(Error::InvalidSnapshot)
+                ));
+            }
+            let mut bytes = bytes;
+            bytes.push(0);
+            assert!(matches!(
+                Pool::<TickListDataProvider>::restore(&bytes),
+                Err(Error::InvalidSnapshot)
+            ));
+        }
+    }
 }
@@ -1,9 +1,13 @@
-use crate::prelude::{Error, *};
-use alloy_primitives::{U160, U256};
+use crate::{
+    prelude::{Error, *},
+    utils::tick_math::saturating_i24,
+};
+use alloy_primitives::{aliases::I24, U160, U256};
 use uniswap_sdk_core::prelude::*;
 
 /// Represents a position on a Uniswap V3 Pool
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position<TP = NoTickDataProvider>
 where
     TP: TickDataProvider,
@@ -11,9 +15,13 @@ where
     pub pool: Pool<TP>,
     pub tick_lower: TP::Index,
     pub tick_upper: TP::Index,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::decimal"))]
     pub liquidity: u128,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _token0_amount: Option<CurrencyAmount<Token>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _token1_amount: Option<CurrencyAmount<Token>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _mint_amounts: Option<MintAmounts>,
 }
 
@@ -74,6 +82,47 @@ impl<TP: TickDataProvider> Position<TP> {
         }
     }
 
+    /// Constructs a position for a given pool with the given liquidity, validating the ticks and
+    /// liquidity instead of panicking like [`Position::new`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: For which pool the liquidity is assigned
+    /// * `liquidity`: The amount of liquidity that is in the position, which must not be zero
+    /// * `tick_lower`: The lower tick of the position
+    /// * `tick_upper`: The upper tick of the position
+    ///
+    /// ## Returns
+    ///
+    /// The position, or
+    /// * [`Error::InvalidRange`] if `tick_lower` is not below `tick_upper`
+    /// * [`Error::TickOutOfBounds`] if a tick is outside of [`MIN_TICK`] and [`MAX_TICK`]
+    /// * [`Error::UnalignedTick`] if a tick is not a multiple of the tick spacing
+    /// * [`Error::ZeroLiquidity`] if `liquidity` is zero
+    ///
+    /// where the tick errors include the nearest usable tick as a suggestion
+    #[inline]
+    pub fn try_new(
+        pool: Pool<TP>,
+        liquidity: u128,
+        tick_lower: TP::Index,
+        tick_upper: TP::Index,
+    ) -> Result<Self, Error> {
+        let tick_spacing = pool.tick_spacing().to_i24();
+        if tick_spacing <= I24::ZERO {
+            return Err(Error::InvalidTickSpacing(tick_spacing));
+        }
+        if tick_lower >= tick_upper {
+            return Err(Error::InvalidRange);
+        }
+        validate_tick(tick_lower, tick_spacing.as_i32())?;
+        validate_tick(tick_upper, tick_spacing.as_i32())?;
+        if liquidity == 0 {
+            return Err(Error::ZeroLiquidity);
+        }
+        Ok(Self::new(pool, liquidity, tick_lower, tick_upper))
+    }
+
     /// Returns the price of token0 at the lower tick
     #[inline]
     pub fn token0_price_lower(&self) -> Result<Price<Token, Token>, Error> {
@@ -94,6 +143,47 @@ impl<TP: TickDataProvider> Position<TP> {
         )
     }
 
+    /// Returns whether the current tick of the pool is within the range of the position, i.e. the
+    /// position is earning fees
+    #[inline]
+    #[must_use]
+    pub fn is_in_range(&self) -> bool {
+        self.tick_lower <= self.pool.tick_current && self.pool.tick_current < self.tick_upper
+    }
+
+    /// Returns the relative distance of the current price of token0 above the price at the lower
+    /// tick, which is negative if the price is below the range
+    #[inline]
+    pub fn distance_to_lower(&self) -> Result<Percent, Error> {
+        let price_lower = get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?
+            .to_big_int()
+            .pow(2);
+        let price = self.pool.sqrt_ratio_x96.to_big_int().pow(2);
+        Ok(Percent::new(&price - price_lower, price))
+    }
+
+    /// Returns the relative distance of the current price of token0 below the price at the upper
+    /// tick, which is negative if the price is above the range
+    #[inline]
+    pub fn distance_to_upper(&self) -> Result<Percent, Error> {
+        let price_upper = get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?
+            .to_big_int()
+            .pow(2);
+        let price = self.pool.sqrt_ratio_x96.to_big_int().pow(2);
+        Ok(Percent::new(price_upper - &price, price))
+    }
+
+    /// Returns the share of the active liquidity of the pool provided by the position, which is
+    /// zero if the position is out of range
+    #[inline]
+    #[must_use]
+    pub fn active_liquidity_share(&self) -> Percent {
+        if !self.is_in_range() || self.pool.liquidity == 0 {
+            return Percent::default();
+        }
+        Percent::new(self.liquidity, self.pool.liquidity)
+    }
+
     /// Returns the amount of token0 that this position's liquidity could be burned for at the
     /// current pool price
     #[inline]
@@ -182,6 +272,67 @@ impl<TP: TickDataProvider> Position<TP> {
         Ok(amount)
     }
 
+    /// Returns the value of the amounts this position's liquidity could be burned for at the
+    /// current pool price plus the uncollected fees, expressed in `quote_token`
+    ///
+    /// ## Arguments
+    ///
+    /// * `quote_token`: The token to express the value in, one of the tokens in the pool
+    /// * `pool_price`: The price of token0 in token1 to value the amounts at
+    /// * `tokens_owed`: The uncollected fees of token0 and token1 owed to the position
+    #[inline]
+    pub fn value_in(
+        &self,
+        quote_token: &Token,
+        pool_price: &Price<Token, Token>,
+        tokens_owed: (U256, U256),
+    ) -> Result<CurrencyAmount<Token>, Error> {
+        value_in_quote(
+            self.amount0()?.quotient() + tokens_owed.0.to_big_int(),
+            self.amount1()?.quotient() + tokens_owed.1.to_big_int(),
+            pool_price,
+            quote_token,
+        )
+    }
+
+    /// Returns the share of the value of the position held in token0 if the pool were at the given
+    /// price, while the rest is held in token1, e.g. to hedge the position or preview how the range
+    /// behaves as the price moves
+    ///
+    /// ## Arguments
+    ///
+    /// * `price`: The price of either token of the pool in terms of the other
+    ///
+    /// ## Returns
+    ///
+    /// The share of the value in token0, [`Error::InvalidToken`] if the price is not of the tokens
+    /// of the pool or [`Error::ZeroLiquidity`] if the position has no value
+    #[inline]
+    pub fn token_ratio_at(&self, price: &Price<Token, Token>) -> Result<Percent, Error> {
+        let (token0, token1) = (&self.pool.token0, &self.pool.token1);
+        // the price of token0 in token1 as a ratio of raw amounts
+        let (price1, price0) =
+            if price.base_currency.equals(token0) && price.quote_currency.equals(token1) {
+                (&price.numerator, &price.denominator)
+            } else if price.base_currency.equals(token1) && price.quote_currency.equals(token0) {
+                (&price.denominator, &price.numerator)
+            } else {
+                return Err(Error::InvalidToken);
+            };
+        let (amount0, amount1) = get_amounts_for_liquidity(
+            SqrtPriceX96::from_price(price)?.get(),
+            get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
+            get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
+            self.liquidity,
+        )?;
+        let value0 = amount0.to_big_int() * price1;
+        let total = &value0 + amount1.to_big_int() * price0;
+        if total.is_zero() {
+            return Err(Error::ZeroLiquidity);
+        }
+        Ok(Percent::new(value0, total))
+    }
+
     /// Returns the lower and upper sqrt ratios if the price 'slips' up to slippage tolerance
     /// percentage
     ///
@@ -192,8 +343,10 @@ impl<TP: TickDataProvider> Position<TP> {
     ///
     /// ## Returns
     ///
-    /// (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper)
-    fn ratios_after_slippage(&self, slippage_tolerance: &Percent) -> (U160, U160) {
+    /// (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper), or [`Error::InvalidSlippage`] if the
+    /// tolerance is not between 0 and 100%
+    fn ratios_after_slippage(&self, slippage_tolerance: &Percent) -> Result<(U160, U160), Error> {
+        validate_slippage(slippage_tolerance)?;
         let one = Percent::new(1, 1);
         let token0_price = self.pool.token0_price().as_fraction();
         let price_lower = (one.clone() - slippage_tolerance).as_fraction() * &token0_price;
@@ -213,7 +366,7 @@ impl<TP: TickDataProvider> Position<TP> {
             encode_sqrt_ratio_x96(price_upper.numerator, price_upper.denominator)
         };
 
-        (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper)
+        Ok((sqrt_ratio_x96_lower, sqrt_ratio_x96_upper))
     }
 
     /// Returns the minimum amounts that must be sent in order to safely mint the amount of
@@ -221,7 +374,8 @@ impl<TP: TickDataProvider> Position<TP> {
     ///
     /// ## Arguments
     ///
-    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price, between 0
+    ///   and 100%
     ///
     /// ## Returns
     ///
@@ -233,7 +387,7 @@ impl<TP: TickDataProvider> Position<TP> {
     ) -> Result<MintAmounts, Error> {
         // Get lower/upper prices
         let (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper) =
-            self.ratios_after_slippage(slippage_tolerance);
+            self.ratios_after_slippage(slippage_tolerance)?;
 
         // Construct counterfactual pools
         let pool_lower = Pool::new(
@@ -297,7 +451,8 @@ impl<TP: TickDataProvider> Position<TP> {
     ///
     /// ## Arguments
     ///
-    /// * `slippage_tolerance`: tolerance of unfavorable slippage from the current price
+    /// * `slippage_tolerance`: tolerance of unfavorable slippage from the current price, between 0
+    ///   and 100%
     ///
     /// ## Returns
     ///
@@ -309,7 +464,7 @@ impl<TP: TickDataProvider> Position<TP> {
     ) -> Result<(U256, U256), Error> {
         // get lower/upper prices
         let (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper) =
-            self.ratios_after_slippage(slippage_tolerance);
+            self.ratios_after_slippage(slippage_tolerance)?;
 
         // construct counterfactual pools
         let pool_lower = Pool::new(
@@ -495,13 +650,97 @@ impl<TP: TickDataProvider> Position<TP> {
         // this function always uses full precision
         Self::from_amounts(pool, tick_lower, tick_upper, U256::MAX, amount1, true)
     }
+
+    /// Computes a single-sided position of token0 entirely above the current tick, which is
+    /// converted to token1 as the price rises through the range, like a limit order to sell token0
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool for which the position is created
+    /// * `tick_lower`: The lower tick, which must be above the current tick
+    /// * `tick_upper`: The upper tick
+    /// * `amount0`: The amount of token0 to sell
+    ///
+    /// ## Returns
+    ///
+    /// The position, or [`Error::InvalidRange`] if the range is not above the current tick
+    #[inline]
+    pub fn from_amount0_above_current(
+        pool: Pool<TP>,
+        tick_lower: TP::Index,
+        tick_upper: TP::Index,
+        amount0: U256,
+    ) -> Result<Self, Error> {
+        if tick_lower >= tick_upper || tick_lower <= pool.tick_current {
+            return Err(Error::InvalidRange);
+        }
+        let liquidity = get_liquidity_for_amount0(
+            get_sqrt_ratio_at_tick(tick_lower.to_i24())?,
+            get_sqrt_ratio_at_tick(tick_upper.to_i24())?,
+            amount0,
+        )?;
+        Self::try_new(pool, liquidity, tick_lower, tick_upper)
+    }
+
+    /// Computes a single-sided position of token1 entirely below the current tick, which is
+    /// converted to token0 as the price falls through the range, like a limit order to buy token0
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool for which the position is created
+    /// * `tick_lower`: The lower tick
+    /// * `tick_upper`: The upper tick, which must be at or below the current tick
+    /// * `amount1`: The amount of token1 to sell
+    ///
+    /// ## Returns
+    ///
+    /// The position, or [`Error::InvalidRange`] if the range is not below the current tick
+    #[inline]
+    pub fn from_amount1_below_current(
+        pool: Pool<TP>,
+        tick_lower: TP::Index,
+        tick_upper: TP::Index,
+        amount1: U256,
+    ) -> Result<Self, Error> {
+        if tick_lower >= tick_upper || tick_upper > pool.tick_current {
+            return Err(Error::InvalidRange);
+        }
+        let liquidity = get_liquidity_for_amount1(
+            get_sqrt_ratio_at_tick(tick_lower.to_i24())?,
+            get_sqrt_ratio_at_tick(tick_upper.to_i24())?,
+            amount1,
+        )?;
+        Self::try_new(pool, liquidity, tick_lower, tick_upper)
+    }
+}
+
+/// Validates that a tick is in range and a multiple of the tick spacing, suggesting the nearest
+/// usable tick otherwise.
+fn validate_tick<I: TickIndex>(tick: I, tick_spacing: i32) -> Result<(), Error> {
+    let tick: i32 = tick
+        .try_into()
+        .unwrap_or(if tick < I::ZERO { i32::MIN } else { i32::MAX });
+    let clamped = tick.clamp(MIN_TICK_I32, MAX_TICK_I32);
+    let nearest_usable_tick = saturating_i24(nearest_usable_tick(clamped, tick_spacing));
+    if clamped != tick {
+        return Err(Error::TickOutOfBounds {
+            tick: saturating_i24(tick),
+            nearest_usable_tick,
+        });
+    }
+    if tick % tick_spacing != 0 {
+        return Err(Error::UnalignedTick {
+            tick: saturating_i24(tick),
+            nearest_usable_tick,
+        });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tests::*;
-    use alloy_primitives::aliases::I24;
     use once_cell::sync::Lazy;
 
     static POOL_SQRT_RATIO_START: Lazy<U160> =
@@ -522,6 +761,32 @@ mod tests {
     });
 
     const TWO: I24 = I24::from_limbs([2]);
+    const TEN: I24 = I24::from_limbs([10]);
+
+    fn nearest_usable_tick_i24(tick: I24) -> I24 {
+        nearest_usable_tick(tick, TICK_SPACING)
+    }
+
+    #[test]
+    fn value_in() {
+        let position = Position::new(POOL_0_1.clone(), 1_000_000, -60, 60);
+        let price = position.pool.token0_price();
+        let amount0 = position.amount0().unwrap().quotient();
+        let amount1 = position.amount1().unwrap().quotient();
+        let value = position
+            .value_in(&TOKEN1, &price, (U256::from(10), U256::from(20)))
+            .unwrap();
+        assert!(value.currency.equals(&*TOKEN1));
+        assert_eq!(value.quotient(), &amount0 + &amount1 + BigInt::from(30));
+        let value = position
+            .value_in(&TOKEN0, &price, (U256::ZERO, U256::ZERO))
+            .unwrap();
+        assert!(value.currency.equals(&*TOKEN0));
+        assert_eq!(value.quotient(), amount0 + amount1);
+        assert!(position
+            .value_in(&TOKEN2, &price, (U256::ZERO, U256::ZERO))
+            .is_err());
+    }
 
     #[test]
     fn can_be_constructed_around_0_tick() {
@@ -586,6 +851,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_new_validates_ticks_and_liquidity() {
+        let pool = || DAI_USDC_POOL.clone();
+        assert!(Position::try_new(pool(), 1, -10, 10).is_ok());
+        assert!(matches!(
+            Position::try_new(pool(), 1, 10, -10),
+            Err(Error::InvalidRange)
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 1, -10, -10),
+            Err(Error::InvalidRange)
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 1, -6, 10),
+            Err(Error::UnalignedTick { tick, nearest_usable_tick })
+                if tick == -I24::from_limbs([6]) && nearest_usable_tick == -TEN
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 1, -10, 14),
+            Err(Error::UnalignedTick { nearest_usable_tick, .. }) if nearest_usable_tick == TEN
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 1, i32::MIN, 10),
+            Err(Error::TickOutOfBounds { tick, nearest_usable_tick })
+                if tick == I24::MIN && nearest_usable_tick == nearest_usable_tick_i24(MIN_TICK)
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 1, -10, MAX_TICK_I32 + 1),
+            Err(Error::TickOutOfBounds { nearest_usable_tick, .. })
+                if nearest_usable_tick == nearest_usable_tick_i24(MAX_TICK)
+        ));
+        assert!(matches!(
+            Position::try_new(pool(), 0, -10, 10),
+            Err(Error::ZeroLiquidity)
+        ));
+    }
+
+    #[test]
+    fn range_helpers() {
+        let pool = Pool::new(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FeeAmount::MEDIUM,
+            encode_sqrt_ratio_x96(1, 1),
+            4_000_000,
+        )
+        .unwrap();
+        let position = Position::new(pool.clone(), 1_000_000, -60, 60);
+        assert!(position.is_in_range());
+        assert_eq!(position.active_liquidity_share(), Percent::new(1, 4));
+        // 1 - 1.0001^-60 < 1.0001^60 - 1
+        let distance_to_lower = position.distance_to_lower().unwrap();
+        let distance_to_upper = position.distance_to_upper().unwrap();
+        assert!(Percent::default() < distance_to_lower && distance_to_lower < distance_to_upper);
+        assert!(distance_to_upper < Percent::new(61, 10000));
+
+        let position = Position::new(pool, 1_000_000, 60, 120);
+        assert!(!position.is_in_range());
+        assert_eq!(position.active_liquidity_share(), Percent::default());
+        assert!(position.distance_to_lower().unwrap() < Percent::default());
+        assert!(position.distance_to_upper().unwrap() > Percent::default());
+    }
+
+    #[test]
+    fn token_ratio_at() {
+        let pool = Pool::new(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FeeAmount::MEDIUM,
+            encode_sqrt_ratio_x96(1, 1),
+            0,
+        )
+        .unwrap();
+        let position = Position::new(pool, 1_000_000_000_000, -60, 60);
+        let price = Price::new(TOKEN0.clone(), TOKEN1.clone(), 1, 1);
+        let ratio = position.token_ratio_at(&price).unwrap();
+        assert!(ratio > Percent::new(49, 100) && ratio < Percent::new(51, 100));
+        assert_eq!(position.token_ratio_at(&price.invert()).unwrap(), ratio);
+        let below = tick_to_price(TOKEN0.clone(), TOKEN1.clone(), -I24::from_limbs([120])).unwrap();
+        assert_eq!(position.token_ratio_at(&below).unwrap(), Percent::new(1, 1));
+        let above = tick_to_price(TOKEN0.clone(), TOKEN1.clone(), I24::from_limbs([120])).unwrap();
+        assert_eq!(position.token_ratio_at(&above).unwrap(), Percent::default());
+        let other = Price::new(TOKEN0.clone(), WETH.clone(), 1, 1);
+        assert!(matches!(
+            position.token_ratio_at(&other),
+            Err(Error::InvalidToken)
+        ));
+    }
+
     #[test]
     fn amount0_is_correct_for_price_above() {
         let position = Position::new(
@@ -895,6 +1249,19 @@ mod tests {
         assert_eq!(amount1.to_string(), "54828800460");
     }
 
+    #[test]
+    fn amounts_with_slippage_reject_invalid_tolerance() {
+        let mut position = Position::new(DAI_USDC_POOL.clone(), 100e18 as u128, -10, 10);
+        assert!(matches!(
+            position.mint_amounts_with_slippage(&Percent::new(101, 100)),
+            Err(Error::InvalidSlippage)
+        ));
+        assert!(matches!(
+            position.burn_amounts_with_slippage(&Percent::new(-1, 100)),
+            Err(Error::InvalidSlippage)
+        ));
+    }
+
     #[test]
     fn mint_amounts_is_correct_for_pool_at_min_price() {
         let mut position = Position::new(
@@ -972,4 +1339,34 @@ mod tests {
         assert_eq!(amount0.to_string(), "120054069145287995769397");
         assert_eq!(amount1.to_string(), "79831926243");
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let position = Position::new(make_pool(USDC.clone(), WETH.clone()), 1_000_000, -60, 60);
+        let json = serde_json::to_value(&position).unwrap();
+        assert_eq!(json["liquidity"], "1000000");
+        assert_eq!(json["pool"]["fee"]["tick_spacing"], 60);
+        assert_eq!(
+            json["pool"]["sqrt_ratio_x96"],
+            position.pool.sqrt_ratio_x96.to_string()
+        );
+        assert_eq!(
+            json["pool"]["tick_data_provider"][0]["liquidity_net"],
+            position.pool.tick_data_provider[0]
+                .liquidity_net
+                .to_string()
+        );
+        let decoded: Position<TickListDataProvider> = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, position);
+        assert_eq!(decoded.pool.tick_current, position.pool.tick_current);
+        assert_eq!(
+            decoded.pool.tick_data_provider,
+            position.pool.tick_data_provider
+        );
+        assert_eq!(
+            decoded.mint_amounts().unwrap(),
+            position.mint_amounts().unwrap()
+        );
+    }
 }
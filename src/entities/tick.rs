@@ -7,9 +7,12 @@ use core::{
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tick<I = i32> {
     pub index: I,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::decimal"))]
     pub liquidity_gross: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_utils::decimal"))]
     pub liquidity_net: i128,
 }
 
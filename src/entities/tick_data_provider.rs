@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use core::ops::Deref;
+use core::{future::Future, ops::Deref};
 
 /// Provides information about ticks
 pub trait TickDataProvider {
@@ -56,9 +56,40 @@ where
     }
 }
 
+/// Provides information about ticks asynchronously, so that they can be fetched on demand, e.g.
+/// only the words of the tick bitmap crossed by a swap, see [`Pool::simulate_swap_exact_in_async`]
+pub trait AsyncTickDataProvider {
+    type Index: TickIndex;
+
+    /// Return information corresponding to a specific tick
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick`: The tick to load
+    fn get_tick(
+        &mut self,
+        tick: Self::Index,
+    ) -> impl Future<Output = Result<Tick<Self::Index>, Error>>;
+
+    /// Return the next tick that is initialized within a single word
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick`: The current tick
+    /// * `lte`: Whether the next tick should be lte the current tick
+    /// * `tick_spacing`: The tick spacing of the pool
+    fn next_initialized_tick_within_one_word(
+        &mut self,
+        tick: Self::Index,
+        lte: bool,
+        tick_spacing: Self::Index,
+    ) -> impl Future<Output = Result<(Self::Index, bool), Error>>;
+}
+
 /// This tick data provider does not know how to fetch any tick data. It throws whenever it is
 /// required. Useful if you do not need to load tick data for your use case.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NoTickDataProvider;
 
 impl TickDataProvider for NoTickDataProvider {
@@ -1,8 +1,9 @@
-use crate::prelude::*;
+use crate::prelude::{Error, *};
 use derive_more::Deref;
 
 /// A data provider for ticks that is backed by an in-memory array of ticks.
 #[derive(Clone, Debug, Default, PartialEq, Deref)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TickListDataProvider<I = i32>(Vec<Tick<I>>);
 
 impl<I: TickIndex> TickListDataProvider<I> {
@@ -11,6 +12,78 @@ impl<I: TickIndex> TickListDataProvider<I> {
         ticks.validate_list(tick_spacing);
         Self(ticks)
     }
+
+    /// Encodes the ticks compactly, as their number followed by the big-endian index, gross
+    /// liquidity and net liquidity of each tick, 36 bytes per tick.
+    #[inline]
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + 36 * self.0.len());
+        self.encode(&mut bytes);
+        bytes
+    }
+
+    /// Decodes the ticks encoded by [`Self::to_bytes`].
+    ///
+    /// Only the order and range of the ticks are validated, so that snapshots of a part of the
+    /// ticks of a pool can be restored.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes`: The encoded ticks
+    #[inline]
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let ticks = Self::decode(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(Error::InvalidSnapshot);
+        }
+        Ok(ticks)
+    }
+
+    pub(crate) fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&(self.0.len() as u32).to_be_bytes());
+        for tick in &self.0 {
+            bytes.extend_from_slice(&tick.index.to_i24().as_i32().to_be_bytes());
+            bytes.extend_from_slice(&tick.liquidity_gross.to_be_bytes());
+            bytes.extend_from_slice(&tick.liquidity_net.to_be_bytes());
+        }
+    }
+
+    pub(crate) fn decode(bytes: &mut &[u8]) -> Result<Self, Error> {
+        let len = u32::from_be_bytes(read_array(bytes)?) as usize;
+        if bytes.len() < len * 36 {
+            return Err(Error::InvalidSnapshot);
+        }
+        let mut ticks: Vec<Tick<I>> = Vec::with_capacity(len);
+        for _ in 0..len {
+            let index = read_tick_index(bytes)?;
+            if ticks.last().is_some_and(|last| last.index >= index) {
+                return Err(Error::InvalidSnapshot);
+            }
+            ticks.push(Tick {
+                index,
+                liquidity_gross: u128::from_be_bytes(read_array(bytes)?),
+                liquidity_net: i128::from_be_bytes(read_array(bytes)?),
+            });
+        }
+        Ok(Self(ticks))
+    }
+}
+
+/// Reads the next `N` bytes of a snapshot.
+pub(crate) fn read_array<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], Error> {
+    let (head, tail) = bytes
+        .split_first_chunk::<N>()
+        .ok_or(Error::InvalidSnapshot)?;
+    *bytes = tail;
+    Ok(*head)
+}
+
+/// Reads the next tick of a snapshot, encoded as a big-endian `i32`.
+pub(crate) fn read_tick_index<I: TickIndex>(bytes: &mut &[u8]) -> Result<I, Error> {
+    let tick = CheckedTick::new(i32::from_be_bytes(read_array(bytes)?))
+        .map_err(|_| Error::InvalidSnapshot)?;
+    Ok(I::from_i24(tick.get()))
 }
 
 #[cfg(test)]
@@ -60,4 +133,22 @@ mod tests {
         assert_eq!(tick.liquidity_net, -1);
         assert_eq!(tick.liquidity_gross, 1);
     }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let bytes = PROVIDER.to_bytes();
+        assert_eq!(bytes.len(), 4 + 36 * 2);
+        assert_eq!(TickListDataProvider::from_bytes(&bytes).unwrap(), *PROVIDER);
+        assert!(matches!(
+            TickListDataProvider::<i32>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::InvalidSnapshot)
+        ));
+        // ticks out of order
+        let mut bytes = bytes;
+        bytes[4..8].copy_from_slice(&2_i32.to_be_bytes());
+        assert!(matches!(
+            TickListDataProvider::<i32>::from_bytes(&bytes),
+            Err(Error::InvalidSnapshot)
+        ));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::prelude::{Error, *};
-use rustc_hash::FxHashSet;
+use alloc::collections::BTreeSet;
 use uniswap_sdk_core::prelude::{sorted_insert::sorted_insert, *};
 
 /// Trades comparator, an extension of the input output comparator that also considers other
@@ -63,6 +63,8 @@ where
     }
 }
 
+/// Options for [`Trade::best_trade_exact_in`] and [`Trade::best_trade_exact_out`], both of which
+/// default to 3.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct BestTradeOptions {
     /// how many results to return
@@ -186,7 +188,7 @@ where
             .iter()
             .flat_map(|swap| swap.route.pools.iter())
             .map(|pool| pool.address(None, None));
-        let pool_address_set = FxHashSet::from_iter(pool_addresses);
+        let pool_address_set = BTreeSet::from_iter(pool_addresses);
         assert_eq!(num_pools, pool_address_set.len(), "POOLS_DUPLICATED");
         Ok(Self {
             swaps,
@@ -294,6 +296,23 @@ where
         Ok(output_amount)
     }
 
+    /// The output amount received by the recipient assuming no slippage, if the output token is a
+    /// fee-on-transfer token taking `output_tax` of every transfer.
+    ///
+    /// ## Arguments
+    ///
+    /// * `output_tax`: The transfer tax of the output token
+    #[inline]
+    pub fn output_amount_after_tax(
+        &self,
+        output_tax: &Percent,
+    ) -> Result<CurrencyAmount<TOutput>, Error> {
+        validate_tax(output_tax)?;
+        self.output_amount()?
+            .multiply(&(Percent::new(1, 1) - output_tax.clone()))
+            .map_err(Error::Core)
+    }
+
     /// The price expressed in terms of output amount/input amount.
     #[inline]
     pub fn execution_price(&self) -> Result<Price<TInput, TOutput>, Error> {
@@ -315,7 +334,7 @@ where
         Ok(execution_price)
     }
 
-    /// Returns the percent difference between the route's mid price and the price impact
+    /// Returns the percent difference between the routes' mid price and the execution price
     #[inline]
     pub fn price_impact(&self) -> Result<Percent, Error> {
         let mut spot_output_amount =
@@ -338,7 +357,7 @@ where
         ))
     }
 
-    /// Returns the percent difference between the route's mid price and the price impact
+    /// Returns the percent difference between the routes' mid price and the execution price
     #[inline]
     pub fn price_impact_cached(&mut self) -> Result<Percent, Error> {
         if let Some(price_impact) = &self._price_impact {
@@ -392,6 +411,27 @@ where
             .map_err(|e| e.into())
     }
 
+    /// Get the minimum amount that the recipient must receive from this trade for the given
+    /// slippage tolerance, if the output token is a fee-on-transfer token taking `output_tax` of
+    /// every transfer.
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: The tolerance of unfavorable slippage from the execution price of
+    ///   this trade
+    /// * `output_tax`: The transfer tax of the output token
+    #[inline]
+    pub fn minimum_amount_out_after_tax(
+        &self,
+        slippage_tolerance: Percent,
+        output_tax: &Percent,
+    ) -> Result<CurrencyAmount<TOutput>, Error> {
+        self.minimum_amount_out(
+            slippage_tolerance,
+            Some(self.output_amount_after_tax(output_tax)?),
+        )
+    }
+
     /// Get the minimum amount that must be received from this trade for the given slippage
     /// tolerance
     ///
@@ -809,6 +849,174 @@ where
         }
         Ok(best_trades)
     }
+
+    /// Given several pools of the same pair, e.g. one per fee tier, and a fixed amount in, splits
+    /// the amount in across single-hop routes through the pools to maximize the total output.
+    ///
+    /// The amount in is divided into `parts` equal shares, each of which is allocated to the pool
+    /// with the largest marginal output given the shares it has already received. The returned
+    /// trade has one swap per pool that receives a share, and its [`Trade::price_impact`] is the
+    /// combined price impact of the split.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools of the pair to split the amount in across
+    /// * `currency_amount_in`: The exact amount of input currency to spend
+    /// * `currency_out`: The desired currency out
+    /// * `parts`: The number of shares to divide the amount in into, e.g. 20 for 5% steps
+    #[inline]
+    pub fn best_split_trade_exact_in(
+        pools: &[Pool<TP>],
+        currency_amount_in: &CurrencyAmount<TInput>,
+        currency_out: &TOutput,
+        parts: usize,
+    ) -> Result<Self, Error> {
+        let token_in = currency_amount_in.currency.wrapped();
+        let allocations = split_amount(
+            pools,
+            token_in,
+            currency_out.wrapped(),
+            currency_amount_in.quotient(),
+            parts,
+            true,
+            |pool, amount| {
+                let amount_in = CurrencyAmount::from_raw_amount(token_in.clone(), amount)?;
+                Ok(pool.get_output_amount(&amount_in, None)?.0.quotient())
+            },
+        )?;
+        let mut routes = Vec::with_capacity(pools.len());
+        for (pool, amount) in pools.iter().zip(allocations) {
+            if amount.is_zero() {
+                continue;
+            }
+            routes.push((
+                CurrencyAmount::from_raw_amount(currency_amount_in.currency.clone(), amount)?,
+                Route::new(
+                    vec![pool.clone()],
+                    currency_amount_in.currency.clone(),
+                    currency_out.clone(),
+                ),
+            ));
+        }
+        Self::from_routes(routes, TradeType::ExactInput)
+    }
+
+    /// Given several pools of the same pair, e.g. one per fee tier, and a fixed amount out, splits
+    /// the amount out across single-hop routes through the pools to minimize the total input.
+    ///
+    /// The amount out is divided into `parts` equal shares, each of which is allocated to the pool
+    /// with the smallest marginal input given the shares it has already received. The returned
+    /// trade has one swap per pool that receives a share, and its [`Trade::price_impact`] is the
+    /// combined price impact of the split.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools of the pair to split the amount out across
+    /// * `currency_in`: The currency to spend
+    /// * `currency_amount_out`: The exact amount of output currency to receive
+    /// * `parts`: The number of shares to divide the amount out into, e.g. 20 for 5% steps
+    #[inline]
+    pub fn best_split_trade_exact_out(
+        pools: &[Pool<TP>],
+        currency_in: &TInput,
+        currency_amount_out: &CurrencyAmount<TOutput>,
+        parts: usize,
+    ) -> Result<Self, Error> {
+        let token_out = currency_amount_out.currency.wrapped();
+        let allocations = split_amount(
+            pools,
+            currency_in.wrapped(),
+            token_out,
+            currency_amount_out.quotient(),
+            parts,
+            false,
+            |pool, amount| {
+                let amount_out = CurrencyAmount::from_raw_amount(token_out.clone(), amount)?;
+                Ok(pool.get_input_amount(&amount_out, None)?.0.quotient())
+            },
+        )?;
+        let mut routes = Vec::with_capacity(pools.len());
+        for (pool, amount) in pools.iter().zip(allocations) {
+            if amount.is_zero() {
+                continue;
+            }
+            routes.push((
+                CurrencyAmount::from_raw_amount(currency_amount_out.currency.clone(), amount)?,
+                Route::new(
+                    vec![pool.clone()],
+                    currency_in.clone(),
+                    currency_amount_out.currency.clone(),
+                ),
+            ));
+        }
+        Self::from_routes(routes, TradeType::ExactOutput)
+    }
+}
+
+/// Greedily allocates `total` in `parts` equal shares among `pools`, giving each share to the pool
+/// with the best marginal quote, and returns the amount allocated to each pool.
+///
+/// ## Arguments
+///
+/// * `pools`: The pools of the pair to split across
+/// * `token_in`: The input token of the pair
+/// * `token_out`: The output token of the pair
+/// * `total`: The total amount to split
+/// * `parts`: The number of shares to divide `total` into
+/// * `maximize`: Whether the best quote is the largest (exact in) or the smallest (exact out)
+/// * `quote`: Quotes the total output (exact in) or input (exact out) of an amount in a pool
+#[inline]
+fn split_amount<TP: TickDataProvider>(
+    pools: &[Pool<TP>],
+    token_in: &Token,
+    token_out: &Token,
+    total: BigInt,
+    parts: usize,
+    maximize: bool,
+    quote: impl Fn(&Pool<TP>, BigInt) -> Result<BigInt, Error>,
+) -> Result<Vec<BigInt>, Error> {
+    assert!(!pools.is_empty(), "POOLS");
+    assert!(parts > 0, "PARTS");
+    for pool in pools {
+        assert!(
+            pool.involves_token(token_in) && pool.involves_token(token_out),
+            "POOL_PAIR"
+        );
+    }
+    let share = total.clone() / BigInt::from(parts);
+    let mut allocations = vec![BigInt::zero(); pools.len()];
+    let mut quotes = vec![BigInt::zero(); pools.len()];
+    for i in 0..parts {
+        // the last share takes the remainder of the division
+        let share = if i == parts - 1 {
+            total.clone() - share.clone() * BigInt::from(parts - 1)
+        } else {
+            share.clone()
+        };
+        if share.is_zero() {
+            continue;
+        }
+        let mut best: Option<(usize, BigInt)> = None;
+        for (j, pool) in pools.iter().enumerate() {
+            let marginal = match quote(pool, allocations[j].clone() + share.clone()) {
+                Ok(quote) => quote - quotes[j].clone(),
+                Err(Error::InsufficientLiquidity) => continue,
+                Err(e) => return Err(e),
+            };
+            let is_better = match &best {
+                Some((_, best_marginal)) if maximize => marginal > *best_marginal,
+                Some((_, best_marginal)) => marginal < *best_marginal,
+                None => true,
+            };
+            if is_better {
+                best = Some((j, marginal));
+            }
+        }
+        let (j, marginal) = best.ok_or(Error::InsufficientLiquidity)?;
+        allocations[j] += share;
+        quotes[j] += marginal;
+    }
+    Ok(allocations)
 }
 
 #[cfg(test)]
@@ -2166,6 +2374,67 @@ mod tests {
         }
     }
 
+    mod output_amount_after_tax {
+        use super::*;
+
+        static EXACT_IN: Lazy<Trade<Token, Token, TickListDataProvider>> = Lazy::new(|| {
+            Trade::from_route(
+                Route::new(
+                    vec![POOL_0_1.clone(), POOL_1_2.clone()],
+                    TOKEN0.clone(),
+                    TOKEN2.clone(),
+                ),
+                CurrencyAmount::from_raw_amount(TOKEN0.clone(), 10000).unwrap(),
+                TradeType::ExactInput,
+            )
+            .unwrap()
+        });
+
+        #[test]
+        fn returns_output_if_0() {
+            assert_eq!(
+                EXACT_IN
+                    .output_amount_after_tax(&Percent::default())
+                    .unwrap(),
+                EXACT_IN.output_amount().unwrap()
+            );
+        }
+
+        #[test]
+        fn reduces_output_by_tax() {
+            assert_eq!(
+                EXACT_IN
+                    .output_amount_after_tax(&Percent::new(5, 100))
+                    .unwrap(),
+                CurrencyAmount::from_fractional_amount(TOKEN2.clone(), 7004 * 95, 100).unwrap()
+            );
+        }
+
+        #[test]
+        fn minimum_amount_out_after_tax() {
+            assert_eq!(
+                EXACT_IN
+                    .minimum_amount_out_after_tax(Percent::new(5, 100), &Percent::new(5, 100))
+                    .unwrap()
+                    .quotient(),
+                // 7004 * 95% / 105%
+                BigInt::from(6337)
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_tax() {
+            assert!(matches!(
+                EXACT_IN.output_amount_after_tax(&Percent::new(-1, 100)),
+                Err(Error::InvalidTax)
+            ));
+            assert!(matches!(
+                EXACT_IN.output_amount_after_tax(&Percent::new(101, 100)),
+                Err(Error::InvalidTax)
+            ));
+        }
+    }
+
     mod best_trade_exact_out {
         use super::*;
 
@@ -2409,4 +2678,93 @@ mod tests {
             assert_eq!(result[1].output_amount().unwrap().currency, ETHER.clone());
         }
     }
+
+    mod best_split_trade {
+        use super::*;
+
+        static POOL_0_1_LOW: Lazy<Pool<TickListDataProvider>> = Lazy::new(|| {
+            v2_style_pool(
+                CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100000).unwrap(),
+                CurrencyAmount::from_raw_amount(TOKEN1.clone(), 100000).unwrap(),
+                Some(FeeAmount::LOW),
+            )
+        });
+
+        #[test]
+        #[should_panic(expected = "POOL_PAIR")]
+        fn throws_with_pool_of_another_pair() {
+            let _ = Trade::best_split_trade_exact_in(
+                &[POOL_0_1.clone(), POOL_0_2.clone()],
+                &CurrencyAmount::from_raw_amount(TOKEN0.clone(), 10000).unwrap(),
+                &TOKEN1.clone(),
+                10,
+            );
+        }
+
+        #[test]
+        fn exact_in_splits_large_amount() {
+            let amount_in = CurrencyAmount::from_raw_amount(TOKEN0.clone(), 20000).unwrap();
+            let pools = [POOL_0_1.clone(), POOL_0_1_LOW.clone()];
+            let trade =
+                Trade::best_split_trade_exact_in(&pools, &amount_in, &TOKEN1.clone(), 10).unwrap();
+            assert_eq!(trade.swaps.len(), 2);
+            assert_eq!(trade.input_amount().unwrap(), amount_in);
+            for pool in pools {
+                let single = Trade::from_route(
+                    Route::new(vec![pool], TOKEN0.clone(), TOKEN1.clone()),
+                    amount_in.clone(),
+                    TradeType::ExactInput,
+                )
+                .unwrap();
+                assert!(
+                    trade.output_amount().unwrap().as_fraction()
+                        > single.output_amount().unwrap().as_fraction()
+                );
+                assert!(
+                    trade.price_impact().unwrap().as_fraction()
+                        < single.price_impact().unwrap().as_fraction()
+                );
+            }
+        }
+
+        #[test]
+        fn exact_in_uses_single_pool_for_small_amount() {
+            let shallow_pool = v2_style_pool(
+                CurrencyAmount::from_raw_amount(TOKEN0.clone(), 1000).unwrap(),
+                CurrencyAmount::from_raw_amount(TOKEN1.clone(), 1000).unwrap(),
+                None,
+            );
+            let trade = Trade::best_split_trade_exact_in(
+                &[shallow_pool, POOL_0_1_LOW.clone()],
+                &CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+                &TOKEN1.clone(),
+                2,
+            )
+            .unwrap();
+            assert_eq!(trade.swaps.len(), 1);
+            assert_eq!(trade.swaps[0].route.pools[0].fee, FeeAmount::LOW);
+        }
+
+        #[test]
+        fn exact_out_splits_large_amount() {
+            let amount_out = CurrencyAmount::from_raw_amount(TOKEN1.clone(), 20000).unwrap();
+            let pools = [POOL_0_1.clone(), POOL_0_1_LOW.clone()];
+            let trade = Trade::best_split_trade_exact_out(&pools, &TOKEN0.clone(), &amount_out, 10)
+                .unwrap();
+            assert_eq!(trade.swaps.len(), 2);
+            assert_eq!(trade.output_amount().unwrap(), amount_out);
+            for pool in pools {
+                let single = Trade::from_route(
+                    Route::new(vec![pool], TOKEN0.clone(), TOKEN1.clone()),
+                    amount_out.clone(),
+                    TradeType::ExactOutput,
+                )
+                .unwrap();
+                assert!(
+                    trade.input_amount().unwrap().as_fraction()
+                        < single.input_amount().unwrap().as_fraction()
+                );
+            }
+        }
+    }
 }
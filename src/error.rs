@@ -32,25 +32,78 @@ pub enum Error {
     #[error("Invalid square root price: {0}")]
     InvalidSqrtPrice(U160),
 
+    /// Thrown when a fee passed to [`FeePips::new`] or [`FeeAmount::try_new`] is not below 100%.
+    #[error("Invalid fee: {0}")]
+    InvalidFee(u32),
+
+    /// Thrown when a tick spacing passed to [`FeeAmount::try_new`] is not between 1 and 16383.
+    #[error("Invalid tick spacing: {0}")]
+    InvalidTickSpacing(I24),
+
+    /// Thrown when a slippage tolerance is negative or above 100%.
+    #[error("Invalid slippage tolerance")]
+    InvalidSlippage,
+
+    /// Thrown when a transfer tax passed to [`Trade::output_amount_after_tax`] or
+    /// [`swap_router02_call_parameters`] is negative or above 100%.
+    #[error("Invalid transfer tax")]
+    InvalidTax,
+
     #[error("Invalid price or liquidity")]
     InvalidPriceOrLiquidity,
 
     #[error("Invalid price")]
     InvalidPrice,
 
-    #[cfg(feature = "extensions")]
+    /// Thrown when a swap amount does not fit in a signed 256-bit integer.
+    #[error("Invalid amount")]
+    InvalidAmount,
+
+    /// Thrown when the path passed to [`decode_path`] is not a sequence of token addresses
+    /// separated by 3-byte fees below 100%.
+    #[error("Invalid path")]
+    InvalidPath,
+
+    /// Thrown when the log passed to [`decode_pool_event`] or [`decode_position_event`] is not an
+    /// event of the contract or fails to decode.
+    #[error("Invalid log")]
+    InvalidLog,
+
+    /// Thrown when the lower tick of a range is not below the upper tick, or when a range order
+    /// is not entirely above or below the current tick.
     #[error("Invalid tick range")]
     InvalidRange,
 
+    /// Thrown when a tick of a position is not a multiple of the tick spacing of the pool.
+    #[error(
+        "Tick {tick} not aligned to tick spacing, nearest usable tick is {nearest_usable_tick}"
+    )]
+    UnalignedTick { tick: I24, nearest_usable_tick: I24 },
+
+    /// Thrown when a tick of a position is outside of [`MIN_TICK`] and [`MAX_TICK`].
+    #[error("Tick {tick} is out of bounds, nearest usable tick is {nearest_usable_tick}")]
+    TickOutOfBounds { tick: I24, nearest_usable_tick: I24 },
+
+    /// Thrown when a position is constructed with zero liquidity.
+    #[error("Zero liquidity")]
+    ZeroLiquidity,
+
     #[error("Overflow in full math mulDiv")]
     MulDivOverflow,
 
+    /// Thrown when the denominator passed to [`try_mul_div`] is zero.
+    #[error("Division by zero")]
+    DivisionByZero,
+
     #[error("Overflow when adding liquidity delta")]
     AddDeltaOverflow,
 
     #[error("Overflow when casting to U160")]
     SafeCastToU160Overflow,
 
+    #[error("Overflow when casting to u128")]
+    SafeCastToU128Overflow,
+
     #[error("Overflow in price calculation")]
     PriceOverflow,
 
@@ -60,6 +113,24 @@ pub enum Error {
     #[error("No tick data provider was given")]
     NoTickDataError,
 
+    /// Thrown when [`backtest`] is given no swaps to replay, or when [`evaluate_paths`] is given an
+    /// empty price path.
+    #[cfg(feature = "std")]
+    #[error("No swaps")]
+    NoSwaps,
+
+    /// Thrown when the window of a time-weighted average or of a fee APR is empty or ends before it
+    /// starts, when the block range passed to [`get_fees_earned`] ends before it starts, or when
+    /// the interval passed to [`ohlc_candles`], the time passed to [`annualized_volatility`] or
+    /// [`realized_volatility`] or the number of steps passed to [`gbm_paths`] is zero.
+    #[error("Invalid window")]
+    InvalidWindow,
+
+    /// Thrown when the bytes passed to [`Pool::restore`] or [`TickListDataProvider::from_bytes`]
+    /// are not a valid snapshot.
+    #[error("Invalid snapshot")]
+    InvalidSnapshot,
+
     #[cfg(feature = "extensions")]
     #[error("{0}")]
     ContractError(#[from] ContractError),
@@ -68,6 +139,92 @@ pub enum Error {
     #[error("{0}")]
     LensError(#[from] LensError),
 
+    /// Thrown when a request to the subgraph fails.
+    #[cfg(feature = "subgraph")]
+    #[error("{0}")]
+    SubgraphRequestError(#[from] reqwest::Error),
+
+    /// Thrown when the subgraph returns errors or no data for a query.
+    #[cfg(feature = "subgraph")]
+    #[error("Subgraph query failed: {0}")]
+    SubgraphQueryError(alloc::string::String),
+
+    /// Thrown when a value returned by the subgraph fails to parse.
+    #[cfg(feature = "subgraph")]
+    #[error("Invalid subgraph value: {0}")]
+    InvalidSubgraphValue(alloc::string::String),
+
+    /// Thrown when the subgraph is queried at a block other than a block number or the latest
+    /// block.
+    #[cfg(feature = "subgraph")]
+    #[error("Unsupported block id: {0}")]
+    UnsupportedBlockId(alloy::eips::BlockId),
+
+    /// Thrown when a pool is not found in the subgraph.
+    #[cfg(feature = "subgraph")]
+    #[error("Pool {0} not found")]
+    PoolNotFound(alloy_primitives::Address),
+
+    /// Thrown when a token is not found in the subgraph.
+    #[cfg(feature = "subgraph")]
+    #[error("Token {0} not found")]
+    TokenNotFound(alloy_primitives::Address),
+
+    /// Thrown when a position is not found in the subgraph.
+    #[cfg(feature = "subgraph")]
+    #[error("Position {0} not found")]
+    PositionNotFound(alloy_primitives::U256),
+
+    /// Thrown when a [`MockPoolProvider`] has no data for the query, or when the block passed to
+    /// [`pin_block_id`] doesn't exist.
+    #[cfg(feature = "extensions")]
+    #[error("Data not found")]
+    DataNotFound,
+
+    /// Thrown when the options passed to a simulation don't match the simulated call, e.g.
+    /// increase liquidity options passed to [`simulate_mint_with_options`], when a limit of
+    /// [`BatchOptions`] or a page size is zero, or when the width passed to
+    /// [`bucket_liquidity_array`] is not positive.
+    #[cfg(feature = "extensions")]
+    #[error("Invalid options")]
+    InvalidOptions,
+
+    /// Thrown when the storage slots of the balances or allowances of a token are not found.
+    #[cfg(feature = "extensions")]
+    #[error("ERC20 storage slots not found for {0}")]
+    Erc20SlotsNotFound(alloy_primitives::Address),
+
+    /// Thrown when the amounts withdrawn from a position exceed its collected and collectable
+    /// amounts in [`get_fees_earned`], e.g. because the logs of the block range are incomplete.
+    #[cfg(feature = "extensions")]
+    #[error("Inconsistent position history")]
+    InconsistentHistory,
+
+    /// Thrown when the token URI of a position is not base64 encoded JSON with an image.
+    #[cfg(feature = "extensions")]
+    #[error("Invalid token URI")]
+    InvalidTokenUri,
+
+    /// Thrown when `eth_createAccessList` fails because the transaction reverts.
+    #[cfg(feature = "extensions")]
+    #[error("Failed to create access list: {0}")]
+    AccessListError(alloc::string::String),
+
+    /// Thrown when the answer of a Chainlink aggregator is older than the maximum age.
+    #[cfg(feature = "extensions")]
+    #[error("Stale oracle answer updated at {updated_at}, block timestamp {timestamp}")]
+    StaleAnswer { updated_at: u64, timestamp: u64 },
+
+    /// Thrown when the answer of a Chainlink aggregator is not positive.
+    #[cfg(feature = "extensions")]
+    #[error("Invalid oracle answer")]
+    InvalidAnswer,
+
+    /// Thrown when the embedded database of a [`PoolStore`] fails.
+    #[cfg(feature = "store")]
+    #[error("{0}")]
+    StoreError(#[from] sled::Error),
+
     #[error("{0}")]
     TickListError(#[from] TickListError),
 }
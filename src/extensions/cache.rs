@@ -0,0 +1,417 @@
+//! ## Cache Extension
+//! A [`CachedClient`] wrapping any [`V3DataSource`] that memoizes the state read at pinned blocks,
+//! so that long-running services don't re-fetch identical immutable data. Pass it to
+//! [`Pool::from_data_source`], [`get_position_from_data_source`] and friends in place of the
+//! wrapped data source. Queries at a block number are pinned to the hash of the block, so that
+//! [`CachedClient::validate`] can drop the entries of blocks that were reorged out.
+
+use crate::prelude::{Error, *};
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{aliases::I24, Address, ChainId, B256, U256};
+use core::{future::Future, hash::Hash};
+use rustc_hash::FxHashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use uniswap_sdk_core::prelude::Token;
+
+/// A block a query is pinned at, whose state can no longer change.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum PinnedBlock {
+    Number(u64),
+    Hash(B256),
+}
+
+impl PinnedBlock {
+    /// The pinned block of a query, or `None` if the state at the queried block may still change.
+    const fn from_block_id(block_id: Option<BlockId>) -> Option<Self> {
+        match block_id {
+            Some(BlockId::Number(BlockNumberOrTag::Number(number))) => Some(Self::Number(number)),
+            Some(BlockId::Hash(hash)) => Some(Self::Hash(hash.block_hash)),
+            _ => None,
+        }
+    }
+
+    /// The block id to fetch the state of the pinned block at.
+    fn block_id(self) -> BlockId {
+        match self {
+            Self::Number(number) => BlockId::from(number),
+            Self::Hash(hash) => BlockId::from(hash),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    slot0s: FxHashMap<(Address, PinnedBlock), Slot0>,
+    liquidities: FxHashMap<(Address, PinnedBlock), u128>,
+    fee_growth_globals: FxHashMap<(Address, PinnedBlock), (U256, U256)>,
+    ticks: FxHashMap<(Address, I24, PinnedBlock), TickInfo>,
+    tick_ranges: FxHashMap<(Address, I24, I24, PinnedBlock), Vec<Tick<I24>>>,
+    tokens: FxHashMap<(ChainId, Address), Token>,
+    factories: FxHashMap<Address, Address>,
+    positions: FxHashMap<(Address, U256, PinnedBlock), PositionInfo>,
+    block_hashes: FxHashMap<u64, B256>,
+}
+
+impl Cache {
+    /// Drops the entries cached at the given block numbers and at the hashes they were pinned to.
+    fn remove_blocks(&mut self, numbers: &[u64]) {
+        let hashes: Vec<B256> = numbers
+            .iter()
+            .filter_map(|number| self.block_hashes.get(number).copied())
+            .collect();
+        let keep = |block: &PinnedBlock| match block {
+            PinnedBlock::Number(number) => !numbers.contains(number),
+            PinnedBlock::Hash(hash) => !hashes.contains(hash),
+        };
+        self.slot0s.retain(|(_, block), _| keep(block));
+        self.liquidities.retain(|(_, block), _| keep(block));
+        self.fee_growth_globals.retain(|(_, block), _| keep(block));
+        self.ticks.retain(|(_, _, block), _| keep(block));
+        self.tick_ranges.retain(|(_, _, _, block), _| keep(block));
+        self.positions.retain(|(_, _, block), _| keep(block));
+        self.block_hashes
+            .retain(|number, _| !numbers.contains(number));
+    }
+}
+
+/// A [`V3DataSource`] that memoizes the results of another data source keyed by address and
+/// block. Queries at a block number or hash are served from the cache after the first fetch,
+/// queries at `Latest` or another tag always reach the wrapped data source. Token metadata and
+/// the factory of a nonfungible position manager are immutable and cached regardless of block.
+///
+/// A query at a block number first resolves the hash of the block, then fetches and caches the
+/// state at that hash, so that the entries of a block are consistent even if it is reorged out in
+/// between and can be dropped with [`CachedClient::validate`]. Entries at a block hash are immune
+/// to reorgs. If the wrapped data source doesn't report block hashes, entries are cached at the
+/// block number.
+#[derive(Debug, Default)]
+pub struct CachedClient<DS> {
+    /// The wrapped data source
+    pub inner: DS,
+    cache: Mutex<Cache>,
+}
+
+impl<DS: V3DataSource> CachedClient<DS> {
+    /// Creates a client with an empty cache.
+    ///
+    /// ## Arguments
+    ///
+    /// * `inner`: The data source to wrap
+    #[inline]
+    pub fn new(inner: DS) -> Self {
+        Self {
+            inner,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Drops all cached entries.
+    #[inline]
+    pub fn clear(&self) {
+        *self.lock() = Cache::default();
+    }
+
+    /// Checks the hash of every block with cached entries against the canonical chain and drops
+    /// the entries of the blocks that were reorged out. Bots reading near the head of the chain
+    /// should call this on every new block.
+    ///
+    /// ## Returns
+    ///
+    /// The numbers of the reorged blocks, in ascending order
+    #[inline]
+    pub async fn validate(&self) -> Result<Vec<u64>, Error> {
+        let recorded: Vec<(u64, B256)> = self
+            .lock()
+            .block_hashes
+            .iter()
+            .map(|(&number, &hash)| (number, hash))
+            .collect();
+        let mut reorged = Vec::new();
+        for (number, hash) in recorded {
+            if self.inner.block_hash(number).await? != Some(hash) {
+                reorged.push(number);
+            }
+        }
+        reorged.sort_unstable();
+        if !reorged.is_empty() {
+            self.lock().remove_blocks(&reorged);
+        }
+        Ok(reorged)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Pins the block of a query, resolving a block number to the hash of the block and recording
+    /// it. Returns `None` if the state at the block may still change or if the hash of the block
+    /// can't be fetched.
+    async fn pin(&self, block_id: Option<BlockId>) -> Option<PinnedBlock> {
+        let block = PinnedBlock::from_block_id(block_id)?;
+        let PinnedBlock::Number(number) = block else {
+            return Some(block);
+        };
+        let known = self.lock().block_hashes.get(&number).copied();
+        let hash = match known {
+            Some(hash) => Some(hash),
+            None => self.inner.block_hash(number).await.ok()?,
+        };
+        Some(hash.map_or(block, |hash| {
+            self.lock().block_hashes.insert(number, hash);
+            PinnedBlock::Hash(hash)
+        }))
+    }
+
+    /// Returns the cached value of `key` in the map chosen by `select`, or fetches and caches it.
+    /// Nothing is cached if `key` is `None`.
+    async fn get_or_fetch<K, V>(
+        &self,
+        select: fn(&mut Cache) -> &mut FxHashMap<K, V>,
+        key: Option<K>,
+        fetch: impl Future<Output = Result<V, Error>>,
+    ) -> Result<V, Error>
+    where
+        K: Hash + Eq,
+        V: Clone,
+    {
+        let Some(key) = key else {
+            return fetch.await;
+        };
+        let cached = select(&mut self.lock()).get(&key).cloned();
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = fetch.await?;
+        select(&mut self.lock()).insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Like [`Self::get_or_fetch`] for state at a block, which is pinned with [`Self::pin`] first,
+    /// then fetched at and cached under the pinned block.
+    async fn get_or_fetch_at<K, V, F>(
+        &self,
+        select: fn(&mut Cache) -> &mut FxHashMap<K, V>,
+        key: impl FnOnce(PinnedBlock) -> K,
+        block_id: Option<BlockId>,
+        fetch: impl FnOnce(Option<BlockId>) -> F,
+    ) -> Result<V, Error>
+    where
+        K: Hash + Eq,
+        V: Clone,
+        F: Future<Output = Result<V, Error>>,
+    {
+        match self.pin(block_id).await {
+            Some(block) => {
+                self.get_or_fetch(select, Some(key(block)), fetch(Some(block.block_id())))
+                    .await
+            }
+            None => fetch(block_id).await,
+        }
+    }
+}
+
+impl<DS: V3DataSource> V3DataSource for CachedClient<DS> {
+    #[inline]
+    async fn slot0(&self, pool: Address, block_id: Option<BlockId>) -> Result<Slot0, Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.slot0s,
+            |block| (pool, block),
+            block_id,
+            |block_id| self.inner.slot0(pool, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn liquidity(&self, pool: Address, block_id: Option<BlockId>) -> Result<u128, Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.liquidities,
+            |block| (pool, block),
+            block_id,
+            |block_id| self.inner.liquidity(pool, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn fee_growth_global(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<(U256, U256), Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.fee_growth_globals,
+            |block| (pool, block),
+            block_id,
+            |block_id| self.inner.fee_growth_global(pool, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn tick(
+        &self,
+        pool: Address,
+        tick: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<TickInfo, Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.ticks,
+            |block| (pool, tick, block),
+            block_id,
+            |block_id| self.inner.tick(pool, tick, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn ticks(
+        &self,
+        pool: Address,
+        tick_lower: I24,
+        tick_upper: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<Tick<I24>>, Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.tick_ranges,
+            |block| (pool, tick_lower, tick_upper, block),
+            block_id,
+            |block_id| self.inner.ticks(pool, tick_lower, tick_upper, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn token(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<Token, Error> {
+        self.get_or_fetch(
+            |cache| &mut cache.tokens,
+            Some((chain_id, token)),
+            self.inner.token(chain_id, token, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn factory(
+        &self,
+        nonfungible_position_manager: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<Address, Error> {
+        self.get_or_fetch(
+            |cache| &mut cache.factories,
+            Some(nonfungible_position_manager),
+            self.inner.factory(nonfungible_position_manager, block_id),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn positions(
+        &self,
+        nonfungible_position_manager: Address,
+        token_id: U256,
+        block_id: Option<BlockId>,
+    ) -> Result<PositionInfo, Error> {
+        self.get_or_fetch_at(
+            |cache| &mut cache.positions,
+            |block| (nonfungible_position_manager, token_id, block),
+            block_id,
+            |block_id| {
+                self.inner
+                    .positions(nonfungible_position_manager, token_id, block_id)
+            },
+        )
+        .await
+    }
+
+    #[inline]
+    async fn block_hash(&self, block_number: u64) -> Result<Option<B256>, Error> {
+        self.inner.block_hash(block_number).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[tokio::test]
+    async fn test_cached_client() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let address = pool.address(None, Some(FACTORY_ADDRESS));
+        let mut client =
+            CachedClient::new(MockPoolProvider::default().with_pool(FACTORY_ADDRESS, &pool));
+        let pinned = Some(BlockId::from(17_000_000));
+        let liquidity = client.liquidity(address, pinned).await.unwrap();
+        assert_eq!(liquidity, pool.liquidity);
+
+        // the state at a pinned block is served from the cache
+        client.inner.liquidities.insert(address, 1);
+        assert_eq!(client.liquidity(address, pinned).await.unwrap(), liquidity);
+        // the latest state always reaches the wrapped data source
+        assert_eq!(client.liquidity(address, None).await.unwrap(), 1);
+        assert_eq!(
+            client
+                .liquidity(address, Some(BlockId::latest()))
+                .await
+                .unwrap(),
+            1
+        );
+        // another block is a different entry
+        assert_eq!(
+            client
+                .liquidity(address, Some(BlockId::from(17_000_001)))
+                .await
+                .unwrap(),
+            1
+        );
+
+        // errors are not cached
+        client.inner.liquidities.remove(&address);
+        assert!(client.liquidity(address, None).await.is_err());
+        client.clear();
+        assert!(client.liquidity(address, pinned).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let address = pool.address(None, Some(FACTORY_ADDRESS));
+        let mut mock = MockPoolProvider::default().with_pool(FACTORY_ADDRESS, &pool);
+        mock.block_hashes.insert(1, B256::with_last_byte(1));
+        mock.block_hashes.insert(2, B256::with_last_byte(2));
+        let mut client = CachedClient::new(mock);
+        for block in [1, 2] {
+            client
+                .liquidity(address, Some(BlockId::from(block)))
+                .await
+                .unwrap();
+        }
+        assert!(client.validate().await.unwrap().is_empty());
+
+        // block 2 is reorged out and the liquidity at the new block 2 differs
+        client.inner.block_hashes.insert(2, B256::with_last_byte(3));
+        client.inner.liquidities.insert(address, 1);
+        assert_eq!(client.validate().await.unwrap(), vec![2]);
+        assert_eq!(
+            client
+                .liquidity(address, Some(BlockId::from(1)))
+                .await
+                .unwrap(),
+            pool.liquidity
+        );
+        assert_eq!(
+            client
+                .liquidity(address, Some(BlockId::from(2)))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(client.validate().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+//! ## Chainlink Extension
+//! Reads the USD prices of tokens from [Chainlink](https://docs.chain.link/data-feeds) aggregators,
+//! so that positions can be valued independently of the pool prices. A [`ChainlinkFeedRegistry`]
+//! maps tokens to their feeds on each chain, and answers older than a maximum age are rejected.
+
+use crate::prelude::{Error, *};
+use alloy::{eips::BlockId, providers::Provider, transports::Transport};
+use alloy_primitives::{address, aliases::U80, Address, ChainId, I256, U256};
+use bigdecimal::BigDecimal;
+use rustc_hash::FxHashMap;
+
+/// The latest answer of a Chainlink aggregator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainlinkAnswer {
+    /// The round of the answer
+    pub round_id: U80,
+    /// The answer, scaled by `10^decimals`
+    pub answer: I256,
+    /// The number of decimals of the answer
+    pub decimals: u8,
+    /// When the answer was last updated, in epoch seconds
+    pub updated_at: u64,
+    /// The timestamp of the block the answer was read at
+    pub timestamp: u64,
+}
+
+impl ChainlinkAnswer {
+    /// The answer as a decimal number.
+    #[inline]
+    #[must_use]
+    pub fn price(&self) -> BigDecimal {
+        BigDecimal::new(self.answer.to_big_int(), self.decimals.into())
+    }
+
+    /// The number of seconds since the answer was last updated.
+    #[inline]
+    #[must_use]
+    pub const fn age(&self) -> u64 {
+        self.timestamp.saturating_sub(self.updated_at)
+    }
+}
+
+/// The Chainlink USD feeds of tokens on each chain, starting with some of the feeds on Ethereum
+/// mainnet.
+#[derive(Clone, Debug)]
+pub struct ChainlinkFeedRegistry {
+    feeds: FxHashMap<(ChainId, Address), Address>,
+}
+
+impl Default for ChainlinkFeedRegistry {
+    #[inline]
+    fn default() -> Self {
+        let mainnet = [
+            // WETH: ETH / USD
+            (
+                address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+                address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            ),
+            // WBTC: BTC / USD
+            (
+                address!("2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
+                address!("F4030086522a5bEEa4988F8cA5B36dbC97BeE88c"),
+            ),
+            // USDC / USD
+            (
+                address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+                address!("8fFfFfd4AfB6115b954Bd326cbe7B4BA576818f6"),
+            ),
+            // USDT / USD
+            (
+                address!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+                address!("3E7d1eAB13ad0104d2750B8863b489D65364e32D"),
+            ),
+            // DAI / USD
+            (
+                address!("6B175474E89094C44Da98b954EedeAC495271d0F"),
+                address!("Aed0c38402a5d19df6E4c03F4E2DceD6e29c1ee9"),
+            ),
+            // LINK / USD
+            (
+                address!("514910771AF9Ca656af840dff83E8264EcF986CA"),
+                address!("2c1d072e956AFFC0D435Cb7AC38EF18d24d9127c"),
+            ),
+        ];
+        Self {
+            feeds: mainnet
+                .into_iter()
+                .map(|(token, feed)| ((1, token), feed))
+                .collect(),
+        }
+    }
+}
+
+impl ChainlinkFeedRegistry {
+    /// Registers the USD feed of a token, replacing the previous one if any.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `token`: The token address
+    /// * `feed`: The address of the aggregator of the token price in USD
+    #[inline]
+    pub fn register(
+        &mut self,
+        chain_id: ChainId,
+        token: Address,
+        feed: Address,
+    ) -> Option<Address> {
+        self.feeds.insert((chain_id, token), feed)
+    }
+
+    /// Returns the USD feed of a token, if registered.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `token`: The token address
+    #[inline]
+    #[must_use]
+    pub fn get(&self, chain_id: ChainId, token: Address) -> Option<Address> {
+        self.feeds.get(&(chain_id, token)).copied()
+    }
+}
+
+/// Get the latest answers of many Chainlink aggregators in as few `eth_call`s as possible.
+///
+/// ## Arguments
+///
+/// * `feeds`: The aggregator addresses
+/// * `max_age`: The maximum number of seconds since an answer was updated, if any
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The answers, in the same order as `feeds`. Fails with [`Error::StaleAnswer`] if any answer is
+/// older than `max_age` and with [`Error::InvalidAnswer`] if any answer is not positive.
+#[inline]
+pub async fn get_chainlink_answers<T, P>(
+    feeds: &[Address],
+    max_age: Option<u64>,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<ChainlinkAnswer>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut calls: Vec<IMulticall3::Call3> = feeds
+        .iter()
+        .flat_map(|&feed| {
+            [
+                call3(feed, &AggregatorV3Interface::latestRoundDataCall {}),
+                call3(feed, &AggregatorV3Interface::decimalsCall {}),
+            ]
+        })
+        .collect();
+    calls.push(call3(
+        MULTICALL3_ADDRESS,
+        &IMulticall3::getCurrentBlockTimestampCall {},
+    ));
+    let results = aggregate3(calls, BatchOptions::default(), provider, block_id).await?;
+    let (timestamp, results) = results.split_last().ok_or(Error::DataNotFound)?;
+    let timestamp: u64 =
+        decode_call3_result::<IMulticall3::getCurrentBlockTimestampCall>(timestamp)?
+            .timestamp
+            .saturating_to();
+    results
+        .chunks(2)
+        .map(|results| {
+            let round =
+                decode_call3_result::<AggregatorV3Interface::latestRoundDataCall>(&results[0])?;
+            let answer = ChainlinkAnswer {
+                round_id: round.roundId,
+                answer: round.answer,
+                decimals: decode_call3_result::<AggregatorV3Interface::decimalsCall>(&results[1])?
+                    ._0,
+                updated_at: round.updatedAt.saturating_to(),
+                timestamp,
+            };
+            if !answer.answer.is_positive() {
+                return Err(Error::InvalidAnswer);
+            }
+            if max_age.is_some_and(|max_age| answer.age() > max_age) {
+                return Err(Error::StaleAnswer {
+                    updated_at: answer.updated_at,
+                    timestamp,
+                });
+            }
+            Ok(answer)
+        })
+        .collect()
+}
+
+/// Get the USD prices of many tokens from their Chainlink feeds.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `tokens`: The token addresses
+/// * `registry`: The USD feeds of the tokens
+/// * `max_age`: The maximum number of seconds since an answer was updated, if any
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The USD prices of whole tokens, in the same order as `tokens`. Fails with
+/// [`Error::DataNotFound`] if any token has no registered feed.
+#[inline]
+pub async fn get_chainlink_usd_prices<T, P>(
+    chain_id: ChainId,
+    tokens: &[Address],
+    registry: &ChainlinkFeedRegistry,
+    max_age: Option<u64>,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<BigDecimal>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let feeds = tokens
+        .iter()
+        .map(|&token| registry.get(chain_id, token).ok_or(Error::DataNotFound))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(get_chainlink_answers(&feeds, max_age, provider, block_id)
+        .await?
+        .iter()
+        .map(ChainlinkAnswer::price)
+        .collect())
+}
+
+/// The USD value of a raw token amount, e.g. an amount or fees of a [`PortfolioPosition`].
+///
+/// ## Arguments
+///
+/// * `amount`: The raw token amount
+/// * `decimals`: The number of decimals of the token
+/// * `usd_price`: The USD price of a whole token
+#[inline]
+#[must_use]
+pub fn usd_value(amount: U256, decimals: u8, usd_price: &BigDecimal) -> BigDecimal {
+    BigDecimal::new(amount.to_big_int(), decimals.into()) * usd_price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::uint;
+    use core::str::FromStr;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[tokio::test]
+    async fn test_get_chainlink_usd_prices() {
+        let registry = ChainlinkFeedRegistry::default();
+        let prices = get_chainlink_usd_prices(
+            1,
+            &[WETH.address(), USDC.address()],
+            &registry,
+            Some(86400),
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(prices[0] > BigDecimal::from(1500) && prices[0] < BigDecimal::from(2500));
+        assert!(prices[1] > BigDecimal::from_str("0.99").unwrap());
+        assert!(prices[1] < BigDecimal::from_str("1.01").unwrap());
+
+        let result = get_chainlink_usd_prices(
+            1,
+            &[TOKEN0.address()],
+            &registry,
+            None,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::DataNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_stale_answer() {
+        let feed = ChainlinkFeedRegistry::default()
+            .get(1, WETH.address())
+            .unwrap();
+        let answer = get_chainlink_answers(&[feed], None, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap()[0];
+        assert_eq!(answer.decimals, 8);
+        let result = get_chainlink_answers(&[feed], Some(0), PROVIDER.clone(), *BLOCK_ID).await;
+        if answer.age() > 0 {
+            assert!(matches!(result, Err(Error::StaleAnswer { .. })));
+        }
+    }
+
+    #[test]
+    fn test_usd_value() {
+        let price = BigDecimal::from(2000);
+        assert_eq!(
+            usd_value(uint!(1_500_000_000_000_000_000_U256), 18, &price),
+            BigDecimal::from(3000)
+        );
+        assert_eq!(usd_value(U256::ZERO, 6, &price), BigDecimal::from(0));
+    }
+}
@@ -0,0 +1,730 @@
+//! ## Data Source Extension
+//! This module provides the [`V3DataSource`] trait through which the extensions read on-chain
+//! state, decoupling the position and fee logic from the RPC stack. It is implemented by
+//! [`AlloyDataSource`] for alloy providers, by `SubgraphDataSource` with the `subgraph` feature,
+//! and by [`MockPoolProvider`] serving canned responses, so that code calling [`get_position`] or
+//! [`Pool::from_pool_key`] can be unit tested without an RPC.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    rpc::types::BlockTransactionsKind,
+    transports::Transport,
+};
+use alloy_primitives::{aliases::I24, Address, ChainId, B256, U160, U256};
+use core::{future::Future, marker::PhantomData};
+use rustc_hash::FxHashMap;
+use uniswap_lens::{
+    bindings::{
+        iuniswapv3nonfungiblepositionmanager::IUniswapV3NonfungiblePositionManager::positionsReturn,
+        iuniswapv3pool::IUniswapV3Pool::{
+            feeGrowthGlobal0X128Call, feeGrowthGlobal1X128Call, slot0Return, ticksReturn,
+            IUniswapV3PoolInstance,
+        },
+    },
+    pool_lens,
+};
+use uniswap_sdk_core::prelude::{BaseCurrency, Token};
+
+/// The `slot0` of a pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Slot0 {
+    /// The current price of the pool as a sqrt(token1/token0) Q64.96 value
+    pub sqrt_price_x96: U160,
+    /// The current tick of the pool
+    pub tick: I24,
+    /// The index of the last written observation
+    pub observation_index: u16,
+    /// The current maximum number of observations stored
+    pub observation_cardinality: u16,
+    /// The next maximum number of observations to store
+    pub observation_cardinality_next: u16,
+    /// The protocol fee of both tokens, 4 bits each
+    pub fee_protocol: u8,
+    /// Whether the pool is unlocked, i.e. not in the middle of a call
+    pub unlocked: bool,
+}
+
+/// The state of a position stored in the nonfungible position manager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionInfo {
+    /// The address of token0 of the pool
+    pub token0: Address,
+    /// The address of token1 of the pool
+    pub token1: Address,
+    /// The fee tier of the pool
+    pub fee: FeeAmount,
+    /// The lower tick of the position
+    pub tick_lower: I24,
+    /// The upper tick of the position
+    pub tick_upper: I24,
+    /// The liquidity of the position
+    pub liquidity: u128,
+    /// The fee growth of token0 inside the range as of the last action on the position
+    pub fee_growth_inside0_last_x128: U256,
+    /// The fee growth of token1 inside the range as of the last action on the position
+    pub fee_growth_inside1_last_x128: U256,
+    /// The uncollected amount of token0 owed to the position as of the last action
+    pub tokens_owed0: u128,
+    /// The uncollected amount of token1 owed to the position as of the last action
+    pub tokens_owed1: u128,
+}
+
+/// The state of a tick of a pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickInfo {
+    /// The total liquidity referencing the tick
+    pub liquidity_gross: u128,
+    /// The liquidity added when the tick is crossed from left to right
+    pub liquidity_net: i128,
+    /// The fee growth of token0 on the other side of the tick from the current tick
+    pub fee_growth_outside0_x128: U256,
+    /// The fee growth of token1 on the other side of the tick from the current tick
+    pub fee_growth_outside1_x128: U256,
+    /// Whether the tick is initialized
+    pub initialized: bool,
+}
+
+/// A source of the on-chain state of pools, tokens and positions read by the extensions.
+pub trait V3DataSource {
+    /// Get the `slot0` of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `block_id`: Optional block number to query
+    fn slot0(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<Slot0, Error>>;
+
+    /// Get the in range liquidity of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `block_id`: Optional block number to query
+    fn liquidity(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<u128, Error>>;
+
+    /// Get the fee growth of token0 and token1 per unit of liquidity over the life of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `block_id`: Optional block number to query
+    fn fee_growth_global(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<(U256, U256), Error>>;
+
+    /// Get the state of a tick of a pool, which is zeroed if the tick is not initialized.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `tick`: The tick
+    /// * `block_id`: Optional block number to query
+    fn tick(
+        &self,
+        pool: Address,
+        tick: I24,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<TickInfo, Error>>;
+
+    /// Get the initialized ticks of a pool within a tick range, sorted by tick index.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `tick_lower`: The lower tick of the range
+    /// * `tick_upper`: The upper tick of the range
+    /// * `block_id`: Optional block number to query
+    fn ticks(
+        &self,
+        pool: Address,
+        tick_lower: I24,
+        tick_upper: I24,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<Vec<Tick<I24>>, Error>>;
+
+    /// Get the metadata of an ERC20 token.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `token`: The token address
+    /// * `block_id`: Optional block number to query
+    fn token(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<Token, Error>>;
+
+    /// Get the factory of a nonfungible position manager.
+    ///
+    /// ## Arguments
+    ///
+    /// * `nonfungible_position_manager`: The nonfungible position manager address
+    /// * `block_id`: Optional block number to query
+    fn factory(
+        &self,
+        nonfungible_position_manager: Address,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<Address, Error>>;
+
+    /// Get the state of a position stored in a nonfungible position manager.
+    ///
+    /// ## Arguments
+    ///
+    /// * `nonfungible_position_manager`: The nonfungible position manager address
+    /// * `token_id`: The token id
+    /// * `block_id`: Optional block number to query
+    fn positions(
+        &self,
+        nonfungible_position_manager: Address,
+        token_id: U256,
+        block_id: Option<BlockId>,
+    ) -> impl Future<Output = Result<PositionInfo, Error>>;
+
+    /// Get the hash of a block on the canonical chain, or `None` if the data source doesn't know
+    /// it. Used by [`CachedClient`] to detect reorgs.
+    ///
+    /// ## Arguments
+    ///
+    /// * `block_number`: The block number
+    #[inline]
+    fn block_hash(&self, block_number: u64) -> impl Future<Output = Result<Option<B256>, Error>> {
+        let _ = block_number;
+        async { Ok(None) }
+    }
+}
+
+/// A [`V3DataSource`] reading from an alloy provider via `eth_call`.
+#[derive(Clone, Debug)]
+pub struct AlloyDataSource<T, P> {
+    pub provider: P,
+    _transport: PhantomData<T>,
+}
+
+impl<T, P> AlloyDataSource<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    /// Creates a data source reading from `provider`.
+    #[inline]
+    pub const fn new(provider: P) -> Self {
+        Self {
+            provider,
+            _transport: PhantomData,
+        }
+    }
+}
+
+impl<T, P> V3DataSource for AlloyDataSource<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    #[inline]
+    async fn slot0(&self, pool: Address, block_id: Option<BlockId>) -> Result<Slot0, Error> {
+        let slot0Return {
+            sqrtPriceX96,
+            tick,
+            observationIndex,
+            observationCardinality,
+            observationCardinalityNext,
+            feeProtocol,
+            unlocked,
+        } = IUniswapV3PoolInstance::new(pool, self.provider.clone())
+            .slot0()
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .call()
+            .await?;
+        Ok(Slot0 {
+            sqrt_price_x96: sqrtPriceX96,
+            tick,
+            observation_index: observationIndex,
+            observation_cardinality: observationCardinality,
+            observation_cardinality_next: observationCardinalityNext,
+            fee_protocol: feeProtocol,
+            unlocked,
+        })
+    }
+
+    #[inline]
+    async fn liquidity(&self, pool: Address, block_id: Option<BlockId>) -> Result<u128, Error> {
+        Ok(IUniswapV3PoolInstance::new(pool, self.provider.clone())
+            .liquidity()
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .call()
+            .await?
+            ._0)
+    }
+
+    #[inline]
+    async fn fee_growth_global(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<(U256, U256), Error> {
+        let results = aggregate3(
+            vec![
+                call3(pool, &feeGrowthGlobal0X128Call {}),
+                call3(pool, &feeGrowthGlobal1X128Call {}),
+            ],
+            BatchOptions::default(),
+            self.provider.clone(),
+            block_id,
+        )
+        .await?;
+        let fee_growth_global0_x128 =
+            decode_call3_result::<feeGrowthGlobal0X128Call>(&results[0])?._0;
+        let fee_growth_global1_x128 =
+            decode_call3_result::<feeGrowthGlobal1X128Call>(&results[1])?._0;
+        Ok((fee_growth_global0_x128, fee_growth_global1_x128))
+    }
+
+    #[inline]
+    async fn tick(
+        &self,
+        pool: Address,
+        tick: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<TickInfo, Error> {
+        let ticksReturn {
+            liquidityGross,
+            liquidityNet,
+            feeGrowthOutside0X128,
+            feeGrowthOutside1X128,
+            initialized,
+            ..
+        } = IUniswapV3PoolInstance::new(pool, self.provider.clone())
+            .ticks(tick)
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .call()
+            .await?;
+        Ok(TickInfo {
+            liquidity_gross: liquidityGross,
+            liquidity_net: liquidityNet,
+            fee_growth_outside0_x128: feeGrowthOutside0X128,
+            fee_growth_outside1_x128: feeGrowthOutside1X128,
+            initialized,
+        })
+    }
+
+    #[inline]
+    async fn ticks(
+        &self,
+        pool: Address,
+        tick_lower: I24,
+        tick_upper: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<Tick<I24>>, Error> {
+        let (ticks, _) = pool_lens::get_populated_ticks_in_range(
+            pool,
+            tick_lower,
+            tick_upper,
+            self.provider.clone(),
+            block_id,
+        )
+        .await
+        .map_err(Error::LensError)?;
+        Ok(ticks
+            .into_iter()
+            .map(|tick| Tick::new(tick.tick, tick.liquidityGross, tick.liquidityNet))
+            .collect())
+    }
+
+    #[inline]
+    async fn token(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<Token, Error> {
+        get_token(chain_id, token, self.provider.clone(), block_id).await
+    }
+
+    #[inline]
+    async fn factory(
+        &self,
+        nonfungible_position_manager: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<Address, Error> {
+        Ok(get_nonfungible_position_manager_contract(
+            nonfungible_position_manager,
+            self.provider.clone(),
+        )
+        .factory()
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?
+        ._0)
+    }
+
+    #[inline]
+    async fn positions(
+        &self,
+        nonfungible_position_manager: Address,
+        token_id: U256,
+        block_id: Option<BlockId>,
+    ) -> Result<PositionInfo, Error> {
+        let positionsReturn {
+            token0,
+            token1,
+            fee,
+            tickLower,
+            tickUpper,
+            liquidity,
+            feeGrowthInside0LastX128,
+            feeGrowthInside1LastX128,
+            tokensOwed0,
+            tokensOwed1,
+            ..
+        } = get_nonfungible_position_manager_contract(
+            nonfungible_position_manager,
+            self.provider.clone(),
+        )
+        .positions(token_id)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+        let factory = self.factory(nonfungible_position_manager, block_id).await?;
+        Ok(PositionInfo {
+            token0,
+            token1,
+            fee: get_fee_amount(factory, fee, self.provider.clone(), block_id).await?,
+            tick_lower: tickLower,
+            tick_upper: tickUpper,
+            liquidity,
+            fee_growth_inside0_last_x128: feeGrowthInside0LastX128,
+            fee_growth_inside1_last_x128: feeGrowthInside1LastX128,
+            tokens_owed0: tokensOwed0,
+            tokens_owed1: tokensOwed1,
+        })
+    }
+
+    #[inline]
+    async fn block_hash(&self, block_number: u64) -> Result<Option<B256>, Error> {
+        Ok(self
+            .provider
+            .get_block(BlockId::from(block_number), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?
+            .map(|block| block.header.hash))
+    }
+}
+
+/// A [`V3DataSource`] serving canned responses for unit tests. The block id is ignored, ticks
+/// that weren't added are uninitialized and queries for other data that wasn't added return
+/// [`Error::DataNotFound`].
+#[derive(Clone, Debug, Default)]
+pub struct MockPoolProvider {
+    /// The `slot0` of each pool
+    pub slot0s: FxHashMap<Address, Slot0>,
+    /// The in range liquidity of each pool
+    pub liquidities: FxHashMap<Address, u128>,
+    /// The global fee growth of token0 and token1 of each pool
+    pub fee_growth_globals: FxHashMap<Address, (U256, U256)>,
+    /// The initialized ticks keyed by pool and tick
+    pub ticks: FxHashMap<(Address, I24), TickInfo>,
+    /// The metadata of each token
+    pub tokens: FxHashMap<Address, Token>,
+    /// The factory of each nonfungible position manager
+    pub factories: FxHashMap<Address, Address>,
+    /// The positions keyed by nonfungible position manager and token id
+    pub positions: FxHashMap<(Address, U256), PositionInfo>,
+    /// The hash of each block of the canonical chain
+    pub block_hashes: FxHashMap<u64, B256>,
+}
+
+impl MockPoolProvider {
+    /// Adds the state and tokens of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+    /// * `pool`: The pool to serve
+    #[inline]
+    #[must_use]
+    pub fn with_pool<TP: TickDataProvider>(
+        mut self,
+        deployment: impl Into<PoolDeployment>,
+        pool: &Pool<TP>,
+    ) -> Self {
+        let address = pool.deployment_address(&deployment.into());
+        self.slot0s.insert(
+            address,
+            Slot0 {
+                sqrt_price_x96: pool.sqrt_ratio_x96,
+                tick: pool.tick_current.to_i24(),
+                unlocked: true,
+                ..Default::default()
+            },
+        );
+        self.liquidities.insert(address, pool.liquidity);
+        self.fee_growth_globals
+            .entry(address)
+            .or_insert((U256::ZERO, U256::ZERO));
+        self.tokens
+            .insert(pool.token0.address(), pool.token0.clone());
+        self.tokens
+            .insert(pool.token1.address(), pool.token1.clone());
+        self
+    }
+
+    /// Adds a position, its pool and the factory of the nonfungible position manager.
+    ///
+    /// ## Arguments
+    ///
+    /// * `nonfungible_position_manager`: The nonfungible position manager address
+    /// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+    /// * `token_id`: The token id
+    /// * `position`: The position to serve
+    #[inline]
+    #[must_use]
+    pub fn with_position<TP: TickDataProvider>(
+        self,
+        nonfungible_position_manager: Address,
+        deployment: impl Into<PoolDeployment>,
+        token_id: U256,
+        position: &Position<TP>,
+    ) -> Self {
+        let deployment = deployment.into();
+        let mut this = self.with_pool(deployment, &position.pool);
+        this.factories
+            .insert(nonfungible_position_manager, deployment.factory);
+        this.positions.insert(
+            (nonfungible_position_manager, token_id),
+            PositionInfo {
+                token0: position.pool.token0.address(),
+                token1: position.pool.token1.address(),
+                fee: position.pool.fee,
+                tick_lower: position.tick_lower.to_i24(),
+                tick_upper: position.tick_upper.to_i24(),
+                liquidity: position.liquidity,
+                fee_growth_inside0_last_x128: U256::ZERO,
+                fee_growth_inside1_last_x128: U256::ZERO,
+                tokens_owed0: 0,
+                tokens_owed1: 0,
+            },
+        );
+        this
+    }
+}
+
+impl V3DataSource for MockPoolProvider {
+    #[inline]
+    async fn slot0(&self, pool: Address, _block_id: Option<BlockId>) -> Result<Slot0, Error> {
+        self.slot0s.get(&pool).copied().ok_or(Error::DataNotFound)
+    }
+
+    #[inline]
+    async fn liquidity(&self, pool: Address, _block_id: Option<BlockId>) -> Result<u128, Error> {
+        self.liquidities
+            .get(&pool)
+            .copied()
+            .ok_or(Error::DataNotFound)
+    }
+
+    #[inline]
+    async fn fee_growth_global(
+        &self,
+        pool: Address,
+        _block_id: Option<BlockId>,
+    ) -> Result<(U256, U256), Error> {
+        self.fee_growth_globals
+            .get(&pool)
+            .copied()
+            .ok_or(Error::DataNotFound)
+    }
+
+    #[inline]
+    async fn tick(
+        &self,
+        pool: Address,
+        tick: I24,
+        _block_id: Option<BlockId>,
+    ) -> Result<TickInfo, Error> {
+        Ok(self.ticks.get(&(pool, tick)).copied().unwrap_or_default())
+    }
+
+    #[inline]
+    async fn ticks(
+        &self,
+        pool: Address,
+        tick_lower: I24,
+        tick_upper: I24,
+        _block_id: Option<BlockId>,
+    ) -> Result<Vec<Tick<I24>>, Error> {
+        let mut ticks: Vec<Tick<I24>> = self
+            .ticks
+            .iter()
+            .filter(|((address, tick), info)| {
+                *address == pool
+                    && tick_lower <= *tick
+                    && *tick <= tick_upper
+                    && info.liquidity_gross > 0
+            })
+            .map(|((_, tick), info)| Tick::new(*tick, info.liquidity_gross, info.liquidity_net))
+            .collect();
+        ticks.sort_unstable_by_key(|tick| tick.index);
+        Ok(ticks)
+    }
+
+    #[inline]
+    async fn token(
+        &self,
+        _chain_id: ChainId,
+        token: Address,
+        _block_id: Option<BlockId>,
+    ) -> Result<Token, Error> {
+        self.tokens.get(&token).cloned().ok_or(Error::DataNotFound)
+    }
+
+    #[inline]
+    async fn factory(
+        &self,
+        nonfungible_position_manager: Address,
+        _block_id: Option<BlockId>,
+    ) -> Result<Address, Error> {
+        self.factories
+            .get(&nonfungible_position_manager)
+            .copied()
+            .ok_or(Error::DataNotFound)
+    }
+
+    #[inline]
+    async fn positions(
+        &self,
+        nonfungible_position_manager: Address,
+        token_id: U256,
+        _block_id: Option<BlockId>,
+    ) -> Result<PositionInfo, Error> {
+        self.positions
+            .get(&(nonfungible_position_manager, token_id))
+            .copied()
+            .ok_or(Error::DataNotFound)
+    }
+    #[inline]
+    async fn block_hash(&self, block_number: u64) -> Result<Option<B256>, Error> {
+        Ok(self.block_hashes.get(&block_number).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+
+    const NPM: Address = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+
+    #[tokio::test]
+    async fn test_mock_pool_provider() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let position = Position::new(pool.clone(), 1_000_000, -600, 600);
+        let mock = MockPoolProvider::default().with_position(
+            NPM,
+            FACTORY_ADDRESS,
+            U256::from(1),
+            &position,
+        );
+        let fetched = Pool::from_data_source(
+            1,
+            FACTORY_ADDRESS,
+            TOKEN1.address(),
+            TOKEN0.address(),
+            FEE_AMOUNT,
+            &mock,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(fetched.token0, pool.token0);
+        assert_eq!(fetched.sqrt_ratio_x96, pool.sqrt_ratio_x96);
+        assert_eq!(fetched.liquidity, pool.liquidity);
+        let fetched = get_position_from_data_source(1, NPM, U256::from(1), &mock, None)
+            .await
+            .unwrap();
+        assert_eq!(fetched.tick_lower, -600);
+        assert_eq!(fetched.tick_upper, 600);
+        assert_eq!(fetched.liquidity, position.liquidity);
+        assert!(matches!(
+            get_position_from_data_source(1, NPM, U256::from(2), &mock, None).await,
+            Err(Error::DataNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_collectable_token_amounts_from_mock() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let address = pool.address(None, Some(FACTORY_ADDRESS));
+        let position = Position::new(pool, 1_000_000, -600, 600);
+        let mut mock = MockPoolProvider::default().with_position(
+            NPM,
+            FACTORY_ADDRESS,
+            U256::from(1),
+            &position,
+        );
+        // one unit of fees per unit of liquidity of each token since the position was minted
+        let q128 = U256::from(1) << 128;
+        mock.fee_growth_globals.insert(address, (q128, q128));
+        let amounts =
+            get_collectable_token_amounts_from_data_source(1, NPM, U256::from(1), &mock, None)
+                .await
+                .unwrap();
+        assert_eq!(
+            amounts,
+            (
+                U256::from(position.liquidity),
+                U256::from(position.liquidity)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alloy_data_source() {
+        let data_source = AlloyDataSource::new(PROVIDER.clone());
+        let pool = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+        let slot0 = data_source.slot0(pool, *BLOCK_ID).await.unwrap();
+        assert!(!slot0.sqrt_price_x96.is_zero());
+        assert!(slot0.unlocked);
+        assert!(data_source.liquidity(pool, *BLOCK_ID).await.unwrap() > 0);
+        let usdc = data_source
+            .token(1, USDC.address(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(
+            data_source.factory(NPM, *BLOCK_ID).await.unwrap(),
+            FACTORY_ADDRESS
+        );
+        let slot0_tick = slot0.tick;
+        let ticks = data_source
+            .ticks(
+                pool,
+                slot0_tick - I24::try_from(1000).unwrap(),
+                slot0_tick + I24::try_from(1000).unwrap(),
+                *BLOCK_ID,
+            )
+            .await
+            .unwrap();
+        assert!(!ticks.is_empty());
+        let tick = data_source
+            .tick(pool, ticks[0].index, *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(tick.initialized);
+        assert_eq!(tick.liquidity_net, ticks[0].liquidity_net);
+    }
+}
@@ -0,0 +1,178 @@
+//! ## Gas Extension
+//! Estimates the total cost of a transaction, including the fee for posting its calldata to L1 on
+//! OP Stack chains, via the `GasPriceOracle` predeploy, and on Arbitrum, via `NodeInterface`.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::CallBuilder, providers::Provider, rpc::types::TransactionRequest,
+    transports::Transport,
+};
+use alloy_primitives::{address, Address, ChainId, U256};
+
+/// The address of the `GasPriceOracle` predeploy on OP Stack chains.
+pub const GAS_PRICE_ORACLE_ADDRESS: Address = address!("420000000000000000000000000000000000000F");
+
+/// The address of the virtual `NodeInterface` contract on Arbitrum chains.
+pub const NODE_INTERFACE_ADDRESS: Address = address!("00000000000000000000000000000000000000C8");
+
+/// How a chain charges for the L1 data of transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum L1FeeModel {
+    /// No L1 data fee, e.g. on Ethereum mainnet
+    None,
+    /// A separate L1 fee computed by the `GasPriceOracle` predeploy
+    OpStack,
+    /// L1 gas included in the gas limit, estimated by `NodeInterface`
+    Arbitrum,
+}
+
+impl L1FeeModel {
+    /// The L1 fee model of a chain, assuming [`L1FeeModel::None`] for unknown chains.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    #[inline]
+    #[must_use]
+    pub const fn from_chain_id(chain_id: ChainId) -> Self {
+        match chain_id {
+            // OP Mainnet, Unichain, World Chain, Base, Mode, Zora, Blast
+            10 | 130 | 480 | 8453 | 34443 | 7777777 | 81457 => Self::OpStack,
+            // Arbitrum One, Arbitrum Nova
+            42161 | 42170 => Self::Arbitrum,
+            _ => Self::None,
+        }
+    }
+}
+
+/// The estimated cost of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasCost {
+    /// The gas used by the execution of the transaction, excluding any L1 component
+    pub execution_gas: u64,
+    /// The current gas price, in wei
+    pub gas_price: u128,
+    /// The fee for posting the calldata to L1, in wei
+    pub l1_fee: U256,
+}
+
+impl GasCost {
+    /// The total cost of the transaction, in wei.
+    #[inline]
+    #[must_use]
+    pub fn total(&self) -> U256 {
+        U256::from(self.execution_gas) * U256::from(self.gas_price) + self.l1_fee
+    }
+}
+
+/// Estimates the total cost of a transaction, including the L1 data fee on L2s.
+///
+/// The L1 fee on OP Stack chains is computed for the calldata of the transaction, which
+/// excludes the few bytes of the other fields of the signed transaction.
+///
+/// ## Arguments
+///
+/// * `tx`: The transaction, e.g. from [`TransactionBuilder::request`]
+/// * `provider`: The alloy provider
+#[inline]
+pub async fn estimate_gas_cost<T, P>(tx: &TransactionRequest, provider: P) -> Result<GasCost, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let chain_id = provider
+        .get_chain_id()
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    let gas = provider
+        .estimate_gas(tx)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    let gas_price = provider
+        .get_gas_price()
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    let data = tx.input.input().cloned().unwrap_or_default();
+    Ok(match L1FeeModel::from_chain_id(chain_id) {
+        L1FeeModel::None => GasCost {
+            execution_gas: gas,
+            gas_price,
+            l1_fee: U256::ZERO,
+        },
+        L1FeeModel::OpStack => {
+            let l1_fee = CallBuilder::new_sol(
+                &provider,
+                &GAS_PRICE_ORACLE_ADDRESS,
+                &IGasPriceOracle::getL1FeeCall { _data: data },
+            )
+            .call()
+            .await?
+            ._0;
+            GasCost {
+                execution_gas: gas,
+                gas_price,
+                l1_fee,
+            }
+        }
+        L1FeeModel::Arbitrum => {
+            let to = tx.to.and_then(|to| to.to().copied());
+            let estimate = CallBuilder::new_sol(
+                &provider,
+                &NODE_INTERFACE_ADDRESS,
+                &INodeInterface::gasEstimateL1ComponentCall {
+                    to: to.unwrap_or_default(),
+                    contractCreation: to.is_none(),
+                    data,
+                },
+            )
+            .call()
+            .await?;
+            GasCost {
+                execution_gas: gas.saturating_sub(estimate.gasEstimateForL1),
+                gas_price,
+                l1_fee: U256::from(estimate.gasEstimateForL1) * estimate.baseFee,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::CurrencyAmount;
+
+    #[test]
+    fn test_l1_fee_model() {
+        assert_eq!(L1FeeModel::from_chain_id(1), L1FeeModel::None);
+        assert_eq!(L1FeeModel::from_chain_id(10), L1FeeModel::OpStack);
+        assert_eq!(L1FeeModel::from_chain_id(8453), L1FeeModel::OpStack);
+        assert_eq!(L1FeeModel::from_chain_id(42161), L1FeeModel::Arbitrum);
+    }
+
+    #[test]
+    fn test_total() {
+        let cost = GasCost {
+            execution_gas: 100_000,
+            gas_price: 10,
+            l1_fee: U256::from(5),
+        };
+        assert_eq!(cost.total(), U256::from(1_000_005));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_cost() {
+        const FROM: Address = address!("0000000000000000000000000000000000000003");
+        let params = unwrap_weth9_call_parameters(
+            &CurrencyAmount::from_raw_amount(WETH.clone(), 0).unwrap(),
+            FROM,
+            None,
+        );
+        let tx = TransactionBuilder::new(PROVIDER.clone(), FROM)
+            .request(ChainConfig::MAINNET.swap_router02, params);
+        let cost = estimate_gas_cost(&tx, PROVIDER.clone()).await.unwrap();
+        assert!(cost.execution_gas > 21000);
+        assert_eq!(cost.l1_fee, U256::ZERO);
+        assert!(cost.total() > U256::ZERO);
+    }
+}
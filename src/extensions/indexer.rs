@@ -0,0 +1,399 @@
+//! ## Indexer Extension
+//! A [`PositionIndexer`] that scans the `Transfer`, `IncreaseLiquidity`, `DecreaseLiquidity` and
+//! `Collect` logs of the nonfungible position manager from a start block and persists the owner of
+//! each position, the positions of each owner and the history of each position in a
+//! [`PoolStore`]. Unlike enumerating the positions of an owner with an ephemeral contract, the
+//! queries are answered locally and aren't limited by the gas of an `eth_call`.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockNumberOrTag, providers::Provider, rpc::types::Filter, transports::Transport,
+};
+use alloy_primitives::{Address, ChainId, LogData, B256, U256};
+use alloy_sol_types::SolEvent;
+use core::marker::PhantomData;
+use INonfungiblePositionManagerEvents::{Collect, DecreaseLiquidity, IncreaseLiquidity, Transfer};
+
+/// Indexes the positions of a nonfungible position manager into a [`PoolStore`].
+///
+/// Only blocks that can no longer be reorged should be indexed, since the indexed logs are never
+/// rolled back.
+#[derive(Clone, Debug)]
+pub struct PositionIndexer<T, P> {
+    pub provider: P,
+    /// The chain id
+    pub chain_id: ChainId,
+    /// The nonfungible position manager address
+    pub nonfungible_position_manager: Address,
+    /// The maximum number of blocks per `eth_getLogs` request, by default 2000
+    pub batch_size: u64,
+    /// `(prefix, owner, token_id)` -> `()`
+    owners: sled::Tree,
+    /// `(prefix, token_id)` -> `owner`
+    positions: sled::Tree,
+    /// `(prefix, token_id, block_number, log_index)` -> `(transaction_hash, topics, data)`
+    logs: sled::Tree,
+    /// `prefix` -> the last indexed block
+    checkpoints: sled::Tree,
+    _transport: PhantomData<T>,
+}
+
+impl<T, P> PositionIndexer<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    /// Creates an indexer persisting into `store`, resuming from its last indexed block if any.
+    ///
+    /// ## Arguments
+    ///
+    /// * `store`: The store to persist the positions in
+    /// * `chain_id`: The chain id
+    /// * `nonfungible_position_manager`: The nonfungible position manager address
+    /// * `provider`: The alloy provider
+    #[inline]
+    pub fn new(
+        store: &PoolStore,
+        chain_id: ChainId,
+        nonfungible_position_manager: Address,
+        provider: P,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            provider,
+            chain_id,
+            nonfungible_position_manager,
+            batch_size: 2000,
+            owners: store.open_tree("position_owners")?,
+            positions: store.open_tree("positions")?,
+            logs: store.open_tree("position_logs")?,
+            checkpoints: store.open_tree("position_checkpoints")?,
+            _transport: PhantomData,
+        })
+    }
+
+    /// Sets the maximum number of blocks per `eth_getLogs` request.
+    ///
+    /// ## Arguments
+    ///
+    /// * `batch_size`: The number of blocks
+    #[inline]
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    fn prefix(&self) -> [u8; 28] {
+        let mut prefix = [0; 28];
+        prefix[..8].copy_from_slice(&self.chain_id.to_be_bytes());
+        prefix[8..].copy_from_slice(self.nonfungible_position_manager.as_slice());
+        prefix
+    }
+
+    fn token_key(&self, token_id: U256) -> Vec<u8> {
+        [&self.prefix()[..], &token_id.to_be_bytes::<32>()].concat()
+    }
+
+    fn owner_key(&self, owner: Address, token_id: U256) -> Vec<u8> {
+        [
+            &self.prefix()[..],
+            owner.as_slice(),
+            &token_id.to_be_bytes::<32>(),
+        ]
+        .concat()
+    }
+
+    /// The last block whose logs were indexed, if any.
+    #[inline]
+    pub fn last_indexed_block(&self) -> Result<Option<u64>, Error> {
+        self.checkpoints
+            .get(self.prefix())?
+            .map(|block| {
+                Ok(u64::from_be_bytes(
+                    block[..].try_into().map_err(|_| Error::InvalidSnapshot)?,
+                ))
+            })
+            .transpose()
+    }
+
+    /// Indexes the logs from `from_block`, or the block after the last indexed block if later,
+    /// through `to_block`, in batches of [`Self::batch_size`] blocks. Progress is persisted after
+    /// each batch, so an interrupted sync resumes where it stopped.
+    ///
+    /// ## Arguments
+    ///
+    /// * `from_block`: The first block to index, e.g. the deployment block of the position manager
+    /// * `to_block`: Optional last block to index, the latest block by default
+    ///
+    /// ## Returns
+    ///
+    /// The last indexed block
+    #[inline]
+    pub async fn sync(&self, from_block: u64, to_block: Option<u64>) -> Result<u64, Error> {
+        let to_block = match to_block {
+            Some(to_block) => to_block,
+            None => self
+                .provider
+                .get_block_number()
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?,
+        };
+        let mut start = match self.last_indexed_block()? {
+            Some(last) => from_block.max(last + 1),
+            None => from_block,
+        };
+        while start <= to_block {
+            let end = to_block.min(start + self.batch_size.max(1) - 1);
+            let filter = Filter::new()
+                .address(self.nonfungible_position_manager)
+                .event_signature(vec![
+                    Transfer::SIGNATURE_HASH,
+                    IncreaseLiquidity::SIGNATURE_HASH,
+                    DecreaseLiquidity::SIGNATURE_HASH,
+                    Collect::SIGNATURE_HASH,
+                ])
+                .from_block(BlockNumberOrTag::Number(start))
+                .to_block(BlockNumberOrTag::Number(end));
+            let logs = self
+                .provider
+                .get_logs(&filter)
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?;
+            for log in &logs {
+                self.apply(&PositionLog {
+                    block_number: log.block_number.unwrap_or_default(),
+                    log_index: log.log_index.unwrap_or_default(),
+                    transaction_hash: log.transaction_hash.unwrap_or_default(),
+                    event: decode_position_event(&log.inner)?,
+                })?;
+            }
+            self.checkpoints
+                .insert(self.prefix(), end.to_be_bytes().to_vec())?;
+            start = end + 1;
+        }
+        self.last_indexed_block()
+            .map(|last| last.unwrap_or(to_block))
+    }
+
+    /// Indexes a single log, e.g. one received from a subscription. Applying the same log twice is
+    /// a no-op.
+    ///
+    /// ## Arguments
+    ///
+    /// * `log`: The decoded log
+    #[inline]
+    pub fn apply(&self, log: &PositionLog) -> Result<(), Error> {
+        let token_id = log.event.token_id();
+        let data = match &log.event {
+            PositionEvent::IncreaseLiquidity(event) => event.encode_log_data(),
+            PositionEvent::DecreaseLiquidity(event) => event.encode_log_data(),
+            PositionEvent::Collect(event) => event.encode_log_data(),
+            PositionEvent::Transfer(event) => event.encode_log_data(),
+        };
+        let mut value = Vec::with_capacity(33 + 32 * data.topics().len() + data.data.len());
+        value.extend_from_slice(log.transaction_hash.as_slice());
+        value.push(data.topics().len() as u8);
+        for topic in data.topics() {
+            value.extend_from_slice(topic.as_slice());
+        }
+        value.extend_from_slice(&data.data);
+        let key = [
+            &self.token_key(token_id)[..],
+            &log.block_number.to_be_bytes(),
+            &log.log_index.to_be_bytes(),
+        ]
+        .concat();
+        self.logs.insert(key, value)?;
+
+        if let PositionEvent::Transfer(transfer) = &log.event {
+            if !transfer.from.is_zero() {
+                self.owners
+                    .remove(self.owner_key(transfer.from, token_id))?;
+            }
+            if transfer.to.is_zero() {
+                // burned
+                self.positions.remove(self.token_key(token_id))?;
+            } else {
+                self.owners
+                    .insert(self.owner_key(transfer.to, token_id), Vec::new())?;
+                self.positions
+                    .insert(self.token_key(token_id), transfer.to.to_vec())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The current owner of a position, or `None` if it doesn't exist or was burned.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token_id`: The token id
+    #[inline]
+    pub fn owner_of(&self, token_id: U256) -> Result<Option<Address>, Error> {
+        Ok(self
+            .positions
+            .get(self.token_key(token_id))?
+            .map(|owner| Address::from_slice(&owner)))
+    }
+
+    /// The token ids of the positions of an owner, in ascending order.
+    ///
+    /// ## Arguments
+    ///
+    /// * `owner`: The owner of the positions
+    #[inline]
+    pub fn token_ids_of(&self, owner: Address) -> Result<Vec<U256>, Error> {
+        let prefix = [&self.prefix()[..], owner.as_slice()].concat();
+        self.owners
+            .scan_prefix(&prefix)
+            .keys()
+            .map(|key| Ok(U256::from_be_slice(&key?[prefix.len()..])))
+            .collect()
+    }
+
+    /// The indexed history of a position.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token_id`: The token id
+    #[inline]
+    pub fn history(&self, token_id: U256) -> Result<PositionHistory, Error> {
+        let prefix = self.token_key(token_id);
+        let logs = self
+            .logs
+            .scan_prefix(&prefix)
+            .map(|entry| {
+                let (key, value) = entry?;
+                decode_indexed_log(
+                    self.nonfungible_position_manager,
+                    &key[prefix.len()..],
+                    &value,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(PositionHistory { token_id, logs })
+    }
+}
+
+/// Decodes a log persisted by [`PositionIndexer::apply`].
+fn decode_indexed_log(
+    nonfungible_position_manager: Address,
+    key: &[u8],
+    value: &[u8],
+) -> Result<PositionLog, Error> {
+    let (block_number, log_index) = key.split_at_checked(8).ok_or(Error::InvalidSnapshot)?;
+    let (transaction_hash, value) = value.split_at_checked(32).ok_or(Error::InvalidSnapshot)?;
+    let (&topic_count, value) = value.split_first().ok_or(Error::InvalidSnapshot)?;
+    let (topics, data) = value
+        .split_at_checked(32 * topic_count as usize)
+        .ok_or(Error::InvalidSnapshot)?;
+    let log = alloy_primitives::Log {
+        address: nonfungible_position_manager,
+        data: LogData::new_unchecked(
+            topics.chunks_exact(32).map(B256::from_slice).collect(),
+            data.to_vec().into(),
+        ),
+    };
+    Ok(PositionLog {
+        block_number: u64::from_be_bytes(
+            block_number
+                .try_into()
+                .map_err(|_| Error::InvalidSnapshot)?,
+        ),
+        log_index: u64::from_be_bytes(log_index.try_into().map_err(|_| Error::InvalidSnapshot)?),
+        transaction_hash: B256::from_slice(transaction_hash),
+        event: decode_position_event(&log)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::{address, uint};
+
+    const NPM: Address = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+    const ALICE: Address = address!("0000000000000000000000000000000000000a11");
+    const BOB: Address = address!("0000000000000000000000000000000000000b0b");
+
+    fn log(block_number: u64, event: PositionEvent) -> PositionLog {
+        PositionLog {
+            block_number,
+            log_index: 0,
+            transaction_hash: B256::with_last_byte(block_number as u8),
+            event,
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let store = PoolStore::temporary().unwrap();
+        let indexer = PositionIndexer::new(&store, 1, NPM, PROVIDER.clone()).unwrap();
+        let token_id = uint!(7_U256);
+        let logs = [
+            log(
+                1,
+                PositionEvent::Transfer(Transfer {
+                    from: Address::ZERO,
+                    to: ALICE,
+                    tokenId: token_id,
+                }),
+            ),
+            log(
+                2,
+                PositionEvent::IncreaseLiquidity(IncreaseLiquidity {
+                    tokenId: token_id,
+                    liquidity: 1000,
+                    amount0: uint!(3_U256),
+                    amount1: uint!(5_U256),
+                }),
+            ),
+            log(
+                3,
+                PositionEvent::Transfer(Transfer {
+                    from: ALICE,
+                    to: BOB,
+                    tokenId: token_id,
+                }),
+            ),
+        ];
+        for log in &logs {
+            indexer.apply(log).unwrap();
+        }
+        assert_eq!(indexer.owner_of(token_id).unwrap(), Some(BOB));
+        assert!(indexer.token_ids_of(ALICE).unwrap().is_empty());
+        assert_eq!(indexer.token_ids_of(BOB).unwrap(), vec![token_id]);
+        let history = indexer.history(token_id).unwrap();
+        assert_eq!(history.logs, logs);
+        assert_eq!(history.liquidity_delta().unwrap(), 1000);
+        assert_eq!(history.owner(), Some(BOB));
+
+        indexer
+            .apply(&log(
+                4,
+                PositionEvent::Transfer(Transfer {
+                    from: BOB,
+                    to: Address::ZERO,
+                    tokenId: token_id,
+                }),
+            ))
+            .unwrap();
+        assert_eq!(indexer.owner_of(token_id).unwrap(), None);
+        assert!(indexer.token_ids_of(BOB).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync() {
+        let store = PoolStore::temporary().unwrap();
+        let indexer = PositionIndexer::new(&store, 1, NPM, PROVIDER.clone())
+            .unwrap()
+            .with_batch_size(5);
+        assert_eq!(indexer.last_indexed_block().unwrap(), None);
+        let last = indexer.sync(17000000, Some(17000009)).await.unwrap();
+        assert_eq!(last, 17000009);
+        assert_eq!(indexer.last_indexed_block().unwrap(), Some(17000009));
+        // resumes from the checkpoint
+        let last = indexer.sync(17000000, Some(17000014)).await.unwrap();
+        assert_eq!(last, 17000014);
+    }
+}
@@ -0,0 +1,162 @@
+//! ## Lazy Tick Data Provider
+//! An [`AsyncTickDataProvider`] that fetches the ticks of a pool one word of the tick bitmap at a
+//! time as a swap simulation reaches them, using an [ephemeral contract](https://github.com/Aperture-Finance/Aperture-Lens/blob/904101e4daed59e02fd4b758b98b0749e70b583b/contracts/EphemeralGetPopulatedTicksInRange.sol),
+//! so that simulating a swap doesn't require downloading all the ticks of a large pool.
+
+use crate::prelude::*;
+use alloy::{eips::BlockId, providers::Provider, transports::Transport};
+use alloy_primitives::{aliases::I24, Address, U256};
+use core::marker::PhantomData;
+use rustc_hash::{FxHashMap, FxHashSet};
+use uniswap_lens::pool_lens;
+
+/// A tick data provider that fetches and caches the ticks of one word of the tick bitmap at a time.
+#[derive(Clone, Debug)]
+pub struct LazyTickDataProvider<T, P, I = I24> {
+    pub pool: Address,
+    pub provider: P,
+    pub block_id: Option<BlockId>,
+    /// The ticks fetched so far
+    pub tick_map: TickMap<I>,
+    /// The positions of the words of the tick bitmap fetched so far
+    pub words: FxHashSet<I>,
+    _transport: PhantomData<T>,
+}
+
+impl<T, P, I> LazyTickDataProvider<T, P, I>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+    I: TickIndex,
+{
+    /// Creates a provider that hasn't fetched any ticks yet.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `tick_spacing`: The tick spacing of the pool
+    /// * `provider`: The alloy provider
+    /// * `block_id`: Optional block number to query
+    #[inline]
+    pub fn new(pool: Address, tick_spacing: I, provider: P, block_id: Option<BlockId>) -> Self {
+        Self {
+            pool,
+            provider,
+            block_id,
+            tick_map: TickMap {
+                bitmap: TickBitMap::default(),
+                inner: FxHashMap::default(),
+                tick_spacing,
+            },
+            words: FxHashSet::default(),
+            _transport: PhantomData,
+        }
+    }
+
+    /// Fetches the populated ticks of a word of the tick bitmap unless already fetched.
+    ///
+    /// ## Arguments
+    ///
+    /// * `word_pos`: The position of the word
+    #[inline]
+    pub async fn fetch_word(&mut self, word_pos: I) -> Result<(), Error> {
+        if self.words.contains(&word_pos) {
+            return Ok(());
+        }
+        let tick_spacing = self.tick_map.tick_spacing;
+        let tick_lower = ((word_pos << 8) * tick_spacing).to_i24().max(MIN_TICK);
+        let tick_upper = (((word_pos << 8) + I::from_i24(I24::from_limbs([255]))) * tick_spacing)
+            .to_i24()
+            .min(MAX_TICK);
+        let (ticks, _) = pool_lens::get_populated_ticks_in_range(
+            self.pool,
+            tick_lower,
+            tick_upper,
+            self.provider.clone(),
+            self.block_id,
+        )
+        .await
+        .map_err(Error::LensError)?;
+        let TickMap { bitmap, inner, .. } = &mut self.tick_map;
+        for tick in ticks {
+            let index = I::from_i24(tick.tick);
+            let (word, bit_pos) = index.compress(tick_spacing).position();
+            *bitmap.entry(word).or_default() |= U256::from(1) << bit_pos;
+            inner.insert(
+                index,
+                Tick::new(index, tick.liquidityGross, tick.liquidityNet),
+            );
+        }
+        self.words.insert(word_pos);
+        Ok(())
+    }
+}
+
+impl<T, P, I> AsyncTickDataProvider for LazyTickDataProvider<T, P, I>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+    I: TickIndex,
+{
+    type Index = I;
+
+    #[inline]
+    async fn get_tick(&mut self, tick: I) -> Result<Tick<I>, Error> {
+        let (word_pos, _) = tick.compress(self.tick_map.tick_spacing).position();
+        self.fetch_word(word_pos).await?;
+        self.tick_map.get_tick(tick).copied()
+    }
+
+    #[inline]
+    async fn next_initialized_tick_within_one_word(
+        &mut self,
+        tick: I,
+        lte: bool,
+        tick_spacing: I,
+    ) -> Result<(I, bool), Error> {
+        let compressed = tick.compress(tick_spacing);
+        // searching to the right starts from the word of the next tick
+        let (word_pos, _) = if lte {
+            compressed.position()
+        } else {
+            (compressed + I::ONE).position()
+        };
+        self.fetch_word(word_pos).await?;
+        self.tick_map
+            .next_initialized_tick_within_one_word(tick, lte, tick_spacing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[tokio::test]
+    async fn test_lazy_tick_data_provider() -> Result<(), Error> {
+        const POOL: Address = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+        let pool = Pool::<EphemeralTickMapDataProvider>::from_pool_key_with_tick_data_provider(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await?;
+        let mut lazy =
+            LazyTickDataProvider::new(POOL, pool.tick_spacing(), PROVIDER.clone(), *BLOCK_ID);
+        let amount_in = U256::from(10_u128.pow(21));
+        let result = pool
+            .simulate_swap_exact_in_async(&mut lazy, false, amount_in, None)
+            .await?;
+        assert_eq!(result, pool.simulate_swap_exact_in(false, amount_in, None)?);
+        // only the words crossed by the swap are fetched
+        assert!(!lazy.words.is_empty());
+        assert!(lazy.tick_map.inner.len() < pool.tick_data_provider.inner.len());
+        Ok(())
+    }
+}
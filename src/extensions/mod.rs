@@ -1,17 +1,77 @@
 //! Extensions to the core library.
 
+#[cfg(feature = "std")]
+mod cache;
+mod chainlink;
+mod data_source;
 mod ephemeral_tick_data_provider;
 mod ephemeral_tick_map_data_provider;
+mod gas;
+#[cfg(feature = "store")]
+mod indexer;
+mod lazy_tick_data_provider;
+mod multicall3;
+mod nft_svg;
+mod oracle;
+mod permit2;
 mod pool;
+mod pool_scanner;
+mod pool_swaps;
+mod pool_tracker;
+mod portfolio;
 mod position;
+mod position_apr;
+mod position_history;
 mod price_tick_conversions;
+mod quoter;
+mod retry;
+mod simulate;
+mod storage;
+#[cfg(feature = "store")]
+mod store;
+#[cfg(feature = "subgraph")]
+mod subgraph;
 mod tick_bit_map;
+mod tick_lens;
 mod tick_map;
+mod token;
+mod transaction;
+mod usd_price;
 
+#[cfg(feature = "std")]
+pub use cache::*;
+pub use chainlink::*;
+pub use data_source::*;
 pub use ephemeral_tick_data_provider::EphemeralTickDataProvider;
 pub use ephemeral_tick_map_data_provider::EphemeralTickMapDataProvider;
+pub use gas::*;
+#[cfg(feature = "store")]
+pub use indexer::*;
+pub use lazy_tick_data_provider::LazyTickDataProvider;
+pub use multicall3::*;
+pub use nft_svg::*;
+pub use oracle::*;
+pub use permit2::*;
 pub use pool::*;
+pub use pool_scanner::*;
+pub use pool_swaps::*;
+pub use pool_tracker::*;
+pub use portfolio::*;
 pub use position::*;
+pub use position_apr::*;
+pub use position_history::*;
 pub use price_tick_conversions::*;
+pub use quoter::*;
+pub use retry::*;
+pub use simulate::*;
+pub use storage::*;
+#[cfg(feature = "store")]
+pub use store::*;
+#[cfg(feature = "subgraph")]
+pub use subgraph::*;
 pub use tick_bit_map::*;
+pub use tick_lens::*;
 pub use tick_map::*;
+pub use token::*;
+pub use transaction::*;
+pub use usd_price::*;
@@ -0,0 +1,305 @@
+//! ## Multicall3 Extension
+//! This module provides functions to batch many view calls into chunked
+//! [Multicall3](https://github.com/mds1/multicall) `aggregate3` calls.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::{CallBuilder, Error as ContractError},
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    rpc::types::BlockTransactionsKind,
+    transports::Transport,
+};
+use alloy_primitives::{address, Address};
+use alloy_sol_types::SolCall;
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+/// The canonical Multicall3 address, the same on all supported chains.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// The default maximum number of calls per `eth_call`, well within the gas limit of most
+/// providers for view calls.
+pub const DEFAULT_MULTICALL_CHUNK_SIZE: usize = 500;
+
+/// The default maximum number of requests of a batch in flight at once.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Options for splitting a large batch read into chunks that are executed concurrently and merged,
+/// to stay within the gas and response size limits of providers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// The maximum number of calls per request
+    pub chunk_size: usize,
+    /// The maximum number of requests in flight at once
+    pub max_concurrency: usize,
+    /// How failed requests are retried, or `None` to fail on the first error
+    pub retry: Option<RetryPolicy>,
+}
+
+impl Default for BatchOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_MULTICALL_CHUNK_SIZE,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            retry: None,
+        }
+    }
+}
+
+impl BatchOptions {
+    /// Checks that the limits and the number of attempts are positive.
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::InvalidOptions`] if any of them is zero
+    #[inline]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.chunk_size == 0
+            || self.max_concurrency == 0
+            || matches!(self.retry, Some(retry) if retry.max_attempts == 0)
+        {
+            return Err(Error::InvalidOptions);
+        }
+        Ok(())
+    }
+}
+
+/// Resolves `block_id` to the number of the block it refers to, so that the requests of a batch read
+/// the state of the same block even if a new block is mined in between.
+///
+/// Block numbers and block hashes are returned as is. `None`, the latest and the pending block are
+/// resolved to the number of the latest block, as the state of the pending block isn't fixed, and
+/// the other tags to the number of the block they currently refer to.
+///
+/// ## Arguments
+///
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn pin_block_id<T, P>(provider: &P, block_id: Option<BlockId>) -> Result<BlockId, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    match block_id {
+        Some(block_id @ (BlockId::Hash(_) | BlockId::Number(BlockNumberOrTag::Number(_)))) => {
+            Ok(block_id)
+        }
+        None | Some(BlockId::Number(BlockNumberOrTag::Latest | BlockNumberOrTag::Pending)) => {
+            provider
+                .get_block_number()
+                .await
+                .map(BlockId::from)
+                .map_err(|e| Error::ContractError(e.into()))
+        }
+        Some(
+            block_id @ BlockId::Number(
+                BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized | BlockNumberOrTag::Earliest,
+            ),
+        ) => provider
+            .get_block(block_id, BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?
+            .map(|block| BlockId::from(block.header.number))
+            .ok_or(Error::DataNotFound),
+    }
+}
+
+/// Creates a Multicall3 call that reverts the whole batch on failure.
+///
+/// ## Arguments
+///
+/// * `target`: The contract to call
+/// * `call`: The call to make
+#[inline]
+#[must_use]
+pub fn call3<C: SolCall>(target: Address, call: &C) -> IMulticall3::Call3 {
+    IMulticall3::Call3 {
+        target,
+        allowFailure: false,
+        callData: call.abi_encode().into(),
+    }
+}
+
+/// Decodes the return data of a Multicall3 call.
+///
+/// ## Arguments
+///
+/// * `result`: The result of the call made with `C`
+#[inline]
+pub fn decode_call3_result<C: SolCall>(result: &IMulticall3::Result) -> Result<C::Return, Error> {
+    C::abi_decode_returns(&result.returnData, true)
+        .map_err(|e| Error::ContractError(ContractError::AbiError(e.into())))
+}
+
+/// Makes `calls` through Multicall3 `aggregate3`, in chunks of `options.chunk_size` calls per
+/// `eth_call` with at most `options.max_concurrency` chunks in flight, retrying failed chunks
+/// according to `options.retry`. If there is more than one chunk, the block is resolved with
+/// [`pin_block_id`] first so that all chunks read the same block.
+///
+/// ## Arguments
+///
+/// * `calls`: The calls to make
+/// * `options`: The chunking, concurrency and retries of the `eth_call`s
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The results of `calls`, in the same order
+#[inline]
+pub async fn aggregate3<T, P>(
+    calls: Vec<IMulticall3::Call3>,
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<IMulticall3::Result>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    options.validate()?;
+    // the chunks are sent concurrently, so they must read the same block
+    let block_id = if calls.len() > options.chunk_size {
+        pin_block_id(&provider, block_id).await?
+    } else {
+        block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest))
+    };
+    let provider = &provider;
+    let chunks: Vec<Vec<IMulticall3::Result>> =
+        stream::iter(calls.chunks(options.chunk_size).map(|chunk| {
+            let call = IMulticall3::aggregate3Call {
+                calls: chunk.to_vec(),
+            };
+            async move {
+                let call = &call;
+                with_retry(options.retry, move || async move {
+                    CallBuilder::new_sol(provider, &MULTICALL3_ADDRESS, call)
+                        .block(block_id)
+                        .call()
+                        .await
+                        .map(|result| result.returnData)
+                        .map_err(Error::ContractError)
+                })
+                .await
+            }
+        }))
+        .buffered(options.max_concurrency)
+        .try_collect()
+        .await?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[test]
+    fn test_validate_batch_options() {
+        assert!(BatchOptions::default().validate().is_ok());
+        for options in [
+            BatchOptions {
+                chunk_size: 0,
+                ..Default::default()
+            },
+            BatchOptions {
+                max_concurrency: 0,
+                ..Default::default()
+            },
+            BatchOptions {
+                retry: Some(RetryPolicy {
+                    max_attempts: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(options.validate(), Err(Error::InvalidOptions)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pin_block_id() {
+        assert_eq!(
+            pin_block_id(&*PROVIDER, *BLOCK_ID).await.unwrap(),
+            BLOCK_ID.unwrap()
+        );
+        let latest = pin_block_id(&*PROVIDER, None).await.unwrap();
+        assert!(matches!(
+            latest,
+            BlockId::Number(BlockNumberOrTag::Number(number)) if number > 17_000_000
+        ));
+        for tag in [
+            BlockNumberOrTag::Pending,
+            BlockNumberOrTag::Safe,
+            BlockNumberOrTag::Finalized,
+        ] {
+            assert!(matches!(
+                pin_block_id(&*PROVIDER, Some(BlockId::Number(tag))).await.unwrap(),
+                BlockId::Number(BlockNumberOrTag::Number(number)) if number > 17_000_000
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3() {
+        let calls = vec![
+            call3(
+                FACTORY_ADDRESS,
+                &IUniswapV3Factory::getPoolCall {
+                    tokenA: USDC.address(),
+                    tokenB: WETH.address(),
+                    fee: FeeAmount::MEDIUM.into(),
+                },
+            ),
+            call3(
+                FACTORY_ADDRESS,
+                &IUniswapV3Factory::getPoolCall {
+                    tokenA: USDC.address(),
+                    tokenB: WETH.address(),
+                    fee: FeeAmount::LOW_200.into(),
+                },
+            ),
+        ];
+        let options = BatchOptions {
+            chunk_size: 1,
+            max_concurrency: 2,
+            retry: Some(RetryPolicy::default()),
+        };
+        let results = aggregate3(calls, options, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        let pool = decode_call3_result::<IUniswapV3Factory::getPoolCall>(&results[0])
+            .unwrap()
+            .pool;
+        assert_eq!(pool, address!("8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8"));
+        let pool = decode_call3_result::<IUniswapV3Factory::getPoolCall>(&results[1])
+            .unwrap()
+            .pool;
+        assert_eq!(pool, Address::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_pins_block() {
+        let calls = vec![call3(MULTICALL3_ADDRESS, &IMulticall3::getBlockNumberCall {}); 4];
+        let options = BatchOptions {
+            chunk_size: 1,
+            ..Default::default()
+        };
+        let results = aggregate3(calls, options, PROVIDER.clone(), None)
+            .await
+            .unwrap();
+        let numbers = results
+            .iter()
+            .map(decode_call3_result::<IMulticall3::getBlockNumberCall>)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(numbers
+            .windows(2)
+            .all(|pair| pair[0].blockNumber == pair[1].blockNumber));
+    }
+}
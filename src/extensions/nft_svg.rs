@@ -0,0 +1,395 @@
+//! ## NFT SVG Extension
+//! Renders the art of position NFTs locally, reproducing the SVG generated by the `NFTDescriptor`
+//! and `NFTSVG` libraries of the periphery contracts from a [`Position`] and its token id, so that
+//! the image of many positions can be rendered without a `tokenURI` call each.
+
+use crate::prelude::*;
+use alloc::format;
+use alloy_primitives::{address, hex, keccak256, Address, ChainId, U256};
+use base64::{engine::general_purpose, Engine};
+use uniswap_sdk_core::prelude::BaseCurrency;
+
+const CURVES: [&str; 8] = [
+    "M1 1C41 41 105 105 145 145",
+    "M1 1C33 49 97 113 145 145",
+    "M1 1C33 57 89 113 145 145",
+    "M1 1C25 65 81 121 145 145",
+    "M1 1C17 73 73 129 145 145",
+    "M1 1C9 81 65 137 145 145",
+    "M1 1C1 89 57.5 145 145 145",
+    "M1 1C1 97 49 145 145 145",
+];
+
+const MAINNET_USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+const MAINNET_USDT: Address = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+const MAINNET_DAI: Address = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+const MAINNET_TBTC: Address = address!("8dAEBADE922dF735c38C80C7eBD708Af50815fAa");
+const MAINNET_WBTC: Address = address!("2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599");
+
+const FONT: &str = "font-family=\"'Courier New', monospace\"";
+
+/// The priority of a token as the numerator of the displayed price, as in
+/// `NonfungibleTokenPositionDescriptor.tokenRatioPriority`.
+fn token_ratio_priority(token: Address, chain_id: ChainId) -> i32 {
+    if ChainConfig::builtin(chain_id).is_some_and(|config| config.weth == token) {
+        return -100;
+    }
+    if chain_id != 1 {
+        return 0;
+    }
+    match token {
+        MAINNET_USDC => 300,
+        MAINNET_USDT => 200,
+        MAINNET_DAI => 100,
+        MAINNET_TBTC => -200,
+        MAINNET_WBTC => -300,
+        _ => 0,
+    }
+}
+
+/// The fee tier as a percentage, e.g. `0.3%` for a fee of 3000.
+///
+/// ## Arguments
+///
+/// * `fee`: The fee in hundredths of bips
+#[inline]
+#[must_use]
+pub fn fee_to_percent_string(fee: u32) -> String {
+    let fraction = format!("{:04}", fee % 10000);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}%", fee / 10000)
+    } else {
+        format!("{}.{fraction}%", fee / 10000)
+    }
+}
+
+/// The path of the curve depicting the width of a range, in number of tick spacings.
+fn curve(tick_lower: i32, tick_upper: i32, tick_spacing: i32) -> &'static str {
+    let tick_range = (tick_upper - tick_lower) / tick_spacing;
+    match tick_range {
+        ..=4 => CURVES[0],
+        5..=8 => CURVES[1],
+        9..=16 => CURVES[2],
+        17..=32 => CURVES[3],
+        33..=64 => CURVES[4],
+        65..=128 => CURVES[5],
+        129..=256 => CURVES[6],
+        _ => CURVES[7],
+    }
+}
+
+/// The coordinates of the dot on the minimap, by the midpoint of a range.
+const fn range_location(tick_lower: i32, tick_upper: i32) -> (&'static str, &'static str) {
+    let mid_point = (tick_lower + tick_upper) / 2;
+    if mid_point < -125_000 {
+        ("8", "7")
+    } else if mid_point < -75_000 {
+        ("8", "10.5")
+    } else if mid_point < -25_000 {
+        ("8", "14.25")
+    } else if mid_point < -5_000 {
+        ("10", "18")
+    } else if mid_point < 0 {
+        ("11", "21")
+    } else if mid_point < 5_000 {
+        ("13", "23")
+    } else if mid_point < 25_000 {
+        ("15", "25")
+    } else if mid_point < 75_000 {
+        ("18", "26")
+    } else if mid_point < 125_000 {
+        ("21", "27")
+    } else {
+        ("24", "27")
+    }
+}
+
+/// Whether a position gets the rare sparkle, as in `NFTSVG.isRare`.
+fn is_rare(token_id: U256, pool: Address) -> bool {
+    let hash =
+        U256::from_be_bytes(keccak256([&token_id.to_be_bytes::<32>()[..], &pool[..]].concat()).0);
+    let msb = if token_id.is_zero() {
+        0
+    } else {
+        most_significant_bit(token_id)
+    };
+    hash < U256::MAX / U256::from(1 + msb * 2)
+}
+
+/// The lowercase hex of the 24 bits of a token address starting at `offset`.
+fn token_to_color_hex(token: U256, offset: usize) -> String {
+    format!("{:06x}", (token >> offset).as_limbs()[0] & 0xff_ffff)
+}
+
+/// The coordinate of a background circle, scaled from `[0, 255)` to `[out_min, out_max]`.
+fn circle_coord(token: U256, offset: usize, token_id: U256, out_min: u64, out_max: u64) -> String {
+    let slice = (token >> offset) & U256::from(0xff);
+    let n = slice.wrapping_mul(token_id) % U256::from(255);
+    (n * U256::from(out_max - out_min) / U256::from(255) + U256::from(out_min)).to_string()
+}
+
+/// A base64 encoded background layer of the card.
+fn background_layer(shape: &str) -> String {
+    general_purpose::STANDARD.encode(format!(
+        "<svg width='290' height='500' viewBox='0 0 290 500' xmlns='http://www.w3.org/2000/svg'>{shape}</svg>"
+    ))
+}
+
+/// Renders the SVG image of a position NFT, identical to the image in its `tokenURI`.
+///
+/// The price is displayed as a quote token per base token, where the quote token is the one
+/// prioritized by the position descriptor, e.g. a stablecoin over WETH. Tokens without a symbol are
+/// displayed without one, whereas the descriptor falls back to a symbol derived from the address.
+///
+/// ## Arguments
+///
+/// * `position`: The position, whose pool provides the tokens, fee and current tick
+/// * `token_id`: The token id of the position
+///
+/// ## Returns
+///
+/// The SVG document
+#[inline]
+#[must_use]
+pub fn render_position_svg<TP: TickDataProvider>(
+    position: &Position<TP>,
+    token_id: U256,
+) -> String {
+    let pool = &position.pool;
+    let chain_id = pool.chain_id();
+    let (quote, base) = if token_ratio_priority(pool.token0.address(), chain_id)
+        > token_ratio_priority(pool.token1.address(), chain_id)
+    {
+        (&pool.token0, &pool.token1)
+    } else {
+        (&pool.token1, &pool.token0)
+    };
+    let quote_address = format!("0x{}", hex::encode(quote.address()));
+    let base_address = format!("0x{}", hex::encode(base.address()));
+    let quote_symbol = quote.symbol.as_deref().unwrap_or_default();
+    let base_symbol = base.symbol.as_deref().unwrap_or_default();
+    let quote_int = U256::from_be_slice(quote.address().as_slice());
+    let base_int = U256::from_be_slice(base.address().as_slice());
+    let tick_lower = position.tick_lower.to_i24().as_i32();
+    let tick_upper = position.tick_upper.to_i24().as_i32();
+    let tick_spacing = pool.tick_spacing().to_i24().as_i32();
+    let tick_current = pool.tick_current.to_i24().as_i32();
+    let over_range = if tick_current < tick_lower {
+        -1
+    } else if tick_current > tick_upper {
+        1
+    } else {
+        0
+    };
+    let color0 = token_to_color_hex(quote_int, 136);
+    let color1 = token_to_color_hex(base_int, 136);
+    let color2 = token_to_color_hex(quote_int, 0);
+    let color3 = token_to_color_hex(base_int, 0);
+
+    let mut svg = String::with_capacity(16384);
+
+    // defs
+    svg.push_str("<svg width=\"290\" height=\"500\" viewBox=\"0 0 290 500\" xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink='http://www.w3.org/1999/xlink'><defs>");
+    svg.push_str(
+        "<filter id=\"f1\"><feImage result=\"p0\" xlink:href=\"data:image/svg+xml;base64,",
+    );
+    svg.push_str(&background_layer(&format!(
+        "<rect width='290px' height='500px' fill='#{color0}'/>"
+    )));
+    for (i, (offset, r, color)) in [(16, 120, &color1), (32, 120, &color2), (48, 100, &color3)]
+        .into_iter()
+        .enumerate()
+    {
+        svg.push_str(match i {
+            0 => "\"/><feImage result=\"p1\" xlink:href=\"data:image/svg+xml;base64,",
+            1 => "\"/><feImage result=\"p2\" xlink:href=\"data:image/svg+xml;base64,",
+            _ => "\" /><feImage result=\"p3\" xlink:href=\"data:image/svg+xml;base64,",
+        });
+        svg.push_str(&background_layer(&format!(
+            "<circle cx='{}' cy='{}' r='{r}px' fill='#{color}'/>",
+            circle_coord(quote_int, offset, token_id, 16, 274),
+            circle_coord(base_int, offset, token_id, 100, 484),
+        )));
+    }
+    svg.push_str("\" /><feBlend mode=\"overlay\" in=\"p0\" in2=\"p1\" /><feBlend mode=\"exclusion\" in2=\"p2\" /><feBlend mode=\"overlay\" in2=\"p3\" result=\"blendOut\" /><feGaussianBlur in=\"blendOut\" stdDeviation=\"42\" /></filter> <clipPath id=\"corners\"><rect width=\"290\" height=\"500\" rx=\"42\" ry=\"42\" /></clipPath>");
+    svg.push_str("<path id=\"text-path-a\" d=\"M40 12 H250 A28 28 0 0 1 278 40 V460 A28 28 0 0 1 250 488 H40 A28 28 0 0 1 12 460 V40 A28 28 0 0 1 40 12 z\" />");
+    svg.push_str("<path id=\"minimap\" d=\"M234 444C234 457.949 242.21 463 253 463\" />");
+    svg.push_str("<filter id=\"top-region-blur\"><feGaussianBlur in=\"SourceGraphic\" stdDeviation=\"24\" /></filter>");
+    svg.push_str("<linearGradient id=\"grad-up\" x1=\"1\" x2=\"0\" y1=\"1\" y2=\"0\"><stop offset=\"0.0\" stop-color=\"white\" stop-opacity=\"1\" /><stop offset=\".9\" stop-color=\"white\" stop-opacity=\"0\" /></linearGradient>");
+    svg.push_str("<linearGradient id=\"grad-down\" x1=\"0\" x2=\"1\" y1=\"0\" y2=\"1\"><stop offset=\"0.0\" stop-color=\"white\" stop-opacity=\"1\" /><stop offset=\"0.9\" stop-color=\"white\" stop-opacity=\"0\" /></linearGradient>");
+    svg.push_str("<mask id=\"fade-up\" maskContentUnits=\"objectBoundingBox\"><rect width=\"1\" height=\"1\" fill=\"url(#grad-up)\" /></mask>");
+    svg.push_str("<mask id=\"fade-down\" maskContentUnits=\"objectBoundingBox\"><rect width=\"1\" height=\"1\" fill=\"url(#grad-down)\" /></mask>");
+    svg.push_str("<mask id=\"none\" maskContentUnits=\"objectBoundingBox\"><rect width=\"1\" height=\"1\" fill=\"white\" /></mask>");
+    svg.push_str("<linearGradient id=\"grad-symbol\"><stop offset=\"0.7\" stop-color=\"white\" stop-opacity=\"1\" /><stop offset=\".95\" stop-color=\"white\" stop-opacity=\"0\" /></linearGradient>");
+    svg.push_str("<mask id=\"fade-symbol\" maskContentUnits=\"userSpaceOnUse\"><rect width=\"290px\" height=\"200px\" fill=\"url(#grad-symbol)\" /></mask></defs>");
+    // the descriptor omits the `#` of the base color
+    svg.push_str(&format!("<g clip-path=\"url(#corners)\"><rect fill=\"{color0}\" x=\"0px\" y=\"0px\" width=\"290px\" height=\"500px\" />"));
+    svg.push_str(
+        "<rect style=\"filter: url(#f1)\" x=\"0px\" y=\"0px\" width=\"290px\" height=\"500px\" />",
+    );
+    svg.push_str(" <g style=\"filter:url(#top-region-blur); transform:scale(1.5); transform-origin:center top;\"><rect fill=\"none\" x=\"0px\" y=\"0px\" width=\"290px\" height=\"500px\" /><ellipse cx=\"50%\" cy=\"0px\" rx=\"180px\" ry=\"120px\" fill=\"#000\" opacity=\"0.85\" /></g>");
+    svg.push_str("<rect x=\"0\" y=\"0\" width=\"290\" height=\"500\" rx=\"42\" ry=\"42\" fill=\"rgba(0,0,0,0)\" stroke=\"rgba(255,255,255,0.2)\" /></g>");
+
+    // border text
+    let animate = "<animate additive=\"sum\" attributeName=\"startOffset\" from=\"0%\" to=\"100%\" begin=\"0s\" dur=\"30s\" repeatCount=\"indefinite\" />";
+    let text_path = |offset: &str| {
+        format!("<textPath startOffset=\"{offset}\" fill=\"white\" {FONT} font-size=\"10px\" xlink:href=\"#text-path-a\">")
+    };
+    svg.push_str(&format!(
+        "<text text-rendering=\"optimizeSpeed\">{}{base_address} • {base_symbol} {animate}</textPath> {}{base_address} • {base_symbol} {animate} </textPath>{}{quote_address} • {quote_symbol} {animate}</textPath>{}{quote_address} • {quote_symbol} {animate}</textPath></text>",
+        text_path("-100%"),
+        text_path("0%"),
+        text_path("50%"),
+        text_path("-50%"),
+    ));
+
+    // card mantle
+    svg.push_str(&format!(
+        "<g mask=\"url(#fade-symbol)\"><rect fill=\"none\" x=\"0px\" y=\"0px\" width=\"290px\" height=\"200px\" /> <text y=\"70px\" x=\"32px\" fill=\"white\" {FONT} font-weight=\"200\" font-size=\"36px\">{quote_symbol}/{base_symbol}</text><text y=\"115px\" x=\"32px\" fill=\"white\" {FONT} font-weight=\"200\" font-size=\"36px\">{}</text></g>",
+        fee_to_percent_string(pool.fee.fee()),
+    ));
+    svg.push_str("<rect x=\"16\" y=\"16\" width=\"258\" height=\"468\" rx=\"26\" ry=\"26\" fill=\"rgba(0,0,0,0)\" stroke=\"rgba(255,255,255,0.2)\" />");
+
+    // curve
+    let fade = match over_range {
+        1 => "#fade-up",
+        -1 => "#fade-down",
+        _ => "#none",
+    };
+    let curve = curve(tick_lower, tick_upper, tick_spacing);
+    svg.push_str(&format!(
+        "<g mask=\"url({fade})\" style=\"transform:translate(72px,189px)\"><rect x=\"-16px\" y=\"-16px\" width=\"180px\" height=\"180px\" fill=\"none\" /><path d=\"{curve}\" stroke=\"rgba(0,0,0,0.3)\" stroke-width=\"32px\" fill=\"none\" stroke-linecap=\"round\" /></g>"
+    ));
+    svg.push_str(&format!(
+        "<g mask=\"url({fade})\" style=\"transform:translate(72px,189px)\"><rect x=\"-16px\" y=\"-16px\" width=\"180px\" height=\"180px\" fill=\"none\" /><path d=\"{curve}\" stroke=\"rgba(255,255,255,1)\" fill=\"none\" stroke-linecap=\"round\" /></g>"
+    ));
+    match over_range {
+        0 => svg.push_str("<circle cx=\"73px\" cy=\"190px\" r=\"4px\" fill=\"white\" /><circle cx=\"217px\" cy=\"334px\" r=\"4px\" fill=\"white\" />"),
+        _ => {
+            let (cx, cy) = if over_range == -1 { (73, 190) } else { (217, 334) };
+            svg.push_str(&format!(
+                "<circle cx=\"{cx}px\" cy=\"{cy}px\" r=\"4px\" fill=\"white\" /><circle cx=\"{cx}px\" cy=\"{cy}px\" r=\"24px\" fill=\"none\" stroke=\"white\" />"
+            ));
+        }
+    }
+
+    // position data and location curve
+    let rare = is_rare(token_id, pool.address(None, None));
+    let token_id = token_id.to_string();
+    let tick_lower_str = tick_lower.to_string();
+    let tick_upper_str = tick_upper.to_string();
+    for (y, width, label, value) in [
+        (384, 7 * (token_id.len() + 8), "ID: ", &token_id),
+        (
+            414,
+            7 * (tick_lower_str.len() + 14),
+            "Min Tick: ",
+            &tick_lower_str,
+        ),
+        (
+            444,
+            7 * (tick_upper_str.len() + 14),
+            "Max Tick: ",
+            &tick_upper_str,
+        ),
+    ] {
+        svg.push_str(&format!(
+            " <g style=\"transform:translate(29px, {y}px)\"><rect width=\"{width}px\" height=\"26px\" rx=\"8px\" ry=\"8px\" fill=\"rgba(0,0,0,0.6)\" /><text x=\"12px\" y=\"17px\" {FONT} font-size=\"12px\" fill=\"white\"><tspan fill=\"rgba(255,255,255,0.6)\">{label}</tspan>{value}</text></g>"
+        ));
+    }
+    let (x, y) = range_location(tick_lower, tick_upper);
+    svg.push_str(&format!(
+        "<g style=\"transform:translate(226px, 433px)\"><rect width=\"36px\" height=\"36px\" rx=\"8px\" ry=\"8px\" fill=\"none\" stroke=\"rgba(255,255,255,0.2)\" /><path stroke-linecap=\"round\" d=\"M8 9C8.00004 22.9494 16.2099 28 27 28\" fill=\"none\" stroke=\"white\" /><circle style=\"transform:translate3d({x}px, {y}px, 0px)\" cx=\"0px\" cy=\"0px\" r=\"4px\" fill=\"white\"/></g>"
+    ));
+
+    // rare sparkle
+    if rare {
+        svg.push_str("<g style=\"transform:translate(226px, 392px)\"><rect width=\"36px\" height=\"36px\" rx=\"8px\" ry=\"8px\" fill=\"none\" stroke=\"rgba(255,255,255,0.2)\" /><g><path style=\"transform:translate(6px,6px)\" d=\"M12 0L12.6522 9.56587L18 1.6077L13.7819 10.2181L22.3923 6L14.4341 11.3478L24 12L14.4341 12.6522L22.3923 18L13.7819 13.7819L18 22.3923L12.6522 14.4341L12 24L11.3478 14.4341L6 22.3923L10.2181 13.7819L1.6077 18L9.56587 12.6522L0 12L9.56587 11.3478L1.6077 6L10.2181 10.2181L6 1.6077L11.3478 9.56587L12 0Z\" fill=\"white\" /><animateTransform attributeName=\"transform\" type=\"rotate\" from=\"0 18 18\" to=\"360 18 18\" dur=\"10s\" repeatCount=\"indefinite\"/></g></g>");
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Renders the image of a position NFT as the base64 data URL found in its `tokenURI`.
+///
+/// ## Arguments
+///
+/// * `position`: The position, whose pool provides the tokens, fee and current tick
+/// * `token_id`: The token id of the position
+#[inline]
+#[must_use]
+pub fn render_position_image<TP: TickDataProvider>(
+    position: &Position<TP>,
+    token_id: U256,
+) -> String {
+    format!(
+        "data:image/svg+xml;base64,{}",
+        general_purpose::STANDARD.encode(render_position_svg(position, token_id))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::uint;
+
+    #[test]
+    fn test_fee_to_percent_string() {
+        assert_eq!(fee_to_percent_string(0), "0%");
+        assert_eq!(fee_to_percent_string(100), "0.01%");
+        assert_eq!(fee_to_percent_string(500), "0.05%");
+        assert_eq!(fee_to_percent_string(2500), "0.25%");
+        assert_eq!(fee_to_percent_string(3000), "0.3%");
+        assert_eq!(fee_to_percent_string(10000), "1%");
+        assert_eq!(fee_to_percent_string(12345), "1.2345%");
+    }
+
+    #[test]
+    fn test_curve() {
+        assert_eq!(curve(-60, 60, 60), CURVES[0]);
+        assert_eq!(curve(-600, 600, 60), CURVES[2]);
+        assert_eq!(curve(-887220, 887220, 60), CURVES[7]);
+    }
+
+    #[test]
+    fn test_range_location() {
+        assert_eq!(range_location(-200_000, -100_000), ("8", "7"));
+        assert_eq!(range_location(-10, 0), ("11", "21"));
+        assert_eq!(range_location(0, 10), ("13", "23"));
+        assert_eq!(range_location(200_000, 300_000), ("24", "27"));
+    }
+
+    #[test]
+    fn test_token_ratio_priority() {
+        assert!(token_ratio_priority(USDC.address(), 1) > token_ratio_priority(WETH.address(), 1));
+        assert_eq!(token_ratio_priority(WETH.address(), 1), -100);
+        assert_eq!(token_ratio_priority(USDC.address(), 10), 0);
+    }
+
+    #[test]
+    fn test_token_to_color_hex() {
+        let token = U256::from_be_slice(USDC.address().as_slice());
+        // 0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48
+        assert_eq!(token_to_color_hex(token, 0), "06eb48");
+        assert_eq!(token_to_color_hex(token, 136), "a0b869");
+    }
+
+    #[test]
+    fn test_render_position_svg() {
+        let pool = make_pool(USDC.clone(), WETH.clone());
+        let position = Position::new(pool, 1, -60, 60);
+        let svg = render_position_svg(&position, uint!(1_U256));
+        assert!(svg.starts_with("<svg width=\"290\" height=\"500\""));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("USDC/WETH"));
+        assert!(svg.contains("0.3%"));
+        assert!(svg.contains("ID: </tspan>1</text>"));
+        assert!(svg.contains("Min Tick: </tspan>-60</text>"));
+        assert!(svg.contains("Max Tick: </tspan>60</text>"));
+        assert!(svg.contains(&format!("0x{}", hex::encode(WETH.address()))));
+        assert!(render_position_image(&position, uint!(1_U256))
+            .starts_with("data:image/svg+xml;base64,PHN2ZyB3aWR0aD0iMjkwIiBoZWlnaHQ9Ij"));
+    }
+}
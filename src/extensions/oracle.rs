@@ -0,0 +1,471 @@
+//! ## Oracle Extension
+//! This module provides functions to read the observation history of a pool, time-weighted
+//! average prices over arbitrary windows and the volatility of the price, following the [`OracleLibrary`](https://github.com/Uniswap/v3-periphery/blob/main/contracts/libraries/OracleLibrary.sol)
+//! of the periphery contracts.
+
+use crate::{
+    prelude::{Error, *},
+    utils::tick_math::saturating_i24,
+};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    transports::Transport,
+};
+use alloy_primitives::{
+    aliases::{I24, I56},
+    Address, U160, U256,
+};
+use uniswap_lens::bindings::iuniswapv3pool::IUniswapV3Pool::{
+    observationsCall, IUniswapV3PoolInstance,
+};
+use uniswap_sdk_core::prelude::{Price, Token};
+
+/// Computes the arithmetic mean tick between two tick cumulatives `seconds` apart, rounding toward
+/// negative infinity.
+///
+/// ## Arguments
+///
+/// * `tick_cumulative_start`: The tick cumulative at the start of the period
+/// * `tick_cumulative_end`: The tick cumulative at the end of the period
+/// * `seconds`: The length of the period in seconds
+///
+/// ## Returns
+///
+/// [`Error::InvalidWindow`] if `seconds` is zero, where `OracleLibrary.consult` reverts with `BP`,
+/// and [`Error::InvalidTick`] if the mean tick is not between [`MIN_TICK`] and [`MAX_TICK`], which
+/// the cumulatives of a pool never produce
+#[inline]
+pub fn arithmetic_mean_tick(
+    tick_cumulative_start: I56,
+    tick_cumulative_end: I56,
+    seconds: u32,
+) -> Result<I24, Error> {
+    if seconds == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let delta = tick_cumulative_end.as_i64() - tick_cumulative_start.as_i64();
+    let seconds = i64::from(seconds);
+    let mut tick = delta / seconds;
+    if delta < 0 && delta % seconds != 0 {
+        tick -= 1;
+    }
+    let tick = i32::try_from(tick).unwrap_or(if tick < 0 { i32::MIN } else { i32::MAX });
+    if !(MIN_TICK_I32..=MAX_TICK_I32).contains(&tick) {
+        return Err(Error::InvalidTick(saturating_i24(tick)));
+    }
+    Ok(saturating_i24(tick))
+}
+
+/// An oracle observation of a pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Observation {
+    /// The block timestamp of the observation
+    pub block_timestamp: u32,
+    /// The tick accumulator, i.e. tick * time elapsed since the pool was first initialized
+    pub tick_cumulative: I56,
+    /// The seconds per liquidity, i.e. seconds elapsed / max(1, liquidity) since the pool was
+    /// first initialized
+    pub seconds_per_liquidity_cumulative_x128: U160,
+}
+
+/// Get the initialized observations of a pool over its observation cardinality, ordered from the
+/// oldest to the newest.
+///
+/// The block is pinned first, see [`pin_block_id`], then `slot0` is read to find the cardinality
+/// and the observation slots are fetched in Multicall3 batches at the same block.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `options`: The chunking and concurrency of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_observations<T, P>(
+    pool: Address,
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<Observation>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let block_id = pin_block_id(&provider, block_id).await?;
+    let slot_0 = IUniswapV3PoolInstance::new(pool, provider.clone())
+        .slot0()
+        .block(block_id)
+        .call()
+        .await?;
+    let cardinality = u32::from(slot_0.observationCardinality);
+    // the oldest observation is the one after the most recently written one, wrapping around
+    let calls = (1..=cardinality)
+        .map(|i| {
+            let index = (u32::from(slot_0.observationIndex) + i) % cardinality;
+            call3(
+                pool,
+                &observationsCall {
+                    index: U256::from(index),
+                },
+            )
+        })
+        .collect();
+    let results = aggregate3(calls, options, provider, Some(block_id)).await?;
+    let mut observations = Vec::with_capacity(results.len());
+    for result in &results {
+        let observation = decode_call3_result::<observationsCall>(result)?;
+        if observation.initialized {
+            observations.push(Observation {
+                block_timestamp: observation.blockTimestamp,
+                tick_cumulative: observation.tickCumulative,
+                seconds_per_liquidity_cumulative_x128: observation
+                    .secondsPerLiquidityCumulativeX128,
+            });
+        }
+    }
+    Ok(observations)
+}
+
+/// Get the tick cumulatives of a pool as of each of `seconds_agos` seconds ago.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `seconds_agos`: How many seconds ago to read each tick cumulative at
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_tick_cumulatives<T, P>(
+    pool: Address,
+    seconds_agos: Vec<u32>,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<I56>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(IUniswapV3PoolInstance::new(pool, provider)
+        .observe(seconds_agos)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?
+        .tickCumulatives)
+}
+
+/// Get the arithmetic mean tick of a pool over the window from `start_seconds_ago` to
+/// `end_seconds_ago` seconds ago.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `start_seconds_ago`: How many seconds ago the window starts
+/// * `end_seconds_ago`: How many seconds ago the window ends, must be less than
+///   `start_seconds_ago`
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The mean tick, or [`Error::InvalidWindow`] if the window is empty or ends before it starts
+#[inline]
+pub async fn get_twap_tick_between<T, P>(
+    pool: Address,
+    start_seconds_ago: u32,
+    end_seconds_ago: u32,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<I24, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    if start_seconds_ago <= end_seconds_ago {
+        return Err(Error::InvalidWindow);
+    }
+    let tick_cumulatives = get_tick_cumulatives(
+        pool,
+        vec![start_seconds_ago, end_seconds_ago],
+        provider,
+        block_id,
+    )
+    .await?;
+    arithmetic_mean_tick(
+        tick_cumulatives[0],
+        tick_cumulatives[1],
+        start_seconds_ago - end_seconds_ago,
+    )
+}
+
+/// Get the arithmetic mean tick of a pool over the last `seconds_ago` seconds.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `seconds_ago`: The length of the period to average over, must be non-zero
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_twap_tick<T, P>(
+    pool: Address,
+    seconds_ago: u32,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<I24, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    get_twap_tick_between(pool, seconds_ago, 0, provider, block_id).await
+}
+
+/// Get the sqrt price at the arithmetic mean tick of a pool over the last `seconds_ago` seconds.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `seconds_ago`: The length of the period to average over, must be non-zero
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_twap_sqrt_price<T, P>(
+    pool: Address,
+    seconds_ago: u32,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<U160, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    get_sqrt_ratio_at_tick(get_twap_tick(pool, seconds_ago, provider, block_id).await?)
+}
+
+/// Get the price of `base_token` in terms of `quote_token` at the arithmetic mean tick of a pool
+/// over the last `seconds_ago` seconds.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `base_token`: The base token of the price, one of the tokens in the pool
+/// * `quote_token`: The quote token of the price, the other token in the pool
+/// * `seconds_ago`: The length of the period to average over, must be non-zero
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_twap_price<T, P>(
+    pool: Address,
+    base_token: Token,
+    quote_token: Token,
+    seconds_ago: u32,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Price<Token, Token>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let tick = get_twap_tick(pool, seconds_ago, provider, block_id).await?;
+    tick_to_price(base_token, quote_token, tick)
+}
+
+/// Estimates the annualized realized volatility of the price of a pool over the last `window`
+/// seconds from its oracle observations.
+///
+/// The window is split into `periods` periods of equal length, and the log price changes between
+/// the arithmetic mean ticks of consecutive periods are annualized with [`annualized_volatility`].
+/// The observation cardinality of the pool must cover the window.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `window`: The length of the window in seconds, must be a multiple of `periods`
+/// * `periods`: The number of periods to split the window into, at least 2
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[cfg(feature = "std")]
+#[inline]
+pub async fn estimate_volatility<T, P>(
+    pool: Address,
+    window: u32,
+    periods: u32,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<f64, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    assert!(periods >= 2, "PERIODS");
+    assert!(window % periods == 0, "WINDOW");
+    let period = window / periods;
+    let seconds_agos = (0..=periods).rev().map(|i| i * period).collect();
+    let tick_cumulatives = get_tick_cumulatives(pool, seconds_agos, provider, block_id).await?;
+    let ticks = tick_cumulatives
+        .windows(2)
+        .map(|w| Ok(arithmetic_mean_tick(w[0], w[1], period)?.as_i32()))
+        .collect::<Result<Vec<i32>, Error>>()?;
+    let log_price_per_tick = 1.0001_f64.ln();
+    let log_returns: Vec<f64> = ticks
+        .windows(2)
+        .map(|w| f64::from(w[1] - w[0]) * log_price_per_tick)
+        .collect();
+    annualized_volatility(&log_returns, u64::from(window - period))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use once_cell::sync::Lazy;
+    use uniswap_sdk_core::{prelude::*, token};
+
+    static WBTC: Lazy<Token> =
+        Lazy::new(|| token!(1, "2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", 8, "WBTC"));
+    static POOL: Lazy<Address> = Lazy::new(|| {
+        compute_pool_address(
+            FACTORY_ADDRESS,
+            WBTC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            None,
+            None,
+        )
+    });
+
+    #[test]
+    fn test_arithmetic_mean_tick() {
+        assert_eq!(
+            arithmetic_mean_tick(I56::ZERO, I56::try_from(600).unwrap(), 60).unwrap(),
+            I24::try_from(10).unwrap()
+        );
+        assert_eq!(
+            arithmetic_mean_tick(I56::ZERO, I56::try_from(-601).unwrap(), 60).unwrap(),
+            I24::try_from(-11).unwrap()
+        );
+        assert_eq!(
+            arithmetic_mean_tick(I56::ZERO, I56::try_from(-600).unwrap(), 60).unwrap(),
+            I24::try_from(-10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_mean_tick_zero_period() {
+        assert!(matches!(
+            arithmetic_mean_tick(I56::ZERO, I56::ZERO, 0),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    fn test_arithmetic_mean_tick_out_of_range() {
+        assert!(matches!(
+            arithmetic_mean_tick(I56::ZERO, I56::MAX, 1),
+            Err(Error::InvalidTick(tick)) if tick == I24::MAX
+        ));
+        assert!(matches!(
+            arithmetic_mean_tick(I56::ZERO, I56::try_from(-887273).unwrap(), 1),
+            Err(Error::InvalidTick(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_twap_tick_invalid_window() {
+        assert!(matches!(
+            get_twap_tick(*POOL, 0, PROVIDER.clone(), *BLOCK_ID).await,
+            Err(Error::InvalidWindow)
+        ));
+        assert!(matches!(
+            get_twap_tick_between(*POOL, 600, 1200, PROVIDER.clone(), *BLOCK_ID).await,
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_twap_tick() {
+        let tick = get_twap_tick(*POOL, 600, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!((tick.as_i32() - 257344).abs() < 100);
+        let sqrt_price = get_twap_sqrt_price(*POOL, 600, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(sqrt_price, get_sqrt_ratio_at_tick(tick).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_observations() {
+        let options = BatchOptions {
+            chunk_size: 100,
+            ..Default::default()
+        };
+        let observations = get_observations(*POOL, options, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(!observations.is_empty());
+        assert!(observations
+            .windows(2)
+            .all(|w| w[0].block_timestamp < w[1].block_timestamp));
+        // the tick of the pool is positive, so the tick cumulatives are increasing
+        assert!(observations
+            .windows(2)
+            .all(|w| w[0].tick_cumulative < w[1].tick_cumulative));
+        let tick_cumulatives = get_tick_cumulatives(*POOL, vec![0], PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(tick_cumulatives[0] >= observations.last().unwrap().tick_cumulative);
+    }
+
+    #[tokio::test]
+    async fn test_get_twap_tick_between() {
+        let tick_cumulatives =
+            get_tick_cumulatives(*POOL, vec![1200, 600, 0], PROVIDER.clone(), *BLOCK_ID)
+                .await
+                .unwrap();
+        let tick = get_twap_tick_between(*POOL, 1200, 600, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            tick,
+            arithmetic_mean_tick(tick_cumulatives[0], tick_cumulatives[1], 600).unwrap()
+        );
+        let tick = get_twap_tick(*POOL, 600, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            tick,
+            arithmetic_mean_tick(tick_cumulatives[1], tick_cumulatives[2], 600).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_twap_price() {
+        let price = get_twap_price(
+            *POOL,
+            WBTC.clone(),
+            WETH.clone(),
+            600,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let tick = get_twap_tick(*POOL, 600, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            price,
+            tick_to_price(WBTC.clone(), WETH.clone(), tick).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "std")]
+    async fn test_estimate_volatility() {
+        let volatility = estimate_volatility(*POOL, 3600, 6, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(volatility > 0.0 && volatility < 10.0);
+    }
+}
@@ -0,0 +1,101 @@
+//! ## Permit2 Extension
+//! This module provides functions to read the Permit2 allowance state needed to build a permit.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::CallBuilder,
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    transports::Transport,
+};
+use alloy_primitives::{aliases::U48, Address};
+
+/// Get the Permit2 allowance of `spender` over the `token` of `owner`.
+///
+/// ## Arguments
+///
+/// * `permit2`: The address of the Permit2 contract
+/// * `owner`: The owner of the tokens
+/// * `token`: The token address
+/// * `spender`: The spender of the allowance, e.g. the Universal Router
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The amount, expiration and nonce of the allowance
+#[inline]
+pub async fn get_permit2_allowance<T, P>(
+    permit2: Address,
+    owner: Address,
+    token: Address,
+    spender: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<IAllowanceTransfer::allowanceReturn, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IAllowanceTransfer::allowanceCall {
+        user: owner,
+        token,
+        spender,
+    };
+    Ok(CallBuilder::new_sol(&provider, &permit2, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?)
+}
+
+/// Get the nonce to sign the next Permit2 `PermitSingle` or `PermitDetails` with.
+///
+/// ## Arguments
+///
+/// * `permit2`: The address of the Permit2 contract
+/// * `owner`: The owner of the tokens
+/// * `token`: The token address
+/// * `spender`: The spender of the allowance, e.g. the Universal Router
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_permit2_nonce<T, P>(
+    permit2: Address,
+    owner: Address,
+    token: Address,
+    spender: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<U48, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(
+        get_permit2_allowance(permit2, owner, token, spender, provider, block_id)
+            .await?
+            .nonce,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+
+    #[tokio::test]
+    async fn test_get_permit2_nonce() {
+        let nonce = get_permit2_nonce(
+            PERMIT2_ADDRESS,
+            address!("0000000000000000000000000000000000000001"),
+            address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            address!("Ef1c6E67703c7BD7107eed8303Fbe6EC2554BF6B"),
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(nonce, U48::ZERO);
+    }
+}
@@ -1,26 +1,30 @@
 //! ## Pool Extension
 //! This module provides functions to create a [`Pool`] struct from a pool key and to fetch the
 //! liquidity map within a tick range for the specified pool using an [ephemeral contract](https://github.com/Aperture-Finance/Aperture-Lens/blob/904101e4daed59e02fd4b758b98b0749e70b583b/contracts/EphemeralGetPopulatedTicksInRange.sol)
-//! in a single `eth_call`.
+//! in a single `eth_call`, a [`PoolCache`] to fetch each pool of a batch of positions once, and
+//! [`get_pools_state`] to fetch the state of many pools in chunked Multicall3 calls.
 
 use crate::prelude::*;
 use alloy::{
+    contract::CallBuilder,
     eips::{BlockId, BlockNumberOrTag},
     providers::Provider,
     transports::Transport,
 };
-use alloy_primitives::{Address, ChainId, B256};
+use alloy_primitives::{
+    aliases::{I24, U24},
+    Address, ChainId, B256, U256,
+};
+use rustc_hash::FxHashMap;
 use uniswap_lens::{
-    bindings::{
-        ierc20metadata::IERC20Metadata, iuniswapv3pool::IUniswapV3Pool::IUniswapV3PoolInstance,
-    },
+    bindings::iuniswapv3pool::IUniswapV3Pool::{self, IUniswapV3PoolInstance},
     pool_lens,
 };
-use uniswap_sdk_core::{prelude::Token, token};
+use uniswap_sdk_core::prelude::{BaseCurrency, Price, Token};
 
 #[inline]
 pub fn get_pool_contract<T, P>(
-    factory: Address,
+    deployment: impl Into<PoolDeployment>,
     token_a: Address,
     token_b: Address,
     fee: FeeAmount,
@@ -31,18 +35,517 @@ where
     P: Provider<T>,
 {
     IUniswapV3PoolInstance::new(
-        compute_pool_address(factory, token_a, token_b, fee, None, None),
+        deployment.into().pool_address(token_a, token_b, fee),
         provider,
     )
 }
 
+/// Get the address of a pool from the factory's `getPool`, which is [`Address::ZERO`] if the pool
+/// hasn't been created.
+///
+/// ## Arguments
+///
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+/// * `token_a`: One of the tokens in the pool
+/// * `token_b`: The other token in the pool
+/// * `fee`: Fee tier of the pool
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query.
+#[inline]
+pub async fn get_pool_from_factory<T, P>(
+    deployment: impl Into<PoolDeployment>,
+    token_a: Address,
+    token_b: Address,
+    fee: FeeAmount,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Address, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IUniswapV3Factory::getPoolCall {
+        tokenA: token_a,
+        tokenB: token_b,
+        fee: fee.into(),
+    };
+    Ok(
+        CallBuilder::new_sol(&provider, &deployment.into().factory, &call)
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .call()
+            .await?
+            .pool,
+    )
+}
+
+/// Get the fee tier of a fee from the factory's `feeAmountTickSpacing`, so that tiers enabled
+/// through `enableFeeAmount`, e.g. by governance or on forks, are supported alongside the default
+/// ones.
+///
+/// ## Arguments
+///
+/// * `factory`: The factory address
+/// * `fee`: The fee in hundredths of bips
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query.
+///
+/// ## Returns
+///
+/// The fee tier, or [`Error::InvalidFee`] if the fee is not enabled in the factory
+#[inline]
+pub async fn get_fee_amount<T, P>(
+    factory: Address,
+    fee: U24,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<FeeAmount, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IUniswapV3Factory::feeAmountTickSpacingCall { fee };
+    let tick_spacing = CallBuilder::new_sol(&provider, &factory, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?
+        ._0;
+    fee_amount(fee, tick_spacing)
+}
+
+/// Get the fee tiers of many fees from the factory's `feeAmountTickSpacing` in chunked Multicall3
+/// calls, see [`get_fee_amount`].
+///
+/// ## Arguments
+///
+/// * `factory`: The factory address
+/// * `fees`: The fees in hundredths of bips
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query.
+///
+/// ## Returns
+///
+/// The fee tier of each distinct fee
+#[inline]
+pub async fn get_fee_amounts<T, P>(
+    factory: Address,
+    fees: &[U24],
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<FxHashMap<U24, FeeAmount>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    fees.dedup();
+    if fees.is_empty() {
+        return Ok(FxHashMap::default());
+    }
+    let calls = fees
+        .iter()
+        .map(|&fee| {
+            call3(
+                factory,
+                &IUniswapV3Factory::feeAmountTickSpacingCall { fee },
+            )
+        })
+        .collect();
+    let results = aggregate3(calls, BatchOptions::default(), provider, block_id).await?;
+    fees.into_iter()
+        .zip(&results)
+        .map(|(fee, result)| {
+            let tick_spacing =
+                decode_call3_result::<IUniswapV3Factory::feeAmountTickSpacingCall>(result)?._0;
+            Ok((fee, fee_amount(fee, tick_spacing)?))
+        })
+        .collect()
+}
+
+/// The fee tier of a fee with the tick spacing read from the factory, which is zero if the fee is
+/// not enabled.
+fn fee_amount(fee: U24, tick_spacing: I24) -> Result<FeeAmount, Error> {
+    let fee = FeePips::try_from(fee)?;
+    if tick_spacing.is_zero() {
+        return Err(Error::InvalidFee(fee.as_u32()));
+    }
+    FeeAmount::try_new(fee.as_u32(), tick_spacing)
+}
+
+/// Get all initialized pools of a pair across the fee tiers enabled in the factory, sorted by
+/// liquidity in descending order. The fees of [`FeeAmount::ALL`] and `fees` are probed, and the
+/// tier of each fee is built from its tick spacing read from `feeAmountTickSpacing`, so that tiers
+/// enabled through `enableFeeAmount` are included. All reads are made at the same block, see
+/// [`pin_block_id`].
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pools
+/// * `token_a`: One of the tokens in the pool
+/// * `token_b`: The other token in the pool
+/// * `fees`: Fees in hundredths of bips to probe besides the default tiers, e.g. tiers enabled by
+///   governance or on forks
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query.
+#[inline]
+pub async fn get_pools_for_pair<T, P>(
+    chain_id: ChainId,
+    deployment: impl Into<PoolDeployment>,
+    token_a: Address,
+    token_b: Address,
+    fees: &[U24],
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<Pool>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let factory = deployment.into().factory;
+    let block_id = Some(pin_block_id(&provider, block_id).await?);
+    let mut fees: Vec<U24> = FeeAmount::ALL
+        .iter()
+        .map(|&fee| fee.into())
+        .chain(fees.iter().copied())
+        .collect();
+    fees.sort_unstable();
+    fees.dedup();
+    let calls = fees
+        .iter()
+        .flat_map(|&fee| {
+            [
+                call3(
+                    factory,
+                    &IUniswapV3Factory::feeAmountTickSpacingCall { fee },
+                ),
+                call3(
+                    factory,
+                    &IUniswapV3Factory::getPoolCall {
+                        tokenA: token_a,
+                        tokenB: token_b,
+                        fee,
+                    },
+                ),
+            ]
+        })
+        .collect();
+    let results = aggregate3(calls, BatchOptions::default(), provider.clone(), block_id).await?;
+    let mut pools = Vec::new();
+    for (&fee, results) in fees.iter().zip(results.chunks(2)) {
+        let tick_spacing =
+            decode_call3_result::<IUniswapV3Factory::feeAmountTickSpacingCall>(&results[0])?._0;
+        let pool = decode_call3_result::<IUniswapV3Factory::getPoolCall>(&results[1])?.pool;
+        if !tick_spacing.is_zero() && !pool.is_zero() {
+            pools.push((fee_amount(fee, tick_spacing)?, pool));
+        }
+    }
+    if pools.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let calls = pools
+        .iter()
+        .flat_map(|&(_, pool)| {
+            [
+                call3(pool, &IUniswapV3Pool::slot0Call {}),
+                call3(pool, &IUniswapV3Pool::liquidityCall {}),
+            ]
+        })
+        .collect();
+    let results = aggregate3(calls, BatchOptions::default(), provider.clone(), block_id).await?;
+    let data_source = AlloyDataSource::new(provider);
+    let token_a = data_source.token(chain_id, token_a, block_id).await?;
+    let token_b = data_source.token(chain_id, token_b, block_id).await?;
+    let mut initialized = Vec::with_capacity(pools.len());
+    for (&(fee, _), results) in pools.iter().zip(results.chunks(2)) {
+        let sqrt_price_x96 =
+            decode_call3_result::<IUniswapV3Pool::slot0Call>(&results[0])?.sqrtPriceX96;
+        let liquidity = decode_call3_result::<IUniswapV3Pool::liquidityCall>(&results[1])?._0;
+        if !sqrt_price_x96.is_zero() {
+            initialized.push(Pool::new(
+                token_a.clone(),
+                token_b.clone(),
+                fee,
+                sqrt_price_x96,
+                liquidity,
+            )?);
+        }
+    }
+    initialized.sort_by(|a, b| b.liquidity.cmp(&a.liquidity));
+    Ok(initialized)
+}
+
+/// A cache of the pools at a block keyed by `(token0, token1, fee)`, shared by batches of positions
+/// so that each pool and token is fetched exactly once, e.g. by [`get_positions_with_cache`].
+///
+/// The cache is bound to the block it is created at. A cache at `None`, i.e. the latest block,
+/// should only be reused while the pool states may be stale.
+#[derive(Clone, Debug, Default)]
+pub struct PoolCache {
+    block_id: Option<BlockId>,
+    pools: FxHashMap<(Address, Address, U24), Pool>,
+    tokens: FxHashMap<Address, Token>,
+}
+
+/// Sorts the tokens of a pool key.
+fn sort_pool_key(token_a: Address, token_b: Address, fee: U24) -> (Address, Address, U24) {
+    if token_a <= token_b {
+        (token_a, token_b, fee)
+    } else {
+        (token_b, token_a, fee)
+    }
+}
+
+impl PoolCache {
+    /// Creates an empty cache of the pools at a block.
+    ///
+    /// ## Arguments
+    ///
+    /// * `block_id`: Optional block number to query
+    #[inline]
+    #[must_use]
+    pub fn new(block_id: Option<BlockId>) -> Self {
+        Self {
+            block_id,
+            ..Default::default()
+        }
+    }
+
+    /// The block the pools are fetched at.
+    #[inline]
+    #[must_use]
+    pub const fn block_id(&self) -> Option<BlockId> {
+        self.block_id
+    }
+
+    /// The number of cached pools.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Whether no pool is cached.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Returns a cached pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token_a`: One of the tokens in the pool
+    /// * `token_b`: The other token in the pool
+    /// * `fee`: The fee of the pool in hundredths of bips
+    #[inline]
+    #[must_use]
+    pub fn get(&self, token_a: Address, token_b: Address, fee: U24) -> Option<&Pool> {
+        self.pools.get(&sort_pool_key(token_a, token_b, fee))
+    }
+
+    /// Fetches the pools and tokens that are not cached yet in chunked Multicall3 calls.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `factory`: The factory of the pools
+    /// * `keys`: The tokens and fees of the pools
+    /// * `options`: The chunking and concurrency of the Multicall3 calls
+    /// * `provider`: The alloy provider
+    #[inline]
+    pub async fn fetch<T, P>(
+        &mut self,
+        chain_id: ChainId,
+        factory: Address,
+        keys: &[(Address, Address, U24)],
+        options: BatchOptions,
+        provider: P,
+    ) -> Result<(), Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T> + Clone,
+    {
+        let mut missing: Vec<(Address, Address, U24)> = keys
+            .iter()
+            .map(|&(token_a, token_b, fee)| sort_pool_key(token_a, token_b, fee))
+            .filter(|key| !self.pools.contains_key(key))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let mut token_addresses: Vec<Address> = missing
+            .iter()
+            .flat_map(|&(token0, token1, _)| [token0, token1])
+            .filter(|token| !self.tokens.contains_key(token))
+            .collect();
+        token_addresses.sort_unstable();
+        token_addresses.dedup();
+
+        let fees: Vec<U24> = missing.iter().map(|&(_, _, fee)| fee).collect();
+        let fee_amounts = get_fee_amounts(factory, &fees, provider.clone(), self.block_id).await?;
+        let fees: Vec<FeeAmount> = fees.iter().map(|fee| fee_amounts[fee]).collect();
+        let calls = missing
+            .iter()
+            .zip(&fees)
+            .flat_map(|(&(token0, token1, _), &fee)| {
+                let pool = compute_pool_address(factory, token0, token1, fee, None, None);
+                [
+                    call3(pool, &IUniswapV3Pool::slot0Call {}),
+                    call3(pool, &IUniswapV3Pool::liquidityCall {}),
+                ]
+            })
+            .collect();
+        let (results, tokens) = futures_util::try_join!(
+            aggregate3(calls, options, provider.clone(), self.block_id),
+            get_tokens(chain_id, &token_addresses, provider, self.block_id),
+        )?;
+        self.tokens.extend(token_addresses.into_iter().zip(tokens));
+        for ((key, fee), results) in missing.into_iter().zip(fees).zip(results.chunks_exact(2)) {
+            let (token0, token1, _) = key;
+            let sqrt_price_x96 =
+                decode_call3_result::<IUniswapV3Pool::slot0Call>(&results[0])?.sqrtPriceX96;
+            let liquidity = decode_call3_result::<IUniswapV3Pool::liquidityCall>(&results[1])?._0;
+            let pool = Pool::new(
+                self.tokens[&token0].clone(),
+                self.tokens[&token1].clone(),
+                fee,
+                sqrt_price_x96,
+                liquidity,
+            )?;
+            self.pools.insert(key, pool);
+        }
+        Ok(())
+    }
+}
+
+/// Get the [`PoolState`] of many pools, i.e. their slot0, liquidity and fee growth, in chunked
+/// Multicall3 calls.
+///
+/// ## Arguments
+///
+/// * `pool_addresses`: The addresses of the pools
+/// * `options`: The chunking and concurrency of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The state of each pool keyed by the pool address
+#[inline]
+pub async fn get_pools_state<T, P>(
+    pool_addresses: &[Address],
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<FxHashMap<Address, PoolState>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut pool_addresses = pool_addresses.to_vec();
+    pool_addresses.sort_unstable();
+    pool_addresses.dedup();
+    if pool_addresses.is_empty() {
+        return Ok(FxHashMap::default());
+    }
+    let calls = pool_addresses
+        .iter()
+        .flat_map(|&pool| {
+            [
+                call3(pool, &IUniswapV3Pool::slot0Call {}),
+                call3(pool, &IUniswapV3Pool::liquidityCall {}),
+                call3(pool, &IUniswapV3Pool::feeGrowthGlobal0X128Call {}),
+                call3(pool, &IUniswapV3Pool::feeGrowthGlobal1X128Call {}),
+            ]
+        })
+        .collect();
+    let results = aggregate3(calls, options, provider, block_id).await?;
+    let mut states = FxHashMap::with_capacity_and_hasher(pool_addresses.len(), Default::default());
+    for (pool, results) in pool_addresses.into_iter().zip(results.chunks_exact(4)) {
+        let slot0 = decode_call3_result::<IUniswapV3Pool::slot0Call>(&results[0])?;
+        states.insert(
+            pool,
+            PoolState {
+                sqrt_price_x96: slot0.sqrtPriceX96,
+                tick: slot0.tick,
+                observation_index: slot0.observationIndex,
+                observation_cardinality: slot0.observationCardinality,
+                observation_cardinality_next: slot0.observationCardinalityNext,
+                fee_protocol: slot0.feeProtocol,
+                unlocked: slot0.unlocked,
+                liquidity: decode_call3_result::<IUniswapV3Pool::liquidityCall>(&results[1])?._0,
+                fee_growth_global0_x128: decode_call3_result::<
+                    IUniswapV3Pool::feeGrowthGlobal0X128Call,
+                >(&results[2])?
+                ._0,
+                fee_growth_global1_x128: decode_call3_result::<
+                    IUniswapV3Pool::feeGrowthGlobal1X128Call,
+                >(&results[3])?
+                ._0,
+            },
+        );
+    }
+    Ok(states)
+}
+
+/// Produces the calldata to add liquidity to `position`. When minting into a pool that the factory
+/// hasn't created or that hasn't been initialized, `createAndInitializePoolIfNecessary` is
+/// prepended so that the pool is created and initialized at the price of `position.pool`.
+///
+/// ## Arguments
+///
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+/// * `position`: The position to mint, in a pool at the target price if the pool doesn't exist
+/// * `options`: Additional information necessary for generating the calldata
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query.
+#[inline]
+pub async fn add_call_parameters_creating_pool<TP, T, P>(
+    deployment: impl Into<PoolDeployment>,
+    position: &mut Position<TP>,
+    mut options: AddLiquidityOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<MethodParameters, Error>
+where
+    TP: TickDataProvider,
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    if let AddLiquiditySpecificOptions::Mint(ref mut opts) = options.specific_opts {
+        let pool = get_pool_from_factory(
+            deployment,
+            position.pool.token0.address(),
+            position.pool.token1.address(),
+            position.pool.fee,
+            provider.clone(),
+            block_id,
+        )
+        .await?;
+        opts.create_pool = pool.is_zero()
+            || IUniswapV3PoolInstance::new(pool, provider)
+                .slot0()
+                .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+                .call()
+                .await?
+                .sqrtPriceX96
+                .is_zero();
+    }
+    add_call_parameters(position, options)
+}
+
 impl Pool {
     /// Get a [`Pool`] struct from pool key
     ///
     /// ## Arguments
     ///
     /// * `chain_id`: The chain id
-    /// * `factory`: The factory address
+    /// * `deployment`: The factory address or [`PoolDeployment`] of the pool
     /// * `token_a`: One of the tokens in the pool
     /// * `token_b`: The other token in the pool
     /// * `fee`: Fee tier of the pool
@@ -51,7 +554,43 @@ impl Pool {
     #[inline]
     pub async fn from_pool_key<T, P>(
         chain_id: ChainId,
-        factory: Address,
+        deployment: impl Into<PoolDeployment>,
+        token_a: Address,
+        token_b: Address,
+        fee: FeeAmount,
+        provider: P,
+        block_id: Option<BlockId>,
+    ) -> Result<Self, Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T> + Clone,
+    {
+        Self::from_data_source(
+            chain_id,
+            deployment,
+            token_a,
+            token_b,
+            fee,
+            &AlloyDataSource::new(provider),
+            block_id,
+        )
+        .await
+    }
+
+    /// Get a [`Pool`] struct from pool key, for a pool deployed by the factory of a
+    /// [`ChainConfig`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `config`: The deployment on the chain
+    /// * `token_a`: One of the tokens in the pool
+    /// * `token_b`: The other token in the pool
+    /// * `fee`: Fee tier of the pool
+    /// * `provider`: The alloy provider
+    /// * `block_id`: Optional block number to query.
+    #[inline]
+    pub async fn from_chain_config<T, P>(
+        config: &ChainConfig,
         token_a: Address,
         token_b: Address,
         fee: FeeAmount,
@@ -62,43 +601,50 @@ impl Pool {
         T: Transport + Clone,
         P: Provider<T> + Clone,
     {
-        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
-        let pool_contract = get_pool_contract(factory, token_a, token_b, fee, provider.clone());
-        let token_a_contract = IERC20Metadata::new(token_a, provider.clone());
-        let token_b_contract = IERC20Metadata::new(token_b, provider.clone());
-        // TODO: use multicall
-        let slot_0 = pool_contract.slot0().block(block_id).call().await?;
-        let liquidity = pool_contract.liquidity().block(block_id).call().await?._0;
-        let token_a_decimals = token_a_contract.decimals().block(block_id).call().await?._0;
-        let token_a_name = token_a_contract.name().block(block_id).call().await?._0;
-        let token_a_symbol = token_a_contract.symbol().block(block_id).call().await?._0;
-        let token_b_decimals = token_b_contract.decimals().block(block_id).call().await?._0;
-        let token_b_name = token_b_contract.name().block(block_id).call().await?._0;
-        let token_b_symbol = token_b_contract.symbol().block(block_id).call().await?._0;
-        let sqrt_price_x96 = slot_0.sqrtPriceX96;
+        Self::from_pool_key(
+            config.chain_id,
+            config.pool_deployment(),
+            token_a,
+            token_b,
+            fee,
+            provider,
+            block_id,
+        )
+        .await
+    }
+
+    /// Get a [`Pool`] struct from pool key, reading the pool and tokens from a [`V3DataSource`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+    /// * `token_a`: One of the tokens in the pool
+    /// * `token_b`: The other token in the pool
+    /// * `fee`: Fee tier of the pool
+    /// * `data_source`: The source of the on-chain state
+    /// * `block_id`: Optional block number to query.
+    #[inline]
+    pub async fn from_data_source<DS: V3DataSource>(
+        chain_id: ChainId,
+        deployment: impl Into<PoolDeployment>,
+        token_a: Address,
+        token_b: Address,
+        fee: FeeAmount,
+        data_source: &DS,
+        block_id: Option<BlockId>,
+    ) -> Result<Self, Error> {
+        let pool = deployment.into().pool_address(token_a, token_b, fee);
+        let slot_0 = data_source.slot0(pool, block_id).await?;
+        let liquidity = data_source.liquidity(pool, block_id).await?;
+        let token_a = data_source.token(chain_id, token_a, block_id).await?;
+        let token_b = data_source.token(chain_id, token_b, block_id).await?;
+        let sqrt_price_x96 = slot_0.sqrt_price_x96;
         assert!(
             !sqrt_price_x96.is_zero(),
             "Pool has been created but not yet initialized"
         );
-        Self::new(
-            token!(
-                chain_id,
-                token_a,
-                token_a_decimals,
-                token_a_symbol,
-                token_a_name
-            ),
-            token!(
-                chain_id,
-                token_b,
-                token_b_decimals,
-                token_b_symbol,
-                token_b_name
-            ),
-            fee,
-            sqrt_price_x96,
-            liquidity,
-        )
+        Self::new(token_a, token_b, fee, sqrt_price_x96, liquidity)
     }
 }
 
@@ -106,7 +652,7 @@ impl<I: TickIndex> Pool<EphemeralTickMapDataProvider<I>> {
     #[inline]
     pub async fn from_pool_key_with_tick_data_provider<T, P>(
         chain_id: ChainId,
-        factory: Address,
+        deployment: impl Into<PoolDeployment>,
         token_a: Address,
         token_b: Address,
         fee: FeeAmount,
@@ -117,9 +663,10 @@ impl<I: TickIndex> Pool<EphemeralTickMapDataProvider<I>> {
         T: Transport + Clone,
         P: Provider<T> + Clone,
     {
+        let deployment = deployment.into();
         let pool = Pool::from_pool_key(
             chain_id,
-            factory,
+            deployment,
             token_a,
             token_b,
             fee,
@@ -128,7 +675,7 @@ impl<I: TickIndex> Pool<EphemeralTickMapDataProvider<I>> {
         )
         .await?;
         let tick_data_provider = EphemeralTickMapDataProvider::new(
-            pool.address(None, None),
+            pool.deployment_address(&deployment),
             provider.clone(),
             None,
             None,
@@ -187,8 +734,8 @@ pub fn reconstruct_liquidity_array<I: TickIndex>(
     let current_index = tick_array
         .iter()
         .position(|&(tick, _)| tick > tick_current_aligned)
-        .unwrap()
-        - 1;
+        .and_then(|index| index.checked_sub(1))
+        .ok_or(Error::InvalidTick(tick_current_aligned.to_i24()))?;
     // Accumulate the liquidity from the current tick to the end of the populated ticks array.
     let mut cumulative_liquidity = current_liquidity;
     let mut liquidity_array = vec![(I::ZERO, 0); tick_array.len()];
@@ -262,11 +809,152 @@ where
     )
 }
 
+/// A bucket of ticks in a [`LiquidityDistribution`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidityBucket<I = i32> {
+    /// The lower tick of the bucket, inclusive
+    pub tick_lower: I,
+    /// The upper tick of the bucket, exclusive
+    pub tick_upper: I,
+    /// The active liquidity averaged over the ticks of the bucket
+    pub liquidity: u128,
+    /// The price of token0 in token1 at the lower tick, if requested
+    pub price_lower: Option<Price<Token, Token>>,
+    /// The price of token0 in token1 at the upper tick, if requested
+    pub price_upper: Option<Price<Token, Token>>,
+}
+
+/// The active liquidity of a pool over a tick range, per populated tick and per bucket.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidityDistribution<I = i32> {
+    /// The populated ticks and the active liquidity from each tick up to the next one
+    pub ticks: Vec<(I, u128)>,
+    /// Buckets of equal width spanning the populated ticks
+    pub buckets: Vec<LiquidityBucket<I>>,
+}
+
+/// Groups a liquidity array into buckets of `bucket_width` ticks aligned to multiples of the width,
+/// spanning from the first to the last populated tick.
+///
+/// ## Arguments
+///
+/// * `liquidity_array`: An array of ticks and corresponding cumulative liquidity sorted by tick, as
+///   returned by [`reconstruct_liquidity_array`]
+/// * `bucket_width`: The number of ticks in a bucket, usually a multiple of the tick spacing
+///
+/// ## Returns
+///
+/// The buckets without prices, each with the active liquidity averaged over all of its ticks. The
+/// ticks of the edge buckets outside of the populated ticks count as zero liquidity. There are no
+/// buckets if the array has fewer than two ticks, and [`Error::InvalidOptions`] is returned if
+/// `bucket_width` is not positive.
+#[inline]
+pub fn bucket_liquidity_array<I: TickIndex>(
+    liquidity_array: &[(I, u128)],
+    bucket_width: I,
+) -> Result<Vec<LiquidityBucket<I>>, Error> {
+    if bucket_width <= I::ZERO {
+        return Err(Error::InvalidOptions);
+    }
+    let &[(first, _), .., (last, _)] = liquidity_array else {
+        return Ok(Vec::new());
+    };
+    let width = bucket_width
+        .try_into()
+        .map_or(u32::MAX, |width: i32| width.unsigned_abs());
+    let mut buckets = Vec::new();
+    let mut segment = 0;
+    let mut bucket_lower = first.compress(bucket_width) * bucket_width;
+    while bucket_lower < last {
+        let bucket_upper = bucket_lower + bucket_width;
+        while segment + 1 < liquidity_array.len() && liquidity_array[segment + 1].0 <= bucket_lower
+        {
+            segment += 1;
+        }
+        // Average the liquidity of the segments between populated ticks overlapping the bucket.
+        let mut weighted_liquidity = U256::ZERO;
+        for window in liquidity_array[segment..].windows(2) {
+            let ((start, liquidity), (end, _)) = (window[0], window[1]);
+            if start >= bucket_upper {
+                break;
+            }
+            let lower = start.max(bucket_lower);
+            let upper = end.min(bucket_upper);
+            if lower < upper {
+                let overlap = (upper - lower)
+                    .try_into()
+                    .map_or(u32::MAX, |overlap: i32| overlap.unsigned_abs());
+                weighted_liquidity += U256::from(liquidity) * U256::from(overlap);
+            }
+        }
+        buckets.push(LiquidityBucket {
+            tick_lower: bucket_lower,
+            tick_upper: bucket_upper,
+            liquidity: (weighted_liquidity / U256::from(width)).to(),
+            price_lower: None,
+            price_upper: None,
+        });
+        bucket_lower = bucket_upper;
+    }
+    Ok(buckets)
+}
+
+/// Fetches the liquidity distribution within a tick range for the specified pool, suitable for
+/// rendering a liquidity depth chart.
+///
+/// ## Arguments
+///
+/// * `pool`: The liquidity pool to fetch the liquidity distribution for.
+/// * `tick_lower`: The lower tick to fetch liquidity for.
+/// * `tick_upper`: The upper tick to fetch liquidity for.
+/// * `bucket_width`: The number of ticks in a bucket, usually a multiple of the tick spacing.
+/// * `with_prices`: Whether to convert the ticks of the buckets to prices of token0 in token1.
+/// * `provider`: The alloy provider.
+/// * `block_id`: Optional block number to query.
+#[inline]
+pub async fn get_liquidity_distribution<TP, T, P>(
+    pool: Pool<TP>,
+    tick_lower: TP::Index,
+    tick_upper: TP::Index,
+    bucket_width: TP::Index,
+    with_prices: bool,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<LiquidityDistribution<TP::Index>, Error>
+where
+    TP: TickDataProvider,
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let token0 = pool.token0.clone();
+    let token1 = pool.token1.clone();
+    let ticks =
+        get_liquidity_array_for_pool(pool, tick_lower, tick_upper, provider, block_id, None, None)
+            .await?;
+    let mut buckets = bucket_liquidity_array(&ticks, bucket_width)?;
+    if with_prices {
+        for bucket in &mut buckets {
+            bucket.price_lower = Some(tick_to_price(
+                token0.clone(),
+                token1.clone(),
+                bucket.tick_lower.to_i24(),
+            )?);
+            bucket.price_upper = Some(tick_to_price(
+                token0.clone(),
+                token1.clone(),
+                bucket.tick_upper.to_i24(),
+            )?);
+        }
+    }
+    Ok(LiquidityDistribution { ticks, buckets })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tests::*;
     use alloy_primitives::address;
+    use uniswap_sdk_core::prelude::*;
 
     async fn pool() -> Pool {
         Pool::from_pool_key(
@@ -291,6 +979,182 @@ mod tests {
         assert_eq!(pool.liquidity, 786352807736110014);
     }
 
+    #[tokio::test]
+    async fn test_get_pools_state() {
+        let wbtc_weth = pool().await;
+        let wbtc_weth_address = compute_pool_address(
+            FACTORY_ADDRESS,
+            wbtc_weth.token0.address(),
+            wbtc_weth.token1.address(),
+            wbtc_weth.fee,
+            None,
+            None,
+        );
+        let usdc_weth_address = compute_pool_address(
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::MEDIUM,
+            None,
+            None,
+        );
+        let states = get_pools_state(
+            &[wbtc_weth_address, usdc_weth_address, wbtc_weth_address],
+            BatchOptions::default(),
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(states.len(), 2);
+        let state = states[&wbtc_weth_address];
+        assert_eq!(state.sqrt_price_x96, wbtc_weth.sqrt_ratio_x96);
+        assert_eq!(state.tick.as_i32(), 257344);
+        assert_eq!(state.liquidity, 786352807736110014);
+        assert!(!state.fee_growth_global0_x128.is_zero());
+        assert!(!state.fee_growth_global1_x128.is_zero());
+        assert!(!states[&usdc_weth_address].sqrt_price_x96.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_get_pool_from_factory() {
+        let pool = get_pool_from_factory(
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::MEDIUM,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(pool, address!("8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8"));
+    }
+
+    #[tokio::test]
+    async fn test_get_fee_amounts() {
+        assert_eq!(
+            get_fee_amount(FACTORY_ADDRESS, U24::from(100), PROVIDER.clone(), *BLOCK_ID)
+                .await
+                .unwrap(),
+            FeeAmount::LOWEST
+        );
+        let fees = [U24::from(3000), U24::from(500), U24::from(3000)];
+        let fee_amounts = get_fee_amounts(FACTORY_ADDRESS, &fees, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(fee_amounts.len(), 2);
+        assert_eq!(fee_amounts[&U24::from(500)], FeeAmount::LOW);
+        assert!(matches!(
+            get_fee_amounts(
+                FACTORY_ADDRESS,
+                &[U24::from(2500)],
+                PROVIDER.clone(),
+                *BLOCK_ID
+            )
+            .await,
+            Err(Error::InvalidFee(2500))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_pools_for_pair() {
+        let pools = get_pools_for_pair(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            &[U24::from(2500)],
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let fees: Vec<FeeAmount> = pools.iter().map(|pool| pool.fee).collect();
+        assert_eq!(fees.len(), 4);
+        assert!(!fees.contains(&FeeAmount::LOW_200));
+        assert!(pools
+            .windows(2)
+            .all(|pair| pair[0].liquidity >= pair[1].liquidity));
+        assert_eq!(
+            pools[0].address(None, None),
+            get_pool_from_factory(
+                FACTORY_ADDRESS,
+                USDC.address(),
+                WETH.address(),
+                pools[0].fee,
+                PROVIDER.clone(),
+                *BLOCK_ID,
+            )
+            .await
+            .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_call_parameters_creating_pool() {
+        let options = |create_pool| AddLiquidityOptions {
+            slippage_tolerance: Percent::new(1, 100),
+            deadline: U256::from(123),
+            use_native: None,
+            token0_permit: None,
+            token1_permit: None,
+            specific_opts: AddLiquiditySpecificOptions::Mint(MintSpecificOptions {
+                recipient: address!("0000000000000000000000000000000000000003"),
+                create_pool,
+            }),
+        };
+        for (fee, expect_create) in [(FeeAmount::MEDIUM, false), (FeeAmount::LOW_200, true)] {
+            let pool = Pool::new(
+                USDC.clone(),
+                WETH.clone(),
+                fee,
+                encode_sqrt_ratio_x96(1, 1),
+                0,
+            )
+            .unwrap();
+            let mut position = Position::new(pool, 1_000_000_000, -600, 600);
+            let MethodParameters { calldata, .. } = add_call_parameters_creating_pool(
+                FACTORY_ADDRESS,
+                &mut position,
+                options(!expect_create),
+                PROVIDER.clone(),
+                *BLOCK_ID,
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                calldata,
+                add_call_parameters(&mut position, options(expect_create))
+                    .unwrap()
+                    .calldata
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swap_with_tick_data_provider() {
+        let pool = Pool::<EphemeralTickMapDataProvider>::from_pool_key_with_tick_data_provider(
+            1,
+            FACTORY_ADDRESS,
+            address!("2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
+            address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        // swap 100 WBTC for WETH, crossing several initialized ticks
+        let amount_in =
+            CurrencyAmount::from_raw_amount(pool.token0.clone(), 10_000_000_000_u64).unwrap();
+        let (amount_out, pool_after) = pool.get_output_amount(&amount_in, None).unwrap();
+        assert!(amount_out.quotient() > BigInt::from(0));
+        assert!(pool_after.tick_current < pool.tick_current);
+        let (amount_in_back, _) = pool.get_input_amount(&amount_out, None).unwrap();
+        assert!(amount_in_back.quotient() <= amount_in.quotient());
+    }
+
     #[tokio::test]
     async fn test_get_liquidity_array_for_pool() {
         let pool = pool().await;
@@ -321,4 +1185,67 @@ mod tests {
             liquidity
         );
     }
+
+    #[test]
+    fn test_bucket_liquidity_array() {
+        let liquidity_array = [(-20, 100), (-5, 300), (10, 200), (30, 0)];
+        let buckets = bucket_liquidity_array(&liquidity_array, 20).unwrap();
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|bucket| (bucket.tick_lower, bucket.tick_upper, bucket.liquidity))
+                .collect::<Vec<_>>(),
+            vec![
+                (-20, 0, (15 * 100 + 5 * 300) / 20),
+                (0, 20, (10 * 300 + 10 * 200) / 20),
+                (20, 40, 10 * 200 / 20)
+            ]
+        );
+        assert!(bucket_liquidity_array::<i32>(&[], 20).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bucket_liquidity_array_edge_cases() {
+        // a single unaligned tick has no segment to average
+        assert!(bucket_liquidity_array(&[(5, 100)], 10).unwrap().is_empty());
+        let buckets = bucket_liquidity_array(&[(5, 100), (15, 0)], 10).unwrap();
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|bucket| (bucket.tick_lower, bucket.tick_upper, bucket.liquidity))
+                .collect::<Vec<_>>(),
+            vec![(0, 10, 50), (10, 20, 50)]
+        );
+        for bucket_width in [0, -10] {
+            assert!(matches!(
+                bucket_liquidity_array(&[(-20, 100), (30, 0)], bucket_width),
+                Err(Error::InvalidOptions)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_liquidity_distribution() {
+        let pool = pool().await;
+        let tick_spacing = pool.tick_spacing();
+        let tick_lower = pool.tick_current - 1000;
+        let tick_upper = pool.tick_current + 1000;
+        let distribution = get_liquidity_distribution(
+            pool,
+            tick_lower,
+            tick_upper,
+            10 * tick_spacing,
+            true,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(!distribution.ticks.is_empty());
+        assert!(!distribution.buckets.is_empty());
+        for bucket in &distribution.buckets {
+            assert_eq!(bucket.tick_upper - bucket.tick_lower, 10 * tick_spacing);
+            assert!(bucket.price_lower.as_ref().unwrap() < bucket.price_upper.as_ref().unwrap());
+        }
+    }
 }
@@ -0,0 +1,354 @@
+//! ## Pool Scanner Extension
+//! Discovers the pools of a factory from its `PoolCreated` events in chunked log queries, either
+//! in one pass or incrementally from the last processed block persisted in a [`PoolStore`].
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockNumberOrTag, providers::Provider, rpc::types::Filter, transports::Transport,
+};
+use alloy_primitives::Address;
+use alloy_sol_types::SolEvent;
+use IUniswapV3Factory::PoolCreated;
+
+/// A pool created by a factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CreatedPool {
+    /// The first token of the pool by address sort order
+    pub token0: Address,
+    /// The second token of the pool by address sort order
+    pub token1: Address,
+    /// The fee tier of the pool
+    pub fee: FeeAmount,
+    /// The pool address
+    pub pool: Address,
+    /// The block the pool was created in
+    pub block_number: u64,
+    /// The index of the `PoolCreated` log in the block
+    pub log_index: u64,
+}
+
+/// Get the pools created by a factory in a range of blocks.
+///
+/// ## Arguments
+///
+/// * `factory`: The factory address
+/// * `from_block`: The first block to scan, e.g. the deployment block of the factory
+/// * `to_block`: Optional last block to scan, the latest block by default
+/// * `batch_size`: The maximum number of blocks per `eth_getLogs` request
+/// * `provider`: The alloy provider
+///
+/// ## Returns
+///
+/// The pools, ordered by creation
+#[inline]
+pub async fn scan_pools<T, P>(
+    factory: Address,
+    from_block: u64,
+    to_block: Option<u64>,
+    batch_size: u64,
+    provider: P,
+) -> Result<Vec<CreatedPool>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => provider
+            .get_block_number()
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?,
+    };
+    let mut pools = Vec::new();
+    let mut start = from_block;
+    while start <= to_block {
+        let end = to_block.min(start + batch_size.max(1) - 1);
+        pools.extend(scan_batch(factory, start, end, &provider).await?);
+        start = end + 1;
+    }
+    Ok(pools)
+}
+
+async fn scan_batch<T, P>(
+    factory: Address,
+    from_block: u64,
+    to_block: u64,
+    provider: &P,
+) -> Result<Vec<CreatedPool>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let filter = Filter::new()
+        .address(factory)
+        .event_signature(PoolCreated::SIGNATURE_HASH)
+        .from_block(BlockNumberOrTag::Number(from_block))
+        .to_block(BlockNumberOrTag::Number(to_block));
+    let logs = provider
+        .get_logs(&filter)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    let mut pools = logs
+        .iter()
+        .map(|log| {
+            let event = PoolCreated::decode_log(&log.inner, true)
+                .map_err(|_| Error::InvalidLog)?
+                .data;
+            Ok(CreatedPool {
+                token0: event.token0,
+                token1: event.token1,
+                fee: FeeAmount::new(FeePips::try_from(event.fee)?, event.tickSpacing),
+                pool: event.pool,
+                block_number: log.block_number.unwrap_or_default(),
+                log_index: log.log_index.unwrap_or_default(),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    pools.sort_unstable_by_key(|pool| (pool.block_number, pool.log_index));
+    Ok(pools)
+}
+
+#[cfg(feature = "store")]
+mod incremental {
+    use super::*;
+    use crate::{entities::tick_list_data_provider::read_array, utils::tick_math::saturating_i24};
+    use alloy_primitives::ChainId;
+
+    const POOLS_TREE: &str = "created_pools";
+    const CHECKPOINTS_TREE: &str = "created_pools_checkpoints";
+
+    fn prefix(chain_id: ChainId, factory: Address) -> Vec<u8> {
+        [&chain_id.to_be_bytes()[..], factory.as_slice()].concat()
+    }
+
+    fn encode(pool: &CreatedPool) -> Vec<u8> {
+        [
+            pool.token0.as_slice(),
+            pool.token1.as_slice(),
+            &pool.fee.fee().to_be_bytes(),
+            &pool.fee.tick_spacing().as_i32().to_be_bytes(),
+            pool.pool.as_slice(),
+        ]
+        .concat()
+    }
+
+    fn decode(key: &[u8], value: &[u8]) -> Result<CreatedPool, Error> {
+        if key.len() != 16 || value.len() != 68 {
+            return Err(Error::InvalidSnapshot);
+        }
+        let (key, value) = (&mut &key[..], &mut &value[..]);
+        let token0 = Address::from(read_array::<20>(value)?);
+        let token1 = Address::from(read_array::<20>(value)?);
+        let fee = u32::from_be_bytes(read_array(value)?);
+        let tick_spacing = saturating_i24(i32::from_be_bytes(read_array(value)?));
+        Ok(CreatedPool {
+            token0,
+            token1,
+            fee: FeeAmount::try_new(fee, tick_spacing).map_err(|_| Error::InvalidSnapshot)?,
+            pool: Address::from(read_array::<20>(value)?),
+            block_number: u64::from_be_bytes(read_array(key)?),
+            log_index: u64::from_be_bytes(read_array(key)?),
+        })
+    }
+
+    /// Get the pools created by a factory since the last scan persisted in `store`, and persist
+    /// them with the last scanned block after each batch, so that an interrupted scan resumes
+    /// where it stopped.
+    ///
+    /// ## Arguments
+    ///
+    /// * `store`: The store of the scanned pools
+    /// * `chain_id`: The chain id
+    /// * `factory`: The factory address
+    /// * `from_block`: The first block to scan if the factory was never scanned
+    /// * `to_block`: Optional last block to scan, the latest block by default
+    /// * `batch_size`: The maximum number of blocks per `eth_getLogs` request
+    /// * `provider`: The alloy provider
+    ///
+    /// ## Returns
+    ///
+    /// The newly discovered pools, ordered by creation
+    #[inline]
+    pub async fn scan_pools_incremental<T, P>(
+        store: &PoolStore,
+        chain_id: ChainId,
+        factory: Address,
+        from_block: u64,
+        to_block: Option<u64>,
+        batch_size: u64,
+        provider: P,
+    ) -> Result<Vec<CreatedPool>, Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let pools_tree = store.open_tree(POOLS_TREE)?;
+        let checkpoints = store.open_tree(CHECKPOINTS_TREE)?;
+        let prefix = prefix(chain_id, factory);
+        let to_block = match to_block {
+            Some(to_block) => to_block,
+            None => provider
+                .get_block_number()
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?,
+        };
+        let mut start = match last_scanned_block(store, chain_id, factory)? {
+            Some(last) => from_block.max(last + 1),
+            None => from_block,
+        };
+        let mut pools = Vec::new();
+        while start <= to_block {
+            let end = to_block.min(start + batch_size.max(1) - 1);
+            let batch = scan_batch(factory, start, end, &provider).await?;
+            for pool in &batch {
+                let key = [
+                    &prefix[..],
+                    &pool.block_number.to_be_bytes(),
+                    &pool.log_index.to_be_bytes(),
+                ]
+                .concat();
+                pools_tree.insert(key, encode(pool))?;
+            }
+            checkpoints.insert(&prefix, end.to_be_bytes().to_vec())?;
+            pools.extend(batch);
+            start = end + 1;
+        }
+        Ok(pools)
+    }
+
+    /// The last block of a factory scanned by [`scan_pools_incremental`], if any.
+    ///
+    /// ## Arguments
+    ///
+    /// * `store`: The store of the scanned pools
+    /// * `chain_id`: The chain id
+    /// * `factory`: The factory address
+    #[inline]
+    pub fn last_scanned_block(
+        store: &PoolStore,
+        chain_id: ChainId,
+        factory: Address,
+    ) -> Result<Option<u64>, Error> {
+        store
+            .open_tree(CHECKPOINTS_TREE)?
+            .get(prefix(chain_id, factory))?
+            .map(|block| {
+                Ok(u64::from_be_bytes(
+                    block[..].try_into().map_err(|_| Error::InvalidSnapshot)?,
+                ))
+            })
+            .transpose()
+    }
+
+    /// All the pools of a factory persisted by [`scan_pools_incremental`], ordered by creation.
+    ///
+    /// ## Arguments
+    ///
+    /// * `store`: The store of the scanned pools
+    /// * `chain_id`: The chain id
+    /// * `factory`: The factory address
+    #[inline]
+    pub fn stored_pools(
+        store: &PoolStore,
+        chain_id: ChainId,
+        factory: Address,
+    ) -> Result<Vec<CreatedPool>, Error> {
+        let prefix = prefix(chain_id, factory);
+        store
+            .open_tree(POOLS_TREE)?
+            .scan_prefix(&prefix)
+            .map(|entry| {
+                let (key, value) = entry?;
+                decode(&key[prefix.len()..], &value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "store")]
+pub use incremental::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+
+    const FROM_BLOCK: u64 = 12369621;
+    const TO_BLOCK: u64 = 12380000;
+
+    #[tokio::test]
+    async fn test_scan_pools() {
+        let pools = scan_pools(
+            FACTORY_ADDRESS,
+            FROM_BLOCK,
+            Some(TO_BLOCK),
+            5000,
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(!pools.is_empty());
+        for pool in &pools {
+            assert!(pool.token0 < pool.token1);
+            assert_eq!(
+                pool.pool,
+                compute_pool_address(
+                    FACTORY_ADDRESS,
+                    pool.token0,
+                    pool.token1,
+                    pool.fee,
+                    None,
+                    None
+                )
+            );
+        }
+        // USDC/WETH 0.05%
+        assert!(pools
+            .iter()
+            .any(|pool| pool.pool == address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")));
+    }
+
+    #[cfg(feature = "store")]
+    #[tokio::test]
+    async fn test_scan_pools_incremental() {
+        let store = PoolStore::temporary().unwrap();
+        let first = scan_pools_incremental(
+            &store,
+            1,
+            FACTORY_ADDRESS,
+            FROM_BLOCK,
+            Some(FROM_BLOCK + 5000),
+            5000,
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            last_scanned_block(&store, 1, FACTORY_ADDRESS).unwrap(),
+            Some(FROM_BLOCK + 5000)
+        );
+        let second = scan_pools_incremental(
+            &store,
+            1,
+            FACTORY_ADDRESS,
+            FROM_BLOCK,
+            Some(TO_BLOCK),
+            5000,
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        let all = scan_pools(
+            FACTORY_ADDRESS,
+            FROM_BLOCK,
+            Some(TO_BLOCK),
+            5000,
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!([first, second].concat(), all);
+        assert_eq!(stored_pools(&store, 1, FACTORY_ADDRESS).unwrap(), all);
+    }
+}
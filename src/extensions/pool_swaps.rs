@@ -0,0 +1,152 @@
+//! ## Pool Swaps Extension
+//! This module provides a function to subscribe to the swaps of a pool over a pubsub transport,
+//! e.g. WebSocket, as a stream of decoded swaps with their execution price.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    transports::Transport,
+};
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::SolEvent;
+use futures_util::{Stream, StreamExt};
+use uniswap_sdk_core::prelude::*;
+use IUniswapV3PoolEvents::Swap;
+
+/// A swap of a pool with the location of its log and its execution price.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolSwap {
+    /// The block of the log
+    pub block_number: u64,
+    /// The index of the log in the block
+    pub log_index: u64,
+    /// The hash of the transaction that emitted the log
+    pub transaction_hash: B256,
+    /// The decoded event
+    pub swap: Swap,
+    /// The price of token0 in token1 the swap executed at, including the fee
+    pub execution_price: Price<Token, Token>,
+}
+
+impl PoolSwap {
+    /// Decodes a `Swap` log of a pool.
+    ///
+    /// ## Arguments
+    ///
+    /// * `log`: The log to decode
+    /// * `token0`: The first token of the pool
+    /// * `token1`: The second token of the pool
+    #[inline]
+    pub fn from_log(log: &Log, token0: Token, token1: Token) -> Result<Self, Error> {
+        let PoolEvent::Swap(swap) = decode_pool_event(&log.inner)? else {
+            return Err(Error::InvalidLog);
+        };
+        if swap.amount0.is_zero() || swap.amount1.is_zero() {
+            return Err(Error::InvalidPrice);
+        }
+        let execution_price = Price::new(
+            token0,
+            token1,
+            swap.amount0.unsigned_abs().to_big_int(),
+            swap.amount1.unsigned_abs().to_big_int(),
+        );
+        Ok(Self {
+            block_number: log.block_number.unwrap_or_default(),
+            log_index: log.log_index.unwrap_or_default(),
+            transaction_hash: log.transaction_hash.unwrap_or_default(),
+            swap,
+            execution_price,
+        })
+    }
+}
+
+/// Subscribes to the swaps of a pool, decoding each `Swap` log as it is emitted.
+///
+/// The provider must use a pubsub transport such as WebSocket or IPC.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `token0`: The first token of the pool
+/// * `token1`: The second token of the pool
+/// * `provider`: The alloy provider
+#[inline]
+pub async fn subscribe_pool_swaps<T, P>(
+    pool: Address,
+    token0: Token,
+    token1: Token,
+    provider: P,
+) -> Result<impl Stream<Item = Result<PoolSwap, Error>>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let filter = Filter::new()
+        .address(pool)
+        .event_signature(Swap::SIGNATURE_HASH);
+    let subscription = provider
+        .subscribe_logs(&filter)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    Ok(subscription
+        .into_stream()
+        .map(move |log| PoolSwap::from_log(&log, token0.clone(), token1.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::{aliases::I24, uint, I256};
+
+    fn log(swap: &Swap) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address: Address::ZERO,
+                data: swap.encode_log_data(),
+            },
+            block_number: Some(1),
+            log_index: Some(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pool_swap_from_log() {
+        let swap = Swap {
+            sender: Address::ZERO,
+            recipient: Address::ZERO,
+            amount0: I256::try_from(100).unwrap(),
+            amount1: I256::try_from(-300).unwrap(),
+            sqrtPriceX96: uint!(79228162514264337593543950336_U160),
+            liquidity: 1_000_000,
+            tick: I24::ZERO,
+        };
+        let pool_swap = PoolSwap::from_log(&log(&swap), TOKEN0.clone(), TOKEN1.clone()).unwrap();
+        assert_eq!(pool_swap.block_number, 1);
+        assert_eq!(pool_swap.log_index, 2);
+        assert_eq!(pool_swap.swap, swap);
+        assert_eq!(
+            pool_swap.execution_price.to_significant(5, None).unwrap(),
+            "3"
+        );
+    }
+
+    #[test]
+    fn test_pool_swap_from_log_zero_amount() {
+        let swap = Swap {
+            sender: Address::ZERO,
+            recipient: Address::ZERO,
+            amount0: I256::ZERO,
+            amount1: I256::ZERO,
+            sqrtPriceX96: uint!(79228162514264337593543950336_U160),
+            liquidity: 0,
+            tick: I24::ZERO,
+        };
+        assert!(matches!(
+            PoolSwap::from_log(&log(&swap), TOKEN0.clone(), TOKEN1.clone()),
+            Err(Error::InvalidPrice)
+        ));
+    }
+}
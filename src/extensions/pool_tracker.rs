@@ -0,0 +1,300 @@
+//! ## Pool Tracker
+//! [`PoolTracker`] mirrors the state of a pool locally. It fetches the state once, then applies the
+//! `Swap`, `Mint`, `Burn` and `Flash` events of the pool to keep the price, tick, liquidity and tick
+//! map up to date without querying the pool again.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockId,
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    transports::Transport,
+};
+use alloy_primitives::{aliases::I24, Address, ChainId};
+
+/// A local mirror of a pool, kept in sync by applying the logs of the pool.
+#[derive(Clone, Debug)]
+pub struct PoolTracker<I: TickIndex = I24> {
+    /// The pool address
+    pub address: Address,
+    /// The pool with all its initialized ticks
+    pub pool: Pool<TickMap<I>>,
+    /// The block of the last applied log
+    pub block_number: u64,
+    /// The index of the last applied log, or `u64::MAX` if all logs of `block_number` are applied
+    pub log_index: u64,
+}
+
+impl<I: TickIndex> PoolTracker<I> {
+    /// Fetches the state of a pool and all its initialized ticks at a block to start tracking from.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+    /// * `token_a`: One of the tokens in the pool
+    /// * `token_b`: The other token in the pool
+    /// * `fee`: Fee tier of the pool
+    /// * `provider`: The alloy provider
+    /// * `block_number`: Optional block number to start from, the latest block by default
+    #[inline]
+    pub async fn new<T, P>(
+        chain_id: ChainId,
+        deployment: impl Into<PoolDeployment>,
+        token_a: Address,
+        token_b: Address,
+        fee: FeeAmount,
+        provider: P,
+        block_number: Option<u64>,
+    ) -> Result<Self, Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T> + Clone,
+    {
+        let block_number = match block_number {
+            Some(block_number) => block_number,
+            None => provider
+                .get_block_number()
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?,
+        };
+        let deployment = deployment.into();
+        let pool = Pool::<EphemeralTickMapDataProvider<I>>::from_pool_key_with_tick_data_provider(
+            chain_id,
+            deployment,
+            token_a,
+            token_b,
+            fee,
+            provider,
+            Some(BlockId::from(block_number)),
+        )
+        .await?;
+        let address = pool.deployment_address(&deployment);
+        let pool = Pool::new_with_tick_data_provider(
+            pool.token0,
+            pool.token1,
+            pool.fee,
+            pool.sqrt_ratio_x96,
+            pool.liquidity,
+            pool.tick_data_provider.tick_map,
+        )?;
+        Ok(Self {
+            address,
+            pool,
+            block_number,
+            log_index: u64::MAX,
+        })
+    }
+
+    /// Applies a log of the pool to the local state.
+    ///
+    /// Logs of other contracts, pending logs, removed logs and logs at or before the last applied
+    /// log are skipped, so overlapping batches of logs may be applied. Reorgs are not reverted, the
+    /// tracker should be recreated instead.
+    ///
+    /// ## Arguments
+    ///
+    /// * `log`: The log to apply
+    ///
+    /// ## Returns
+    ///
+    /// Whether the log was applied
+    #[inline]
+    pub fn apply_log(&mut self, log: &Log) -> Result<bool, Error> {
+        if log.inner.address != self.address || log.removed {
+            return Ok(false);
+        }
+        let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+            return Ok(false);
+        };
+        if (block_number, log_index) <= (self.block_number, self.log_index) {
+            return Ok(false);
+        }
+        match decode_pool_event(&log.inner)? {
+            PoolEvent::Swap(swap) => {
+                self.pool.sqrt_ratio_x96 = swap.sqrtPriceX96;
+                self.pool.liquidity = swap.liquidity;
+                self.pool.tick_current = I::from_i24(swap.tick);
+            }
+            PoolEvent::Mint(mint) => {
+                self.pool.tick_data_provider.apply_mint(&mint)?;
+                self.update_liquidity(
+                    mint.tickLower,
+                    mint.tickUpper,
+                    i128::try_from(mint.amount).map_err(|_| Error::AddDeltaOverflow)?,
+                )?;
+            }
+            PoolEvent::Burn(burn) => {
+                self.pool.tick_data_provider.apply_burn(&burn)?;
+                self.update_liquidity(
+                    burn.tickLower,
+                    burn.tickUpper,
+                    -i128::try_from(burn.amount).map_err(|_| Error::AddDeltaOverflow)?,
+                )?;
+            }
+            // other events, including flash loans, only affect fees and oracle state, which are
+            // not tracked
+            _ => {}
+        }
+        self.block_number = block_number;
+        self.log_index = log_index;
+        Ok(true)
+    }
+
+    /// Applies the logs of the pool in order, see [`Self::apply_log`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `logs`: The logs to apply, ordered by block number and log index
+    #[inline]
+    pub fn apply_logs<'a>(&mut self, logs: impl IntoIterator<Item = &'a Log>) -> Result<(), Error> {
+        for log in logs {
+            self.apply_log(log)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches and applies the logs of the pool after the last applied log up to `to_block`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `provider`: The alloy provider
+    /// * `to_block`: Optional block number to sync to, the latest block by default
+    #[inline]
+    pub async fn sync<T, P>(&mut self, provider: P, to_block: Option<u64>) -> Result<(), Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let to_block = match to_block {
+            Some(to_block) => to_block,
+            None => provider
+                .get_block_number()
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?,
+        };
+        let from_block = if self.log_index == u64::MAX {
+            self.block_number + 1
+        } else {
+            self.block_number
+        };
+        if from_block > to_block {
+            return Ok(());
+        }
+        let filter = Filter::new()
+            .address(self.address)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?;
+        self.apply_logs(&logs)?;
+        self.block_number = to_block;
+        self.log_index = u64::MAX;
+        Ok(())
+    }
+
+    /// Watches the logs of the pool with a polling filter and applies them as they arrive, calling
+    /// `on_update` after each batch until it returns `false`.
+    ///
+    /// The filter only receives logs after it is installed, so [`Self::sync`] should be called
+    /// before, and again after this returns early because the poller closed or lagged behind.
+    ///
+    /// ## Arguments
+    ///
+    /// * `provider`: The alloy provider
+    /// * `on_update`: Called with the tracker and the applied batch of logs, returns whether to
+    ///   keep watching
+    #[inline]
+    pub async fn watch<T, P, F>(&mut self, provider: P, mut on_update: F) -> Result<(), Error>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+        F: FnMut(&Self, &[Log]) -> bool,
+    {
+        let filter = Filter::new().address(self.address);
+        let mut poller = provider
+            .watch_logs(&filter)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?
+            .spawn();
+        while let Ok(logs) = poller.recv().await {
+            self.apply_logs(&logs)?;
+            if !on_update(self, &logs) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the active liquidity for a change of liquidity of a position.
+    fn update_liquidity(
+        &mut self,
+        tick_lower: I24,
+        tick_upper: I24,
+        liquidity_delta: i128,
+    ) -> Result<(), Error> {
+        let tick_lower = I::from_i24(tick_lower);
+        let tick_upper = I::from_i24(tick_upper);
+        if tick_lower <= self.pool.tick_current && self.pool.tick_current < tick_upper {
+            self.pool.liquidity = add_delta(self.pool.liquidity, liquidity_delta)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[tokio::test]
+    async fn test_pool_tracker_sync() {
+        const BLOCK_NUMBER: u64 = 17000000;
+        let mut tracker: PoolTracker = PoolTracker::new(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            Some(BLOCK_NUMBER - 20),
+        )
+        .await
+        .unwrap();
+        tracker
+            .sync(PROVIDER.clone(), Some(BLOCK_NUMBER))
+            .await
+            .unwrap();
+        assert_eq!(tracker.block_number, BLOCK_NUMBER);
+        let pool = Pool::<EphemeralTickMapDataProvider>::from_pool_key_with_tick_data_provider(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            Some(BlockId::from(BLOCK_NUMBER)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(tracker.pool.sqrt_ratio_x96, pool.sqrt_ratio_x96);
+        assert_eq!(tracker.pool.liquidity, pool.liquidity);
+        assert_eq!(
+            tracker.pool.tick_data_provider.inner,
+            pool.tick_data_provider.tick_map.inner
+        );
+        assert_eq!(
+            tracker.pool.tick_data_provider.bitmap,
+            pool.tick_data_provider.tick_map.bitmap
+        );
+        // syncing again is a no-op
+        tracker
+            .sync(PROVIDER.clone(), Some(BLOCK_NUMBER))
+            .await
+            .unwrap();
+        assert_eq!(tracker.pool.sqrt_ratio_x96, pool.sqrt_ratio_x96);
+    }
+}
@@ -0,0 +1,248 @@
+//! ## Portfolio Extension
+//! This module provides a function to summarize all positions of an owner, grouped by pool.
+
+use crate::prelude::{Error, *};
+use alloy::{eips::BlockId, providers::Provider, transports::Transport};
+use alloy_primitives::{
+    aliases::{I24, U24},
+    Address, ChainId, U256,
+};
+use rustc_hash::FxHashMap;
+use uniswap_sdk_core::{prelude::*, token};
+
+/// A position in a [`PortfolioSummary`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortfolioPosition {
+    /// The token id of the position
+    pub token_id: U256,
+    /// The lower tick of the position
+    pub tick_lower: I24,
+    /// The upper tick of the position
+    pub tick_upper: I24,
+    /// The liquidity of the position
+    pub liquidity: u128,
+    /// The amount of token0 the liquidity could be burned for at the current pool price
+    pub amount0: U256,
+    /// The amount of token1 the liquidity could be burned for at the current pool price
+    pub amount1: U256,
+    /// The uncollected fees of token0
+    pub fees0: U256,
+    /// The uncollected fees of token1
+    pub fees1: U256,
+    /// Whether the current tick of the pool is within the range of the position
+    pub in_range: bool,
+}
+
+/// The positions of an owner in a single pool and their totals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolPortfolio {
+    /// The pool address
+    pub pool: Address,
+    /// The first token of the pool
+    pub token0: Token,
+    /// The second token of the pool
+    pub token1: Token,
+    /// The fee tier of the pool
+    pub fee: FeeAmount,
+    /// The current tick of the pool
+    pub tick_current: I24,
+    /// The positions of the owner in the pool
+    pub positions: Vec<PortfolioPosition>,
+    /// The total liquidity of the positions
+    pub liquidity: u128,
+    /// The total amount of token0 of the positions
+    pub amount0: U256,
+    /// The total amount of token1 of the positions
+    pub amount1: U256,
+    /// The total uncollected fees of token0 of the positions
+    pub fees0: U256,
+    /// The total uncollected fees of token1 of the positions
+    pub fees1: U256,
+}
+
+impl PoolPortfolio {
+    /// The number of positions whose range contains the current tick of the pool.
+    #[inline]
+    #[must_use]
+    pub fn in_range_count(&self) -> usize {
+        self.positions.iter().filter(|p| p.in_range).count()
+    }
+
+    /// The number of positions whose range does not contain the current tick of the pool.
+    #[inline]
+    #[must_use]
+    pub fn out_of_range_count(&self) -> usize {
+        self.positions.len() - self.in_range_count()
+    }
+}
+
+/// All positions of an owner, grouped by pool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortfolioSummary {
+    /// The owner of the positions
+    pub owner: Address,
+    /// The positions grouped by pool, ordered by pool address
+    pub pools: Vec<PoolPortfolio>,
+}
+
+/// Get a summary of all positions of the specified owner, grouped by pool with their total
+/// liquidity, principal amounts, uncollected fees and in-range status.
+///
+/// The positions are fetched with [`get_all_positions_by_owner`] and the uncollected fees with
+/// [`get_collectable_token_amounts_batch`] from the pools of `deployment`, both at the same block.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pools of the nonfungible
+///   position manager
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `owner`: The owner address
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_portfolio_summary<T, P>(
+    chain_id: ChainId,
+    deployment: impl Into<PoolDeployment>,
+    nonfungible_position_manager: Address,
+    owner: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<PortfolioSummary, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let deployment = deployment.into();
+    let block_id = Some(pin_block_id(&provider, block_id).await?);
+    let states = get_all_positions_by_owner(
+        nonfungible_position_manager,
+        owner,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let pool_fees: Vec<U24> = states.iter().map(|state| state.position.fee).collect();
+    let fee_amounts =
+        get_fee_amounts(deployment.factory, &pool_fees, provider.clone(), block_id).await?;
+    let fees = get_collectable_token_amounts_batch(
+        deployment,
+        nonfungible_position_manager,
+        states.iter().map(|state| state.tokenId).collect(),
+        BatchOptions::default(),
+        provider,
+        block_id,
+    )
+    .await?;
+
+    let mut pools: Vec<PoolPortfolio> = Vec::new();
+    let mut pool_indices = FxHashMap::default();
+    for state in states {
+        let pool = Pool::new(
+            token!(chain_id, state.position.token0, state.decimals0),
+            token!(chain_id, state.position.token1, state.decimals1),
+            fee_amounts[&state.position.fee],
+            state.slot0.sqrtPriceX96,
+            state.activeLiquidity,
+        )?;
+        let address = pool.deployment_address(&deployment);
+        let tick_current = state.slot0.tick;
+        let position = Position::new(
+            pool.clone(),
+            state.position.liquidity,
+            state.position.tickLower.as_i32(),
+            state.position.tickUpper.as_i32(),
+        );
+        let (fees0, fees1) = fees[&state.tokenId];
+        let position = PortfolioPosition {
+            token_id: state.tokenId,
+            tick_lower: state.position.tickLower,
+            tick_upper: state.position.tickUpper,
+            liquidity: state.position.liquidity,
+            amount0: U256::from_big_int(position.amount0()?.quotient()),
+            amount1: U256::from_big_int(position.amount1()?.quotient()),
+            fees0,
+            fees1,
+            in_range: state.position.tickLower <= tick_current
+                && tick_current < state.position.tickUpper,
+        };
+        let index = *pool_indices.entry(address).or_insert_with(|| {
+            pools.push(PoolPortfolio {
+                pool: address,
+                token0: pool.token0,
+                token1: pool.token1,
+                fee: pool.fee,
+                tick_current,
+                positions: Vec::new(),
+                liquidity: 0,
+                amount0: U256::ZERO,
+                amount1: U256::ZERO,
+                fees0: U256::ZERO,
+                fees1: U256::ZERO,
+            });
+            pools.len() - 1
+        });
+        let summary = &mut pools[index];
+        summary.liquidity += position.liquidity;
+        summary.amount0 += position.amount0;
+        summary.amount1 += position.amount1;
+        summary.fees0 += position.fees0;
+        summary.fees1 += position.fees1;
+        summary.positions.push(position);
+    }
+    pools.sort_unstable_by_key(|pool| pool.pool);
+    Ok(PortfolioSummary { owner, pools })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::PROVIDER;
+    use alloy_primitives::address;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[tokio::test]
+    async fn test_get_portfolio_summary() {
+        let npm = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+        let owner = address!("4bD047CA72fa05F0B89ad08FE5Ba5ccdC07DFFBF");
+        let block_id = Some(BlockId::from(17188000));
+        let summary =
+            get_portfolio_summary(1, FACTORY_ADDRESS, npm, owner, PROVIDER.clone(), block_id)
+                .await
+                .unwrap();
+        let states = get_all_positions_by_owner(npm, owner, PROVIDER.clone(), block_id)
+            .await
+            .unwrap();
+        assert_eq!(summary.owner, owner);
+        assert_eq!(
+            summary
+                .pools
+                .iter()
+                .map(|pool| pool.positions.len())
+                .sum::<usize>(),
+            states.len()
+        );
+        assert!(summary.pools.windows(2).all(|w| w[0].pool < w[1].pool));
+        for pool in &summary.pools {
+            assert_eq!(
+                pool.liquidity,
+                pool.positions.iter().map(|p| p.liquidity).sum::<u128>()
+            );
+            assert_eq!(
+                pool.in_range_count() + pool.out_of_range_count(),
+                pool.positions.len()
+            );
+            assert_eq!(
+                pool.pool,
+                compute_pool_address(
+                    FACTORY_ADDRESS,
+                    pool.token0.address(),
+                    pool.token1.address(),
+                    pool.fee,
+                    None,
+                    None
+                )
+            );
+        }
+    }
+}
@@ -3,21 +3,32 @@
 //! and pool for all positions of the specified owner by deploying an ephemeral contract via
 //! `eth_call`, etc.
 
-use crate::prelude::{Error, *};
+use crate::{
+    prelude::{Error, *},
+    utils::tick_math::saturating_i24,
+};
 use alloy::{
     eips::{BlockId, BlockNumberOrTag},
     providers::Provider,
     transports::Transport,
 };
-use alloy_primitives::{Address, ChainId, U256};
-use anyhow::Result;
+use alloy_primitives::{
+    aliases::{I24, U24},
+    Address, ChainId, U256,
+};
 use base64::{engine::general_purpose, Engine};
+use rustc_hash::FxHashMap;
 use uniswap_lens::{
     bindings::{
         ephemeralallpositionsbyowner::EphemeralAllPositionsByOwner,
         ephemeralgetposition::EphemeralGetPosition,
+        ephemeralgetpositions::EphemeralGetPositions,
         iuniswapv3nonfungiblepositionmanager::IUniswapV3NonfungiblePositionManager::{
-            positionsReturn, IUniswapV3NonfungiblePositionManagerInstance,
+            factoryCall, positionsCall, tokenOfOwnerByIndexCall,
+            IUniswapV3NonfungiblePositionManagerInstance,
+        },
+        iuniswapv3pool::IUniswapV3Pool::{
+            feeGrowthGlobal0X128Call, feeGrowthGlobal1X128Call, slot0Call, ticksCall,
         },
     },
     position_lens,
@@ -57,32 +68,85 @@ where
     T: Transport + Clone,
     P: Provider<T> + Clone,
 {
-    let block_id_ = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
-    let npm_contract =
-        get_nonfungible_position_manager_contract(nonfungible_position_manager, provider.clone());
-    // TODO: use multicall
-    let factory = npm_contract.factory().block(block_id_).call().await?._0;
-    let position = npm_contract
-        .positions(token_id)
-        .block(block_id_)
-        .call()
+    get_position_from_data_source(
+        chain_id,
+        nonfungible_position_manager,
+        token_id,
+        &AlloyDataSource::new(provider),
+        block_id,
+    )
+    .await
+}
+
+/// Get a [`Position`] struct from the token id of the nonfungible position manager of a
+/// [`ChainConfig`]
+///
+/// ## Arguments
+///
+/// * `config`: The deployment on the chain
+/// * `token_id`: The token id
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_position_from_chain_config<T, P>(
+    config: &ChainConfig,
+    token_id: U256,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Position, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    get_position(
+        config.chain_id,
+        config.nonfungible_position_manager,
+        token_id,
+        provider,
+        block_id,
+    )
+    .await
+}
+
+/// Get a [`Position`] struct from the token id, reading the position and its pool from a
+/// [`V3DataSource`]
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id
+/// * `data_source`: The source of the on-chain state
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_position_from_data_source<DS: V3DataSource>(
+    chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    data_source: &DS,
+    block_id: Option<BlockId>,
+) -> Result<Position, Error> {
+    let factory = data_source
+        .factory(nonfungible_position_manager, block_id)
         .await?;
-    let positionsReturn {
+    let PositionInfo {
         token0,
         token1,
         fee,
-        tickLower: tick_lower,
-        tickUpper: tick_upper,
+        tick_lower,
+        tick_upper,
         liquidity,
         ..
-    } = position;
-    let pool = Pool::from_pool_key(
+    } = data_source
+        .positions(nonfungible_position_manager, token_id, block_id)
+        .await?;
+    let pool = Pool::from_data_source(
         chain_id,
         factory,
         token0,
         token1,
-        fee.into(),
-        provider,
+        fee,
+        data_source,
         block_id,
     )
     .await?;
@@ -94,9 +158,120 @@ where
     ))
 }
 
+/// Get [`Position`] structs from many token ids, reading the positions and the factory and then
+/// the states of the distinct pools and tokens in chunked Multicall3 calls.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_ids`: The token ids
+/// * `options`: The chunking and concurrency of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The positions, in the same order as `token_ids`
+#[inline]
+pub async fn get_positions<T, P>(
+    chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_ids: &[U256],
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<Position>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    get_positions_with_cache(
+        chain_id,
+        nonfungible_position_manager,
+        token_ids,
+        options,
+        provider,
+        &mut PoolCache::new(block_id),
+    )
+    .await
+}
+
+/// Get [`Position`] structs from many token ids at the block of a [`PoolCache`], reading the
+/// positions and the factory in chunked Multicall3 calls and only the pools and tokens that are not
+/// cached yet.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_ids`: The token ids
+/// * `options`: The chunking and concurrency of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `cache`: The pools at the block to query, shared across batches
+///
+/// ## Returns
+///
+/// The positions, in the same order as `token_ids`
+#[inline]
+pub async fn get_positions_with_cache<T, P>(
+    chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_ids: &[U256],
+    options: BatchOptions,
+    provider: P,
+    cache: &mut PoolCache,
+) -> Result<Vec<Position>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    if token_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    // the factory followed by each position
+    let mut calls = Vec::with_capacity(token_ids.len() + 1);
+    calls.push(call3(nonfungible_position_manager, &factoryCall {}));
+    calls.extend(token_ids.iter().map(|&token_id| {
+        call3(
+            nonfungible_position_manager,
+            &positionsCall { tokenId: token_id },
+        )
+    }));
+    let results = aggregate3(calls, options, provider.clone(), cache.block_id()).await?;
+    let factory = decode_call3_result::<factoryCall>(&results[0])?._0;
+    let positions = results[1..]
+        .iter()
+        .map(decode_call3_result::<positionsCall>)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pool_keys: Vec<(Address, Address, U24)> = positions
+        .iter()
+        .map(|position| (position.token0, position.token1, position.fee))
+        .collect();
+    cache
+        .fetch(chain_id, factory, &pool_keys, options, provider)
+        .await?;
+    positions
+        .into_iter()
+        .map(|position| {
+            let pool = cache
+                .get(position.token0, position.token1, position.fee)
+                .ok_or(Error::DataNotFound)?;
+            Ok(Position::new(
+                pool.clone(),
+                position.liquidity,
+                position.tickLower.as_i32(),
+                position.tickUpper.as_i32(),
+            ))
+        })
+        .collect()
+}
+
 impl Position {
-    /// Get a [`Position`] struct from the token id in a single call by deploying an ephemeral
-    /// contract via `eth_call`
+    /// Get a [`Position`] struct from the token id, reading the position and its pool in a single
+    /// call by deploying an ephemeral contract via `eth_call` and the tick spacing of its fee tier
+    /// from the factory
     ///
     /// ## Arguments
     ///
@@ -127,15 +302,23 @@ impl Position {
         } = position_lens::get_position_details(
             nonfungible_position_manager,
             token_id,
-            provider,
+            &provider,
             block_id,
         )
         .await
         .map_err(Error::LensError)?;
+        let factory =
+            get_nonfungible_position_manager_contract(nonfungible_position_manager, &provider)
+                .factory()
+                .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+                .call()
+                .await?
+                ._0;
+        let fee = get_fee_amount(factory, position.fee, &provider, block_id).await?;
         let pool = Pool::new(
             token!(chain_id, position.token0, decimals0),
             token!(chain_id, position.token1, decimals1),
-            position.fee.into(),
+            fee,
             slot0.sqrtPriceX96,
             active_liquidity,
         )?;
@@ -202,8 +385,14 @@ impl<I: TickIndex> Position<EphemeralTickMapDataProvider<I>> {
         Ok(Self::new(
             pool,
             position.liquidity,
-            position.tick_lower.try_into().unwrap(),
-            position.tick_upper.try_into().unwrap(),
+            position
+                .tick_lower
+                .try_into()
+                .map_err(|_| Error::InvalidTick(saturating_i24(position.tick_lower)))?,
+            position
+                .tick_upper
+                .try_into()
+                .map_err(|_| Error::InvalidTick(saturating_i24(position.tick_upper)))?,
         ))
     }
 }
@@ -243,6 +432,324 @@ where
     .map_err(Error::LensError)
 }
 
+/// Filters applied to the positions of an owner by [`get_all_positions_by_owner_filtered`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionFilter {
+    /// Only keep the positions in this pool
+    pub pool: Option<Address>,
+    /// Only keep the positions with nonzero liquidity
+    pub only_active: bool,
+    /// Only keep the positions whose range contains the current tick of the pool
+    pub only_in_range: bool,
+}
+
+/// Get the state and pool for the positions of the specified owner that pass `filter`, by
+/// deploying an ephemeral contract via `eth_call` and filtering the states client side.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `owner`: The owner address
+/// * `filter`: The pool and the liquidity and range conditions to keep positions by
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_all_positions_by_owner_filtered<T, P>(
+    nonfungible_position_manager: Address,
+    owner: Address,
+    filter: PositionFilter,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<EphemeralAllPositionsByOwner::PositionState>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let mut states = get_all_positions_by_owner(
+        nonfungible_position_manager,
+        owner,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let in_pool = match filter.pool {
+        Some(pool) => {
+            let factory = get_nonfungible_position_manager_contract(
+                nonfungible_position_manager,
+                provider.clone(),
+            )
+            .factory()
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .call()
+            .await?
+            ._0;
+            let fees: Vec<U24> = states.iter().map(|state| state.position.fee).collect();
+            let fee_amounts = get_fee_amounts(factory, &fees, provider, block_id).await?;
+            Some((pool, factory, fee_amounts))
+        }
+        None => None,
+    };
+    states.retain(|state| {
+        let position = &state.position;
+        let tick_current = state.slot0.tick;
+        let in_pool = in_pool
+            .as_ref()
+            .map_or(true, |(pool, factory, fee_amounts)| {
+                compute_pool_address(
+                    *factory,
+                    position.token0,
+                    position.token1,
+                    fee_amounts[&position.fee],
+                    None,
+                    None,
+                ) == *pool
+            });
+        in_pool
+            && (!filter.only_active || position.liquidity > 0)
+            && (!filter.only_in_range
+                || (position.tickLower <= tick_current && tick_current < position.tickUpper))
+    });
+    Ok(states)
+}
+
+/// Get the state and pool for the positions of the specified owner in a pool.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `owner`: The owner address
+/// * `pool`: The pool address
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_positions_by_owner_in_pool<T, P>(
+    nonfungible_position_manager: Address,
+    owner: Address,
+    pool: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<EphemeralAllPositionsByOwner::PositionState>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    get_all_positions_by_owner_filtered(
+        nonfungible_position_manager,
+        owner,
+        PositionFilter {
+            pool: Some(pool),
+            ..Default::default()
+        },
+        provider,
+        block_id,
+    )
+    .await
+}
+
+/// Get the state and pool for a page of positions of the specified owner.
+///
+/// The token ids are enumerated via `tokenOfOwnerByIndex` in Multicall3 batches and their states
+/// are then fetched in a single `eth_call` by deploying an ephemeral contract, all at the same
+/// block, see [`pin_block_id`].
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `owner`: The owner address
+/// * `start_index`: The index of the first position to fetch in the owner's enumeration
+/// * `count`: The maximum number of positions to fetch
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The positions in `[start_index, start_index + count)`, truncated at the owner's balance.
+#[inline]
+pub async fn get_positions_by_owner_paginated<T, P>(
+    nonfungible_position_manager: Address,
+    owner: Address,
+    start_index: usize,
+    count: usize,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<EphemeralGetPositions::PositionState>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let block_id = pin_block_id(&provider, block_id).await?;
+    let balance: usize =
+        get_nonfungible_position_manager_contract(nonfungible_position_manager, provider.clone())
+            .balanceOf(owner)
+            .block(block_id)
+            .call()
+            .await?
+            .balance
+            .saturating_to();
+    let end_index = start_index.saturating_add(count).min(balance);
+    if start_index >= end_index {
+        return Ok(Vec::new());
+    }
+    let calls = (start_index..end_index)
+        .map(|index| {
+            call3(
+                nonfungible_position_manager,
+                &tokenOfOwnerByIndexCall {
+                    owner,
+                    index: U256::from(index),
+                },
+            )
+        })
+        .collect();
+    let token_ids = aggregate3(
+        calls,
+        BatchOptions::default(),
+        provider.clone(),
+        Some(block_id),
+    )
+    .await?
+    .iter()
+    .map(|result| decode_call3_result::<tokenOfOwnerByIndexCall>(result).map(|id| id._0))
+    .collect::<Result<Vec<_>, _>>()?;
+    position_lens::get_positions(
+        nonfungible_position_manager,
+        token_ids,
+        provider,
+        Some(block_id),
+    )
+    .await
+    .map_err(Error::LensError)
+}
+
+/// Get the state and pool for all positions of the specified owner by walking the owner's
+/// enumeration in pages of `page_size`.
+///
+/// Unlike [`get_all_positions_by_owner`], this is not bounded by the provider gas limit, so it can
+/// enumerate owners with thousands of positions. The block is pinned before the first page, so all
+/// pages read the same state.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `owner`: The owner address
+/// * `page_size`: The number of positions fetched per `eth_call`, which must be positive
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_all_positions_by_owner_paginated<T, P>(
+    nonfungible_position_manager: Address,
+    owner: Address,
+    page_size: usize,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<EphemeralGetPositions::PositionState>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    if page_size == 0 {
+        return Err(Error::InvalidOptions);
+    }
+    let block_id = Some(pin_block_id(&provider, block_id).await?);
+    let mut positions = Vec::new();
+    loop {
+        let page = get_positions_by_owner_paginated(
+            nonfungible_position_manager,
+            owner,
+            positions.len(),
+            page_size,
+            provider.clone(),
+            block_id,
+        )
+        .await?;
+        let exhausted = page.len() < page_size;
+        positions.extend(page);
+        if exhausted {
+            return Ok(positions);
+        }
+    }
+}
+
+/// Get the fee growth per unit of liquidity inside a tick range of a pool.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// A tuple of the fee growth inside the range of token0 and token1 as Q128.128 numbers.
+#[inline]
+pub async fn get_pool_fee_growth_inside<T, P>(
+    pool: Address,
+    tick_lower: I24,
+    tick_upper: I24,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<(U256, U256), Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    get_pool_fee_growth_inside_from_data_source(
+        pool,
+        tick_lower,
+        tick_upper,
+        &AlloyDataSource::new(provider),
+        block_id,
+    )
+    .await
+}
+
+/// Get the fee growth per unit of liquidity inside a tick range of a pool from a [`V3DataSource`].
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `data_source`: The source of the on-chain state
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// A tuple of the fee growth inside the range of token0 and token1 as Q128.128 numbers.
+#[inline]
+pub async fn get_pool_fee_growth_inside_from_data_source<DS: V3DataSource>(
+    pool: Address,
+    tick_lower: I24,
+    tick_upper: I24,
+    data_source: &DS,
+    block_id: Option<BlockId>,
+) -> Result<(U256, U256), Error> {
+    let tick = data_source.slot0(pool, block_id).await?.tick;
+    let (fee_growth_global_0x128, fee_growth_global_1x128) =
+        data_source.fee_growth_global(pool, block_id).await?;
+    let tick_info_lower = data_source.tick(pool, tick_lower, block_id).await?;
+    let tick_info_upper = data_source.tick(pool, tick_upper, block_id).await?;
+
+    // https://github.com/Uniswap/v4-core/blob/f630c8ca8c669509d958353200953762fd15761a/contracts/libraries/Pool.sol#L566
+    Ok(get_fee_growth_inside(
+        FeeGrowthOutside {
+            fee_growth_outside0_x128: tick_info_lower.fee_growth_outside0_x128,
+            fee_growth_outside1_x128: tick_info_lower.fee_growth_outside1_x128,
+        },
+        FeeGrowthOutside {
+            fee_growth_outside0_x128: tick_info_upper.fee_growth_outside0_x128,
+            fee_growth_outside1_x128: tick_info_upper.fee_growth_outside1_x128,
+        },
+        tick_lower,
+        tick_upper,
+        tick,
+        fee_growth_global_0x128,
+        fee_growth_global_1x128,
+    ))
+}
+
 /// Get the real-time collectable token amounts.
 ///
 /// ## Arguments
@@ -258,96 +765,274 @@ where
 /// A tuple of the collectable token amounts.
 #[inline]
 pub async fn get_collectable_token_amounts<T, P>(
-    _chain_id: ChainId,
+    chain_id: ChainId,
     nonfungible_position_manager: Address,
     token_id: U256,
     provider: P,
     block_id: Option<BlockId>,
-) -> Result<(U256, U256)>
+) -> Result<(U256, U256), Error>
 where
     T: Transport + Clone,
     P: Provider<T> + Clone,
 {
-    let block_id_ = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
-    let npm_contract =
-        get_nonfungible_position_manager_contract(nonfungible_position_manager, provider.clone());
-    // TODO: use multicall
-    let factory = npm_contract.factory().block(block_id_).call().await?._0;
-    let position = npm_contract
-        .positions(token_id)
-        .block(block_id_)
-        .call()
-        .await?;
-    let pool_contract = get_pool_contract(
-        factory,
-        position.token0,
-        position.token1,
-        position.fee.into(),
-        provider,
-    );
-    let tick = pool_contract.slot0().block(block_id_).call().await?.tick;
-    let fee_growth_global_0x128 = pool_contract
-        .feeGrowthGlobal0X128()
-        .block(block_id_)
-        .call()
-        .await?
-        ._0;
-    let fee_growth_global_1x128 = pool_contract
-        .feeGrowthGlobal1X128()
-        .block(block_id_)
-        .call()
-        .await?
-        ._0;
-    let tick_info_lower = pool_contract
-        .ticks(position.tickLower)
-        .block(block_id_)
-        .call()
+    get_collectable_token_amounts_from_data_source(
+        chain_id,
+        nonfungible_position_manager,
+        token_id,
+        &AlloyDataSource::new(provider),
+        block_id,
+    )
+    .await
+}
+
+/// Get the real-time collectable token amounts from a [`V3DataSource`].
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id
+/// * `data_source`: The source of the on-chain state
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// A tuple of the collectable token amounts.
+#[inline]
+pub async fn get_collectable_token_amounts_from_data_source<DS: V3DataSource>(
+    _chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    data_source: &DS,
+    block_id: Option<BlockId>,
+) -> Result<(U256, U256), Error> {
+    let factory = data_source
+        .factory(nonfungible_position_manager, block_id)
         .await?;
-    let fee_growth_outside_0x128_lower = tick_info_lower.feeGrowthOutside0X128;
-    let fee_growth_outside_1x128_lower = tick_info_lower.feeGrowthOutside1X128;
-    let tick_info_upper = pool_contract
-        .ticks(position.tickUpper)
-        .block(block_id_)
-        .call()
+    let position = data_source
+        .positions(nonfungible_position_manager, token_id, block_id)
         .await?;
-    let fee_growth_outside_0x128_upper = tick_info_upper.feeGrowthOutside0X128;
-    let fee_growth_outside_1x128_upper = tick_info_upper.feeGrowthOutside1X128;
-
-    // https://github.com/Uniswap/v4-core/blob/f630c8ca8c669509d958353200953762fd15761a/contracts/libraries/Pool.sol#L566
-    let (fee_growth_inside_0x128, fee_growth_inside_1x128) = if tick < position.tickLower {
-        (
-            fee_growth_outside_0x128_lower - fee_growth_outside_0x128_upper,
-            fee_growth_outside_1x128_lower - fee_growth_outside_1x128_upper,
-        )
-    } else if tick >= position.tickUpper {
-        (
-            fee_growth_outside_0x128_upper - fee_growth_outside_0x128_lower,
-            fee_growth_outside_1x128_upper - fee_growth_outside_1x128_lower,
-        )
-    } else {
-        (
-            fee_growth_global_0x128
-                - fee_growth_outside_0x128_lower
-                - fee_growth_outside_0x128_upper,
-            fee_growth_global_1x128
-                - fee_growth_outside_1x128_lower
-                - fee_growth_outside_1x128_upper,
+    let (fee_growth_inside_0x128, fee_growth_inside_1x128) =
+        get_pool_fee_growth_inside_from_data_source(
+            compute_pool_address(
+                factory,
+                position.token0,
+                position.token1,
+                position.fee,
+                None,
+                None,
+            ),
+            position.tick_lower,
+            position.tick_upper,
+            data_source,
+            block_id,
         )
-    };
+        .await?;
     let (tokens_owed_0, tokens_owed_1) = get_tokens_owed(
-        position.feeGrowthInside0LastX128,
-        position.feeGrowthInside1LastX128,
+        position.fee_growth_inside0_last_x128,
+        position.fee_growth_inside1_last_x128,
         position.liquidity,
         fee_growth_inside_0x128,
         fee_growth_inside_1x128,
     );
     Ok((
-        U256::from(position.tokensOwed0) + tokens_owed_0,
-        U256::from(position.tokensOwed1) + tokens_owed_1,
+        U256::from(position.tokens_owed0) + tokens_owed_0,
+        U256::from(position.tokens_owed1) + tokens_owed_1,
     ))
 }
 
-/// Get the token SVG URL of the specified position.
+/// Get the real-time collectable token amounts of many positions, reading the positions and then
+/// the pool states in chunked Multicall3 calls, all at the same block, see [`pin_block_id`].
+///
+/// ## Arguments
+///
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pools of the nonfungible
+///   position manager
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_ids`: The token ids
+/// * `options`: The chunking and concurrency of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// A map of the token id to a tuple of the collectable token amounts.
+#[inline]
+pub async fn get_collectable_token_amounts_batch<T, P>(
+    deployment: impl Into<PoolDeployment>,
+    nonfungible_position_manager: Address,
+    token_ids: Vec<U256>,
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<FxHashMap<U256, (U256, U256)>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let deployment = deployment.into();
+    let block_id = Some(pin_block_id(&provider, block_id).await?);
+    let calls = token_ids
+        .iter()
+        .map(|&token_id| {
+            call3(
+                nonfungible_position_manager,
+                &positionsCall { tokenId: token_id },
+            )
+        })
+        .collect();
+    let positions = aggregate3(calls, options, provider.clone(), block_id)
+        .await?
+        .iter()
+        .map(decode_call3_result::<positionsCall>)
+        .collect::<Result<Vec<_>, _>>()?;
+    let fees: Vec<U24> = positions.iter().map(|position| position.fee).collect();
+    let fee_amounts =
+        get_fee_amounts(deployment.factory, &fees, provider.clone(), block_id).await?;
+    let pools: Vec<Address> = positions
+        .iter()
+        .map(|position| {
+            deployment.pool_address(position.token0, position.token1, fee_amounts[&position.fee])
+        })
+        .collect();
+    let mut unique_pools = pools.clone();
+    unique_pools.sort_unstable();
+    unique_pools.dedup();
+
+    // the state of each pool followed by the ticks of each position
+    let mut calls = Vec::with_capacity(3 * unique_pools.len() + 2 * positions.len());
+    for &pool in &unique_pools {
+        calls.push(call3(pool, &slot0Call {}));
+        calls.push(call3(pool, &feeGrowthGlobal0X128Call {}));
+        calls.push(call3(pool, &feeGrowthGlobal1X128Call {}));
+    }
+    for (&pool, position) in pools.iter().zip(&positions) {
+        calls.push(call3(
+            pool,
+            &ticksCall {
+                tick: position.tickLower,
+            },
+        ));
+        calls.push(call3(
+            pool,
+            &ticksCall {
+                tick: position.tickUpper,
+            },
+        ));
+    }
+    let results = aggregate3(calls, options, provider, block_id).await?;
+    let (pool_results, tick_results) = results.split_at(3 * unique_pools.len());
+    let mut pool_states = FxHashMap::default();
+    for (pool, results) in unique_pools.into_iter().zip(pool_results.chunks_exact(3)) {
+        pool_states.insert(
+            pool,
+            (
+                decode_call3_result::<slot0Call>(&results[0])?.tick,
+                decode_call3_result::<feeGrowthGlobal0X128Call>(&results[1])?._0,
+                decode_call3_result::<feeGrowthGlobal1X128Call>(&results[2])?._0,
+            ),
+        );
+    }
+
+    let mut amounts = FxHashMap::default();
+    for (((token_id, position), pool), results) in token_ids
+        .into_iter()
+        .zip(positions)
+        .zip(pools)
+        .zip(tick_results.chunks_exact(2))
+    {
+        let (tick, fee_growth_global_0x128, fee_growth_global_1x128) = pool_states[&pool];
+        let tick_info_lower = decode_call3_result::<ticksCall>(&results[0])?;
+        let tick_info_upper = decode_call3_result::<ticksCall>(&results[1])?;
+        let (fee_growth_inside_0x128, fee_growth_inside_1x128) = get_fee_growth_inside(
+            FeeGrowthOutside {
+                fee_growth_outside0_x128: tick_info_lower.feeGrowthOutside0X128,
+                fee_growth_outside1_x128: tick_info_lower.feeGrowthOutside1X128,
+            },
+            FeeGrowthOutside {
+                fee_growth_outside0_x128: tick_info_upper.feeGrowthOutside0X128,
+                fee_growth_outside1_x128: tick_info_upper.feeGrowthOutside1X128,
+            },
+            position.tickLower,
+            position.tickUpper,
+            tick,
+            fee_growth_global_0x128,
+            fee_growth_global_1x128,
+        );
+        let (tokens_owed_0, tokens_owed_1) = get_tokens_owed(
+            position.feeGrowthInside0LastX128,
+            position.feeGrowthInside1LastX128,
+            position.liquidity,
+            fee_growth_inside_0x128,
+            fee_growth_inside_1x128,
+        );
+        amounts.insert(
+            token_id,
+            (
+                U256::from(position.tokensOwed0) + tokens_owed_0,
+                U256::from(position.tokensOwed1) + tokens_owed_1,
+            ),
+        );
+    }
+    Ok(amounts)
+}
+
+/// Get a [`PositionSnapshot`] of a position at a block, to compare with another block via
+/// [`PositionSnapshot::diff`].
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id
+/// * `provider`: The alloy provider
+/// * `block_number`: The block of the snapshot
+#[inline]
+pub async fn get_position_snapshot<T, P>(
+    chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    provider: P,
+    block_number: u64,
+) -> Result<PositionSnapshot, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let block_id = Some(BlockId::from(block_number));
+    let (position, uncollected_fees) = futures_util::try_join!(
+        get_position(
+            chain_id,
+            nonfungible_position_manager,
+            token_id,
+            provider.clone(),
+            block_id,
+        ),
+        get_collectable_token_amounts(
+            chain_id,
+            nonfungible_position_manager,
+            token_id,
+            provider,
+            block_id,
+        ),
+    )?;
+    PositionSnapshot::from_position(&position, block_number, uncollected_fees)
+}
+
+/// The metadata of a position NFT decoded from its token URI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// The name of the NFT, e.g. `Uniswap - 0.3% - USDC/WETH - 1234.5<>2345.6`
+    pub name: String,
+    /// The description of the position, including the pool and token addresses
+    pub description: String,
+    /// The SVG image as a base64 data URL
+    pub image: String,
+    /// The full JSON metadata
+    pub json: serde_json::Value,
+}
+
+/// Get the metadata of the specified position from its token URI.
 ///
 /// ## Arguments
 ///
@@ -356,12 +1041,12 @@ where
 /// * `provider`: The alloy provider
 /// * `block_id`: Optional block number to query
 #[inline]
-pub async fn get_token_svg<T, P>(
+pub async fn get_token_metadata<T, P>(
     nonfungible_position_manager: Address,
     token_id: U256,
     provider: P,
     block_id: Option<BlockId>,
-) -> Result<String>
+) -> Result<TokenMetadata, Error>
 where
     T: Transport + Clone,
     P: Provider<T>,
@@ -372,13 +1057,52 @@ where
         .call()
         .await?
         ._0;
-    let json_uri =
-        general_purpose::URL_SAFE.decode(uri.replace("data:application/json;base64,", ""))?;
-    let image = serde_json::from_slice::<serde_json::Value>(&json_uri)?
-        .get("image")
-        .unwrap()
-        .to_string();
-    Ok(image[1..image.len() - 1].to_string())
+    let json = uri
+        .strip_prefix("data:application/json;base64,")
+        .ok_or(Error::InvalidTokenUri)?;
+    let json = general_purpose::STANDARD
+        .decode(json)
+        .map_err(|_| Error::InvalidTokenUri)?;
+    let json: serde_json::Value =
+        serde_json::from_slice(&json).map_err(|_| Error::InvalidTokenUri)?;
+    let field = |key: &str| {
+        json.get(key)
+            .and_then(serde_json::Value::as_str)
+            .map(ToString::to_string)
+            .ok_or(Error::InvalidTokenUri)
+    };
+    Ok(TokenMetadata {
+        name: field("name")?,
+        description: field("description")?,
+        image: field("image")?,
+        json,
+    })
+}
+
+/// Get the token SVG URL of the specified position.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_token_svg<T, P>(
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<String, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(
+        get_token_metadata(nonfungible_position_manager, token_id, provider, block_id)
+            .await?
+            .image,
+    )
 }
 
 /// Predict the position after rebalance assuming the pool price remains the same.
@@ -498,6 +1222,77 @@ mod tests {
         assert_eq!(position.tick_upper, 264600);
     }
 
+    #[tokio::test]
+    async fn test_get_positions() {
+        let token_ids = [uint!(4_U256), uint!(5_U256), uint!(4_U256)];
+        let positions = get_positions(
+            1,
+            NPM,
+            &token_ids,
+            BatchOptions::default(),
+            PROVIDER.clone(),
+            BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(positions.len(), token_ids.len());
+        for (&token_id, position) in token_ids.iter().zip(&positions) {
+            let expected = Position::from_token_id(1, NPM, token_id, PROVIDER.clone(), BLOCK_ID)
+                .await
+                .unwrap();
+            assert_eq!(*position, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_positions_with_cache() {
+        let mut cache = PoolCache::new(BLOCK_ID);
+        let positions = get_positions_with_cache(
+            1,
+            NPM,
+            &[uint!(4_U256)],
+            BatchOptions::default(),
+            PROVIDER.clone(),
+            &mut cache,
+        )
+        .await
+        .unwrap();
+        assert_eq!(cache.len(), 1);
+        let pool = &positions[0].pool;
+        assert!(cache
+            .get(
+                pool.token1.address(),
+                pool.token0.address(),
+                pool.fee.into()
+            )
+            .is_some());
+        let positions = get_positions_with_cache(
+            1,
+            NPM,
+            &[uint!(4_U256), uint!(5_U256)],
+            BatchOptions::default(),
+            PROVIDER.clone(),
+            &mut cache,
+        )
+        .await
+        .unwrap();
+        assert_eq!(positions.len(), 2);
+        assert!(cache.len() <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_position_from_chain_config() {
+        let position = get_position_from_chain_config(
+            &ChainConfig::MAINNET,
+            uint!(4_U256),
+            PROVIDER.clone(),
+            BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(position.liquidity, 34399999543676);
+    }
+
     #[tokio::test]
     async fn test_from_token_id_with_tick_data_provider() {
         let position = Position::from_token_id_with_tick_data_provider(
@@ -547,6 +1342,79 @@ mod tests {
         // });
     }
 
+    #[tokio::test]
+    async fn test_get_all_positions_by_owner_filtered() {
+        let owner = address!("4bD047CA72fa05F0B89ad08FE5Ba5ccdC07DFFBF");
+        let all = get_all_positions_by_owner(NPM, owner, PROVIDER.clone(), BLOCK_ID)
+            .await
+            .unwrap();
+        let filter = PositionFilter {
+            only_active: true,
+            only_in_range: true,
+            ..Default::default()
+        };
+        let in_range =
+            get_all_positions_by_owner_filtered(NPM, owner, filter, PROVIDER.clone(), BLOCK_ID)
+                .await
+                .unwrap();
+        assert!(in_range.len() <= all.len());
+        for state in &in_range {
+            assert!(state.position.liquidity > 0);
+            assert!(state.position.tickLower <= state.slot0.tick);
+            assert!(state.slot0.tick < state.position.tickUpper);
+        }
+        let position = &all[0].position;
+        let pool = compute_pool_address(
+            FACTORY_ADDRESS,
+            position.token0,
+            position.token1,
+            position.fee.try_into().unwrap(),
+            None,
+            None,
+        );
+        let in_pool = get_positions_by_owner_in_pool(NPM, owner, pool, PROVIDER.clone(), BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(in_pool.iter().any(|state| state.tokenId == all[0].tokenId));
+        assert!(in_pool
+            .iter()
+            .all(|state| state.position.token0 == position.token0
+                && state.position.token1 == position.token1
+                && state.position.fee == position.fee));
+    }
+
+    #[tokio::test]
+    async fn test_get_all_positions_by_owner_paginated() {
+        let provider = PROVIDER.clone();
+        let owner = address!("4bD047CA72fa05F0B89ad08FE5Ba5ccdC07DFFBF");
+        let positions = get_all_positions_by_owner(NPM, owner, provider.clone(), BLOCK_ID)
+            .await
+            .unwrap();
+        let paginated =
+            get_all_positions_by_owner_paginated(NPM, owner, 3, provider.clone(), BLOCK_ID)
+                .await
+                .unwrap();
+        assert_eq!(paginated.len(), positions.len());
+        for (paginated, position) in paginated.iter().zip(positions.iter()) {
+            assert_eq!(paginated.tokenId, position.tokenId);
+        }
+        let page = get_positions_by_owner_paginated(
+            NPM,
+            owner,
+            positions.len(),
+            3,
+            provider.clone(),
+            BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(page.is_empty());
+        assert!(matches!(
+            get_all_positions_by_owner_paginated(NPM, owner, 0, provider, BLOCK_ID).await,
+            Err(Error::InvalidOptions)
+        ));
+    }
+
     #[tokio::test]
     async fn test_get_collectable_token_amounts() {
         let (tokens_owed_0, tokens_owed_1) =
@@ -557,6 +1425,65 @@ mod tests {
         assert_eq!(tokens_owed_1, uint!(516299277575296150_U256));
     }
 
+    #[tokio::test]
+    async fn test_get_position_snapshot() {
+        let block_number = 17_188_000;
+        let earlier = get_position_snapshot(1, NPM, uint!(4_U256), PROVIDER.clone(), block_number)
+            .await
+            .unwrap();
+        let later =
+            get_position_snapshot(1, NPM, uint!(4_U256), PROVIDER.clone(), block_number + 1000)
+                .await
+                .unwrap();
+        assert_eq!(earlier.block_number, block_number);
+        let diff = earlier.diff(&later).unwrap();
+        assert_eq!(diff.liquidity_delta, 0);
+        assert!(diff.liquidity_value_change.abs() <= BigInt::from(1));
+    }
+
+    #[tokio::test]
+    async fn test_get_collectable_token_amounts_batch() {
+        let token_ids = vec![uint!(4_U256), uint!(5_U256), uint!(6_U256)];
+        let amounts = get_collectable_token_amounts_batch(
+            FACTORY_ADDRESS,
+            NPM,
+            token_ids.clone(),
+            BatchOptions {
+                chunk_size: 4,
+                max_concurrency: 2,
+                ..Default::default()
+            },
+            PROVIDER.clone(),
+            BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(amounts.len(), token_ids.len());
+        assert_eq!(
+            amounts[&uint!(4_U256)],
+            (uint!(3498422_U256), uint!(516299277575296150_U256))
+        );
+        for token_id in token_ids {
+            assert_eq!(
+                amounts[&token_id],
+                get_collectable_token_amounts(1, NPM, token_id, PROVIDER.clone(), BLOCK_ID)
+                    .await
+                    .unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_token_metadata() {
+        let metadata = get_token_metadata(NPM, uint!(4_U256), PROVIDER.clone(), BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(metadata.name.starts_with("Uniswap - "));
+        assert!(metadata.description.contains("Token ID: 4"));
+        assert!(metadata.image.starts_with("data:image/svg+xml;base64,"));
+        assert_eq!(metadata.json["image"], metadata.image);
+    }
+
     #[tokio::test]
     async fn test_get_token_svg() {
         let svg = get_token_svg(NPM, uint!(4_U256), PROVIDER.clone(), BLOCK_ID)
@@ -0,0 +1,213 @@
+//! ## Position APR Extension
+//! This module provides functions to estimate the annualized fee APR of a position from the fee
+//! growth inside its range between two blocks.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockId, providers::Provider, rpc::types::BlockTransactionsKind, transports::Transport,
+};
+use alloy_primitives::U256;
+use uniswap_sdk_core::prelude::*;
+
+/// Computes the annualized fee APR of a position from two snapshots of the fee growth inside its
+/// range, assuming its liquidity was constant in between.
+///
+/// The fees earned are valued in token1 at the current pool price and divided by the current value
+/// of the position in token1.
+///
+/// ## Arguments
+///
+/// * `position`: The position with the current pool state
+/// * `fee_growth_inside_start`: The fee growth inside the range of token0 and token1 at the start
+///   of the window
+/// * `fee_growth_inside_end`: The fee growth inside the range of token0 and token1 at the end of
+///   the window
+/// * `seconds`: The length of the window in seconds
+///
+/// ## Returns
+///
+/// The APR, or [`Error::InvalidWindow`] if `seconds` is zero
+#[inline]
+pub fn get_fee_apr<TP: TickDataProvider>(
+    position: &Position<TP>,
+    fee_growth_inside_start: (U256, U256),
+    fee_growth_inside_end: (U256, U256),
+    seconds: u64,
+) -> Result<Percent, Error> {
+    if seconds == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let (fees0, fees1) = get_tokens_owed(
+        fee_growth_inside_start.0,
+        fee_growth_inside_start.1,
+        position.liquidity,
+        fee_growth_inside_end.0,
+        fee_growth_inside_end.1,
+    );
+    let price = position.pool.token0_price();
+    let fees = price
+        .quote(&CurrencyAmount::from_raw_amount(
+            position.pool.token0.clone(),
+            fees0.to_big_int(),
+        )?)?
+        .add(&CurrencyAmount::from_raw_amount(
+            position.pool.token1.clone(),
+            fees1.to_big_int(),
+        )?)?;
+    let value = price
+        .quote(&position.amount0()?)?
+        .add(&position.amount1()?)?;
+    if value.quotient().is_zero() {
+        return Err(Error::InsufficientLiquidity);
+    }
+    Ok(Percent::new(
+        fees.quotient() * BigInt::from(SECONDS_PER_YEAR),
+        value.quotient() * BigInt::from(seconds),
+    ))
+}
+
+/// Get the timestamp of a block.
+async fn get_block_timestamp<T, P>(provider: &P, block_id: BlockId) -> Result<u64, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(provider
+        .get_block(block_id, BlockTransactionsKind::Hashes)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?
+        .ok_or(Error::DataNotFound)?
+        .header
+        .timestamp)
+}
+
+/// Get the annualized fee APR of a position from the fee growth inside its range between
+/// `start_block` and `end_block`, over the time elapsed between the timestamps of the blocks.
+///
+/// ## Arguments
+///
+/// * `position`: The position with the current pool state
+/// * `deployment`: The factory address or [`PoolDeployment`] of the pool
+/// * `start_block`: The block at the start of the lookback window
+/// * `end_block`: The block at the end of the lookback window
+/// * `provider`: The alloy provider
+///
+/// ## Returns
+///
+/// The APR, or [`Error::InvalidWindow`] if `end_block` is not later than `start_block`
+#[inline]
+pub async fn get_position_apr<TP, T, P>(
+    position: &Position<TP>,
+    deployment: impl Into<PoolDeployment>,
+    start_block: BlockId,
+    end_block: BlockId,
+    provider: P,
+) -> Result<Percent, Error>
+where
+    TP: TickDataProvider,
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let seconds = get_block_timestamp(&provider, end_block)
+        .await?
+        .checked_sub(get_block_timestamp(&provider, start_block).await?)
+        .ok_or(Error::InvalidWindow)?;
+    let pool = position.pool.deployment_address(&deployment.into());
+    let tick_lower = position.tick_lower.to_i24();
+    let tick_upper = position.tick_upper.to_i24();
+    let fee_growth_inside_start = get_pool_fee_growth_inside(
+        pool,
+        tick_lower,
+        tick_upper,
+        provider.clone(),
+        Some(start_block),
+    )
+    .await?;
+    let fee_growth_inside_end =
+        get_pool_fee_growth_inside(pool, tick_lower, tick_upper, provider, Some(end_block)).await?;
+    get_fee_apr(
+        position,
+        fee_growth_inside_start,
+        fee_growth_inside_end,
+        seconds,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy::eips::BlockNumberOrTag;
+    use alloy_primitives::{address, uint};
+
+    #[test]
+    fn test_get_fee_apr() {
+        let pool = Pool::new(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FeeAmount::MEDIUM,
+            encode_sqrt_ratio_x96(1, 1),
+            0,
+        )
+        .unwrap();
+        let position = Position::new(pool, 1_000_000_000, -60, 60);
+        let value = position.amount0().unwrap().quotient() + position.amount1().unwrap().quotient();
+        // the position earns 1% of its value in token0 over a day
+        let fees = &value / BigInt::from(100);
+        let fee_growth = U256::from_big_int(fees) * Q128 / U256::from(position.liquidity);
+        let apr = get_fee_apr(
+            &position,
+            (U256::ZERO, U256::ZERO),
+            (fee_growth, U256::ZERO),
+            24 * 60 * 60,
+        )
+        .unwrap();
+        assert_eq!(apr.to_fixed(0, None), "365");
+        assert!(matches!(
+            get_fee_apr(
+                &position,
+                (U256::ZERO, U256::ZERO),
+                (fee_growth, U256::ZERO),
+                0
+            ),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_position_apr() {
+        let end_block = BlockId::Number(BlockNumberOrTag::Number(17188000));
+        let position = Position::from_token_id(
+            1,
+            address!("C36442b4a4522E871399CD717aBDD847Ab11FE88"),
+            uint!(4_U256),
+            PROVIDER.clone(),
+            Some(end_block),
+        )
+        .await
+        .unwrap();
+        let start_block = BlockId::Number(BlockNumberOrTag::Number(17188000 - 7200));
+        let apr = get_position_apr(
+            &position,
+            FACTORY_ADDRESS,
+            start_block,
+            end_block,
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(apr >= Percent::default());
+        assert!(apr < Percent::new(1, 1));
+        assert!(matches!(
+            get_position_apr(
+                &position,
+                FACTORY_ADDRESS,
+                end_block,
+                start_block,
+                PROVIDER.clone()
+            )
+            .await,
+            Err(Error::InvalidWindow)
+        ));
+    }
+}
@@ -0,0 +1,377 @@
+//! ## Position History Extension
+//! This module provides functions to reconstruct the lifecycle of a position from the logs of the
+//! nonfungible position manager and to compute the fees it earned over a range of blocks.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    transports::Transport,
+};
+use alloy_primitives::{Address, ChainId, B256, U256};
+use alloy_sol_types::SolEvent;
+use INonfungiblePositionManagerEvents::{Collect, DecreaseLiquidity, IncreaseLiquidity, Transfer};
+
+/// A [`PositionEvent`] with the location of its log.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PositionLog {
+    /// The block of the log
+    pub block_number: u64,
+    /// The index of the log in the block
+    pub log_index: u64,
+    /// The hash of the transaction that emitted the log
+    pub transaction_hash: B256,
+    /// The decoded event
+    pub event: PositionEvent,
+}
+
+/// The events of a position in a range of blocks, see [`get_position_history`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct PositionHistory {
+    /// The token id of the position
+    pub token_id: U256,
+    /// The events of the position, ordered by block number and log index
+    pub logs: Vec<PositionLog>,
+}
+
+impl PositionHistory {
+    /// The net change of liquidity of the position, or [`Error::AddDeltaOverflow`] if it doesn't
+    /// fit in an `i128`.
+    #[inline]
+    pub fn liquidity_delta(&self) -> Result<i128, Error> {
+        self.logs.iter().try_fold(0_i128, |delta, log| {
+            let change = match &log.event {
+                PositionEvent::IncreaseLiquidity(event) => i128::try_from(event.liquidity),
+                PositionEvent::DecreaseLiquidity(event) => {
+                    i128::try_from(event.liquidity).map(|l| -l)
+                }
+                _ => Ok(0),
+            };
+            change
+                .ok()
+                .and_then(|change| delta.checked_add(change))
+                .ok_or(Error::AddDeltaOverflow)
+        })
+    }
+
+    /// The total amounts of token0 and token1 deposited by increasing liquidity.
+    #[inline]
+    #[must_use]
+    pub fn deposited(&self) -> (U256, U256) {
+        self.sum_amounts(|event| match event {
+            PositionEvent::IncreaseLiquidity(event) => Some((event.amount0, event.amount1)),
+            _ => None,
+        })
+    }
+
+    /// The total amounts of token0 and token1 withdrawn by decreasing liquidity, which become
+    /// collectable.
+    #[inline]
+    #[must_use]
+    pub fn withdrawn(&self) -> (U256, U256) {
+        self.sum_amounts(|event| match event {
+            PositionEvent::DecreaseLiquidity(event) => Some((event.amount0, event.amount1)),
+            _ => None,
+        })
+    }
+
+    /// The total amounts of token0 and token1 collected, including both withdrawn liquidity and
+    /// fees.
+    #[inline]
+    #[must_use]
+    pub fn collected(&self) -> (U256, U256) {
+        self.sum_amounts(|event| match event {
+            PositionEvent::Collect(event) => Some((event.amount0, event.amount1)),
+            _ => None,
+        })
+    }
+
+    /// The owner of the position after the last transfer, if any.
+    #[inline]
+    #[must_use]
+    pub fn owner(&self) -> Option<Address> {
+        self.logs.iter().rev().find_map(|log| match &log.event {
+            PositionEvent::Transfer(event) => Some(event.to),
+            _ => None,
+        })
+    }
+
+    fn sum_amounts(
+        &self,
+        amounts: impl Fn(&PositionEvent) -> Option<(U256, U256)>,
+    ) -> (U256, U256) {
+        self.logs.iter().filter_map(|log| amounts(&log.event)).fold(
+            (U256::ZERO, U256::ZERO),
+            |(total0, total1), (amount0, amount1)| (total0 + amount0, total1 + amount1),
+        )
+    }
+}
+
+/// Get the mints, transfers, liquidity changes and collects of a position in a range of blocks.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id
+/// * `from_block`: The first block to search
+/// * `to_block`: Optional last block to search, the latest block by default
+/// * `provider`: The alloy provider
+#[inline]
+pub async fn get_position_history<T, P>(
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    from_block: u64,
+    to_block: Option<u64>,
+    provider: P,
+) -> Result<PositionHistory, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let to_block = to_block.map_or(BlockNumberOrTag::Latest, BlockNumberOrTag::Number);
+    let filter = Filter::new()
+        .address(nonfungible_position_manager)
+        .from_block(from_block)
+        .to_block(to_block);
+    // the token id is the first indexed topic of the position events and the third of `Transfer`
+    let position_filter = filter
+        .clone()
+        .event_signature(vec![
+            IncreaseLiquidity::SIGNATURE_HASH,
+            DecreaseLiquidity::SIGNATURE_HASH,
+            Collect::SIGNATURE_HASH,
+        ])
+        .topic1(B256::from(token_id));
+    let transfer_filter = filter
+        .event_signature(Transfer::SIGNATURE_HASH)
+        .topic3(B256::from(token_id));
+    let mut logs = provider
+        .get_logs(&position_filter)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    logs.extend(
+        provider
+            .get_logs(&transfer_filter)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?,
+    );
+    let mut logs = logs
+        .iter()
+        .map(|log: &Log| {
+            Ok(PositionLog {
+                block_number: log.block_number.unwrap_or_default(),
+                log_index: log.log_index.unwrap_or_default(),
+                transaction_hash: log.transaction_hash.unwrap_or_default(),
+                event: decode_position_event(&log.inner)?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    logs.sort_unstable_by_key(|log| (log.block_number, log.log_index));
+    Ok(PositionHistory { token_id, logs })
+}
+
+/// Get the fees earned by a position between `from_block` and `to_block`.
+///
+/// The fees are the change of the collectable amounts between the two blocks, computed from the
+/// fee growth inside the range of the position, plus the amounts collected in between, minus the
+/// liquidity withdrawn in between.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The token id, which must exist at `from_block`
+/// * `from_block`: The block at the start of the range
+/// * `to_block`: The block at the end of the range, not before `from_block`
+/// * `provider`: The alloy provider
+///
+/// ## Returns
+///
+/// The fees earned in token0 and token1, [`Error::InvalidWindow`] if `to_block` is before
+/// `from_block` or [`Error::InconsistentHistory`] if the amounts of the logs don't add up
+#[inline]
+pub async fn get_fees_earned<T, P>(
+    chain_id: ChainId,
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    from_block: u64,
+    to_block: u64,
+    provider: P,
+) -> Result<(U256, U256), Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    if from_block > to_block {
+        return Err(Error::InvalidWindow);
+    }
+    let (start0, start1) = get_collectable_token_amounts(
+        chain_id,
+        nonfungible_position_manager,
+        token_id,
+        provider.clone(),
+        Some(BlockId::from(from_block)),
+    )
+    .await?;
+    let (end0, end1) = get_collectable_token_amounts(
+        chain_id,
+        nonfungible_position_manager,
+        token_id,
+        provider.clone(),
+        Some(BlockId::from(to_block)),
+    )
+    .await?;
+    let history = get_position_history(
+        nonfungible_position_manager,
+        token_id,
+        from_block + 1,
+        Some(to_block),
+        provider,
+    )
+    .await?;
+    let (collected0, collected1) = history.collected();
+    let (withdrawn0, withdrawn1) = history.withdrawn();
+    Ok((
+        fees_earned(start0, end0, collected0, withdrawn0)?,
+        fees_earned(start1, end1, collected1, withdrawn1)?,
+    ))
+}
+
+/// The fees earned in a token, `end + collected - withdrawn - start`, where the collectable
+/// amounts at the start and the end include the withdrawn liquidity that isn't collected yet.
+fn fees_earned(start: U256, end: U256, collected: U256, withdrawn: U256) -> Result<U256, Error> {
+    end.checked_add(collected)
+        .and_then(|total| total.checked_sub(withdrawn))
+        .and_then(|total| total.checked_sub(start))
+        .ok_or(Error::InconsistentHistory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::PROVIDER;
+    use alloy_primitives::{address, uint};
+
+    #[tokio::test]
+    async fn test_get_position_history() {
+        let npm = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+        let token_id = uint!(4_U256);
+        let to_block = 17188000;
+        // the block the nonfungible position manager was deployed
+        let history =
+            get_position_history(npm, token_id, 12369651, Some(to_block), PROVIDER.clone())
+                .await
+                .unwrap();
+        assert_eq!(history.token_id, token_id);
+        assert!(history
+            .logs
+            .windows(2)
+            .all(|w| (w[0].block_number, w[0].log_index) < (w[1].block_number, w[1].log_index)));
+        assert!(history
+            .logs
+            .iter()
+            .all(|log| log.event.token_id() == token_id));
+        // the position is minted to its first owner
+        let PositionEvent::Transfer(mint) = &history.logs[0].event else {
+            panic!("expected a mint");
+        };
+        assert_eq!(mint.from, Address::ZERO);
+        let position = get_position(
+            1,
+            npm,
+            token_id,
+            PROVIDER.clone(),
+            Some(BlockId::from(to_block)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            history.liquidity_delta().unwrap(),
+            i128::try_from(position.liquidity).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fees_earned() {
+        let amount = |amount: u64| U256::from(amount);
+        // fees accrue between the blocks
+        assert_eq!(
+            fees_earned(amount(100), amount(250), amount(0), amount(0)).unwrap(),
+            amount(150)
+        );
+        // 1000 is withdrawn and 600 of it collected along with 50 of fees, the rest is still owed
+        assert_eq!(
+            fees_earned(amount(100), amount(480), amount(650), amount(1000)).unwrap(),
+            amount(30)
+        );
+        // withdrawn exceeds the end amount plus the collected amount, which doesn't add up
+        assert!(matches!(
+            fees_earned(amount(0), amount(100), amount(0), amount(1000)),
+            Err(Error::InconsistentHistory)
+        ));
+        assert!(matches!(
+            fees_earned(U256::ZERO, U256::MAX, amount(1), U256::ZERO),
+            Err(Error::InconsistentHistory)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_fees_earned() {
+        let npm = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+        let token_id = uint!(4_U256);
+        let to_block = 17188000;
+        // the range starts at the mint of the position, so that it includes all its later events
+        let mint_block =
+            get_position_history(npm, token_id, 12369651, Some(to_block), PROVIDER.clone())
+                .await
+                .unwrap()
+                .logs[0]
+                .block_number;
+        let (fees0, fees1) =
+            get_fees_earned(1, npm, token_id, mint_block, to_block, PROVIDER.clone())
+                .await
+                .unwrap();
+        let history = get_position_history(
+            npm,
+            token_id,
+            mint_block + 1,
+            Some(to_block),
+            PROVIDER.clone(),
+        )
+        .await
+        .unwrap();
+        let (start0, start1) = get_collectable_token_amounts(
+            1,
+            npm,
+            token_id,
+            PROVIDER.clone(),
+            Some(BlockId::from(mint_block)),
+        )
+        .await
+        .unwrap();
+        let (end0, end1) = get_collectable_token_amounts(
+            1,
+            npm,
+            token_id,
+            PROVIDER.clone(),
+            Some(BlockId::from(to_block)),
+        )
+        .await
+        .unwrap();
+        let (collected0, collected1) = history.collected();
+        let (withdrawn0, withdrawn1) = history.withdrawn();
+        assert_eq!(
+            (fees0, fees1),
+            (
+                end0 + collected0 - withdrawn0 - start0,
+                end1 + collected1 - withdrawn1 - start1
+            )
+        );
+        assert!(fees0 > U256::ZERO && fees1 > U256::ZERO);
+        assert!(matches!(
+            get_fees_earned(1, npm, token_id, to_block, mint_block, PROVIDER.clone()).await,
+            Err(Error::InvalidWindow)
+        ));
+    }
+}
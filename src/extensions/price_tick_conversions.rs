@@ -5,7 +5,6 @@
 use crate::prelude::{Error, *};
 use alloc::format;
 use alloy_primitives::{aliases::I24, U160};
-use anyhow::{bail, Result};
 use num_traits::Signed;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -50,7 +49,7 @@ pub fn parse_price<TBase, TQuote>(
     base_token: TBase,
     quote_token: TQuote,
     price: &str,
-) -> Result<Price<TBase, TQuote>>
+) -> Result<Price<TBase, TQuote>, Error>
 where
     TBase: BaseCurrency,
     TQuote: BaseCurrency,
@@ -60,7 +59,7 @@ where
     // at least one digit.
     let re = Regex::new(r"^\d*\.?\d+$").unwrap();
     if !re.is_match(price) {
-        bail!("Invalid price string");
+        return Err(Error::InvalidPrice);
     }
 
     let (whole, fraction) = match price.split_once('.') {
@@ -68,7 +67,8 @@ where
         None => (price, ""),
     };
     let decimals = fraction.len();
-    let without_decimals = BigInt::from_str(&format!("{}{}", whole, fraction))?;
+    let without_decimals =
+        BigInt::from_str(&format!("{}{}", whole, fraction)).map_err(|_| Error::InvalidPrice)?;
     let numerator = without_decimals * BigInt::from(10).pow(quote_token.decimals() as u32);
     let denominator = BigInt::from(10).pow(decimals as u32 + base_token.decimals() as u32);
     Ok(Price::new(base_token, quote_token, denominator, numerator))
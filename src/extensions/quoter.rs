@@ -0,0 +1,316 @@
+//! ## Quoter Extension
+//! This module provides functions to fetch chain-verified quotes from
+//! [QuoterV2](https://github.com/Uniswap/v3-periphery/blob/main/contracts/lens/QuoterV2.sol) via
+//! `eth_call`, which can be used to cross-check the SDK math.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::CallBuilder,
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    transports::Transport,
+};
+use alloy_primitives::{Address, Bytes, U160, U256};
+
+/// The result of a QuoterV2 quote.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuoteResult {
+    /// The amount out for exact input quotes or the amount in for exact output quotes
+    pub amount: U256,
+    /// The sqrt price of each pool in the path after the swap
+    pub sqrt_price_x96_after_list: Vec<U160>,
+    /// The number of initialized ticks crossed in each pool in the path
+    pub initialized_ticks_crossed_list: Vec<u32>,
+    /// The estimated gas the swap consumes
+    pub gas_estimate: U256,
+}
+
+/// Quotes the amount out received for an exact input swap in a single pool.
+///
+/// ## Arguments
+///
+/// * `quoter`: The QuoterV2 address
+/// * `token_in`: The token being swapped in
+/// * `token_out`: The token being swapped out
+/// * `fee`: The fee tier of the pool
+/// * `amount_in`: The amount of `token_in` to swap
+/// * `sqrt_price_limit_x96`: Optional price limit of the swap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub async fn quote_exact_input_single<T, P>(
+    quoter: Address,
+    token_in: Address,
+    token_out: Address,
+    fee: FeeAmount,
+    amount_in: U256,
+    sqrt_price_limit_x96: Option<U160>,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<QuoteResult, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IQuoterV2::quoteExactInputSingleCall {
+        params: IQuoterV2::QuoteExactInputSingleParams {
+            tokenIn: token_in,
+            tokenOut: token_out,
+            amountIn: amount_in,
+            fee: fee.into(),
+            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+        },
+    };
+    let IQuoterV2::quoteExactInputSingleReturn {
+        amountOut,
+        sqrtPriceX96After,
+        initializedTicksCrossed,
+        gasEstimate,
+    } = CallBuilder::new_sol(&provider, &quoter, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+    Ok(QuoteResult {
+        amount: amountOut,
+        sqrt_price_x96_after_list: vec![sqrtPriceX96After],
+        initialized_ticks_crossed_list: vec![initializedTicksCrossed],
+        gas_estimate: gasEstimate,
+    })
+}
+
+/// Quotes the amount in required for an exact output swap in a single pool.
+///
+/// ## Arguments
+///
+/// * `quoter`: The QuoterV2 address
+/// * `token_in`: The token being swapped in
+/// * `token_out`: The token being swapped out
+/// * `fee`: The fee tier of the pool
+/// * `amount_out`: The amount of `token_out` to receive
+/// * `sqrt_price_limit_x96`: Optional price limit of the swap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub async fn quote_exact_output_single<T, P>(
+    quoter: Address,
+    token_in: Address,
+    token_out: Address,
+    fee: FeeAmount,
+    amount_out: U256,
+    sqrt_price_limit_x96: Option<U160>,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<QuoteResult, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IQuoterV2::quoteExactOutputSingleCall {
+        params: IQuoterV2::QuoteExactOutputSingleParams {
+            tokenIn: token_in,
+            tokenOut: token_out,
+            amount: amount_out,
+            fee: fee.into(),
+            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+        },
+    };
+    let IQuoterV2::quoteExactOutputSingleReturn {
+        amountIn,
+        sqrtPriceX96After,
+        initializedTicksCrossed,
+        gasEstimate,
+    } = CallBuilder::new_sol(&provider, &quoter, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+    Ok(QuoteResult {
+        amount: amountIn,
+        sqrt_price_x96_after_list: vec![sqrtPriceX96After],
+        initialized_ticks_crossed_list: vec![initializedTicksCrossed],
+        gas_estimate: gasEstimate,
+    })
+}
+
+/// Quotes the amount out received for an exact input swap along a multi-hop path.
+///
+/// ## Arguments
+///
+/// * `quoter`: The QuoterV2 address
+/// * `path`: The path of the swap, see [`encode_route_to_path`]
+/// * `amount_in`: The amount of the first token to swap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn quote_exact_input<T, P>(
+    quoter: Address,
+    path: Bytes,
+    amount_in: U256,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<QuoteResult, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IQuoterV2::quoteExactInputCall {
+        path,
+        amountIn: amount_in,
+    };
+    let IQuoterV2::quoteExactInputReturn {
+        amountOut,
+        sqrtPriceX96AfterList,
+        initializedTicksCrossedList,
+        gasEstimate,
+    } = CallBuilder::new_sol(&provider, &quoter, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+    Ok(QuoteResult {
+        amount: amountOut,
+        sqrt_price_x96_after_list: sqrtPriceX96AfterList,
+        initialized_ticks_crossed_list: initializedTicksCrossedList,
+        gas_estimate: gasEstimate,
+    })
+}
+
+/// Quotes the amount in required for an exact output swap along a multi-hop path.
+///
+/// ## Arguments
+///
+/// * `quoter`: The QuoterV2 address
+/// * `path`: The reversed path of the swap, see [`encode_route_to_path`]
+/// * `amount_out`: The amount of the last token to receive
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn quote_exact_output<T, P>(
+    quoter: Address,
+    path: Bytes,
+    amount_out: U256,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<QuoteResult, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = IQuoterV2::quoteExactOutputCall {
+        path,
+        amountOut: amount_out,
+    };
+    let IQuoterV2::quoteExactOutputReturn {
+        amountIn,
+        sqrtPriceX96AfterList,
+        initializedTicksCrossedList,
+        gasEstimate,
+    } = CallBuilder::new_sol(&provider, &quoter, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+    Ok(QuoteResult {
+        amount: amountIn,
+        sqrt_price_x96_after_list: sqrtPriceX96AfterList,
+        initialized_ticks_crossed_list: initializedTicksCrossedList,
+        gas_estimate: gasEstimate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::{address, uint};
+
+    const QUOTER_V2: Address = address!("61fFE014bA17989E743c5F6cB21bF9697530B21e");
+    const WBTC: Address = address!("2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599");
+    const WETH: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+    #[tokio::test]
+    async fn test_quote_exact_input_single_matches_simulation() {
+        let amount_in = uint!(100000000_U256);
+        let quote = quote_exact_input_single(
+            QUOTER_V2,
+            WBTC,
+            WETH,
+            FeeAmount::LOW,
+            amount_in,
+            None,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let pool = Pool::<EphemeralTickMapDataProvider>::from_pool_key_with_tick_data_provider(
+            1,
+            FACTORY_ADDRESS,
+            WBTC,
+            WETH,
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let result = pool.simulate_swap_exact_in(true, amount_in, None).unwrap();
+        assert_eq!(quote.amount, result.amount_out);
+        assert_eq!(quote.sqrt_price_x96_after_list, vec![result.sqrt_price_x96_after]);
+        assert!(quote.gas_estimate > U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_quote_exact_output_single() {
+        let quote = quote_exact_output_single(
+            QUOTER_V2,
+            WBTC,
+            WETH,
+            FeeAmount::LOW,
+            uint!(1000000000000000000_U256),
+            None,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let quote_back = quote_exact_input_single(
+            QUOTER_V2,
+            WBTC,
+            WETH,
+            FeeAmount::LOW,
+            quote.amount,
+            None,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(quote_back.amount >= uint!(1000000000000000000_U256));
+    }
+
+    #[tokio::test]
+    async fn test_quote_exact_input() {
+        let usdc = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let path: Bytes = [
+            WBTC.as_slice(),
+            &[0, 1, 0xf4],
+            WETH.as_slice(),
+            &[0, 1, 0xf4],
+            usdc.as_slice(),
+        ]
+        .concat()
+        .into();
+        let quote = quote_exact_input(
+            QUOTER_V2,
+            path,
+            uint!(100000000_U256),
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(quote.amount > U256::ZERO);
+        assert_eq!(quote.sqrt_price_x96_after_list.len(), 2);
+        assert_eq!(quote.initialized_ticks_crossed_list.len(), 2);
+    }
+}
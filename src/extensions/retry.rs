@@ -0,0 +1,196 @@
+//! ## Retry Extension
+//! An opt-in [`RetryPolicy`] with exponential backoff for the RPC calls of the extensions, so that
+//! transient provider failures and rate limits don't abort long batch jobs.
+
+use crate::prelude::Error;
+use alloy::{
+    contract::Error as ContractError,
+    transports::{RpcError, TransportErrorKind},
+};
+use core::{future::Future, time::Duration};
+
+/// How failed RPC calls are retried, with the delay doubling after every failed attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// The delay before the first retry
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts
+    pub max_backoff: Duration,
+    /// Whether to retry when the provider rejects a call for exceeding its rate limit
+    pub retry_on_rate_limit: bool,
+}
+
+impl Default for RetryPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(8),
+            retry_on_rate_limit: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay after the failed attempt `attempt`, counting from zero.
+    #[inline]
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(1_u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Returns true if the call that failed with `error` is worth retrying, i.e. the request
+    /// didn't reach the node or was rate limited. Reverts and decoding errors are never retried.
+    #[inline]
+    #[must_use]
+    pub fn should_retry(&self, error: &Error) -> bool {
+        let Error::ContractError(ContractError::TransportError(error)) = error else {
+            return false;
+        };
+        match error {
+            RpcError::Transport(TransportErrorKind::HttpError(e)) => {
+                e.status != 429 || self.retry_on_rate_limit
+            }
+            RpcError::Transport(_) => true,
+            RpcError::ErrorResp(payload) => {
+                self.retry_on_rate_limit && is_rate_limit(payload.code, &payload.message)
+            }
+            RpcError::DeserError { text, .. } => self.retry_on_rate_limit && is_rate_limit(0, text),
+            _ => false,
+        }
+    }
+
+    /// Makes a call, retrying it according to the policy.
+    ///
+    /// ## Arguments
+    ///
+    /// * `f`: Makes a fresh attempt of the call
+    #[inline]
+    pub async fn retry<F, Fut, R>(&self, mut f: F) -> Result<R, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Err(e) if attempt + 1 < self.max_attempts && self.should_retry(&e) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Makes a call, retrying it according to `policy` if any.
+///
+/// ## Arguments
+///
+/// * `policy`: The retry policy, or `None` to make the call once
+/// * `f`: Makes a fresh attempt of the call
+#[inline]
+pub async fn with_retry<F, Fut, R>(policy: Option<RetryPolicy>, mut f: F) -> Result<R, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<R, Error>>,
+{
+    match policy {
+        Some(policy) => policy.retry(f).await,
+        None => f().await,
+    }
+}
+
+/// Whether an error response is a rate limit, by its JSON-RPC or HTTP code or its message.
+fn is_rate_limit(code: i64, message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    code == 429
+        || code == -32005
+        || message.contains("rate limit")
+        || message.contains("too many requests")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::rpc::json_rpc::ErrorPayload;
+
+    fn error_resp(code: i64, message: &'static str) -> Error {
+        Error::ContractError(ContractError::TransportError(RpcError::ErrorResp(
+            ErrorPayload {
+                code,
+                message: message.into(),
+                data: None,
+            },
+        )))
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            retry_on_rate_limit: true,
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(64), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::default();
+        let rate_limited = error_resp(-32005, "limit exceeded");
+        assert!(policy.should_retry(&rate_limited));
+        assert!(policy.should_retry(&error_resp(-32000, "Too Many Requests")));
+        assert!(!policy.should_retry(&error_resp(3, "execution reverted")));
+        assert!(!policy.should_retry(&Error::InvalidTick(Default::default())));
+        let policy = RetryPolicy {
+            retry_on_rate_limit: false,
+            ..policy
+        };
+        assert!(!policy.should_retry(&rate_limited));
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let mut attempts = 0;
+        let result = policy
+            .retry(|| {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt < 3 {
+                        Err(error_resp(429, "rate limited"))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(result, 3);
+
+        let mut attempts = 0;
+        let result = with_retry(Some(policy), || {
+            attempts += 1;
+            async { Err::<(), _>(error_resp(3, "execution reverted")) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
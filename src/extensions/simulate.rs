@@ -0,0 +1,804 @@
+//! ## Simulation Extension
+//! This module provides functions to simulate minting, increasing liquidity and swapping via
+//! `eth_call` from any address, using state overrides to give the sender the token balances and
+//! allowances the transaction needs, including the mint calldata generated by
+//! [`add_call_parameters`], and to preview the exact amounts of a withdrawal with
+//! [`simulate_remove`].
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::{CallBuilder, Error as ContractError},
+    eips::{BlockId, BlockNumberOrTag},
+    network::TransactionBuilder,
+    providers::Provider,
+    rpc::types::{state::StateOverride, TransactionRequest},
+    transports::Transport,
+};
+use alloy_primitives::{address, keccak256, Address, B256, U160, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use futures_util::future::try_join_all;
+use uniswap_lens::bindings::ierc20metadata::IERC20Metadata;
+use uniswap_sdk_core::prelude::*;
+
+/// The number of storage slots searched for the `balanceOf` and `allowance` mappings of a token.
+pub const MAX_PROBED_SLOT: u64 = 32;
+
+/// An address unlikely to hold any token, used to locate the mappings of a token.
+const PROBE_ADDRESS: Address = address!("00000000000000000000000000000000DeaDBeef");
+
+/// The storage slots of the `balanceOf` and `allowance` mappings of an ERC20 token.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Erc20Slots {
+    /// The slot of the `balanceOf` mapping
+    pub balance: U256,
+    /// The slot of the `allowance` mapping
+    pub allowance: U256,
+}
+
+/// Computes the storage slot of the balance of `owner` in a Solidity `balanceOf` mapping.
+///
+/// ## Arguments
+///
+/// * `owner`: The token holder
+/// * `slot`: The slot of the `balanceOf` mapping
+#[inline]
+#[must_use]
+pub fn erc20_balance_slot(owner: Address, slot: U256) -> B256 {
+    keccak256((owner, slot).abi_encode())
+}
+
+/// Computes the storage slot of the allowance of `spender` over the tokens of `owner` in a
+/// Solidity `allowance` mapping.
+///
+/// ## Arguments
+///
+/// * `owner`: The token holder
+/// * `spender`: The approved spender
+/// * `slot`: The slot of the `allowance` mapping
+#[inline]
+#[must_use]
+pub fn erc20_allowance_slot(owner: Address, spender: Address, slot: U256) -> B256 {
+    keccak256((spender, erc20_balance_slot(owner, slot)).abi_encode())
+}
+
+/// Adds state overrides setting the balance of `owner` and its allowance to `spender` of a token.
+///
+/// ## Arguments
+///
+/// * `overrides`: The state overrides to add to
+/// * `token`: The token address
+/// * `slots`: The storage slots of the mappings of the token
+/// * `owner`: The token holder
+/// * `spender`: The approved spender
+/// * `amount`: The balance and allowance to set
+#[inline]
+pub fn add_erc20_overrides(
+    overrides: &mut StateOverride,
+    token: Address,
+    slots: Erc20Slots,
+    owner: Address,
+    spender: Address,
+    amount: U256,
+) {
+    let state_diff = overrides
+        .entry(token)
+        .or_default()
+        .state_diff
+        .get_or_insert_with(Default::default);
+    state_diff.insert(erc20_balance_slot(owner, slots.balance), amount.into());
+    state_diff.insert(
+        erc20_allowance_slot(owner, spender, slots.allowance),
+        amount.into(),
+    );
+}
+
+/// Finds the storage slots of the `balanceOf` and `allowance` mappings of a token by overriding
+/// each candidate slot and reading the value back.
+///
+/// Only tokens storing the mappings at one of the first [`MAX_PROBED_SLOT`] slots with the
+/// Solidity layout are supported.
+///
+/// ## Arguments
+///
+/// * `token`: The token address
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn find_erc20_slots<T, P>(
+    token: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Erc20Slots, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+    let contract = IERC20Metadata::new(token, provider);
+    let contract = &contract;
+    let sentinel = U256::from(0xdead_beef_u64);
+    let probe = |slot: u64, balance: bool| async move {
+        let slot = U256::from(slot);
+        let key = if balance {
+            erc20_balance_slot(PROBE_ADDRESS, slot)
+        } else {
+            erc20_allowance_slot(PROBE_ADDRESS, PROBE_ADDRESS, slot)
+        };
+        let mut overrides = StateOverride::default();
+        overrides
+            .entry(token)
+            .or_default()
+            .state_diff
+            .get_or_insert_with(Default::default)
+            .insert(key, sentinel.into());
+        let value = if balance {
+            contract
+                .balanceOf(PROBE_ADDRESS)
+                .block(block_id)
+                .state(overrides)
+                .call()
+                .await
+                .map(|r| r._0)
+        } else {
+            contract
+                .allowance(PROBE_ADDRESS, PROBE_ADDRESS)
+                .block(block_id)
+                .state(overrides)
+                .call()
+                .await
+                .map(|r| r._0)
+        };
+        // a reverting call means the slot is not the mapping
+        Ok::<_, Error>((value.is_ok_and(|value| value == sentinel)).then_some(slot))
+    };
+    let find = |balance: bool| async move {
+        try_join_all((0..MAX_PROBED_SLOT).map(|slot| probe(slot, balance)))
+            .await?
+            .into_iter()
+            .flatten()
+            .next()
+            .ok_or(Error::Erc20SlotsNotFound(token))
+    };
+    let (balance, allowance) = futures_util::try_join!(find(true), find(false))?;
+    Ok(Erc20Slots { balance, allowance })
+}
+
+/// Builds the state overrides funding `owner` with the given amounts of the tokens and approving
+/// `spender` to spend them.
+async fn fund_overrides<T, P>(
+    tokens: &[(Address, U256)],
+    owner: Address,
+    spender: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<StateOverride, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let slots = try_join_all(
+        tokens
+            .iter()
+            .map(|(token, _)| find_erc20_slots(*token, provider.clone(), block_id)),
+    )
+    .await?;
+    let mut overrides = StateOverride::default();
+    for ((token, amount), slots) in tokens.iter().zip(slots) {
+        add_erc20_overrides(&mut overrides, *token, slots, owner, spender, *amount);
+    }
+    Ok(overrides)
+}
+
+/// Simulates minting a position from any address, returning the token id, liquidity and amounts
+/// the mint would produce.
+///
+/// The sender is funded with and approves exactly the [`Position::mint_amounts`] of the position.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `position`: The position to mint
+/// * `from`: The sender and recipient of the position
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn simulate_mint<T, P, TP>(
+    nonfungible_position_manager: Address,
+    position: &Position<TP>,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<INonfungiblePositionManager::mintReturn, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+    TP: TickDataProvider,
+{
+    let MintAmounts { amount0, amount1 } = position.mint_amounts()?;
+    let token0 = position.pool.token0.address();
+    let token1 = position.pool.token1.address();
+    let overrides = fund_overrides(
+        &[(token0, amount0), (token1, amount1)],
+        from,
+        nonfungible_position_manager,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let call = INonfungiblePositionManager::mintCall {
+        params: INonfungiblePositionManager::MintParams {
+            token0,
+            token1,
+            fee: position.pool.fee.into(),
+            tickLower: position.tick_lower.to_i24(),
+            tickUpper: position.tick_upper.to_i24(),
+            amount0Desired: amount0,
+            amount1Desired: amount1,
+            amount0Min: U256::ZERO,
+            amount1Min: U256::ZERO,
+            recipient: from,
+            deadline: U256::MAX,
+        },
+    };
+    Ok(
+        CallBuilder::new_sol(&provider, &nonfungible_position_manager, &call)
+            .from(from)
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .state(overrides)
+            .call()
+            .await?,
+    )
+}
+
+/// Simulates the calldata generated by [`add_call_parameters`] to mint a position from any address,
+/// returning the token id, liquidity and amounts the mint would produce.
+///
+/// The sender is funded with and approves the [`Position::mint_amounts`] of the position, and is
+/// given the ether to send when minting with native ether, so the slippage, deadline, pool creation
+/// and refund of the options are simulated exactly as they would be sent.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `position`: The position to mint
+/// * `options`: The options to generate the calldata with, which must mint a new position, or
+///   [`Error::InvalidOptions`] is returned
+/// * `from`: The sender
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn simulate_mint_with_options<T, P, TP>(
+    nonfungible_position_manager: Address,
+    position: &mut Position<TP>,
+    options: AddLiquidityOptions,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<INonfungiblePositionManager::mintReturn, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+    TP: TickDataProvider,
+{
+    let AddLiquiditySpecificOptions::Mint(mint_options) = options.specific_opts else {
+        return Err(Error::InvalidOptions);
+    };
+    // the mint follows the pool creation and the permits in the multicall
+    let mint_index = usize::from(mint_options.create_pool)
+        + usize::from(options.token0_permit.is_some())
+        + usize::from(options.token1_permit.is_some());
+    let params = add_call_parameters(position, options)?;
+    let MintAmounts { amount0, amount1 } = position.mint_amounts_cached()?;
+    let mut overrides = fund_overrides(
+        &[
+            (position.pool.token0.address(), amount0),
+            (position.pool.token1.address(), amount1),
+        ],
+        from,
+        nonfungible_position_manager,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    if !params.value.is_zero() {
+        overrides.entry(from).or_default().balance = Some(params.value);
+    }
+    let tx = TransactionRequest::default()
+        .with_from(from)
+        .with_to(nonfungible_position_manager)
+        .with_input(params.calldata.clone())
+        .with_value(params.value);
+    let output = provider
+        .call(&tx)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .overrides(&overrides)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?;
+    let output = if params
+        .calldata
+        .starts_with(&IMulticall::multicallCall::SELECTOR)
+    {
+        IMulticall::multicallCall::abi_decode_returns(&output, true)
+            .map_err(|e| Error::ContractError(ContractError::AbiError(e.into())))?
+            .results
+            .swap_remove(mint_index)
+    } else {
+        output
+    };
+    INonfungiblePositionManager::mintCall::abi_decode_returns(&output, true)
+        .map_err(|e| Error::ContractError(ContractError::AbiError(e.into())))
+}
+
+/// Simulates adding the liquidity of `position` to an existing position from any address,
+/// returning the liquidity and amounts the transaction would produce.
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The id of the position to increase
+/// * `position`: The liquidity to add, in the range of the existing position
+/// * `from`: The sender
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn simulate_increase_liquidity<T, P, TP>(
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    position: &Position<TP>,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<INonfungiblePositionManager::increaseLiquidityReturn, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+    TP: TickDataProvider,
+{
+    let MintAmounts { amount0, amount1 } = position.mint_amounts()?;
+    let overrides = fund_overrides(
+        &[
+            (position.pool.token0.address(), amount0),
+            (position.pool.token1.address(), amount1),
+        ],
+        from,
+        nonfungible_position_manager,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let call = INonfungiblePositionManager::increaseLiquidityCall {
+        params: INonfungiblePositionManager::IncreaseLiquidityParams {
+            tokenId: token_id,
+            amount0Desired: amount0,
+            amount1Desired: amount1,
+            amount0Min: U256::ZERO,
+            amount1Min: U256::ZERO,
+            deadline: U256::MAX,
+        },
+    };
+    Ok(
+        CallBuilder::new_sol(&provider, &nonfungible_position_manager, &call)
+            .from(from)
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .state(overrides)
+            .call()
+            .await?,
+    )
+}
+
+/// The amounts a withdrawal from a position would produce, as simulated by [`simulate_remove`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RemoveAmounts {
+    /// The liquidity removed from the position
+    pub liquidity: u128,
+    /// The amount of token0 the removed liquidity is worth
+    pub amount0: U256,
+    /// The amount of token1 the removed liquidity is worth
+    pub amount1: U256,
+    /// The amount of token0 received, including the fees owed to the position
+    pub collected0: U256,
+    /// The amount of token1 received, including the fees owed to the position
+    pub collected1: U256,
+}
+
+/// Simulates decreasing the liquidity of a position by a percentage and collecting the tokens in
+/// one multicall, returning the exact amounts the owner would receive after rounding.
+///
+/// The removed liquidity is rounded down from the percentage as in [`remove_call_parameters`].
+///
+/// ## Arguments
+///
+/// * `nonfungible_position_manager`: The nonfungible position manager address
+/// * `token_id`: The id of the position
+/// * `position`: The position, whose liquidity is the current liquidity of `token_id`
+/// * `liquidity_percentage`: The percentage of the liquidity of the position to remove
+/// * `from`: The owner or an approved operator of the position, and the recipient of the tokens
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn simulate_remove<T, P, TP>(
+    nonfungible_position_manager: Address,
+    token_id: U256,
+    position: &Position<TP>,
+    liquidity_percentage: &Percent,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<RemoveAmounts, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+    TP: TickDataProvider,
+{
+    let liquidity = (liquidity_percentage.clone() * Percent::new(position.liquidity, 1))
+        .quotient()
+        .to_u128()
+        .ok_or(Error::InvalidAmount)?;
+    if liquidity == 0 {
+        return Err(Error::ZeroLiquidity);
+    }
+    let call = IMulticall::multicallCall {
+        data: vec![
+            INonfungiblePositionManager::decreaseLiquidityCall {
+                params: INonfungiblePositionManager::DecreaseLiquidityParams {
+                    tokenId: token_id,
+                    liquidity,
+                    amount0Min: U256::ZERO,
+                    amount1Min: U256::ZERO,
+                    deadline: U256::MAX,
+                },
+            }
+            .abi_encode()
+            .into(),
+            INonfungiblePositionManager::collectCall {
+                params: INonfungiblePositionManager::CollectParams {
+                    tokenId: token_id,
+                    recipient: from,
+                    amount0Max: u128::MAX,
+                    amount1Max: u128::MAX,
+                },
+            }
+            .abi_encode()
+            .into(),
+        ],
+    };
+    let results = CallBuilder::new_sol(&provider, &nonfungible_position_manager, &call)
+        .from(from)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?
+        .results;
+    let decreased =
+        INonfungiblePositionManager::decreaseLiquidityCall::abi_decode_returns(&results[0], true)
+            .map_err(|e| Error::ContractError(ContractError::AbiError(e.into())))?;
+    let collected = INonfungiblePositionManager::collectCall::abi_decode_returns(&results[1], true)
+        .map_err(|e| Error::ContractError(ContractError::AbiError(e.into())))?;
+    Ok(RemoveAmounts {
+        liquidity,
+        amount0: decreased.amount0,
+        amount1: decreased.amount1,
+        collected0: collected.amount0,
+        collected1: collected.amount1,
+    })
+}
+
+/// Simulates an exact input swap in a single pool through
+/// [SwapRouter](https://github.com/Uniswap/v3-periphery/blob/main/contracts/SwapRouter.sol) from
+/// any address, returning the amount out.
+///
+/// ## Arguments
+///
+/// * `swap_router`: The SwapRouter address
+/// * `token_in`: The token being swapped in
+/// * `token_out`: The token being swapped out
+/// * `fee`: The fee tier of the pool
+/// * `amount_in`: The amount of `token_in` to swap
+/// * `sqrt_price_limit_x96`: Optional price limit of the swap
+/// * `from`: The sender and recipient of the swap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub async fn simulate_exact_input_single<T, P>(
+    swap_router: Address,
+    token_in: Address,
+    token_out: Address,
+    fee: FeeAmount,
+    amount_in: U256,
+    sqrt_price_limit_x96: Option<U160>,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<U256, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let overrides = fund_overrides(
+        &[(token_in, amount_in)],
+        from,
+        swap_router,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let call = ISwapRouter::exactInputSingleCall {
+        params: ISwapRouter::ExactInputSingleParams {
+            tokenIn: token_in,
+            tokenOut: token_out,
+            fee: fee.into(),
+            recipient: from,
+            deadline: U256::MAX,
+            amountIn: amount_in,
+            amountOutMinimum: U256::ZERO,
+            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+        },
+    };
+    Ok(CallBuilder::new_sol(&provider, &swap_router, &call)
+        .from(from)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .state(overrides)
+        .call()
+        .await?
+        .amountOut)
+}
+
+/// Simulates an exact output swap in a single pool through
+/// [SwapRouter](https://github.com/Uniswap/v3-periphery/blob/main/contracts/SwapRouter.sol) from
+/// any address, returning the amount in.
+///
+/// ## Arguments
+///
+/// * `swap_router`: The SwapRouter address
+/// * `token_in`: The token being swapped in
+/// * `token_out`: The token being swapped out
+/// * `fee`: The fee tier of the pool
+/// * `amount_out`: The amount of `token_out` to receive
+/// * `amount_in_maximum`: The maximum amount of `token_in` the sender is funded with
+/// * `sqrt_price_limit_x96`: Optional price limit of the swap
+/// * `from`: The sender and recipient of the swap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub async fn simulate_exact_output_single<T, P>(
+    swap_router: Address,
+    token_in: Address,
+    token_out: Address,
+    fee: FeeAmount,
+    amount_out: U256,
+    amount_in_maximum: U256,
+    sqrt_price_limit_x96: Option<U160>,
+    from: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<U256, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let overrides = fund_overrides(
+        &[(token_in, amount_in_maximum)],
+        from,
+        swap_router,
+        provider.clone(),
+        block_id,
+    )
+    .await?;
+    let call = ISwapRouter::exactOutputSingleCall {
+        params: ISwapRouter::ExactOutputSingleParams {
+            tokenIn: token_in,
+            tokenOut: token_out,
+            fee: fee.into(),
+            recipient: from,
+            deadline: U256::MAX,
+            amountOut: amount_out,
+            amountInMaximum: amount_in_maximum,
+            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+        },
+    };
+    Ok(CallBuilder::new_sol(&provider, &swap_router, &call)
+        .from(from)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .state(overrides)
+        .call()
+        .await?
+        .amountIn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::uint;
+
+    const NPM: Address = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+    const SWAP_ROUTER: Address = address!("E592427A0AEce92De3Edee1F18E0157C05861564");
+    const FROM: Address = address!("1111111111111111111111111111111111111111");
+
+    #[tokio::test]
+    async fn test_find_erc20_slots() {
+        let slots = find_erc20_slots(WETH.address(), PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            slots,
+            Erc20Slots {
+                balance: U256::from(3),
+                allowance: U256::from(4),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulate_mint() {
+        let pool = Pool::from_pool_key(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let tick_spacing = pool.tick_spacing();
+        let tick_lower = nearest_usable_tick(pool.tick_current, tick_spacing) - tick_spacing * 10;
+        let tick_upper = tick_lower + tick_spacing * 20;
+        let position = Position::new(pool, 10_u128.pow(15), tick_lower, tick_upper);
+        let MintAmounts { amount0, amount1 } = position.mint_amounts().unwrap();
+        let result = simulate_mint(NPM, &position, FROM, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(result.tokenId > U256::ZERO);
+        assert!(result.liquidity > 0 && result.liquidity <= position.liquidity);
+        assert!(result.amount0 <= amount0 && result.amount1 <= amount1);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_mint_with_options() {
+        let pool = Pool::from_pool_key(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let tick_spacing = pool.tick_spacing();
+        let tick_lower = nearest_usable_tick(pool.tick_current, tick_spacing) - tick_spacing * 10;
+        let tick_upper = tick_lower + tick_spacing * 20;
+        let mut position = Position::new(pool, 10_u128.pow(15), tick_lower, tick_upper);
+        let expected = simulate_mint(NPM, &position, FROM, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        let options = AddLiquidityOptions {
+            slippage_tolerance: Percent::new(5, 1000),
+            deadline: U256::MAX,
+            use_native: None,
+            token0_permit: None,
+            token1_permit: None,
+            specific_opts: AddLiquiditySpecificOptions::Mint(MintSpecificOptions {
+                recipient: FROM,
+                create_pool: false,
+            }),
+        };
+        let result = simulate_mint_with_options(
+            NPM,
+            &mut position,
+            options.clone(),
+            FROM,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.tokenId, expected.tokenId);
+        assert_eq!(result.liquidity, expected.liquidity);
+        assert_eq!(result.amount0, expected.amount0);
+        assert_eq!(result.amount1, expected.amount1);
+
+        // minting with native ether goes through a multicall with a refund
+        let result = simulate_mint_with_options(
+            NPM,
+            &mut position,
+            AddLiquidityOptions {
+                use_native: Some(ETHER.clone()),
+                ..options
+            },
+            FROM,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.liquidity, expected.liquidity);
+        assert_eq!(result.amount0, expected.amount0);
+        assert_eq!(result.amount1, expected.amount1);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_remove() {
+        let token_id = uint!(4_U256);
+        let position = get_position(1, NPM, token_id, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        let owner = get_nonfungible_position_manager_contract(NPM, PROVIDER.clone())
+            .ownerOf(token_id)
+            .block(BLOCK_ID.unwrap())
+            .call()
+            .await
+            .unwrap()
+            .owner;
+        let (tokens_owed0, tokens_owed1) =
+            get_collectable_token_amounts(1, NPM, token_id, PROVIDER.clone(), *BLOCK_ID)
+                .await
+                .unwrap();
+        let result = simulate_remove(
+            NPM,
+            token_id,
+            &position,
+            &Percent::new(1, 1),
+            owner,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.liquidity, position.liquidity);
+        assert_eq!(result.collected0, result.amount0 + tokens_owed0);
+        assert_eq!(result.collected1, result.amount1 + tokens_owed1);
+        // the amounts are rounded down
+        assert!(result.amount0.to_big_int() <= position.amount0().unwrap().quotient());
+        assert!(result.amount1.to_big_int() <= position.amount1().unwrap().quotient());
+
+        let half = simulate_remove(
+            NPM,
+            token_id,
+            &position,
+            &Percent::new(1, 2),
+            owner,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(half.liquidity, position.liquidity / 2);
+        assert!(half.amount0 <= result.amount0 && half.amount1 <= result.amount1);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_exact_input_single() {
+        let amount_in = uint!(1000000000_U256);
+        let amount_out = simulate_exact_input_single(
+            SWAP_ROUTER,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            amount_in,
+            None,
+            FROM,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let quote = quote_exact_input_single(
+            address!("61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            amount_in,
+            None,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(amount_out, quote.amount);
+    }
+}
@@ -0,0 +1,325 @@
+//! ## Storage Extension
+//! This module provides functions to read the state of a pool directly from its storage slots with
+//! `eth_getStorageAt`, which is cheaper than contract calls when only a few values are needed.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    transports::Transport,
+};
+use alloy_primitives::{
+    aliases::{I24, U24},
+    keccak256, Address, U160, U256,
+};
+use alloy_sol_types::SolValue;
+use core::future::IntoFuture;
+use futures_util::{future::try_join_all, stream, StreamExt, TryFutureExt, TryStreamExt};
+
+/// The storage slot of `slot0` of a pool.
+pub const SLOT0_SLOT: U256 = U256::ZERO;
+
+/// The storage slot of `feeGrowthGlobal0X128` of a pool.
+pub const FEE_GROWTH_GLOBAL0_SLOT: U256 = U256::from_limbs([1, 0, 0, 0]);
+
+/// The storage slot of `feeGrowthGlobal1X128` of a pool.
+pub const FEE_GROWTH_GLOBAL1_SLOT: U256 = U256::from_limbs([2, 0, 0, 0]);
+
+/// The storage slot of `liquidity` of a pool.
+pub const LIQUIDITY_SLOT: U256 = U256::from_limbs([4, 0, 0, 0]);
+
+/// The storage slot of the `tickBitmap` mapping of a pool.
+pub const TICK_BITMAP_SLOT: U256 = U256::from_limbs([6, 0, 0, 0]);
+
+/// Computes the storage slot of a word of the `tickBitmap` of a pool.
+///
+/// ## Arguments
+///
+/// * `word_pos`: The position of the word
+#[inline]
+#[must_use]
+pub fn tick_bitmap_slot(word_pos: i16) -> U256 {
+    keccak256((word_pos, TICK_BITMAP_SLOT).abi_encode()).into()
+}
+
+/// Get the nonzero words of the tick bitmap of a pool covering a tick range, reading the words
+/// from storage in chunks of `options.chunk_size` concurrent `eth_getStorageAt` requests with at
+/// most `options.max_concurrency` chunks in flight.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `tick_spacing`: The tick spacing of the pool
+/// * `options`: The chunking, concurrency and retries of the requests
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_tick_bitmap_via_storage<I, T, P>(
+    pool: Address,
+    tick_lower: I,
+    tick_upper: I,
+    tick_spacing: I,
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<TickBitMap<I>, Error>
+where
+    I: TickIndex,
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    if tick_lower > tick_upper {
+        return Err(Error::InvalidRange);
+    }
+    options.validate()?;
+    let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+    let word_pos = |tick: I| -> Result<i16, Error> {
+        let (word_pos, _) = tick.compress(tick_spacing).position();
+        word_pos
+            .try_into()
+            .ok()
+            .and_then(|word_pos: i32| i16::try_from(word_pos).ok())
+            .ok_or(Error::InvalidTick(tick.to_i24()))
+    };
+    let word_positions: Vec<i16> = (word_pos(tick_lower)?..=word_pos(tick_upper)?).collect();
+    let provider = &provider;
+    let chunks: Vec<Vec<(i16, U256)>> =
+        stream::iter(word_positions.chunks(options.chunk_size).map(|chunk| {
+            with_retry(options.retry, move || {
+                try_join_all(chunk.iter().map(|&word_pos| {
+                    provider
+                        .get_storage_at(pool, tick_bitmap_slot(word_pos))
+                        .block_id(block_id)
+                        .into_future()
+                        .map_ok(move |word| (word_pos, word))
+                }))
+                .map_err(|e| Error::ContractError(e.into()))
+            })
+        }))
+        .buffered(options.max_concurrency)
+        .try_collect()
+        .await?;
+    chunks
+        .into_iter()
+        .flatten()
+        .filter(|(_, word)| !word.is_zero())
+        .map(|(word_pos, word)| {
+            let word_pos = I::try_from(i32::from(word_pos)).map_err(|_| Error::InvalidRange)?;
+            Ok((word_pos, word))
+        })
+        .collect()
+}
+
+/// The global state of a pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolState {
+    /// The current price of the pool as a sqrt(token1/token0) Q64.96 value
+    pub sqrt_price_x96: U160,
+    /// The current tick of the pool
+    pub tick: I24,
+    /// The index of the last written observation
+    pub observation_index: u16,
+    /// The current maximum number of observations stored
+    pub observation_cardinality: u16,
+    /// The next maximum number of observations to store
+    pub observation_cardinality_next: u16,
+    /// The protocol fee of both tokens, 4 bits each
+    pub fee_protocol: u8,
+    /// Whether the pool is unlocked, i.e. not in the middle of a call
+    pub unlocked: bool,
+    /// The current in range liquidity
+    pub liquidity: u128,
+    /// The fee growth of token0 per unit of liquidity over the life of the pool
+    pub fee_growth_global0_x128: U256,
+    /// The fee growth of token1 per unit of liquidity over the life of the pool
+    pub fee_growth_global1_x128: U256,
+}
+
+impl PoolState {
+    /// Decodes the state of a pool from the raw values of its storage slots.
+    ///
+    /// ## Arguments
+    ///
+    /// * `slot0`: The packed `slot0`
+    /// * `liquidity`: The value of the `liquidity` slot
+    /// * `fee_growth_global0_x128`: The value of the `feeGrowthGlobal0X128` slot
+    /// * `fee_growth_global1_x128`: The value of the `feeGrowthGlobal1X128` slot
+    #[inline]
+    #[must_use]
+    pub fn from_slots(
+        slot0: U256,
+        liquidity: U256,
+        fee_growth_global0_x128: U256,
+        fee_growth_global1_x128: U256,
+    ) -> Self {
+        // the fields of slot0 are packed from the lowest bits in declaration order
+        let bits = |offset: usize, width: usize| -> U256 {
+            (slot0 >> offset) & ((U256::from(1) << width) - U256::from(1))
+        };
+        Self {
+            sqrt_price_x96: bits(0, 160).to(),
+            tick: I24::from_raw(bits(160, 24).to::<U24>()),
+            observation_index: bits(184, 16).to(),
+            observation_cardinality: bits(200, 16).to(),
+            observation_cardinality_next: bits(216, 16).to(),
+            fee_protocol: bits(232, 8).to(),
+            unlocked: !bits(240, 8).is_zero(),
+            liquidity: (liquidity & U256::from(u128::MAX)).to(),
+            fee_growth_global0_x128,
+            fee_growth_global1_x128,
+        }
+    }
+}
+
+/// Get the `slot0`, liquidity and global fee growth of a pool from its storage slots, read with
+/// concurrent `eth_getStorageAt` requests.
+///
+/// The block is resolved with [`pin_block_id`] before the requests are sent, so that all slots are
+/// read at the same block even if `block_id` is `None` or a tag such as `latest`.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool address
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_pool_state_via_storage<T, P>(
+    pool: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<PoolState, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let block_id = pin_block_id(&provider, block_id).await?;
+    let get_storage_at = |slot: U256| {
+        provider
+            .get_storage_at(pool, slot)
+            .block_id(block_id)
+            .into_future()
+    };
+    let (slot0, liquidity, fee_growth_global0_x128, fee_growth_global1_x128) =
+        futures_util::try_join!(
+            get_storage_at(SLOT0_SLOT),
+            get_storage_at(LIQUIDITY_SLOT),
+            get_storage_at(FEE_GROWTH_GLOBAL0_SLOT),
+            get_storage_at(FEE_GROWTH_GLOBAL1_SLOT)
+        )
+        .map_err(|e| Error::ContractError(e.into()))?;
+    Ok(PoolState::from_slots(
+        slot0,
+        liquidity,
+        fee_growth_global0_x128,
+        fee_growth_global1_x128,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use uniswap_lens::bindings::iuniswapv3pool::IUniswapV3Pool::IUniswapV3PoolInstance;
+
+    #[test]
+    fn test_tick_bitmap_slot() {
+        // negative keys are sign extended
+        assert_ne!(tick_bitmap_slot(-1), tick_bitmap_slot(1));
+        assert_eq!(
+            tick_bitmap_slot(-1),
+            U256::from_be_bytes(
+                keccak256([[0xff; 32], TICK_BITMAP_SLOT.to_be_bytes::<32>()].concat()).0
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_tick_bitmap_via_storage() {
+        let pool = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+        let tick_spacing = I24::try_from(10).unwrap();
+        let tick_lower = I24::try_from(200000).unwrap();
+        let tick_upper = I24::try_from(210000).unwrap();
+        let bitmap = get_tick_bitmap_via_storage(
+            pool,
+            tick_lower,
+            tick_upper,
+            tick_spacing,
+            BatchOptions {
+                chunk_size: 16,
+                max_concurrency: 2,
+                ..Default::default()
+            },
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(!bitmap.is_empty());
+        let tick_map = EphemeralTickMapDataProvider::new(
+            pool,
+            PROVIDER.clone(),
+            Some(tick_lower),
+            Some(tick_upper),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        for (word_pos, word) in &bitmap {
+            // the words at the edges of the range may contain ticks outside the range
+            if let Some(expected) = tick_map.bitmap.get(word_pos) {
+                assert_eq!(word & expected, *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_state_from_slots() {
+        let tick = I24::try_from(-201000).unwrap();
+        let sqrt_price_x96 = get_sqrt_ratio_at_tick(tick).unwrap();
+        let slot0 = U256::from(sqrt_price_x96)
+            | U256::from(tick.into_raw()) << 160
+            | U256::from(7) << 184
+            | U256::from(100) << 200
+            | U256::from(200) << 216
+            | U256::from(0x44) << 232
+            | U256::from(1) << 240;
+        let state = PoolState::from_slots(slot0, U256::from(12345), U256::from(1), U256::from(2));
+        assert_eq!(state.sqrt_price_x96, sqrt_price_x96);
+        assert_eq!(state.tick, tick);
+        assert_eq!(state.observation_index, 7);
+        assert_eq!(state.observation_cardinality, 100);
+        assert_eq!(state.observation_cardinality_next, 200);
+        assert_eq!(state.fee_protocol, 0x44);
+        assert!(state.unlocked);
+        assert_eq!(state.liquidity, 12345);
+        assert_eq!(state.fee_growth_global0_x128, U256::from(1));
+        assert_eq!(state.fee_growth_global1_x128, U256::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_get_pool_state_via_storage() {
+        let pool = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+        let state = get_pool_state_via_storage(pool, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        let contract = IUniswapV3PoolInstance::new(pool, PROVIDER.clone());
+        let block_id = BLOCK_ID.unwrap();
+        let slot0 = contract.slot0().block(block_id).call().await.unwrap();
+        assert_eq!(state.sqrt_price_x96, slot0.sqrtPriceX96);
+        assert_eq!(state.tick, slot0.tick);
+        assert_eq!(state.observation_index, slot0.observationIndex);
+        assert_eq!(state.unlocked, slot0.unlocked);
+        let liquidity = contract.liquidity().block(block_id).call().await.unwrap();
+        assert_eq!(state.liquidity, liquidity._0);
+        let fee_growth = contract
+            .feeGrowthGlobal0X128()
+            .block(block_id)
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(state.fee_growth_global0_x128, fee_growth._0);
+    }
+}
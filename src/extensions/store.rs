@@ -0,0 +1,224 @@
+//! ## Store Extension
+//! A [`PoolStore`] persisting the state and ticks of pools in an embedded [sled](https://sled.rs)
+//! database, keyed by chain, pool and block, so that indexers can resume and historical swaps can
+//! be simulated across process restarts without fetching the ticks from chain again. The restored
+//! pools are backed by a [`TickListDataProvider`].
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{Address, ChainId};
+use std::path::Path;
+
+/// The length of the key of a snapshot: the chain id, the pool address and the block number.
+const KEY_LEN: usize = 8 + 20 + 8;
+
+/// A persistent store of pool snapshots, keyed by `(chain_id, pool, block)`.
+///
+/// Keys are big-endian, so the snapshots of a pool are ordered by block and the latest snapshot
+/// at or before any block is found with a single range scan.
+#[derive(Clone, Debug)]
+pub struct PoolStore {
+    db: sled::Db,
+}
+
+fn key(chain_id: ChainId, pool: Address, block: u64) -> [u8; KEY_LEN] {
+    let mut key = [0; KEY_LEN];
+    key[..8].copy_from_slice(&chain_id.to_be_bytes());
+    key[8..28].copy_from_slice(pool.as_slice());
+    key[28..].copy_from_slice(&block.to_be_bytes());
+    key
+}
+
+fn decode_entry<I: TickIndex>(
+    (key, value): (sled::IVec, sled::IVec),
+) -> Result<(u64, Pool<TickListDataProvider<I>>), Error> {
+    let block = u64::from_be_bytes(key[28..].try_into().map_err(|_| Error::InvalidSnapshot)?);
+    Ok((block, Pool::restore(&value)?))
+}
+
+impl PoolStore {
+    /// Opens the store at `path`, creating it if it doesn't exist.
+    ///
+    /// ## Arguments
+    ///
+    /// * `path`: The directory of the database
+    #[inline]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+
+    /// Opens a store that is deleted when dropped, e.g. for tests.
+    #[inline]
+    pub fn temporary() -> Result<Self, Error> {
+        Ok(Self {
+            db: sled::Config::new().temporary(true).open()?,
+        })
+    }
+
+    /// Persists the state of a pool at a block, replacing any previous snapshot at that block.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block`: The block number of the state
+    /// * `state`: The pool and its ticks
+    #[inline]
+    pub fn insert<I: TickIndex>(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block: u64,
+        state: &Pool<TickListDataProvider<I>>,
+    ) -> Result<(), Error> {
+        self.db
+            .insert(key(chain_id, pool, block), state.snapshot())?;
+        Ok(())
+    }
+
+    /// Returns the state of a pool at exactly `block`, if stored.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block`: The block number
+    #[inline]
+    pub fn get<I: TickIndex>(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block: u64,
+    ) -> Result<Option<Pool<TickListDataProvider<I>>>, Error> {
+        self.db
+            .get(key(chain_id, pool, block))?
+            .map(|value| Pool::restore(&value))
+            .transpose()
+    }
+
+    /// Returns the latest state of a pool stored at or before `block`, e.g. to simulate swaps at a
+    /// historical block when the pool hasn't changed since its last snapshot.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block`: The block number
+    ///
+    /// ## Returns
+    ///
+    /// The block number of the snapshot and the pool, if any
+    #[inline]
+    pub fn get_at_or_before<I: TickIndex>(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block: u64,
+    ) -> Result<Option<(u64, Pool<TickListDataProvider<I>>)>, Error> {
+        self.db
+            .range(key(chain_id, pool, 0)..=key(chain_id, pool, block))
+            .next_back()
+            .transpose()?
+            .map(decode_entry)
+            .transpose()
+    }
+
+    /// Returns the latest stored state of a pool, from which an indexer can resume.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    #[inline]
+    pub fn latest<I: TickIndex>(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+    ) -> Result<Option<(u64, Pool<TickListDataProvider<I>>)>, Error> {
+        self.get_at_or_before(chain_id, pool, u64::MAX)
+    }
+
+    /// Removes the snapshots of a pool after `block`, e.g. those of reorged blocks.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block`: The last block to keep
+    ///
+    /// ## Returns
+    ///
+    /// The number of removed snapshots
+    #[inline]
+    pub fn remove_after(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block: u64,
+    ) -> Result<usize, Error> {
+        if block == u64::MAX {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        for entry in self
+            .db
+            .range(key(chain_id, pool, block + 1)..=key(chain_id, pool, u64::MAX))
+        {
+            let (key, _) = entry?;
+            self.db.remove(key)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Opens a named tree of the database, e.g. for the [`PositionIndexer`].
+    pub(crate) fn open_tree(&self, name: &str) -> Result<sled::Tree, Error> {
+        Ok(self.db.open_tree(name)?)
+    }
+
+    /// Flushes the pending writes to disk.
+    #[inline]
+    pub fn flush(&self) -> Result<(), Error> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    const POOL: Address = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+
+    #[test]
+    fn test_pool_store() {
+        let store = PoolStore::temporary().unwrap();
+        let pool = make_pool(USDC.clone(), WETH.clone());
+        assert!(store.latest::<i32>(1, POOL).unwrap().is_none());
+        store.insert(1, POOL, 100, &pool).unwrap();
+        store.insert(1, POOL, 200, &pool).unwrap();
+        // another chain and pool
+        store.insert(10, POOL, 300, &pool).unwrap();
+        store.insert(1, USDC.address(), 300, &pool).unwrap();
+
+        assert_eq!(store.get(1, POOL, 100).unwrap(), Some(pool.clone()));
+        assert!(store.get::<i32>(1, POOL, 150).unwrap().is_none());
+        let (block, restored) = store.get_at_or_before(1, POOL, 150).unwrap().unwrap();
+        assert_eq!(block, 100);
+        assert_eq!(restored.tick_data_provider, pool.tick_data_provider);
+        assert!(store
+            .get_at_or_before::<i32>(1, POOL, 99)
+            .unwrap()
+            .is_none());
+        assert_eq!(store.latest::<i32>(1, POOL).unwrap().unwrap().0, 200);
+
+        assert_eq!(store.remove_after(1, POOL, 100).unwrap(), 1);
+        assert_eq!(store.latest::<i32>(1, POOL).unwrap().unwrap().0, 100);
+        assert_eq!(store.latest::<i32>(10, POOL).unwrap().unwrap().0, 300);
+        store.flush().unwrap();
+    }
+}
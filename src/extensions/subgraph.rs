@@ -0,0 +1,890 @@
+//! ## Subgraph Extension
+//! A typed client for the [Uniswap V3 subgraph](https://github.com/Uniswap/v3-subgraph) to fetch
+//! pools, positions, ticks and historical data without an archive node.
+
+use crate::prelude::{Error, *};
+use alloc::format;
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{aliases::I24, Address, ChainId, U160, U256};
+use core::str::FromStr;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use uniswap_sdk_core::{prelude::*, token};
+
+/// The id of the Uniswap V3 subgraph on Ethereum mainnet in The Graph Network.
+pub const UNISWAP_V3_MAINNET_SUBGRAPH_ID: &str = "5zvR82QoaXYFyDEKLZ9t6v9adgnptxYpKpSbxtgVENFV";
+
+/// The maximum number of entities the subgraph returns per query.
+const PAGE_SIZE: usize = 1000;
+
+const POOL_FIELDS: &str = "
+    id
+    token0 { id symbol name decimals }
+    token1 { id symbol name decimals }
+    feeTier
+    sqrtPrice
+    liquidity
+";
+
+/// A client for the Uniswap V3 subgraph.
+#[derive(Clone, Debug)]
+pub struct SubgraphClient {
+    /// The GraphQL endpoint of the subgraph
+    pub url: String,
+    client: reqwest::Client,
+}
+
+/// The daily aggregated data of a pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolDayData {
+    /// The unix timestamp of the start of the day
+    pub date: u64,
+    /// The volume in token0
+    pub volume_token0: BigDecimal,
+    /// The volume in token1
+    pub volume_token1: BigDecimal,
+    /// The volume in USD
+    pub volume_usd: BigDecimal,
+    /// The fees in USD
+    pub fees_usd: BigDecimal,
+    /// The total value locked in USD
+    pub tvl_usd: BigDecimal,
+    /// The in range liquidity at the end of the day
+    pub liquidity: u128,
+    /// The sqrt price at the end of the day
+    pub sqrt_price_x96: U160,
+}
+
+/// A snapshot of a position taken whenever it changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionChangeSnapshot {
+    /// The block of the snapshot
+    pub block_number: u64,
+    /// The unix timestamp of the snapshot
+    pub timestamp: u64,
+    /// The liquidity of the position
+    pub liquidity: u128,
+    /// The total amount of token0 deposited
+    pub deposited_token0: BigDecimal,
+    /// The total amount of token1 deposited
+    pub deposited_token1: BigDecimal,
+    /// The total amount of token0 withdrawn
+    pub withdrawn_token0: BigDecimal,
+    /// The total amount of token1 withdrawn
+    pub withdrawn_token1: BigDecimal,
+    /// The total fees of token0 collected
+    pub collected_fees_token0: BigDecimal,
+    /// The total fees of token1 collected
+    pub collected_fees_token1: BigDecimal,
+}
+
+#[derive(Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<Value>>,
+}
+
+#[derive(Deserialize)]
+struct SubgraphToken {
+    id: String,
+    symbol: String,
+    name: String,
+    decimals: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphPool {
+    token0: SubgraphToken,
+    token1: SubgraphToken,
+    fee_tier: String,
+    sqrt_price: String,
+    liquidity: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphTickIdx {
+    tick_idx: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphPosition {
+    liquidity: String,
+    tick_lower: SubgraphTickIdx,
+    tick_upper: SubgraphTickIdx,
+    pool: SubgraphPool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphTick {
+    tick_idx: String,
+    liquidity_gross: String,
+    liquidity_net: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphPoolDayData {
+    date: u64,
+    volume_token0: String,
+    volume_token1: String,
+    #[serde(rename = "volumeUSD")]
+    volume_usd: String,
+    #[serde(rename = "feesUSD")]
+    fees_usd: String,
+    #[serde(rename = "tvlUSD")]
+    tvl_usd: String,
+    liquidity: String,
+    sqrt_price: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphPositionSnapshot {
+    block_number: String,
+    timestamp: String,
+    liquidity: String,
+    deposited_token0: String,
+    deposited_token1: String,
+    withdrawn_token0: String,
+    withdrawn_token1: String,
+    collected_fees_token0: String,
+    collected_fees_token1: String,
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidSubgraphValue(value.to_string()))
+}
+
+/// The fee tier of a `feeTier`, which must be a default tier as the subgraph doesn't index tick
+/// spacings.
+fn fee_amount(fee_tier: &str) -> Result<FeeAmount, Error> {
+    FeeAmount::try_from(FeePips::new(parse(fee_tier)?)?.get())
+}
+
+impl SubgraphToken {
+    fn to_token(&self, chain_id: ChainId) -> Result<Token, Error> {
+        Ok(token!(
+            chain_id,
+            parse::<Address>(&self.id)?,
+            parse::<u8>(&self.decimals)?,
+            self.symbol.clone(),
+            self.name.clone()
+        ))
+    }
+}
+
+impl SubgraphPool {
+    fn to_pool(&self, chain_id: ChainId) -> Result<Pool, Error> {
+        Pool::new(
+            self.token0.to_token(chain_id)?,
+            self.token1.to_token(chain_id)?,
+            fee_amount(&self.fee_tier)?,
+            parse(&self.sqrt_price)?,
+            parse(&self.liquidity)?,
+        )
+    }
+}
+
+impl SubgraphClient {
+    /// Creates a client for a GraphQL endpoint of the subgraph.
+    ///
+    /// ## Arguments
+    ///
+    /// * `url`: The GraphQL endpoint
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Creates a client for a subgraph published on The Graph Network.
+    ///
+    /// ## Arguments
+    ///
+    /// * `api_key`: The API key of The Graph gateway
+    /// * `subgraph_id`: The id of the subgraph, e.g. [`UNISWAP_V3_MAINNET_SUBGRAPH_ID`]
+    #[inline]
+    pub fn from_gateway(api_key: &str, subgraph_id: &str) -> Self {
+        Self::new(format!(
+            "https://gateway.thegraph.com/api/{api_key}/subgraphs/id/{subgraph_id}"
+        ))
+    }
+
+    /// Sends a GraphQL query and deserializes its data.
+    ///
+    /// ## Arguments
+    ///
+    /// * `query`: The GraphQL query
+    /// * `variables`: The variables of the query
+    #[inline]
+    pub async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, Error> {
+        let response: GraphQLResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(errors) = response.errors {
+            return Err(Error::SubgraphQueryError(Value::Array(errors).to_string()));
+        }
+        response
+            .data
+            .ok_or_else(|| Error::SubgraphQueryError("no data".to_string()))
+    }
+
+    /// Get a [`Pool`] struct from the subgraph.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block_number`: Optional block number to query
+    #[inline]
+    pub async fn get_pool(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block_number: Option<u64>,
+    ) -> Result<Pool, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            pool: Option<SubgraphPool>,
+        }
+        let data: Data = self
+            .query(
+                &format!(
+                    "query($id: ID!, $block: Block_height) {{ pool(id: $id, block: $block) {{ {POOL_FIELDS} }} }}"
+                ),
+                json!({ "id": pool_id(pool), "block": block_height(block_number) }),
+            )
+            .await?;
+        match data.pool {
+            Some(pool) => pool.to_pool(chain_id),
+            None => Err(Error::PoolNotFound(pool)),
+        }
+    }
+
+    /// Get all initialized ticks of a pool from the subgraph, sorted by tick index.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `block_number`: Optional block number to query
+    #[inline]
+    pub async fn get_ticks<I: TickIndex>(
+        &self,
+        pool: Address,
+        block_number: Option<u64>,
+    ) -> Result<Vec<Tick<I>>, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            ticks: Vec<SubgraphTick>,
+        }
+        let mut ticks = Vec::new();
+        let mut tick_idx_gt = i64::from(MIN_TICK_I32) - 1;
+        loop {
+            let data: Data = self
+                .query(
+                    "query($pool: String!, $tickIdxGt: BigInt!, $first: Int!, $block: Block_height) {
+                        ticks(
+                            where: { pool: $pool, tickIdx_gt: $tickIdxGt, liquidityGross_gt: 0 }
+                            orderBy: tickIdx
+                            first: $first
+                            block: $block
+                        ) { tickIdx liquidityGross liquidityNet }
+                    }",
+                    json!({
+                        "pool": pool_id(pool),
+                        "tickIdxGt": tick_idx_gt.to_string(),
+                        "first": PAGE_SIZE,
+                        "block": block_height(block_number),
+                    }),
+                )
+                .await?;
+            let len = data.ticks.len();
+            for tick in data.ticks {
+                let index = parse::<I24>(&tick.tick_idx)?;
+                tick_idx_gt = i64::from(index.as_i32());
+                ticks.push(Tick::new(
+                    I::from_i24(index),
+                    parse(&tick.liquidity_gross)?,
+                    parse(&tick.liquidity_net)?,
+                ));
+            }
+            if len < PAGE_SIZE {
+                return Ok(ticks);
+            }
+        }
+    }
+
+    /// Get a [`Pool`] struct with all its initialized ticks from the subgraph.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `pool`: The pool address
+    /// * `block_number`: Optional block number to query
+    #[inline]
+    pub async fn get_pool_with_ticks<I: TickIndex>(
+        &self,
+        chain_id: ChainId,
+        pool: Address,
+        block_number: Option<u64>,
+    ) -> Result<Pool<TickListDataProvider<I>>, Error> {
+        let pool_ = self.get_pool(chain_id, pool, block_number).await?;
+        let ticks = self.get_ticks(pool, block_number).await?;
+        let tick_spacing = I::from_i24(pool_.fee.tick_spacing());
+        Pool::new_with_tick_data_provider(
+            pool_.token0,
+            pool_.token1,
+            pool_.fee,
+            pool_.sqrt_ratio_x96,
+            pool_.liquidity,
+            TickListDataProvider::new(ticks, tick_spacing),
+        )
+    }
+
+    /// Get a [`Position`] struct from the subgraph.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `token_id`: The token id
+    /// * `block_number`: Optional block number to query
+    #[inline]
+    pub async fn get_position(
+        &self,
+        chain_id: ChainId,
+        token_id: U256,
+        block_number: Option<u64>,
+    ) -> Result<Position, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            position: Option<SubgraphPosition>,
+        }
+        let data: Data = self
+            .query(
+                &format!(
+                    "query($id: ID!, $block: Block_height) {{
+                        position(id: $id, block: $block) {{
+                            liquidity
+                            tickLower {{ tickIdx }}
+                            tickUpper {{ tickIdx }}
+                            pool {{ {POOL_FIELDS} }}
+                        }}
+                    }}"
+                ),
+                json!({ "id": token_id.to_string(), "block": block_height(block_number) }),
+            )
+            .await?;
+        let Some(position) = data.position else {
+            return Err(Error::PositionNotFound(token_id));
+        };
+        Ok(Position::new(
+            position.pool.to_pool(chain_id)?,
+            parse(&position.liquidity)?,
+            parse(&position.tick_lower.tick_idx)?,
+            parse(&position.tick_upper.tick_idx)?,
+        ))
+    }
+
+    /// Get the snapshots of a position, ordered by block number.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token_id`: The token id
+    #[inline]
+    pub async fn get_position_snapshots(
+        &self,
+        token_id: U256,
+    ) -> Result<Vec<PositionChangeSnapshot>, Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            position_snapshots: Vec<SubgraphPositionSnapshot>,
+        }
+        let data: Data = self
+            .query(
+                "query($position: String!, $first: Int!) {
+                    positionSnapshots(
+                        where: { position: $position }
+                        orderBy: blockNumber
+                        first: $first
+                    ) {
+                        blockNumber
+                        timestamp
+                        liquidity
+                        depositedToken0
+                        depositedToken1
+                        withdrawnToken0
+                        withdrawnToken1
+                        collectedFeesToken0
+                        collectedFeesToken1
+                    }
+                }",
+                json!({ "position": token_id.to_string(), "first": PAGE_SIZE }),
+            )
+            .await?;
+        data.position_snapshots
+            .iter()
+            .map(|snapshot| {
+                Ok(PositionChangeSnapshot {
+                    block_number: parse(&snapshot.block_number)?,
+                    timestamp: parse(&snapshot.timestamp)?,
+                    liquidity: parse(&snapshot.liquidity)?,
+                    deposited_token0: parse(&snapshot.deposited_token0)?,
+                    deposited_token1: parse(&snapshot.deposited_token1)?,
+                    withdrawn_token0: parse(&snapshot.withdrawn_token0)?,
+                    withdrawn_token1: parse(&snapshot.withdrawn_token1)?,
+                    collected_fees_token0: parse(&snapshot.collected_fees_token0)?,
+                    collected_fees_token1: parse(&snapshot.collected_fees_token1)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the daily data of a pool for the most recent days, ordered by date.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: The pool address
+    /// * `days`: The number of days, at most 1000
+    #[inline]
+    pub async fn get_pool_day_data(
+        &self,
+        pool: Address,
+        days: usize,
+    ) -> Result<Vec<PoolDayData>, Error> {
+        assert!(days <= PAGE_SIZE, "DAYS");
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            pool_day_datas: Vec<SubgraphPoolDayData>,
+        }
+        let data: Data = self
+            .query(
+                "query($pool: String!, $first: Int!) {
+                    poolDayDatas(
+                        where: { pool: $pool }
+                        orderBy: date
+                        orderDirection: desc
+                        first: $first
+                    ) {
+                        date
+                        volumeToken0
+                        volumeToken1
+                        volumeUSD
+                        feesUSD
+                        tvlUSD
+                        liquidity
+                        sqrtPrice
+                    }
+                }",
+                json!({ "pool": pool_id(pool), "first": days }),
+            )
+            .await?;
+        data.pool_day_datas
+            .iter()
+            .rev()
+            .map(|day| {
+                Ok(PoolDayData {
+                    date: day.date,
+                    volume_token0: parse(&day.volume_token0)?,
+                    volume_token1: parse(&day.volume_token1)?,
+                    volume_usd: parse(&day.volume_usd)?,
+                    fees_usd: parse(&day.fees_usd)?,
+                    tvl_usd: parse(&day.tvl_usd)?,
+                    liquidity: parse(&day.liquidity)?,
+                    sqrt_price_x96: parse(&day.sqrt_price)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A [`V3DataSource`] reading from the subgraph. Only block numbers and the latest block can be
+/// queried, and the amounts owed to positions are not indexed so they read as zero.
+#[derive(Clone, Debug)]
+pub struct SubgraphDataSource {
+    /// The subgraph client
+    pub client: SubgraphClient,
+    /// The factory the subgraph indexes, returned for any nonfungible position manager
+    pub factory: Address,
+}
+
+impl SubgraphDataSource {
+    /// Creates a data source reading from a subgraph indexing the pools of `factory`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `client`: The subgraph client
+    /// * `factory`: The factory address
+    #[inline]
+    #[must_use]
+    pub const fn new(client: SubgraphClient, factory: Address) -> Self {
+        Self { client, factory }
+    }
+
+    async fn pool_fields<T: DeserializeOwned>(
+        &self,
+        pool: Address,
+        fields: &str,
+        block_id: Option<BlockId>,
+    ) -> Result<T, Error> {
+        #[derive(Deserialize)]
+        struct Data<T> {
+            pool: Option<T>,
+        }
+        let data: Data<T> = self
+            .client
+            .query(
+                &format!("query($id: ID!, $block: Block_height) {{ pool(id: $id, block: $block) {{ {fields} }} }}"),
+                json!({ "id": pool_id(pool), "block": block_height(block_number(block_id)?) }),
+            )
+            .await?;
+        data.pool.ok_or(Error::PoolNotFound(pool))
+    }
+}
+
+impl V3DataSource for SubgraphDataSource {
+    #[inline]
+    async fn slot0(&self, pool: Address, block_id: Option<BlockId>) -> Result<Slot0, Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields {
+            sqrt_price: String,
+            tick: Option<String>,
+            observation_index: String,
+        }
+        let fields: Fields = self
+            .pool_fields(pool, "sqrtPrice tick observationIndex", block_id)
+            .await?;
+        Ok(Slot0 {
+            sqrt_price_x96: parse(&fields.sqrt_price)?,
+            tick: parse(fields.tick.as_deref().unwrap_or("0"))?,
+            observation_index: parse(&fields.observation_index)?,
+            unlocked: true,
+            ..Default::default()
+        })
+    }
+
+    #[inline]
+    async fn liquidity(&self, pool: Address, block_id: Option<BlockId>) -> Result<u128, Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            liquidity: String,
+        }
+        let fields: Fields = self.pool_fields(pool, "liquidity", block_id).await?;
+        Ok(parse(&fields.liquidity)?)
+    }
+
+    #[inline]
+    async fn fee_growth_global(
+        &self,
+        pool: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<(U256, U256), Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields {
+            fee_growth_global0_x128: String,
+            fee_growth_global1_x128: String,
+        }
+        let fields: Fields = self
+            .pool_fields(pool, "feeGrowthGlobal0X128 feeGrowthGlobal1X128", block_id)
+            .await?;
+        Ok((
+            parse(&fields.fee_growth_global0_x128)?,
+            parse(&fields.fee_growth_global1_x128)?,
+        ))
+    }
+
+    #[inline]
+    async fn tick(
+        &self,
+        pool: Address,
+        tick: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<TickInfo, Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields {
+            liquidity_gross: String,
+            liquidity_net: String,
+            fee_growth_outside0_x128: String,
+            fee_growth_outside1_x128: String,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            tick: Option<Fields>,
+        }
+        let data: Data = self
+            .client
+            .query(
+                "query($id: ID!, $block: Block_height) {
+                    tick(id: $id, block: $block) {
+                        liquidityGross liquidityNet feeGrowthOutside0X128 feeGrowthOutside1X128
+                    }
+                }",
+                json!({
+                    "id": format!("{}#{tick}", pool_id(pool)),
+                    "block": block_height(block_number(block_id)?),
+                }),
+            )
+            .await?;
+        // ticks that were never initialized are not indexed
+        let Some(fields) = data.tick else {
+            return Ok(TickInfo::default());
+        };
+        let liquidity_gross = parse(&fields.liquidity_gross)?;
+        Ok(TickInfo {
+            liquidity_gross,
+            liquidity_net: parse(&fields.liquidity_net)?,
+            fee_growth_outside0_x128: parse(&fields.fee_growth_outside0_x128)?,
+            fee_growth_outside1_x128: parse(&fields.fee_growth_outside1_x128)?,
+            initialized: liquidity_gross > 0,
+        })
+    }
+
+    #[inline]
+    async fn ticks(
+        &self,
+        pool: Address,
+        tick_lower: I24,
+        tick_upper: I24,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<Tick<I24>>, Error> {
+        let ticks = self
+            .client
+            .get_ticks::<I24>(pool, block_number(block_id)?)
+            .await?;
+        Ok(ticks
+            .into_iter()
+            .filter(|tick| tick_lower <= tick.index && tick.index <= tick_upper)
+            .collect())
+    }
+
+    #[inline]
+    async fn token(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        block_id: Option<BlockId>,
+    ) -> Result<Token, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            token: Option<SubgraphToken>,
+        }
+        let data: Data = self
+            .client
+            .query(
+                "query($id: ID!, $block: Block_height) {
+                    token(id: $id, block: $block) { id symbol name decimals }
+                }",
+                json!({ "id": pool_id(token), "block": block_height(block_number(block_id)?) }),
+            )
+            .await?;
+        match data.token {
+            Some(token) => token.to_token(chain_id),
+            None => Err(Error::TokenNotFound(token)),
+        }
+    }
+
+    #[inline]
+    async fn factory(
+        &self,
+        _nonfungible_position_manager: Address,
+        _block_id: Option<BlockId>,
+    ) -> Result<Address, Error> {
+        Ok(self.factory)
+    }
+
+    #[inline]
+    async fn positions(
+        &self,
+        _nonfungible_position_manager: Address,
+        token_id: U256,
+        block_id: Option<BlockId>,
+    ) -> Result<PositionInfo, Error> {
+        #[derive(Deserialize)]
+        struct SubgraphId {
+            id: String,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SubgraphFeeTier {
+            fee_tier: String,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields {
+            token0: SubgraphId,
+            token1: SubgraphId,
+            pool: SubgraphFeeTier,
+            tick_lower: SubgraphTickIdx,
+            tick_upper: SubgraphTickIdx,
+            liquidity: String,
+            fee_growth_inside0_last_x128: String,
+            fee_growth_inside1_last_x128: String,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            position: Option<Fields>,
+        }
+        let data: Data = self
+            .client
+            .query(
+                "query($id: ID!, $block: Block_height) {
+                    position(id: $id, block: $block) {
+                        token0 { id }
+                        token1 { id }
+                        pool { feeTier }
+                        tickLower { tickIdx }
+                        tickUpper { tickIdx }
+                        liquidity
+                        feeGrowthInside0LastX128
+                        feeGrowthInside1LastX128
+                    }
+                }",
+                json!({
+                    "id": token_id.to_string(),
+                    "block": block_height(block_number(block_id)?),
+                }),
+            )
+            .await?;
+        let Some(position) = data.position else {
+            return Err(Error::PositionNotFound(token_id));
+        };
+        Ok(PositionInfo {
+            token0: parse(&position.token0.id)?,
+            token1: parse(&position.token1.id)?,
+            fee: fee_amount(&position.pool.fee_tier)?,
+            tick_lower: parse(&position.tick_lower.tick_idx)?,
+            tick_upper: parse(&position.tick_upper.tick_idx)?,
+            liquidity: parse(&position.liquidity)?,
+            fee_growth_inside0_last_x128: parse(&position.fee_growth_inside0_last_x128)?,
+            fee_growth_inside1_last_x128: parse(&position.fee_growth_inside1_last_x128)?,
+            tokens_owed0: 0,
+            tokens_owed1: 0,
+        })
+    }
+}
+
+/// The id of a pool entity, the lowercase address.
+fn pool_id(pool: Address) -> String {
+    format!("{pool:#x}")
+}
+
+fn block_height(block_number: Option<u64>) -> Value {
+    block_number.map_or(Value::Null, |number| json!({ "number": number }))
+}
+
+/// The block number the subgraph is queried at, `None` for the latest block.
+fn block_number(block_id: Option<BlockId>) -> Result<Option<u64>, Error> {
+    match block_id {
+        None | Some(BlockId::Number(BlockNumberOrTag::Latest)) => Ok(None),
+        Some(BlockId::Number(BlockNumberOrTag::Number(number))) => Ok(Some(number)),
+        Some(block_id) => Err(Error::UnsupportedBlockId(block_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_pool_id() {
+        assert_eq!(
+            pool_id(address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")),
+            "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"
+        );
+    }
+
+    #[test]
+    fn test_subgraph_pool_to_pool() {
+        let pool: SubgraphPool = serde_json::from_value(json!({
+            "id": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+            "token0": {
+                "id": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "symbol": "USDC",
+                "name": "USD Coin",
+                "decimals": "6"
+            },
+            "token1": {
+                "id": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "symbol": "WETH",
+                "name": "Wrapped Ether",
+                "decimals": "18"
+            },
+            "feeTier": "500",
+            "sqrtPrice": "1854991263964413633595575627937937",
+            "liquidity": "21119089307004004271"
+        }))
+        .unwrap();
+        let pool = pool.to_pool(1).unwrap();
+        assert_eq!(pool.fee, FeeAmount::LOW);
+        assert_eq!(pool.token0.decimals(), 6);
+        assert_eq!(
+            pool.token1.address(),
+            address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")
+        );
+        assert_eq!(pool.liquidity, 21119089307004004271);
+        assert_eq!(
+            pool.address(None, None),
+            address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")
+        );
+    }
+
+    #[test]
+    fn test_block_number() {
+        assert_eq!(block_number(None).unwrap(), None);
+        assert_eq!(block_number(Some(BlockId::from(1))).unwrap(), Some(1));
+        assert!(matches!(
+            block_number(Some(BlockId::Number(BlockNumberOrTag::Pending))),
+            Err(Error::UnsupportedBlockId(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse::<u128>("21119089307004004271").unwrap(),
+            21119089307004004271
+        );
+        assert_eq!(
+            parse::<I24>("-887220").unwrap(),
+            I24::try_from(-887220).unwrap()
+        );
+        assert!(matches!(
+            parse::<u8>("256"),
+            Err(Error::InvalidSubgraphValue(value)) if value == "256"
+        ));
+        assert_eq!(fee_amount("3000").unwrap(), FeeAmount::MEDIUM);
+        assert!(matches!(
+            fee_amount("1000000"),
+            Err(Error::InvalidFee(1000000))
+        ));
+    }
+
+    #[test]
+    fn test_block_height() {
+        assert_eq!(block_height(None), Value::Null);
+        assert_eq!(block_height(Some(1)), json!({ "number": 1 }));
+    }
+}
@@ -0,0 +1,205 @@
+//! ## Tick Lens Extension
+//! This module provides functions to fetch the populated ticks of a pool from the periphery
+//! [TickLens](https://github.com/Uniswap/v3-periphery/blob/main/contracts/lens/TickLens.sol), one
+//! word of the tick bitmap at a time or over the words covering a tick range in chunked Multicall3
+//! calls.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    contract::CallBuilder,
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    transports::Transport,
+};
+use alloy_primitives::Address;
+
+/// Get the populated ticks in a word of the tick bitmap of a pool from the TickLens.
+///
+/// ## Arguments
+///
+/// * `tick_lens`: The TickLens address
+/// * `pool`: The pool address
+/// * `word_index`: The index of the word in the tick bitmap
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The `(tick, liquidity_net, liquidity_gross)` of each populated tick in the word, sorted by tick
+#[inline]
+pub async fn get_populated_ticks_in_word<I, T, P>(
+    tick_lens: Address,
+    pool: Address,
+    word_index: i16,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<(I, i128, u128)>, Error>
+where
+    I: TickIndex,
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let call = ITickLens::getPopulatedTicksInWordCall {
+        pool,
+        tickBitmapIndex: word_index,
+    };
+    let populated_ticks = CallBuilder::new_sol(&provider, &tick_lens, &call)
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?
+        .populatedTicks;
+    Ok(to_tick_tuples(populated_ticks))
+}
+
+/// Get the populated ticks of a pool within a tick range from the TickLens, walking the words of
+/// the tick bitmap covering the range in chunked Multicall3 calls. To walk the words around the
+/// current tick, pass a range centered at `pool.tick_current`.
+///
+/// ## Arguments
+///
+/// * `tick_lens`: The TickLens address
+/// * `pool`: The pool address
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `tick_spacing`: The tick spacing of the pool
+/// * `options`: The chunking, concurrency and retries of the Multicall3 calls
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The `(tick, liquidity_net, liquidity_gross)` of each populated tick in
+/// `[tick_lower, tick_upper]`, sorted by tick
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_populated_ticks_in_word_range<I, T, P>(
+    tick_lens: Address,
+    pool: Address,
+    tick_lower: I,
+    tick_upper: I,
+    tick_spacing: I,
+    options: BatchOptions,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<(I, i128, u128)>, Error>
+where
+    I: TickIndex,
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    if tick_lower > tick_upper {
+        return Err(Error::InvalidRange);
+    }
+    let word_index = |tick: I| -> Result<i16, Error> {
+        let (word, _) = tick.compress(tick_spacing).position();
+        word.try_into()
+            .ok()
+            .and_then(|word: i32| i16::try_from(word).ok())
+            .ok_or(Error::InvalidTick(tick.to_i24()))
+    };
+    let calls = (word_index(tick_lower)?..=word_index(tick_upper)?)
+        .map(|tick_bitmap_index| {
+            call3(
+                tick_lens,
+                &ITickLens::getPopulatedTicksInWordCall {
+                    pool,
+                    tickBitmapIndex: tick_bitmap_index,
+                },
+            )
+        })
+        .collect();
+    let results = aggregate3(calls, options, provider, block_id).await?;
+    let mut ticks = Vec::new();
+    for result in &results {
+        let populated_ticks =
+            decode_call3_result::<ITickLens::getPopulatedTicksInWordCall>(result)?.populatedTicks;
+        ticks.extend(
+            to_tick_tuples::<I>(populated_ticks)
+                .into_iter()
+                .filter(|&(tick, _, _)| tick >= tick_lower && tick <= tick_upper),
+        );
+    }
+    Ok(ticks)
+}
+
+/// Converts the populated ticks returned by the TickLens, which are in descending order within a
+/// word, to tuples sorted by tick.
+fn to_tick_tuples<I: TickIndex>(
+    populated_ticks: Vec<ITickLens::PopulatedTick>,
+) -> Vec<(I, i128, u128)> {
+    let mut ticks: Vec<(I, i128, u128)> = populated_ticks
+        .into_iter()
+        .map(|tick| {
+            (
+                I::from_i24(tick.tick),
+                tick.liquidityNet,
+                tick.liquidityGross,
+            )
+        })
+        .collect();
+    ticks.sort_unstable_by_key(|&(tick, _, _)| tick);
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::{address, aliases::I24};
+    use uniswap_lens::pool_lens;
+
+    const POOL: Address = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+
+    #[tokio::test]
+    async fn test_get_populated_ticks_in_word() {
+        let ticks = get_populated_ticks_in_word::<i32, _, _>(
+            ChainConfig::MAINNET.tick_lens,
+            POOL,
+            // the word of the ticks around 201_000 with a tick spacing of 10
+            78,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert!(!ticks.is_empty());
+        assert!(ticks.windows(2).all(|w| w[0].0 < w[1].0));
+        for &(tick, _, liquidity_gross) in &ticks {
+            assert_eq!(tick.compress(10).position().0, 78);
+            assert!(liquidity_gross > 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_populated_ticks_in_word_range() {
+        let tick_lower = I24::try_from(200000).unwrap();
+        let tick_upper = I24::try_from(210000).unwrap();
+        let ticks = get_populated_ticks_in_word_range(
+            ChainConfig::MAINNET.tick_lens,
+            POOL,
+            tick_lower,
+            tick_upper,
+            I24::try_from(10).unwrap(),
+            BatchOptions::default(),
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let (expected, _) = pool_lens::get_populated_ticks_in_range(
+            POOL,
+            tick_lower,
+            tick_upper,
+            PROVIDER.clone(),
+            *BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ticks.len(), expected.len());
+        for (&(tick, liquidity_net, liquidity_gross), expected) in ticks.iter().zip(&expected) {
+            assert_eq!(tick, expected.tick);
+            assert_eq!(liquidity_net, expected.liquidityNet);
+            assert_eq!(liquidity_gross, expected.liquidityGross);
+        }
+    }
+}
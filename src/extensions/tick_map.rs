@@ -1,6 +1,7 @@
 //! ## Tick Map
 //! [`TickMap`] provides a way to access tick data directly from a hashmap, supposedly more
-//! efficient than [`TickList`].
+//! efficient than [`TickList`], and can be kept up to date by applying the `Mint` and `Burn`
+//! events of the pool.
 
 use crate::prelude::*;
 use alloy::uint;
@@ -32,6 +33,88 @@ impl<I: TickIndex> TickMap<I> {
             tick_spacing,
         }
     }
+
+    /// Updates the ticks of a position for a change of its liquidity, inserting ticks that get
+    /// initialized and removing ticks that get uninitialized.
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick_lower`: The lower tick of the position
+    /// * `tick_upper`: The upper tick of the position
+    /// * `liquidity_delta`: The change in the liquidity of the position
+    #[inline]
+    pub fn update_position(
+        &mut self,
+        tick_lower: I,
+        tick_upper: I,
+        liquidity_delta: i128,
+    ) -> Result<(), Error> {
+        if liquidity_delta == 0 {
+            return Ok(());
+        }
+        self.update_tick(tick_lower, liquidity_delta, false)?;
+        self.update_tick(tick_upper, liquidity_delta, true)
+    }
+
+    /// Applies a decoded `Mint` event of the pool to the ticks of the minted position.
+    ///
+    /// ## Arguments
+    ///
+    /// * `mint`: The `Mint` event
+    #[inline]
+    pub fn apply_mint(&mut self, mint: &IUniswapV3PoolEvents::Mint) -> Result<(), Error> {
+        self.update_position(
+            I::from_i24(mint.tickLower),
+            I::from_i24(mint.tickUpper),
+            i128::try_from(mint.amount).map_err(|_| Error::AddDeltaOverflow)?,
+        )
+    }
+
+    /// Applies a decoded `Burn` event of the pool to the ticks of the burned position.
+    ///
+    /// ## Arguments
+    ///
+    /// * `burn`: The `Burn` event
+    #[inline]
+    pub fn apply_burn(&mut self, burn: &IUniswapV3PoolEvents::Burn) -> Result<(), Error> {
+        self.update_position(
+            I::from_i24(burn.tickLower),
+            I::from_i24(burn.tickUpper),
+            -i128::try_from(burn.amount).map_err(|_| Error::AddDeltaOverflow)?,
+        )
+    }
+
+    /// Updates a tick for a change of liquidity of a position, flipping it in the bitmap when it
+    /// gets initialized or uninitialized.
+    fn update_tick(&mut self, tick: I, liquidity_delta: i128, upper: bool) -> Result<(), Error> {
+        let mut entry = self
+            .inner
+            .get(&tick)
+            .copied()
+            .unwrap_or_else(|| Tick::new(tick, 0, 0));
+        let liquidity_gross_before = entry.liquidity_gross;
+        entry.liquidity_gross = add_delta(liquidity_gross_before, liquidity_delta)?;
+        entry.liquidity_net = if upper {
+            entry.liquidity_net - liquidity_delta
+        } else {
+            entry.liquidity_net + liquidity_delta
+        };
+        let liquidity_gross_after = entry.liquidity_gross;
+        if (liquidity_gross_before == 0) != (liquidity_gross_after == 0) {
+            let (word_pos, bit_pos) = tick.compress(self.tick_spacing).position();
+            let word = self.bitmap.entry(word_pos).or_default();
+            *word ^= uint!(1_U256) << bit_pos;
+            if word.is_zero() {
+                self.bitmap.remove(&word_pos);
+            }
+        }
+        if liquidity_gross_after == 0 {
+            self.inner.remove(&tick);
+        } else {
+            self.inner.insert(tick, entry);
+        }
+        Ok(())
+    }
 }
 
 impl<I: TickIndex> TickDataProvider for TickMap<I> {
@@ -55,3 +138,89 @@ impl<I: TickIndex> TickDataProvider for TickMap<I> {
             .next_initialized_tick_within_one_word(tick, lte, tick_spacing)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+
+    fn mint(tick_lower: i32, tick_upper: i32, amount: u128) -> IUniswapV3PoolEvents::Mint {
+        IUniswapV3PoolEvents::Mint {
+            sender: Address::ZERO,
+            owner: Address::ZERO,
+            tickLower: I24::try_from(tick_lower).unwrap(),
+            tickUpper: I24::try_from(tick_upper).unwrap(),
+            amount,
+            amount0: U256::ZERO,
+            amount1: U256::ZERO,
+        }
+    }
+
+    fn burn(tick_lower: i32, tick_upper: i32, amount: u128) -> IUniswapV3PoolEvents::Burn {
+        IUniswapV3PoolEvents::Burn {
+            owner: Address::ZERO,
+            tickLower: I24::try_from(tick_lower).unwrap(),
+            tickUpper: I24::try_from(tick_upper).unwrap(),
+            amount,
+            amount0: U256::ZERO,
+            amount1: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_apply_mint_and_burn() {
+        let mut tick_map = TickMap::<i32> {
+            bitmap: TickBitMap::default(),
+            inner: FxHashMap::default(),
+            tick_spacing: 10,
+        };
+        tick_map.apply_mint(&mint(-100, 100, 1000)).unwrap();
+        tick_map.apply_mint(&mint(0, 100, 500)).unwrap();
+        assert_eq!(
+            *tick_map.get_tick(-100).unwrap(),
+            Tick::new(-100, 1000, 1000)
+        );
+        assert_eq!(*tick_map.get_tick(0).unwrap(), Tick::new(0, 500, 500));
+        assert_eq!(
+            *tick_map.get_tick(100).unwrap(),
+            Tick::new(100, 1500, -1500)
+        );
+        assert_eq!(
+            tick_map
+                .next_initialized_tick_within_one_word(-50, true, 10)
+                .unwrap(),
+            (-100, true)
+        );
+        assert_eq!(
+            tick_map
+                .next_initialized_tick_within_one_word(-50, false, 10)
+                .unwrap(),
+            (0, true)
+        );
+
+        // burning part of a position keeps its ticks
+        tick_map.apply_burn(&burn(0, 100, 200)).unwrap();
+        assert_eq!(*tick_map.get_tick(0).unwrap(), Tick::new(0, 300, 300));
+        assert_eq!(
+            *tick_map.get_tick(100).unwrap(),
+            Tick::new(100, 1300, -1300)
+        );
+
+        // burning a position entirely uninitializes the ticks it alone references
+        tick_map.apply_burn(&burn(0, 100, 300)).unwrap();
+        assert!(tick_map.get_tick(0).is_err());
+        assert_eq!(
+            tick_map
+                .next_initialized_tick_within_one_word(-50, false, 10)
+                .unwrap(),
+            (100, true)
+        );
+        tick_map.apply_burn(&burn(-100, 100, 1000)).unwrap();
+        assert!(tick_map.inner.is_empty());
+        assert!(tick_map.bitmap.is_empty());
+
+        // burning more than the liquidity of a tick fails
+        assert!(tick_map.apply_burn(&burn(-100, 100, 1)).is_err());
+        assert!(tick_map.inner.is_empty());
+    }
+}
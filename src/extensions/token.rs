@@ -0,0 +1,168 @@
+//! ## Token Extension
+//! This module provides functions to fetch the metadata of ERC20 tokens and build [`Token`]s,
+//! batching the `decimals`, `symbol` and `name` calls of many tokens into Multicall3 calls.
+
+use crate::prelude::{Error, *};
+use alloy::{eips::BlockId, providers::Provider, transports::Transport};
+use alloy_primitives::{Address, ChainId, B256};
+use alloy_sol_types::SolValue;
+use uniswap_lens::bindings::ierc20metadata::IERC20Metadata;
+use uniswap_sdk_core::prelude::Token;
+
+/// Get the metadata of an ERC20 token and build a [`Token`].
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `address`: The token address
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_token<T, P>(
+    chain_id: ChainId,
+    address: Address,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Token, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut tokens = get_tokens(chain_id, &[address], provider, block_id).await?;
+    Ok(tokens.remove(0))
+}
+
+/// Get the metadata of many ERC20 tokens and build [`Token`]s in as few `eth_call`s as possible.
+///
+/// The symbol and name may be encoded as `bytes32` as in old tokens such as MKR, and are left
+/// empty if not implemented. Fails if any address doesn't implement `decimals`.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `addresses`: The token addresses
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+///
+/// ## Returns
+///
+/// The tokens, in the same order as `addresses`
+#[inline]
+pub async fn get_tokens<T, P>(
+    chain_id: ChainId,
+    addresses: &[Address],
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<Vec<Token>, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let calls = addresses
+        .iter()
+        .flat_map(|&address| {
+            [
+                call3(address, &IERC20Metadata::decimalsCall {}),
+                IMulticall3::Call3 {
+                    allowFailure: true,
+                    ..call3(address, &IERC20Metadata::symbolCall {})
+                },
+                IMulticall3::Call3 {
+                    allowFailure: true,
+                    ..call3(address, &IERC20Metadata::nameCall {})
+                },
+            ]
+        })
+        .collect();
+    let results = aggregate3(calls, BatchOptions::default(), provider, block_id).await?;
+    addresses
+        .iter()
+        .zip(results.chunks(3))
+        .map(|(&address, results)| decode_token(chain_id, address, results))
+        .collect()
+}
+
+/// Builds a [`Token`] from the results of its `decimals`, `symbol` and `name` calls.
+fn decode_token(
+    chain_id: ChainId,
+    address: Address,
+    results: &[IMulticall3::Result],
+) -> Result<Token, Error> {
+    let decimals = decode_call3_result::<IERC20Metadata::decimalsCall>(&results[0])?._0;
+    Ok(Token::new(
+        chain_id,
+        address,
+        decimals,
+        decode_string(&results[1]),
+        decode_string(&results[2]),
+        None,
+        None,
+    ))
+}
+
+/// Decodes a string returned by `symbol` or `name`, falling back to a null-terminated `bytes32`.
+fn decode_string(result: &IMulticall3::Result) -> Option<String> {
+    if !result.success {
+        return None;
+    }
+    if let Ok(value) = String::abi_decode(&result.returnData, true) {
+        return Some(value);
+    }
+    let value = B256::abi_decode(&result.returnData, true).ok()?;
+    let len = value.iter().position(|&c| c == 0).unwrap_or(32);
+    Some(String::from_utf8_lossy(&value[..len]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use alloy_primitives::Bytes;
+    use uniswap_sdk_core::prelude::BaseCurrency;
+
+    #[test]
+    fn test_decode_token_without_symbol() {
+        let result = |success: bool, return_data: Vec<u8>| IMulticall3::Result {
+            success,
+            returnData: Bytes::from(return_data),
+        };
+        let token = decode_token(
+            1,
+            Address::ZERO,
+            &[
+                result(true, 18_u8.abi_encode()),
+                result(false, Vec::new()),
+                result(true, String::from("Maker").abi_encode()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(token.decimals, 18);
+        assert_eq!(token.symbol, None);
+        assert_eq!(token.name.as_deref(), Some("Maker"));
+    }
+
+    #[tokio::test]
+    async fn test_get_token() {
+        let usdc = get_token(1, USDC.address(), PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(usdc.symbol.unwrap(), "USDC");
+        assert_eq!(usdc.name.unwrap(), "USD Coin");
+    }
+
+    #[tokio::test]
+    async fn test_get_tokens() {
+        const MKR: Address = address!("9f8F72aA9304c8B593d555F12eF6589cC3A579A2");
+        let tokens = get_tokens(1, &[WETH.address(), MKR], PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].symbol.as_deref(), Some("WETH"));
+        // MKR returns its symbol and name as bytes32
+        assert_eq!(tokens[1].symbol.as_deref(), Some("MKR"));
+        assert_eq!(tokens[1].name.as_deref(), Some("Maker"));
+        assert_eq!(tokens[1].decimals, 18);
+    }
+}
@@ -0,0 +1,332 @@
+//! ## Transaction Extension
+//! A [`TransactionBuilder`] that turns the [`MethodParameters`] produced by the swap and position
+//! manager calldata builders into complete EIP-1559 transactions, ready to be signed and sent,
+//! helpers to attach the access list generated by `eth_createAccessList`, and
+//! [`deadline_from_now`] to compute deadlines from the timestamp of the latest block.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockId,
+    network::TransactionBuilder as _,
+    providers::Provider,
+    rpc::types::{AccessList, BlockTransactionsKind, TransactionRequest},
+    transports::Transport,
+};
+use alloy_primitives::{Address, U256};
+use core::{marker::PhantomData, time::Duration};
+
+/// Assembles transactions from [`MethodParameters`], filling in the chain id, nonce, gas limit and
+/// EIP-1559 fees from the provider.
+#[derive(Clone, Debug)]
+pub struct TransactionBuilder<T, P> {
+    pub provider: P,
+    /// The sender of the transactions
+    pub from: Address,
+    /// The percentage added to the gas estimate as headroom, by default 20
+    pub gas_buffer: u64,
+    /// Whether to attach the generated access list when it lowers the gas estimate, by default
+    /// false
+    pub access_list: bool,
+    _transport: PhantomData<T>,
+}
+
+impl<T, P> TransactionBuilder<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    /// Creates a builder of transactions sent by `from`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `provider`: The alloy provider
+    /// * `from`: The sender of the transactions
+    #[inline]
+    pub const fn new(provider: P, from: Address) -> Self {
+        Self {
+            provider,
+            from,
+            gas_buffer: 20,
+            access_list: false,
+            _transport: PhantomData,
+        }
+    }
+
+    /// Sets the percentage added to the gas estimate.
+    ///
+    /// ## Arguments
+    ///
+    /// * `gas_buffer`: The percentage of headroom, e.g. 20 for 1.2x the estimate
+    #[inline]
+    #[must_use]
+    pub const fn with_gas_buffer(mut self, gas_buffer: u64) -> Self {
+        self.gas_buffer = gas_buffer;
+        self
+    }
+
+    /// Sets whether to attach the generated access list when it lowers the gas estimate.
+    ///
+    /// ## Arguments
+    ///
+    /// * `access_list`: Whether to generate access lists
+    #[inline]
+    #[must_use]
+    pub const fn with_access_list(mut self, access_list: bool) -> Self {
+        self.access_list = access_list;
+        self
+    }
+
+    /// The transaction calling `to` with `params`, without any field filled in from the provider.
+    ///
+    /// ## Arguments
+    ///
+    /// * `to`: The contract to call, e.g. the router or the position manager
+    /// * `params`: The calldata and value to send
+    #[inline]
+    #[must_use]
+    pub fn request(&self, to: Address, params: MethodParameters) -> TransactionRequest {
+        TransactionRequest::default()
+            .with_from(self.from)
+            .with_to(to)
+            .with_input(params.calldata)
+            .with_value(params.value)
+    }
+
+    /// Builds a complete EIP-1559 transaction calling `to` with `params`. The gas limit is the
+    /// estimate of the provider plus [`Self::gas_buffer`] percent, so the estimation fails if the
+    /// call would revert.
+    ///
+    /// ## Arguments
+    ///
+    /// * `to`: The contract to call, e.g. the router or the position manager
+    /// * `params`: The calldata and value to send
+    ///
+    /// ## Returns
+    ///
+    /// The transaction request, which can be signed with `build` or sent with `send_transaction`
+    #[inline]
+    pub async fn build(
+        &self,
+        to: Address,
+        params: MethodParameters,
+    ) -> Result<TransactionRequest, Error> {
+        let tx = self.request(to, params);
+        self.fill(tx).await
+    }
+
+    /// Fills in the chain id, nonce, gas limit and EIP-1559 fees of a transaction, and the access
+    /// list if enabled and cheaper.
+    ///
+    /// ## Arguments
+    ///
+    /// * `tx`: The transaction to fill
+    #[inline]
+    pub async fn fill(&self, tx: TransactionRequest) -> Result<TransactionRequest, Error> {
+        let chain_id = self
+            .provider
+            .get_chain_id()
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?;
+        let nonce = self
+            .provider
+            .get_transaction_count(self.from)
+            .pending()
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?;
+        let fees = self
+            .provider
+            .estimate_eip1559_fees(None)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?;
+        let mut gas = self
+            .provider
+            .estimate_gas(&tx)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?;
+        let mut tx = tx;
+        if self.access_list {
+            let with_access_list = attach_access_list(tx.clone(), &self.provider, None).await?;
+            let gas_with_access_list = self
+                .provider
+                .estimate_gas(&with_access_list)
+                .await
+                .map_err(|e| Error::ContractError(e.into()))?;
+            if gas_with_access_list < gas {
+                tx = with_access_list;
+                gas = gas_with_access_list;
+            }
+        }
+        Ok(tx
+            .with_chain_id(chain_id)
+            .with_nonce(nonce)
+            .with_gas_limit(gas + gas * self.gas_buffer / 100)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas))
+    }
+}
+
+/// Generates the access list of a transaction with `eth_createAccessList`.
+///
+/// ## Arguments
+///
+/// * `tx`: The transaction, e.g. from [`TransactionBuilder::request`]
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to simulate the transaction at
+///
+/// ## Returns
+///
+/// The storage slots the transaction accesses, other than those of the sender and the recipient,
+/// and the gas used with the access list. Fails with [`Error::AccessListError`] if the transaction
+/// reverts.
+#[inline]
+pub async fn create_access_list<T, P>(
+    tx: &TransactionRequest,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<(AccessList, u64), Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut call = provider.create_access_list(tx);
+    if let Some(block_id) = block_id {
+        call = call.block_id(block_id);
+    }
+    let result = call.await.map_err(|e| Error::ContractError(e.into()))?;
+    if let Some(error) = result.error {
+        return Err(Error::AccessListError(error));
+    }
+    Ok((result.access_list, result.gas_used.saturating_to()))
+}
+
+/// Attaches the access list generated by `eth_createAccessList` to a transaction.
+///
+/// ## Arguments
+///
+/// * `tx`: The transaction, e.g. from [`TransactionBuilder::request`]
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to simulate the transaction at
+#[inline]
+pub async fn attach_access_list<T, P>(
+    tx: TransactionRequest,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<TransactionRequest, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let (access_list, _) = create_access_list(&tx, provider, block_id).await?;
+    Ok(tx.with_access_list(access_list))
+}
+
+/// Returns a deadline `duration` after the timestamp of the latest block, for the `deadline` of
+/// the calldata builders. The chain's clock is used rather than the local one, since the
+/// timestamps of L2s and local clocks can diverge enough for a transaction to revert with
+/// `Transaction too old`.
+///
+/// ## Arguments
+///
+/// * `provider`: The alloy provider
+/// * `duration`: How long the transaction is valid for
+///
+/// ## Returns
+///
+/// The deadline in epoch seconds
+#[inline]
+pub async fn deadline_from_now<T, P>(provider: P, duration: Duration) -> Result<U256, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let block = provider
+        .get_block(BlockId::latest(), BlockTransactionsKind::Hashes)
+        .await
+        .map_err(|e| Error::ContractError(e.into()))?
+        .ok_or(Error::DataNotFound)?;
+    Ok(U256::from(
+        block.header.timestamp.saturating_add(duration.as_secs()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use uniswap_sdk_core::prelude::{BaseCurrency, CurrencyAmount};
+
+    #[tokio::test]
+    async fn test_build() {
+        const FROM: Address = address!("0000000000000000000000000000000000000003");
+        // unwrapping zero WETH succeeds from any account
+        let params = unwrap_weth9_call_parameters(
+            &CurrencyAmount::from_raw_amount(WETH.clone(), 0).unwrap(),
+            FROM,
+            None,
+        );
+        let builder = TransactionBuilder::new(PROVIDER.clone(), FROM).with_gas_buffer(50);
+        let tx = builder
+            .build(ChainConfig::MAINNET.swap_router02, params.clone())
+            .await
+            .unwrap();
+        assert_eq!(tx.from, Some(FROM));
+        assert_eq!(tx.to, Some(ChainConfig::MAINNET.swap_router02.into()));
+        assert_eq!(tx.input.input(), Some(&params.calldata));
+        assert_eq!(tx.value, Some(U256::ZERO));
+        assert_eq!(tx.chain_id, Some(1));
+        assert!(tx.gas.unwrap() > 21000);
+        assert!(tx.max_fee_per_gas.unwrap() >= tx.max_priority_fee_per_gas.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_create_access_list() {
+        const FROM: Address = address!("0000000000000000000000000000000000000003");
+        let params = unwrap_weth9_call_parameters(
+            &CurrencyAmount::from_raw_amount(WETH.clone(), 0).unwrap(),
+            FROM,
+            None,
+        );
+        let builder = TransactionBuilder::new(PROVIDER.clone(), FROM);
+        let tx = builder.request(ChainConfig::MAINNET.swap_router02, params);
+        let (access_list, gas_used) = create_access_list(&tx, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        // the router reads the WETH balances
+        assert!(access_list
+            .iter()
+            .any(|item| item.address == WETH.address()));
+        assert!(gas_used > 21000);
+        let tx = attach_access_list(tx, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(tx.access_list, Some(access_list));
+
+        // pulling WETH the sender doesn't have reverts
+        let params = unwrap_weth9_call_parameters(
+            &CurrencyAmount::from_raw_amount(WETH.clone(), 1).unwrap(),
+            FROM,
+            None,
+        );
+        let tx = builder.request(ChainConfig::MAINNET.swap_router02, params);
+        assert!(create_access_list(&tx, PROVIDER.clone(), *BLOCK_ID)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_from_now() {
+        let deadline = deadline_from_now(PROVIDER.clone(), Duration::from_secs(1800))
+            .await
+            .unwrap();
+        let block = PROVIDER
+            .get_block(BlockId::latest(), BlockTransactionsKind::Hashes)
+            .await
+            .unwrap()
+            .unwrap();
+        // a block may have been mined in between
+        let timestamp = deadline.to::<u64>() - 1800;
+        assert!(timestamp <= block.header.timestamp);
+        assert!(timestamp + 60 > block.header.timestamp);
+    }
+}
@@ -0,0 +1,223 @@
+//! ## USD Price Extension
+//! Prices a token in USD through the deepest route to a set of stablecoins, either directly or
+//! through an intermediary such as WETH, across all fee tiers.
+
+use crate::prelude::{Error, *};
+use alloy::{eips::BlockId, providers::Provider, transports::Transport};
+use alloy_primitives::{address, Address, ChainId};
+use futures_util::future::try_join_all;
+use rustc_hash::FxHashMap;
+use uniswap_sdk_core::prelude::*;
+
+/// USDC, USDT and DAI on Ethereum mainnet.
+pub const MAINNET_STABLECOINS: [Address; 3] = [
+    address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+    address!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+    address!("6B175474E89094C44Da98b954EedeAC495271d0F"),
+];
+
+/// Where [`get_usd_price`] looks for routes to USD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsdPriceConfig {
+    /// The deployment of the pools
+    pub deployment: PoolDeployment,
+    /// The stablecoins considered to be worth one USD
+    pub stablecoins: Vec<Address>,
+    /// The tokens a route may hop through, e.g. the wrapped native currency
+    pub intermediaries: Vec<Address>,
+}
+
+impl UsdPriceConfig {
+    /// Prices tokens against `stablecoins` directly or through the wrapped native currency of a
+    /// chain.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config`: The deployment on the chain
+    /// * `stablecoins`: The stablecoins on the chain
+    #[inline]
+    #[must_use]
+    pub fn new(config: &ChainConfig, stablecoins: Vec<Address>) -> Self {
+        Self {
+            deployment: config.pool_deployment(),
+            stablecoins,
+            intermediaries: vec![config.weth],
+        }
+    }
+
+    /// Prices tokens against USDC, USDT and DAI on Ethereum mainnet, directly or through WETH.
+    #[inline]
+    #[must_use]
+    pub fn mainnet() -> Self {
+        Self::new(&ChainConfig::MAINNET, MAINNET_STABLECOINS.to_vec())
+    }
+}
+
+/// The USD price of a token and the route it was discovered through.
+#[derive(Clone, Debug)]
+pub struct UsdPrice {
+    /// The mid price of the token in the stablecoin at the end of the route
+    pub price: Price<Token, Token>,
+    /// The route from the token to the stablecoin
+    pub route: Route<Token, Token, NoTickDataProvider>,
+    /// The smallest USD value of the virtual reserves of the tokens the route swaps into
+    pub depth: BigDecimal,
+}
+
+/// Get the USD price of a token through the deepest route to a stablecoin, either directly or
+/// through one of the intermediaries. The depth of a route is the USD value of the virtual reserves
+/// of its shallowest hop, and only the most liquid pool of each pair is considered.
+///
+/// ## Arguments
+///
+/// * `chain_id`: The chain id
+/// * `token`: The token to price
+/// * `config`: The stablecoins and intermediaries to route through
+/// * `provider`: The alloy provider
+/// * `block_id`: Optional block number to query
+#[inline]
+pub async fn get_usd_price<T, P>(
+    chain_id: ChainId,
+    token: Address,
+    config: &UsdPriceConfig,
+    provider: P,
+    block_id: Option<BlockId>,
+) -> Result<UsdPrice, Error>
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    let mut paths: Vec<Vec<Address>> = Vec::new();
+    for &stablecoin in &config.stablecoins {
+        if stablecoin == token {
+            continue;
+        }
+        paths.push(vec![token, stablecoin]);
+        for &intermediary in &config.intermediaries {
+            if intermediary != token && intermediary != stablecoin {
+                paths.push(vec![token, intermediary, stablecoin]);
+            }
+        }
+    }
+    let mut pairs: Vec<(Address, Address)> = paths
+        .iter()
+        .flat_map(|path| path.windows(2).map(|pair| (pair[0], pair[1])))
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    let pools = try_join_all(pairs.iter().map(|&(a, b)| {
+        get_pools_for_pair(
+            chain_id,
+            config.deployment,
+            a,
+            b,
+            &[],
+            provider.clone(),
+            block_id,
+        )
+    }))
+    .await?;
+    let deepest: FxHashMap<(Address, Address), Pool> = pairs
+        .into_iter()
+        .zip(pools)
+        .filter_map(|(pair, pools)| Some((pair, pools.into_iter().next()?)))
+        .collect();
+
+    let mut best: Option<UsdPrice> = None;
+    'paths: for path in paths {
+        let mut pools = Vec::with_capacity(path.len() - 1);
+        for pair in path.windows(2) {
+            match deepest.get(&(pair[0], pair[1])) {
+                Some(pool) => pools.push(pool.clone()),
+                None => continue 'paths,
+            }
+        }
+        let input = token_of(&pools[0], path[0]);
+        let output = token_of(&pools[pools.len() - 1], path[path.len() - 1]);
+        let route = Route::new(pools, input, output);
+        let depth = route_depth(&route)?;
+        if best.as_ref().map_or(true, |best| depth > best.depth) {
+            best = Some(UsdPrice {
+                price: route.mid_price()?,
+                route,
+                depth,
+            });
+        }
+    }
+    best.ok_or(Error::DataNotFound)
+}
+
+/// The token of a pool with the given address.
+fn token_of(pool: &Pool, address: Address) -> Token {
+    if pool.token0.address() == address {
+        pool.token0.clone()
+    } else {
+        pool.token1.clone()
+    }
+}
+
+/// The virtual reserve of a token in the current tick range of a pool.
+fn virtual_reserve(pool: &Pool, token: &Token) -> BigInt {
+    let liquidity = BigInt::from(pool.liquidity);
+    let sqrt_price_x96 = pool.sqrt_ratio_x96.to_big_int();
+    if pool.token0.equals(token) {
+        (liquidity << 96) / sqrt_price_x96
+    } else {
+        (liquidity * sqrt_price_x96) >> 96
+    }
+}
+
+/// The smallest value in the output token of the virtual reserves of the token each hop swaps into.
+fn route_depth(route: &Route<Token, Token, NoTickDataProvider>) -> Result<BigDecimal, Error> {
+    let path = route.token_path();
+    let mut depth: Option<BigDecimal> = None;
+    for (i, pool) in route.pools.iter().enumerate() {
+        let token = &path[i + 1];
+        let mut value =
+            CurrencyAmount::from_raw_amount(token.clone(), virtual_reserve(pool, token))?;
+        if i + 1 < route.pools.len() {
+            value = Route::new(
+                route.pools[i + 1..].to_vec(),
+                token.clone(),
+                route.output.clone(),
+            )
+            .mid_price()?
+            .quote(&value)?;
+        }
+        let value = BigDecimal::new(value.quotient(), route.output.decimals.into());
+        depth = Some(match depth {
+            Some(depth) => depth.min(value),
+            None => value,
+        });
+    }
+    Ok(depth.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[tokio::test]
+    async fn test_get_usd_price() {
+        let config = UsdPriceConfig::mainnet();
+        let UsdPrice { price, route, .. } =
+            get_usd_price(1, WETH.address(), &config, PROVIDER.clone(), *BLOCK_ID)
+                .await
+                .unwrap();
+        assert_eq!(route.pools.len(), 1);
+        assert!(MAINNET_STABLECOINS.contains(&route.output.address()));
+        let price: f64 = price.to_significant(6, None).unwrap().parse().unwrap();
+        assert!((1500.0..2500.0).contains(&price));
+
+        const LINK: Address = address!("514910771AF9Ca656af840dff83E8264EcF986CA");
+        let UsdPrice { price, route, .. } =
+            get_usd_price(1, LINK, &config, PROVIDER.clone(), *BLOCK_ID)
+                .await
+                .unwrap();
+        assert_eq!(route.token_path()[0].address(), LINK);
+        let price: f64 = price.to_significant(6, None).unwrap().parse().unwrap();
+        assert!((3.0..15.0).contains(&price));
+    }
+}
@@ -14,20 +14,84 @@
 //! - An [`extensions`](./src/extensions) feature for additional functionalities related to Uniswap
 //!   V3, including:
 //!
+//!     - [`data_source`](./src/extensions/data_source.rs) module with the `V3DataSource` trait
+//!       the extensions read on-chain state through, implemented for alloy providers, the
+//!       subgraph and a `MockPoolProvider` with canned responses for unit tests.
+//!     - [`cache`](./src/extensions/cache.rs) module with a `CachedClient` memoizing the state
+//!       read by any `V3DataSource` at pinned blocks and dropping the entries of reorged blocks.
 //!     - [`pool`](./src/extensions/pool.rs) module for creating a `Pool` struct from a pool key and
 //!       fetching the liquidity map within a tick range for the specified pool, using RPC client.
+//!     - [`pool_scanner`](./src/extensions/pool_scanner.rs) module for discovering the pools of a
+//!       factory from its `PoolCreated` events, incrementally with the `store` feature.
+//!     - [`pool_swaps`](./src/extensions/pool_swaps.rs) module for subscribing to the swaps of a
+//!       pool with their execution price.
+//!     - [`pool_tracker`](./src/extensions/pool_tracker.rs) module for mirroring the state of a
+//!       pool locally by applying its logs.
 //!     - [`position`](./src/extensions/position.rs) module for creating a `Position` struct from a
 //!       token id and fetching the state and pool for all positions of the specified owner, using
 //!       RPC client, etc.
+//!     - [`portfolio`](./src/extensions/portfolio.rs) module for summarizing all positions of an
+//!       owner grouped by pool.
+//!     - [`position_apr`](./src/extensions/position_apr.rs) module for estimating the fee APR of a
+//!       position from the fee growth inside its range.
+//!     - [`position_history`](./src/extensions/position_history.rs) module for reconstructing the
+//!       lifecycle of a position from the logs of the nonfungible position manager and computing
+//!       the fees it earned over a range of blocks.
 //!     - [`price_tick_conversions`](./src/extensions/price_tick_conversions.rs) module for
 //!       converting between prices and ticks.
+//!     - [`permit2`](./src/extensions/permit2.rs) module for fetching Permit2 allowances and
+//!       nonces.
+//!     - [`multicall3`](./src/extensions/multicall3.rs) module for batching view calls into
+//!       chunked Multicall3 calls.
+//!     - [`nft_svg`](./src/extensions/nft_svg.rs) module for rendering the SVG art of position
+//!       NFTs locally, identical to the image in their `tokenURI`.
+//!     - [`oracle`](./src/extensions/oracle.rs) module for reading the observation history,
+//!       time-weighted average ticks and prices and the realized volatility of a pool.
+//!     - [`chainlink`](./src/extensions/chainlink.rs) module for reading USD prices from
+//!       Chainlink feeds with staleness checks, to value positions independently of pool prices.
+//!     - [`quoter`](./src/extensions/quoter.rs) module for fetching quotes from QuoterV2 via
+//!       `eth_call`.
+//!     - [`retry`](./src/extensions/retry.rs) module for retrying failed RPC calls with
+//!       exponential backoff.
+//!     - [`simulate`](./src/extensions/simulate.rs) module for simulating mints, liquidity
+//!       increases and swaps from any address via `eth_call` with token balance and allowance
+//!       state overrides.
+//!     - [`storage`](./src/extensions/storage.rs) module for reading the state and tick bitmap of
+//!       a pool directly from its storage slots.
+//!     - [`token`](./src/extensions/token.rs) module for fetching the metadata of ERC20 tokens in
+//!       batches.
+//!     - [`gas`](./src/extensions/gas.rs) module for estimating the total cost of a transaction,
+//!       including the L1 data fee on OP Stack chains and Arbitrum.
+//!     - [`transaction`](./src/extensions/transaction.rs) module for assembling the produced
+//!       calldata into EIP-1559 transactions with the nonce, gas limit, fees and access list
+//!       filled in, and computing deadlines from the chain's clock.
+//!     - [`usd_price`](./src/extensions/usd_price.rs) module for pricing tokens in USD through
+//!       the deepest route to a set of stablecoins.
 //!     - [`ephemeral_tick_data_provider`](./src/extensions/ephemeral_tick_data_provider.rs) module for fetching ticks using
 //!       an [ephemeral contract](https://github.com/Aperture-Finance/Aperture-Lens/blob/904101e4daed59e02fd4b758b98b0749e70b583b/contracts/EphemeralGetPopulatedTicksInRange.sol)
 //!       in a single `eth_call`.
 //!     - [`ephemeral_tick_map_data_provider`](./src/extensions/ephemeral_tick_map_data_provider.rs)
 //!       fetches ticks in a single `eth_call` and creates a `TickMap`
+//!     - [`lazy_tick_data_provider`](./src/extensions/lazy_tick_data_provider.rs) fetches ticks
+//!       one bitmap word at a time on demand for async swap simulation
 //!     - [`tick_map`](./src/extensions/tick_map.rs) provides a way to access tick data directly
 //!       from a hashmap, supposedly more efficient than `TickList`
+//! - A [`subgraph`](./src/extensions/subgraph.rs) feature for fetching pools, positions, ticks and
+//!   historical data from the Uniswap V3 subgraph without an archive node.
+//! - A [`store`](./src/extensions/store.rs) feature with a `PoolStore` persisting snapshots of
+//!   pools and their ticks in an embedded sled database, keyed by chain, pool and block, and an
+//!   [`indexer`](./src/extensions/indexer.rs) maintaining the owners and history of positions
+//!   from the logs of the nonfungible position manager.
+//! - A [`serde`](./src/utils/serde_utils.rs) feature implementing `Serialize` and `Deserialize` for
+//!   pools, positions and ticks, and `with` helpers for tokens and prices, with big integers as
+//!   decimal strings.
+//! - A [`token-list`](./src/token_list.rs) feature for parsing Uniswap token lists into tokens,
+//!   filtered by chain, with their extension fields.
+//! - A [`wasm`](./src/wasm.rs) feature converting the errors into JavaScript errors for
+//!   `wasm-bindgen` exports. The core entities, math and calldata encoding compile to
+//!   `wasm32-unknown-unknown` without the `extensions` feature.
+//! - A [`test-utils`](./src/test_utils.rs) feature with an `AnvilFork` harness for integration
+//!   tests of calldata builders against an anvil fork of mainnet at a pinned block.
 
 #![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(
@@ -56,29 +120,55 @@
 extern crate alloc;
 
 pub mod abi;
+pub mod analytics;
+#[cfg(feature = "std")]
+pub mod backtest;
+pub mod chain;
 pub mod constants;
 pub mod entities;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod monte_carlo;
 pub mod multicall;
 pub mod nonfungible_position_manager;
 pub mod payments;
+pub mod permit2;
 pub mod quoter;
+#[cfg(feature = "std")]
+pub mod range_optimizer;
+pub mod rebalance;
 pub mod self_permit;
 pub mod staker;
 pub mod swap_router;
+pub mod swap_router02;
+#[cfg(feature = "token-list")]
+pub mod token_list;
+pub mod universal_router;
 pub mod utils;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub mod zap;
 
 #[cfg(feature = "extensions")]
 pub mod extensions;
 
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
 #[cfg(test)]
 mod tests;
 
 pub mod prelude {
     pub use crate::{
-        abi::*, constants::*, entities::*, error::*, multicall::*, nonfungible_position_manager::*,
-        payments::*, quoter::*, self_permit::*, staker::*, swap_router::*, utils::*,
+        abi::*, analytics::*, chain::*, constants::*, entities::*, error::*, multicall::*,
+        nonfungible_position_manager::*, payments::*, permit2::*, quoter::*, rebalance::*,
+        self_permit::*, staker::*, swap_router::*, swap_router02::*, universal_router::*, utils::*,
+        zap::*,
     };
+    #[cfg(feature = "std")]
+    pub use crate::{backtest::*, monte_carlo::*, range_optimizer::*};
+    #[cfg(feature = "token-list")]
+    pub use crate::token_list::*;
     pub use alloc::{
         string::{String, ToString},
         vec,
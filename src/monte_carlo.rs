@@ -0,0 +1,368 @@
+//! ## Monte Carlo
+//! Evaluates a liquidity providing [`Strategy`] over many simulated or user provided price paths
+//! with the [`Backtester`], and summarizes the distribution of its fees, impermanent loss and
+//! profit and loss by percentiles.
+//!
+//! Since simulated paths have no swaps, the fees are modeled from the ratio of the daily volume to
+//! the total value locked of the pool: a position earns the fee APR of a full range position scaled
+//! by its capital efficiency while the price is in its range.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::aliases::I24;
+use num_traits::ToPrimitive;
+
+/// The inputs of a Monte Carlo simulation shared by all paths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonteCarloConfig {
+    /// The inputs of the backtest of each path
+    pub backtest: BacktestConfig,
+    /// The length of each path in seconds
+    pub horizon: u64,
+    /// The expected daily volume of the pool divided by its total value locked
+    pub daily_volume_to_tvl: f64,
+}
+
+/// The parameters of geometric Brownian motion price paths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GbmParams {
+    /// The raw price of token0 in token1 at the start
+    pub price: f64,
+    /// The annualized drift of the price
+    pub drift: f64,
+    /// The annualized volatility of the log price
+    pub volatility: f64,
+    /// The number of steps of each path
+    pub steps: u32,
+    /// The number of paths
+    pub paths: u32,
+    /// The seed of the random number generator, the same seed generates the same paths
+    pub seed: u64,
+}
+
+/// The 5th, 25th, 50th, 75th and 95th percentiles of a metric across paths.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Percentiles {
+    /// The 5th percentile
+    pub p5: f64,
+    /// The 25th percentile
+    pub p25: f64,
+    /// The median
+    pub p50: f64,
+    /// The 75th percentile
+    pub p75: f64,
+    /// The 95th percentile
+    pub p95: f64,
+}
+
+impl Percentiles {
+    /// Computes the percentiles of a set of values with linear interpolation.
+    ///
+    /// ## Arguments
+    ///
+    /// * `values`: The values, in any order
+    #[inline]
+    #[must_use]
+    pub fn new(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let last = (values.len() - 1).to_f64().unwrap();
+        let percentile = |q: f64| {
+            let rank = q * last;
+            let lower = rank.floor().to_usize().unwrap();
+            let upper = rank.ceil().to_usize().unwrap();
+            values[lower] + (values[upper] - values[lower]) * (rank - rank.floor())
+        };
+        Self {
+            p5: percentile(0.05),
+            p25: percentile(0.25),
+            p50: percentile(0.5),
+            p75: percentile(0.75),
+            p95: percentile(0.95),
+        }
+    }
+}
+
+/// The outcomes of a strategy across paths.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonteCarloReport {
+    /// The report of the backtest of each path
+    pub reports: Vec<BacktestReport>,
+}
+
+impl MonteCarloReport {
+    /// The percentiles of a metric of the backtest reports.
+    ///
+    /// ## Arguments
+    ///
+    /// * `metric`: The metric of a report, e.g. [`BacktestReport::pnl`]
+    #[inline]
+    #[must_use]
+    pub fn percentiles(&self, metric: impl Fn(&BacktestReport) -> f64) -> Percentiles {
+        Percentiles::new(self.reports.iter().map(metric).collect())
+    }
+
+    /// The percentiles of the value of the fees earned.
+    #[inline]
+    #[must_use]
+    pub fn fees(&self) -> Percentiles {
+        self.percentiles(|report| report.fees_value)
+    }
+
+    /// The percentiles of the impermanent loss.
+    #[inline]
+    #[must_use]
+    pub fn impermanent_loss(&self) -> Percentiles {
+        self.percentiles(|report| report.impermanent_loss)
+    }
+
+    /// The percentiles of the profit and loss relative to the initial value.
+    #[inline]
+    #[must_use]
+    pub fn pnl(&self) -> Percentiles {
+        self.percentiles(BacktestReport::pnl)
+    }
+}
+
+/// Generates price paths following a geometric Brownian motion.
+///
+/// ## Arguments
+///
+/// * `params`: The parameters of the paths
+/// * `horizon`: The length of each path in seconds
+///
+/// ## Returns
+///
+/// The prices of each path at evenly spaced steps, starting with `params.price`, or
+/// [`Error::InvalidWindow`] if `params.steps` is zero
+#[inline]
+pub fn gbm_paths(params: &GbmParams, horizon: u64) -> Result<Vec<Vec<f64>>, Error> {
+    if params.steps == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let dt =
+        horizon.to_f64().unwrap() / SECONDS_PER_YEAR.to_f64().unwrap() / f64::from(params.steps);
+    let drift = (params.drift - params.volatility * params.volatility / 2.0) * dt;
+    let diffusion = params.volatility * dt.sqrt();
+    let mut rng = SplitMix64(params.seed);
+    Ok((0..params.paths)
+        .map(|_| {
+            let mut log_price = params.price.ln();
+            let mut path = Vec::with_capacity(usize::try_from(params.steps).unwrap() + 1);
+            path.push(params.price);
+            for _ in 0..params.steps {
+                log_price += drift + diffusion * rng.next_normal();
+                path.push(log_price.exp());
+            }
+            path
+        })
+        .collect())
+}
+
+/// Runs a strategy over price paths.
+///
+/// ## Arguments
+///
+/// * `config`: The inputs shared by all paths
+/// * `strategy`: The strategy, cloned for each path
+/// * `paths`: The raw prices of token0 in token1 of each path at evenly spaced steps over the
+///   horizon, starting with the initial price
+///
+/// ## Returns
+///
+/// The reports of the strategy over each path, or [`Error::NoSwaps`] if a path is empty
+#[inline]
+pub fn evaluate_paths<S, P>(
+    config: &MonteCarloConfig,
+    strategy: &S,
+    paths: impl IntoIterator<Item = P>,
+) -> Result<MonteCarloReport, Error>
+where
+    S: Strategy + Clone,
+    P: AsRef<[f64]>,
+{
+    let fee_rate = f64::from(config.backtest.fee.fee()) / 1e6;
+    let full_range_apr = config.daily_volume_to_tvl * fee_rate * 365.0;
+    let reports = paths
+        .into_iter()
+        .map(|path| {
+            let path = path.as_ref();
+            let Some((&first, rest)) = path.split_first() else {
+                return Err(Error::NoSwaps);
+            };
+            let steps = u64::try_from(rest.len()).unwrap().max(1);
+            let seconds = config.horizon / steps;
+            let years = seconds.to_f64().unwrap() / SECONDS_PER_YEAR.to_f64().unwrap();
+            let sqrt_price = first.sqrt();
+            let mut backtester = Backtester::new(
+                config.backtest,
+                strategy.clone(),
+                0,
+                sqrt_price,
+                price_to_tick(first),
+            )?;
+            let mut sqrt_price_before = sqrt_price;
+            for (i, &price) in (1..).zip(rest) {
+                // a full range position of liquidity L is worth 2 L sqrt(p), half in each token
+                let fees = backtester.liquidity() * full_range_apr * years;
+                backtester.step(
+                    seconds * i,
+                    price.sqrt(),
+                    price_to_tick(price),
+                    (fees / sqrt_price_before, fees * sqrt_price_before),
+                    0.0,
+                )?;
+                sqrt_price_before = price.sqrt();
+            }
+            Ok(backtester.report())
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(MonteCarloReport { reports })
+}
+
+/// Runs a strategy over price paths following a geometric Brownian motion, see [`gbm_paths`].
+///
+/// ## Arguments
+///
+/// * `config`: The inputs shared by all paths
+/// * `params`: The parameters of the paths
+/// * `strategy`: The strategy, cloned for each path
+#[inline]
+pub fn simulate_gbm<S: Strategy + Clone>(
+    config: &MonteCarloConfig,
+    params: &GbmParams,
+    strategy: &S,
+) -> Result<MonteCarloReport, Error> {
+    evaluate_paths(config, strategy, gbm_paths(params, config.horizon)?)
+}
+
+/// The tick of a raw price, rounded down and clamped to the tick range.
+fn price_to_tick(price: f64) -> I24 {
+    let tick = (price.ln() / 1.0001_f64.ln())
+        .floor()
+        .to_i32()
+        .unwrap_or(MIN_TICK_I32)
+        .clamp(MIN_TICK_I32, MAX_TICK_I32);
+    I24::try_from(tick).unwrap()
+}
+
+/// The SplitMix64 pseudorandom number generator, which is enough for simulation and keeps the
+/// paths reproducible from a seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform sample in (0, 1].
+    fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1).to_f64().unwrap() / (1_u64 << 53).to_f64().unwrap()
+    }
+
+    /// A standard normal sample by the Box-Muller transform.
+    fn next_normal(&mut self) -> f64 {
+        let u1 = self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * core::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: MonteCarloConfig = MonteCarloConfig {
+        backtest: BacktestConfig {
+            fee: FeeAmount::MEDIUM,
+            amount0: 1e18,
+            amount1: 1e18,
+            gas_cost: 0.0,
+        },
+        horizon: 30 * 24 * 60 * 60,
+        daily_volume_to_tvl: 0.5,
+    };
+
+    const PARAMS: GbmParams = GbmParams {
+        price: 1.0,
+        drift: 0.0,
+        volatility: 0.8,
+        steps: 30,
+        paths: 200,
+        seed: 42,
+    };
+
+    #[test]
+    fn test_percentiles() {
+        let percentiles = Percentiles::new((0..=100).rev().map(f64::from).collect());
+        assert_eq!(percentiles.p5, 5.0);
+        assert_eq!(percentiles.p50, 50.0);
+        assert_eq!(percentiles.p95, 95.0);
+        assert_eq!(Percentiles::new(vec![]), Percentiles::default());
+    }
+
+    #[test]
+    fn test_gbm_paths() {
+        let paths = gbm_paths(&PARAMS, CONFIG.horizon).unwrap();
+        assert_eq!(paths.len(), 200);
+        assert!(paths.iter().all(|path| path.len() == 31 && path[0] == 1.0));
+        assert_eq!(paths, gbm_paths(&PARAMS, CONFIG.horizon).unwrap());
+        // the price is a martingale without drift
+        let mean = paths.iter().map(|path| path[30]).sum::<f64>() / 200.0;
+        assert!((mean - 1.0).abs() < 0.1);
+        let params = GbmParams { steps: 0, ..PARAMS };
+        assert!(matches!(
+            gbm_paths(&params, CONFIG.horizon),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_paths_constant_price() {
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        let report = evaluate_paths(&CONFIG, &strategy, [vec![1.0; 31]]).unwrap();
+        let report = &report.reports[0];
+        assert_eq!(report.end, CONFIG.horizon / 30 * 30);
+        assert!(report.impermanent_loss.abs() < 1e6);
+        // a full range position would earn 0.5 * 0.3% * 30 days of its value
+        let full_range_fees = 2e18 * 0.5 * 0.003 * 30.0;
+        assert!(report.fees_value > full_range_fees);
+    }
+
+    #[test]
+    fn test_evaluate_paths_empty_path() {
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-600).unwrap(),
+            tick_upper: I24::try_from(600).unwrap(),
+        };
+        assert!(matches!(
+            evaluate_paths(&CONFIG, &strategy, [vec![1.0; 31], vec![]]),
+            Err(Error::NoSwaps)
+        ));
+    }
+
+    #[test]
+    fn test_simulate_gbm() {
+        let strategy = PassiveStrategy {
+            tick_lower: I24::try_from(-6000).unwrap(),
+            tick_upper: I24::try_from(6000).unwrap(),
+        };
+        let report = simulate_gbm(&CONFIG, &PARAMS, &strategy).unwrap();
+        assert_eq!(report.reports.len(), 200);
+        let il = report.impermanent_loss();
+        assert!(il.p5 <= il.p50 && il.p50 <= il.p95);
+        assert!(il.p95 <= 1e6);
+        let fees = report.fees();
+        assert!(fees.p5 > 0.0);
+        let pnl = report.pnl();
+        assert!(pnl.p5 <= pnl.p95);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use alloy_primitives::Bytes;
+use alloy_primitives::{Bytes, U256};
 use alloy_sol_types::{Error, SolCall};
 
 #[inline]
@@ -13,6 +13,19 @@ pub fn encode_multicall<B: Into<Bytes>>(data: Vec<B>) -> Bytes {
     }
 }
 
+/// Encodes the calls with a deadline check via `multicall(uint256,bytes[])`, as supported by
+/// SwapRouter02.
+#[inline]
+#[must_use]
+pub fn encode_multicall_with_deadline<B: Into<Bytes>>(deadline: U256, data: Vec<B>) -> Bytes {
+    IMulticallExtended::multicallCall {
+        deadline,
+        data: data.into_iter().map(Into::into).collect(),
+    }
+    .abi_encode()
+    .into()
+}
+
 #[inline]
 pub fn decode_multicall<B, E>(encoded: E) -> Result<Vec<B>, Error>
 where
@@ -76,6 +89,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_multicall_with_deadline() {
+        let calldata = encode_multicall_with_deadline(U256::from(123), vec![vec![0x01]]);
+        assert_eq!(calldata[..4], hex!("5ae401dc"));
+        let decoded = IMulticallExtended::multicallCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(decoded.deadline, U256::from(123));
+        assert_eq!(decoded.data, vec![Bytes::from(vec![0x01])]);
+    }
+
     mod decode {
         use super::*;
 
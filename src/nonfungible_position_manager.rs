@@ -17,6 +17,7 @@ pub struct IncreaseSpecificOptions {
     pub token_id: U256,
 }
 
+/// Whether [`add_call_parameters`] mints a new position or increases an existing one.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AddLiquiditySpecificOptions {
     Mint(MintSpecificOptions),
@@ -26,7 +27,7 @@ pub enum AddLiquiditySpecificOptions {
 /// Options for producing the calldata to add liquidity.
 #[derive(Debug, Clone, PartialEq)]
 pub struct AddLiquidityOptions {
-    /// How much the pool price is allowed to move.
+    /// How much the pool price is allowed to move, between 0 and 100%.
     pub slippage_tolerance: Percent,
     /// When the transaction expires, in epoch seconds.
     pub deadline: U256,
@@ -86,7 +87,7 @@ pub struct RemoveLiquidityOptions<Currency0: BaseCurrency, Currency1: BaseCurren
     pub token_id: U256,
     /// The percentage of position liquidity to exit.
     pub liquidity_percentage: Percent,
-    /// How much the pool price is allowed to move.
+    /// How much the pool price is allowed to move, between 0 and 100%.
     pub slippage_tolerance: Percent,
     /// When the transaction expires, in epoch seconds.
     pub deadline: U256,
@@ -111,6 +112,12 @@ fn encode_create<TP: TickDataProvider>(pool: &Pool<TP>) -> Bytes {
     .into()
 }
 
+/// Produces the calldata for `createAndInitializePoolIfNecessary`, which creates the pool if it
+/// doesn't exist and initializes it at the current price of `pool` if it isn't initialized yet
+///
+/// ## Arguments
+///
+/// * `pool`: The pool to create, at the price to initialize it at
 #[inline]
 pub fn create_call_parameters<TP: TickDataProvider>(pool: &Pool<TP>) -> MethodParameters {
     MethodParameters {
@@ -119,12 +126,20 @@ pub fn create_call_parameters<TP: TickDataProvider>(pool: &Pool<TP>) -> MethodPa
     }
 }
 
+/// Produces the calldata for minting a new position or increasing the liquidity of an existing
+/// one, optionally creating the pool and spending native ether
+///
+/// ## Arguments
+///
+/// * `position`: The position to add liquidity to
+/// * `options`: Additional information necessary for generating the calldata
 #[inline]
 pub fn add_call_parameters<TP: TickDataProvider>(
     position: &mut Position<TP>,
     options: AddLiquidityOptions,
 ) -> Result<MethodParameters, Error> {
     assert!(position.liquidity > 0, "ZERO_LIQUIDITY");
+    validate_slippage(&options.slippage_tolerance)?;
 
     let mut calldatas: Vec<Bytes> = Vec::with_capacity(5);
 
@@ -274,6 +289,13 @@ fn encode_collect<Currency0: BaseCurrency, Currency1: BaseCurrency>(
     calldatas
 }
 
+/// Produces the calldata for collecting the fees and tokens owed to a position. If either
+/// currency is native, the tokens are collected to the manager and then unwrapped and swept to
+/// the recipient.
+///
+/// ## Arguments
+///
+/// * `options`: The position to collect for, the expected amounts owed and the recipient
 #[inline]
 pub fn collect_call_parameters<Currency0: BaseCurrency, Currency1: BaseCurrency>(
     options: &CollectOptions<Currency0, Currency1>,
@@ -302,6 +324,7 @@ where
     Currency1: BaseCurrency,
     TP: TickDataProvider,
 {
+    validate_slippage(&options.slippage_tolerance)?;
     let mut calldatas: Vec<Bytes> = Vec::with_capacity(6);
 
     let deadline = options.deadline;
@@ -544,6 +567,33 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_add_call_parameters_invalid_slippage() {
+        let mut position = Position::new(
+            POOL_0_1.clone(),
+            1,
+            -FeeAmount::MEDIUM.tick_spacing().as_i32(),
+            FeeAmount::MEDIUM.tick_spacing().as_i32(),
+        );
+        for slippage_tolerance in [Percent::new(-1, 100), Percent::new(101, 100)] {
+            let result = add_call_parameters(
+                &mut position,
+                AddLiquidityOptions {
+                    slippage_tolerance,
+                    deadline: DEADLINE,
+                    use_native: None,
+                    token0_permit: None,
+                    token1_permit: None,
+                    specific_opts: AddLiquiditySpecificOptions::Mint(MintSpecificOptions {
+                        recipient: RECIPIENT,
+                        create_pool: false,
+                    }),
+                },
+            );
+            assert!(matches!(result, Err(Error::InvalidSlippage)));
+        }
+    }
+
     #[test]
     #[should_panic(expected = "NO_WETH")]
     fn test_add_call_parameters_no_weth() {
@@ -714,6 +764,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_collect_call_parameters_eth_as_currency0() {
+        let MethodParameters { calldata, value } = collect_call_parameters(&CollectOptions {
+            token_id: TOKEN_ID,
+            expected_currency_owed0: CurrencyAmount::from_raw_amount(ETHER.clone(), 10).unwrap(),
+            expected_currency_owed1: CurrencyAmount::from_raw_amount(TOKEN1.clone(), 20).unwrap(),
+            recipient: RECIPIENT,
+        });
+        assert_eq!(value, U256::ZERO);
+        let calldatas: Vec<Bytes> = decode_multicall(calldata).unwrap();
+        assert_eq!(calldatas.len(), 3);
+        let collect =
+            INonfungiblePositionManager::collectCall::abi_decode(&calldatas[0], true).unwrap();
+        assert_eq!(collect.params.recipient, Address::ZERO);
+        assert_eq!(
+            calldatas[1],
+            encode_unwrap_weth9(uint!(10_U256), RECIPIENT, None)
+        );
+        assert_eq!(
+            calldatas[2],
+            encode_sweep_token(TOKEN1.address(), uint!(20_U256), RECIPIENT, None)
+        );
+    }
+
     #[test]
     #[should_panic(expected = "ZERO_LIQUIDITY")]
     fn test_remove_call_parameters_zero_liquidity() {
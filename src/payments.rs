@@ -1,4 +1,4 @@
-use crate::prelude::{FromBig, IPeripheryPaymentsWithFee};
+use crate::prelude::{FromBig, IPeripheryPaymentsExtended, IPeripheryPaymentsWithFee};
 use alloy_primitives::{Address, Bytes, U256};
 use alloy_sol_types::SolCall;
 use uniswap_sdk_core::prelude::{FractionBase, Percent};
@@ -12,7 +12,7 @@ pub struct FeeOptions {
 }
 
 #[inline]
-fn encode_fee_bips(fee: Percent) -> U256 {
+pub(crate) fn encode_fee_bips(fee: Percent) -> U256 {
     U256::from_big_int((fee * Percent::new(10000, 1)).quotient())
 }
 
@@ -77,6 +77,26 @@ pub fn encode_refund_eth() -> Bytes {
         .into()
 }
 
+/// Encodes `wrapETH` of SwapRouter02, which wraps `value` of the ether held by the router into
+/// WETH held by the router.
+#[inline]
+#[must_use]
+pub fn encode_wrap_eth(value: U256) -> Bytes {
+    IPeripheryPaymentsExtended::wrapETHCall { value }
+        .abi_encode()
+        .into()
+}
+
+/// Encodes `pull` of SwapRouter02, which transfers `value` of `token` from the sender to the
+/// router.
+#[inline]
+#[must_use]
+pub fn encode_pull(token: Address, value: U256) -> Bytes {
+    IPeripheryPaymentsExtended::pullCall { token, value }
+        .abi_encode()
+        .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,4 +152,19 @@ mod tests {
         let calldata = encode_refund_eth();
         assert_eq!(calldata.to_vec(), hex!("12210e8a"));
     }
+
+    #[test]
+    fn test_encode_wrap_eth() {
+        let calldata = encode_wrap_eth(AMOUNT);
+        let decoded = IPeripheryPaymentsExtended::wrapETHCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(decoded.value, AMOUNT);
+    }
+
+    #[test]
+    fn test_encode_pull() {
+        let calldata = encode_pull(TOKEN, AMOUNT);
+        let decoded = IPeripheryPaymentsExtended::pullCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(decoded.token, TOKEN);
+        assert_eq!(decoded.value, AMOUNT);
+    }
 }
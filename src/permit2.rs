@@ -0,0 +1,237 @@
+//! ## Permit2
+//! EIP-712 typed data and calldata for [Permit2](https://github.com/Uniswap/permit2) allowance
+//! transfers, ported from the TypeScript [permit2-sdk](https://github.com/Uniswap/sdks/tree/main/sdks/permit2-sdk).
+
+use crate::prelude::*;
+use alloy_primitives::{address, aliases::U48, Address, Bytes, U160, U256};
+use alloy_sol_types::{eip712_domain, Eip712Domain, SolCall, SolStruct};
+
+/// The canonical Permit2 address, the same on all supported chains.
+pub const PERMIT2_ADDRESS: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+/// The maximum amount of an allowance transfer.
+pub const MAX_ALLOWANCE_TRANSFER_AMOUNT: U160 = U160::MAX;
+
+/// The maximum expiration of an allowance.
+pub const MAX_ALLOWANCE_EXPIRATION: U48 = U48::MAX;
+
+/// The maximum signature deadline of a permit.
+pub const MAX_SIG_DEADLINE: U256 = U256::MAX;
+
+/// A signed Permit2 allowance for a single token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permit2Permit {
+    /// The permit that was signed
+    pub permit: IAllowanceTransfer::PermitSingle,
+    /// The signature over the permit
+    pub signature: Bytes,
+}
+
+/// A signed Permit2 allowance for multiple tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permit2PermitBatch {
+    /// The permit that was signed
+    pub permit: IAllowanceTransfer::PermitBatch,
+    /// The signature over the permit
+    pub signature: Bytes,
+}
+
+/// Get the EIP-712 domain of the Permit2 contract.
+///
+/// ## Arguments
+///
+/// * `permit2`: The address of the Permit2 contract
+/// * `chain_id`: The chain ID
+#[inline]
+#[must_use]
+pub fn permit2_domain(permit2: Address, chain_id: u64) -> Eip712Domain {
+    eip712_domain! {
+        name: "Permit2",
+        chain_id: chain_id,
+        verifying_contract: permit2,
+    }
+}
+
+/// Get the EIP-712 domain and values to sign for a Permit2 `PermitSingle` or `PermitBatch`.
+///
+/// ## Arguments
+///
+/// * `permit`: The Permit2 permit
+/// * `permit2`: The address of the Permit2 contract
+/// * `chain_id`: The chain ID
+///
+/// ## Examples
+///
+/// ```
+/// use alloy_primitives::{address, aliases::U48, PrimitiveSignature, B256, U256};
+/// use alloy_signer::SignerSync;
+/// use alloy_signer_local::PrivateKeySigner;
+/// use alloy_sol_types::SolStruct;
+/// use uniswap_v3_sdk::prelude::*;
+///
+/// let signer = PrivateKeySigner::random();
+/// let permit = IAllowanceTransfer::PermitSingle {
+///     details: IAllowanceTransfer::PermitDetails {
+///         token: address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+///         amount: MAX_ALLOWANCE_TRANSFER_AMOUNT,
+///         expiration: MAX_ALLOWANCE_EXPIRATION,
+///         nonce: U48::ZERO,
+///     },
+///     spender: address!("3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"),
+///     sigDeadline: U256::from(1700000000),
+/// };
+/// let data = get_permit2_data(permit.clone(), PERMIT2_ADDRESS, 1);
+///
+/// // Derive the EIP-712 signing hash.
+/// let hash: B256 = data.values.eip712_signing_hash(&data.domain);
+///
+/// let signature: PrimitiveSignature = signer.sign_hash_sync(&hash).unwrap();
+/// assert_eq!(
+///     signature.recover_address_from_prehash(&hash).unwrap(),
+///     signer.address()
+/// );
+///
+/// let permit = Permit2Permit {
+///     permit,
+///     signature: signature.as_bytes().into(),
+/// };
+/// ```
+#[inline]
+#[must_use]
+pub fn get_permit2_data<P: SolStruct>(
+    permit: P,
+    permit2: Address,
+    chain_id: u64,
+) -> ERC20PermitData<P> {
+    ERC20PermitData {
+        domain: permit2_domain(permit2, chain_id),
+        values: permit,
+    }
+}
+
+/// Encodes a call to `Permit2.permit` that sets the allowance of a single token.
+///
+/// ## Arguments
+///
+/// * `owner`: The owner of the tokens who signed the permit
+/// * `permit`: The signed permit
+#[inline]
+#[must_use]
+pub fn encode_permit2_permit(owner: Address, permit: Permit2Permit) -> Bytes {
+    IAllowanceTransfer::permit_0Call {
+        owner,
+        permitSingle: permit.permit,
+        signature: permit.signature,
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Encodes a call to `Permit2.permit` that sets the allowances of multiple tokens.
+///
+/// ## Arguments
+///
+/// * `owner`: The owner of the tokens who signed the permit
+/// * `permit`: The signed permit
+#[inline]
+#[must_use]
+pub fn encode_permit2_permit_batch(owner: Address, permit: Permit2PermitBatch) -> Bytes {
+    IAllowanceTransfer::permit_1Call {
+        owner,
+        permitBatch: permit.permit,
+        signature: permit.signature,
+    }
+    .abi_encode()
+    .into()
+}
+
+impl From<Permit2Permit> for Command {
+    #[inline]
+    fn from(Permit2Permit { permit, signature }: Permit2Permit) -> Self {
+        Self::Permit2Permit {
+            permit_single: permit,
+            signature,
+        }
+    }
+}
+
+impl From<Permit2PermitBatch> for Command {
+    #[inline]
+    fn from(Permit2PermitBatch { permit, signature }: Permit2PermitBatch) -> Self {
+        Self::Permit2PermitBatch {
+            permit_batch: permit,
+            signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    fn permit_details(token: Address, nonce: u64) -> IAllowanceTransfer::PermitDetails {
+        IAllowanceTransfer::PermitDetails {
+            token,
+            amount: MAX_ALLOWANCE_TRANSFER_AMOUNT,
+            expiration: MAX_ALLOWANCE_EXPIRATION,
+            nonce: U48::from(nonce),
+        }
+    }
+
+    #[test]
+    fn test_type_hashes() {
+        let permit = IAllowanceTransfer::PermitSingle {
+            details: permit_details(Address::ZERO, 0),
+            spender: Address::ZERO,
+            sigDeadline: MAX_SIG_DEADLINE,
+        };
+        assert_eq!(
+            permit.eip712_type_hash(),
+            keccak256("PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)")
+        );
+        let permit = IAllowanceTransfer::PermitBatch {
+            details: vec![permit_details(Address::ZERO, 0)],
+            spender: Address::ZERO,
+            sigDeadline: MAX_SIG_DEADLINE,
+        };
+        assert_eq!(
+            permit.eip712_type_hash(),
+            keccak256("PermitBatch(PermitDetails[] details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)")
+        );
+    }
+
+    #[test]
+    fn test_permit2_domain() {
+        let domain = permit2_domain(PERMIT2_ADDRESS, 1);
+        assert_eq!(domain.name.as_deref(), Some("Permit2"));
+        assert_eq!(domain.version, None);
+        assert_eq!(domain.chain_id, Some(U256::from(1)));
+        assert_eq!(domain.verifying_contract, Some(PERMIT2_ADDRESS));
+    }
+
+    #[test]
+    fn test_encode_permit2_permit_batch() {
+        let owner = address!("0000000000000000000000000000000000000001");
+        let permit = Permit2PermitBatch {
+            permit: IAllowanceTransfer::PermitBatch {
+                details: vec![
+                    permit_details(address!("0000000000000000000000000000000000000002"), 0),
+                    permit_details(address!("0000000000000000000000000000000000000003"), 1),
+                ],
+                spender: address!("0000000000000000000000000000000000000004"),
+                sigDeadline: MAX_SIG_DEADLINE,
+            },
+            signature: Bytes::from(vec![1; 65]),
+        };
+        let calldata = encode_permit2_permit_batch(owner, permit.clone());
+        let decoded = IAllowanceTransfer::permit_1Call::abi_decode(&calldata, true).unwrap();
+        assert_eq!(decoded.owner, owner);
+        assert_eq!(decoded.permitBatch, permit.permit);
+        assert_eq!(decoded.signature, permit.signature);
+
+        let mut planner = RoutePlanner::new();
+        planner.add_command(permit.into(), false);
+        assert_eq!(planner.commands, vec![0x03]);
+    }
+}
@@ -0,0 +1,337 @@
+//! ## Range Optimizer
+//! Suggests tick ranges for a new position from the estimated volatility of the price, a time
+//! horizon and the fee tier of a pool, by trading off the expected fees earned in range against the
+//! expected impermanent loss.
+//!
+//! The log price is modeled as a driftless Brownian motion with the given annualized volatility,
+//! see [`realized_volatility`] and the `estimate_volatility` extension to estimate it.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::aliases::I24;
+use num_traits::{FromPrimitive, ToPrimitive};
+use uniswap_sdk_core::prelude::*;
+
+/// The number of points used to integrate over the distribution of the price.
+const INTEGRATION_STEPS: u32 = 64;
+
+/// The inputs of the range optimizer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeOptimizerParams {
+    /// The current tick of the pool
+    pub tick_current: I24,
+    /// The fee tier of the pool
+    pub fee: FeeAmount,
+    /// The annualized volatility of the log price
+    pub volatility: f64,
+    /// The time horizon of the position in seconds
+    pub horizon: u64,
+    /// The expected daily volume of the pool divided by its total value locked
+    pub daily_volume_to_tvl: f64,
+}
+
+/// A candidate range with its expected outcomes over the horizon, as fractions of the value of the
+/// position when entered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeEstimate {
+    /// The lower tick of the range
+    pub tick_lower: I24,
+    /// The upper tick of the range
+    pub tick_upper: I24,
+    /// The liquidity per unit of value relative to a full range position
+    pub capital_efficiency: f64,
+    /// The expected fraction of the horizon the price spends in the range
+    pub time_in_range: f64,
+    /// The expected fees earned
+    pub expected_fees: f64,
+    /// The expected impermanent loss at the end of the horizon, at most 0
+    pub expected_impermanent_loss: f64,
+    /// The expected fees plus impermanent loss, annualized
+    pub expected_apr: f64,
+}
+
+/// The default objective of [`optimize_range`], the expected fees plus impermanent loss.
+#[inline]
+#[must_use]
+pub fn expected_net_return(estimate: &RangeEstimate) -> f64 {
+    estimate.expected_fees + estimate.expected_impermanent_loss
+}
+
+/// Estimates the expected fees and impermanent loss of a range over the horizon.
+///
+/// ## Arguments
+///
+/// * `params`: The inputs of the range optimizer
+/// * `tick_lower`: The lower tick of the range, at most the current tick
+/// * `tick_upper`: The upper tick of the range, greater than the current tick
+///
+/// ## Returns
+///
+/// The estimate, [`Error::InvalidWindow`] if the horizon is zero or [`Error::InvalidRange`] if the
+/// range doesn't contain the current tick
+#[inline]
+pub fn estimate_range(
+    params: &RangeOptimizerParams,
+    tick_lower: I24,
+    tick_upper: I24,
+) -> Result<RangeEstimate, Error> {
+    if params.horizon == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    if !(tick_lower <= params.tick_current && params.tick_current < tick_upper) {
+        return Err(Error::InvalidRange);
+    }
+    let years = params.horizon.to_f64().unwrap() / SECONDS_PER_YEAR.to_f64().unwrap();
+    let log_tick = 1.0001_f64.ln();
+    // the log price distances from the current price to the bounds
+    let lower = f64::from((tick_lower - params.tick_current).as_i32()) * log_tick;
+    let upper = f64::from((tick_upper - params.tick_current).as_i32()) * log_tick;
+    // the value of a unit of liquidity is 2 - sqrt(p_a / p) - sqrt(p / p_b) times that of a full
+    // range position
+    let capital_efficiency = 2.0 / (2.0 - (lower / 2.0).exp() - (-upper / 2.0).exp());
+    let sigma = params.volatility;
+    let time_in_range = if sigma > 0.0 {
+        (1..=INTEGRATION_STEPS)
+            .map(|i| {
+                let t = years * (f64::from(i) - 0.5) / f64::from(INTEGRATION_STEPS);
+                let std_dev = sigma * t.sqrt();
+                normal_cdf(upper / std_dev) - normal_cdf(lower / std_dev)
+            })
+            .sum::<f64>()
+            / f64::from(INTEGRATION_STEPS)
+    } else {
+        1.0
+    };
+    let fee_rate = f64::from(params.fee.fee()) / 1e6;
+    let full_range_apr = params.daily_volume_to_tvl * fee_rate * 365.0;
+    let expected_fees = full_range_apr * capital_efficiency * time_in_range * years;
+    let expected_impermanent_loss = expected_impermanent_loss(
+        sigma * years.sqrt(),
+        (tick_lower - params.tick_current).max(MIN_TICK),
+        (tick_upper - params.tick_current).min(MAX_TICK),
+    )?;
+    Ok(RangeEstimate {
+        tick_lower,
+        tick_upper,
+        capital_efficiency,
+        time_in_range,
+        expected_fees,
+        expected_impermanent_loss,
+        expected_apr: (expected_fees + expected_impermanent_loss) / years,
+    })
+}
+
+/// Suggests ranges around the current tick, ordered from the best to the worst by `objective`.
+///
+/// The candidates are ranges aligned to the tick spacing of the fee tier, with half widths from a
+/// quarter to four times the standard deviation of the log price over the horizon, and at least
+/// one tick spacing. Candidates that don't contain the current tick once clamped to the usable
+/// ticks, e.g. near [`MAX_TICK`], are skipped.
+///
+/// ## Arguments
+///
+/// * `params`: The inputs of the range optimizer
+/// * `objective`: The score of a candidate to maximize, e.g. [`expected_net_return`]
+///
+/// ## Returns
+///
+/// The estimates of the candidates, or [`Error::InvalidWindow`] if the horizon is zero
+#[inline]
+pub fn optimize_range(
+    params: &RangeOptimizerParams,
+    objective: impl Fn(&RangeEstimate) -> f64,
+) -> Result<Vec<RangeEstimate>, Error> {
+    if params.horizon == 0 {
+        return Err(Error::InvalidWindow);
+    }
+    let tick_spacing = params.fee.tick_spacing().as_i32();
+    let years = params.horizon.to_f64().unwrap() / SECONDS_PER_YEAR.to_f64().unwrap();
+    let std_dev_ticks = params.volatility * years.sqrt() / 1.0001_f64.ln();
+    let tick_current = params.tick_current.as_i32();
+    let max_tick = MAX_TICK_I32 / tick_spacing * tick_spacing;
+    let mut ranges: Vec<(I24, I24)> = Vec::new();
+    for i in 1..=16 {
+        let half_width = (std_dev_ticks * f64::from(i) / 4.0)
+            .round()
+            .to_i32()
+            .unwrap_or(MAX_TICK_I32)
+            .clamp(tick_spacing, MAX_TICK_I32);
+        // round the lower tick down and the upper tick up to the tick spacing
+        let tick_lower =
+            ((tick_current - half_width).div_euclid(tick_spacing) * tick_spacing).max(-max_tick);
+        let tick_upper = ((tick_current + half_width).div_euclid(tick_spacing) + 1) * tick_spacing;
+        let tick_upper = tick_upper.min(max_tick);
+        if !(tick_lower <= tick_current && tick_current < tick_upper) {
+            continue;
+        }
+        let range = (
+            I24::try_from(tick_lower).unwrap(),
+            I24::try_from(tick_upper).unwrap(),
+        );
+        if !ranges.contains(&range) {
+            ranges.push(range);
+        }
+    }
+    let mut estimates = ranges
+        .into_iter()
+        .map(|(tick_lower, tick_upper)| estimate_range(params, tick_lower, tick_upper))
+        .collect::<Result<Vec<_>, Error>>()?;
+    estimates.sort_by(|a, b| objective(b).total_cmp(&objective(a)));
+    Ok(estimates)
+}
+
+/// The expected impermanent loss of a range entered at tick 0 when the log price at the end of
+/// the horizon is normally distributed with mean 0 and standard deviation `std_dev`.
+fn expected_impermanent_loss(std_dev: f64, tick_lower: I24, tick_upper: I24) -> Result<f64, Error> {
+    if std_dev <= 0.0 {
+        return Ok(0.0);
+    }
+    let entry_price = BigDecimal::from(1);
+    // integrate over 6 standard deviations with the midpoint rule
+    let step = 12.0 / f64::from(INTEGRATION_STEPS);
+    let mut total = 0.0;
+    for i in 0..INTEGRATION_STEPS {
+        let z = -6.0 + step * (f64::from(i) + 0.5);
+        let price = BigDecimal::from_f64((z * std_dev).exp()).ok_or(Error::InvalidPrice)?;
+        let il = impermanent_loss(&entry_price, &price, tick_lower, tick_upper)?;
+        total += il.to_f64().unwrap() * normal_pdf(z) * step;
+    }
+    Ok(total)
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-z * z / 2.0).exp() / (2.0 * core::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    (1.0 + erf(z / core::f64::consts::SQRT_2)) / 2.0
+}
+
+/// The error function, with an absolute error below 1.5e-7 (Abramowitz and Stegun 7.1.26).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(volatility: f64) -> RangeOptimizerParams {
+        RangeOptimizerParams {
+            tick_current: I24::try_from(-1234).unwrap(),
+            fee: FeeAmount::MEDIUM,
+            volatility,
+            horizon: 30 * 24 * 60 * 60,
+            daily_volume_to_tvl: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-1.96) - 0.025).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_estimate_range() {
+        let params = params(0.8);
+        let narrow = estimate_range(
+            &params,
+            I24::try_from(-1320).unwrap(),
+            I24::try_from(-1140).unwrap(),
+        )
+        .unwrap();
+        let wide = estimate_range(
+            &params,
+            I24::try_from(-12000).unwrap(),
+            I24::try_from(9600).unwrap(),
+        )
+        .unwrap();
+        assert!(narrow.capital_efficiency > wide.capital_efficiency);
+        assert!(narrow.time_in_range < wide.time_in_range);
+        assert!(narrow.expected_impermanent_loss < wide.expected_impermanent_loss);
+        assert!(wide.expected_impermanent_loss < 0.0);
+        assert!(narrow.time_in_range > 0.0 && wide.time_in_range < 1.0);
+    }
+
+    #[test]
+    fn test_estimate_range_out_of_range() {
+        assert!(matches!(
+            estimate_range(&params(0.8), I24::ZERO, I24::try_from(60).unwrap()),
+            Err(Error::InvalidRange)
+        ));
+    }
+
+    #[test]
+    fn test_optimize_range() {
+        let params = params(0.8);
+        let estimates = optimize_range(&params, expected_net_return).unwrap();
+        assert!(!estimates.is_empty());
+        let tick_spacing = params.fee.tick_spacing().as_i32();
+        for estimate in &estimates {
+            assert!(estimate.tick_lower <= params.tick_current);
+            assert!(estimate.tick_upper > params.tick_current);
+            assert_eq!(estimate.tick_lower.as_i32() % tick_spacing, 0);
+            assert_eq!(estimate.tick_upper.as_i32() % tick_spacing, 0);
+        }
+        assert!(estimates
+            .windows(2)
+            .all(|w| expected_net_return(&w[0]) >= expected_net_return(&w[1])));
+        let by_fees = optimize_range(&params, |estimate| estimate.expected_fees).unwrap();
+        assert_eq!(by_fees.len(), estimates.len());
+        assert!(by_fees
+            .windows(2)
+            .all(|w| w[0].expected_fees >= w[1].expected_fees));
+    }
+
+    #[test]
+    fn test_zero_horizon() {
+        let params = RangeOptimizerParams {
+            horizon: 0,
+            ..params(0.8)
+        };
+        assert!(matches!(
+            estimate_range(
+                &params,
+                I24::try_from(-1320).unwrap(),
+                I24::try_from(-1140).unwrap()
+            ),
+            Err(Error::InvalidWindow)
+        ));
+        assert!(matches!(
+            optimize_range(&params, expected_net_return),
+            Err(Error::InvalidWindow)
+        ));
+    }
+
+    #[test]
+    fn test_optimize_range_near_max_tick() {
+        // above the largest usable tick of the tier, no range contains the current tick
+        let params = RangeOptimizerParams {
+            tick_current: MAX_TICK - I24::ONE,
+            ..params(0.8)
+        };
+        assert!(optimize_range(&params, expected_net_return)
+            .unwrap()
+            .is_empty());
+        let params = RangeOptimizerParams {
+            tick_current: I24::try_from(887100).unwrap(),
+            ..params
+        };
+        let estimates = optimize_range(&params, expected_net_return).unwrap();
+        assert!(!estimates.is_empty());
+        assert!(estimates
+            .iter()
+            .all(|estimate| estimate.tick_upper == I24::try_from(887220).unwrap()));
+    }
+}
@@ -0,0 +1,693 @@
+//! ## Rebalance
+//! Plans moving the liquidity of an existing position to a new range of the same pool: exiting the
+//! position, swapping the proceeds into the ratio of the new range and minting a new position.
+//! Also plans compounding the fees of a position back into its liquidity.
+//!
+//! The plans are not atomic. Uniswap V3 has no contract that can both swap and mint in one call,
+//! as the `NonfungiblePositionManager` can't swap and the routers can't mint, so a plan is a
+//! sequence of transactions to send in the order of [`RebalancePlan::calls`]. Each call carries
+//! its own slippage bounds, so a price move between them makes a later call revert instead of
+//! executing at a worse price, leaving the tokens already received with the recipient.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{Address, U256};
+use uniswap_sdk_core::prelude::*;
+
+/// The tick data of a pool with the liquidity of a position removed from its ticks, i.e. the tick
+/// data after the position is burned, see [`get_rebalance_plan`].
+#[derive(Clone, Debug)]
+pub struct BurnedTickDataProvider<TP: TickDataProvider> {
+    /// The tick data including the liquidity of the position
+    pub inner: TP,
+    /// The lower and upper ticks of the position with its liquidity removed, `None` for ticks
+    /// unknown to `inner`, which a swap through `inner` never crosses
+    pub ticks: [Option<Tick<TP::Index>>; 2],
+}
+
+impl<TP: TickDataProvider> BurnedTickDataProvider<TP> {
+    /// Removes the liquidity of a position from the ticks of `inner`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `inner`: The tick data including the liquidity of the position
+    /// * `tick_lower`: The lower tick of the position
+    /// * `tick_upper`: The upper tick of the position
+    /// * `liquidity`: The liquidity of the position
+    #[inline]
+    pub fn new(
+        inner: TP,
+        tick_lower: TP::Index,
+        tick_upper: TP::Index,
+        liquidity: u128,
+    ) -> Result<Self, Error> {
+        let liquidity_delta = i128::try_from(liquidity).map_err(|_| Error::AddDeltaOverflow)?;
+        let burn = |index: TP::Index, upper: bool| -> Result<Option<Tick<TP::Index>>, Error> {
+            if liquidity == 0 {
+                return Ok(None);
+            }
+            let Ok(tick) = inner.get_tick(index) else {
+                return Ok(None);
+            };
+            let liquidity_net = if upper {
+                tick.liquidity_net.checked_add(liquidity_delta)
+            } else {
+                tick.liquidity_net.checked_sub(liquidity_delta)
+            };
+            Ok(Some(Tick {
+                index,
+                liquidity_gross: add_delta(tick.liquidity_gross, -liquidity_delta)?,
+                liquidity_net: liquidity_net.ok_or(Error::AddDeltaOverflow)?,
+            }))
+        };
+        let ticks = [burn(tick_lower, false)?, burn(tick_upper, true)?];
+        Ok(Self { inner, ticks })
+    }
+}
+
+impl<TP: TickDataProvider> TickDataProvider for BurnedTickDataProvider<TP> {
+    type Index = TP::Index;
+
+    #[inline]
+    fn get_tick(&self, tick: Self::Index) -> Result<&Tick<Self::Index>, Error> {
+        match self
+            .ticks
+            .iter()
+            .flatten()
+            .find(|burned| burned.index == tick)
+        {
+            Some(burned) => Ok(burned),
+            None => self.inner.get_tick(tick),
+        }
+    }
+
+    #[inline]
+    fn next_initialized_tick_within_one_word(
+        &self,
+        tick: Self::Index,
+        lte: bool,
+        tick_spacing: Self::Index,
+    ) -> Result<(Self::Index, bool), Error> {
+        // ticks uninitialized by the burn are still reported as initialized, but crossing them
+        // doesn't change the liquidity as their net liquidity is zero
+        self.inner
+            .next_initialized_tick_within_one_word(tick, lte, tick_spacing)
+    }
+}
+
+/// Returns the pool of `position` after the position is burned, with its liquidity removed from the
+/// ticks and from the active liquidity if the position is in range.
+///
+/// ## Arguments
+///
+/// * `position`: The position to burn
+#[inline]
+pub fn pool_after_burn<TP: Clone + TickDataProvider>(
+    position: &Position<TP>,
+) -> Result<Pool<BurnedTickDataProvider<TP>>, Error> {
+    let pool = &position.pool;
+    let liquidity =
+        if pool.tick_current >= position.tick_lower && pool.tick_current < position.tick_upper {
+            add_delta(
+                pool.liquidity,
+                -i128::try_from(position.liquidity).map_err(|_| Error::AddDeltaOverflow)?,
+            )?
+        } else {
+            pool.liquidity
+        };
+    Ok(Pool {
+        token0: pool.token0.clone(),
+        token1: pool.token1.clone(),
+        fee: pool.fee,
+        sqrt_ratio_x96: pool.sqrt_ratio_x96,
+        liquidity,
+        tick_current: pool.tick_current,
+        tick_data_provider: BurnedTickDataProvider::new(
+            pool.tick_data_provider.clone(),
+            position.tick_lower,
+            position.tick_upper,
+            position.liquidity,
+        )?,
+    })
+}
+
+/// The contract a call of a [`RebalancePlan`] or [`CompoundPlan`] is sent to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanTarget {
+    /// The `NonfungiblePositionManager`
+    NonfungiblePositionManager,
+    /// The `SwapRouter`
+    SwapRouter,
+}
+
+/// Options for producing the calldata of a [`RebalancePlan`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RebalanceOptions {
+    /// The ID of the position to exit
+    pub token_id: U256,
+    /// The account that receives the collected tokens, the swap output and the new position.
+    pub recipient: Address,
+    /// How much the pool price is allowed to move for each of the calls.
+    pub slippage_tolerance: Percent,
+    /// When the transactions expire, in epoch seconds.
+    pub deadline: U256,
+    /// Whether the NFT of the exited position should be burned.
+    pub burn_token: bool,
+}
+
+/// The steps to move a position to a new range, see [`get_rebalance_plan`].
+///
+/// The calls are separate transactions that must be sent by `recipient` in the order of
+/// [`Self::calls`], they are not executed atomically.
+#[derive(Clone, Debug)]
+pub struct RebalancePlan<TP: TickDataProvider> {
+    /// The amounts of token0 and token1 the liquidity of the position is burned for
+    pub burn_amounts: (U256, U256),
+    /// The amounts of token0 and token1 collected, including the fees owed to the position
+    pub collect_amounts: (U256, U256),
+    /// The swap that converts the collected amounts into the ratio of the new range, through the
+    /// pool without the liquidity of the burned position
+    pub swap: OptimalSwap<BurnedTickDataProvider<TP>>,
+    /// The new position to mint at the pool price after the swap
+    pub position: Position<BurnedTickDataProvider<TP>>,
+    /// The calldata to decrease the liquidity of the position and collect, sent to the
+    /// `NonfungiblePositionManager`
+    pub remove_call: MethodParameters,
+    /// The calldata of the swap, sent to the `SwapRouter`, or `None` if no swap is needed
+    pub swap_call: Option<MethodParameters>,
+    /// The calldata to mint the new position, sent to the `NonfungiblePositionManager`
+    pub mint_call: MethodParameters,
+}
+
+impl<TP: TickDataProvider> RebalancePlan<TP> {
+    /// Returns the calls of the plan with their targets, in the order they must be sent.
+    #[inline]
+    #[must_use]
+    pub fn calls(&self) -> Vec<(PlanTarget, &MethodParameters)> {
+        let mut calls = vec![(PlanTarget::NonfungiblePositionManager, &self.remove_call)];
+        if let Some(swap_call) = &self.swap_call {
+            calls.push((PlanTarget::SwapRouter, swap_call));
+        }
+        calls.push((PlanTarget::NonfungiblePositionManager, &self.mint_call));
+        calls
+    }
+}
+
+/// Plans the swap that converts `amounts` into the ratio of the range of a position in `pool`.
+///
+/// ## Returns
+///
+/// The swap, its calldata if any, and the balances after the swap with the output reduced by the
+/// slippage tolerance
+fn plan_swap<TP: Clone + TickDataProvider>(
+    pool: &Pool<TP>,
+    tick_lower: TP::Index,
+    tick_upper: TP::Index,
+    amounts: (U256, U256),
+    recipient: Address,
+    slippage_tolerance: &Percent,
+    deadline: U256,
+) -> Result<(OptimalSwap<TP>, Option<MethodParameters>, (U256, U256)), Error> {
+    let mut swap = get_optimal_swap_amount(pool, tick_lower, tick_upper, amounts.0, amounts.1)?;
+    // a swap without output only loses the dust swapped to fees
+    if swap.amount_out.is_zero() {
+        swap.amount_in = U256::ZERO;
+        swap.pool = pool.clone();
+    }
+    let (swap_call, balances) = if swap.amount_in.is_zero() {
+        (None, amounts)
+    } else {
+        let (token_in, token_out) = if swap.zero_for_one {
+            (pool.token0.clone(), pool.token1.clone())
+        } else {
+            (pool.token1.clone(), pool.token0.clone())
+        };
+        let amount_in =
+            CurrencyAmount::from_raw_amount(token_in.clone(), swap.amount_in.to_big_int())?;
+        let trade = Trade::exact_in(
+            Route::new(vec![pool.clone()], token_in, token_out),
+            amount_in,
+        )?;
+        let amount_out_min = U256::from_big_int(
+            trade
+                .minimum_amount_out(slippage_tolerance.clone(), None)?
+                .quotient(),
+        );
+        let swap_call = swap_call_parameters(
+            &mut [trade],
+            SwapOptions {
+                slippage_tolerance: slippage_tolerance.clone(),
+                recipient,
+                deadline,
+                input_token_permit: None,
+                sqrt_price_limit_x96: None,
+                fee: None,
+            },
+        )?;
+        let balances = if swap.zero_for_one {
+            (amounts.0 - swap.amount_in, amounts.1 + amount_out_min)
+        } else {
+            (amounts.0 + amount_out_min, amounts.1 - swap.amount_in)
+        };
+        (Some(swap_call), balances)
+    };
+    Ok((swap, swap_call, balances))
+}
+
+/// Plans moving all liquidity of `position` to the range `[new_tick_lower, new_tick_upper)` of the
+/// same pool.
+///
+/// The swap and the new position are computed on the pool after the position is burned, see
+/// [`pool_after_burn`]. The new position is sized from the collected amounts with the swap output
+/// reduced by the slippage tolerance, so that the mint doesn't require more than the swap returns.
+///
+/// The plan is **not atomic** and can't be encoded as a single multicall, see the
+/// [module documentation](crate::rebalance): the calls of [`RebalancePlan::calls`] are up to three
+/// transactions, and if a later one reverts, the position is already exited and its tokens stay
+/// with `recipient`.
+///
+/// ## Arguments
+///
+/// * `position`: The position to exit, with the current state and tick data of its pool
+/// * `new_tick_lower`: The lower tick of the new range
+/// * `new_tick_upper`: The upper tick of the new range
+/// * `tokens_owed`: The fees of token0 and token1 collectable from the position
+/// * `options`: Additional information necessary for generating the calldata
+#[inline]
+pub fn get_rebalance_plan<TP: Clone + TickDataProvider>(
+    position: &Position<TP>,
+    new_tick_lower: TP::Index,
+    new_tick_upper: TP::Index,
+    tokens_owed: (U256, U256),
+    options: RebalanceOptions,
+) -> Result<RebalancePlan<TP>, Error> {
+    let RebalanceOptions {
+        token_id,
+        recipient,
+        slippage_tolerance,
+        deadline,
+        burn_token,
+    } = options;
+    validate_slippage(&slippage_tolerance)?;
+    let pool = &position.pool;
+    let burned_pool = pool_after_burn(position)?;
+    let burn_amounts = (
+        U256::from_big_int(position.amount0()?.quotient()),
+        U256::from_big_int(position.amount1()?.quotient()),
+    );
+    let collect_amounts = (
+        burn_amounts.0 + tokens_owed.0,
+        burn_amounts.1 + tokens_owed.1,
+    );
+    let remove_call = remove_call_parameters(
+        position,
+        RemoveLiquidityOptions {
+            token_id,
+            liquidity_percentage: Percent::new(1, 1),
+            slippage_tolerance: slippage_tolerance.clone(),
+            deadline,
+            burn_token,
+            permit: None,
+            collect_options: CollectOptions {
+                token_id,
+                expected_currency_owed0: CurrencyAmount::from_raw_amount(
+                    pool.token0.clone(),
+                    tokens_owed.0.to_big_int(),
+                )?,
+                expected_currency_owed1: CurrencyAmount::from_raw_amount(
+                    pool.token1.clone(),
+                    tokens_owed.1.to_big_int(),
+                )?,
+                recipient,
+            },
+        },
+    )?;
+
+    let (swap, swap_call, (amount0, amount1)) = plan_swap(
+        &burned_pool,
+        new_tick_lower,
+        new_tick_upper,
+        collect_amounts,
+        recipient,
+        &slippage_tolerance,
+        deadline,
+    )?;
+
+    let mut new_position = Position::from_amounts(
+        swap.pool.clone(),
+        new_tick_lower,
+        new_tick_upper,
+        amount0,
+        amount1,
+        true,
+    )?;
+    let mint_call = add_call_parameters(
+        &mut new_position,
+        AddLiquidityOptions {
+            slippage_tolerance,
+            deadline,
+            use_native: None,
+            token0_permit: None,
+            token1_permit: None,
+            specific_opts: AddLiquiditySpecificOptions::Mint(MintSpecificOptions {
+                recipient,
+                create_pool: false,
+            }),
+        },
+    )?;
+    Ok(RebalancePlan {
+        burn_amounts,
+        collect_amounts,
+        swap,
+        position: new_position,
+        remove_call,
+        swap_call,
+        mint_call,
+    })
+}
+
+/// Options for producing the calldata of a [`CompoundPlan`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompoundOptions {
+    /// The ID of the position to compound
+    pub token_id: U256,
+    /// The account that receives the collected fees and the swap output.
+    pub recipient: Address,
+    /// How much the pool price is allowed to move for each of the calls.
+    pub slippage_tolerance: Percent,
+    /// When the transactions expire, in epoch seconds.
+    pub deadline: U256,
+}
+
+/// The steps to reinvest the fees of a position into its liquidity, see [`auto_compound`].
+///
+/// Like a [`RebalancePlan`], the calls are separate transactions that must be sent by `recipient`
+/// in the order of [`Self::calls`].
+#[derive(Clone, Debug)]
+pub struct CompoundPlan<TP: TickDataProvider> {
+    /// The amounts of token0 and token1 collected
+    pub collect_amounts: (U256, U256),
+    /// The swap that converts the collected fees into the ratio of the range of the position
+    pub swap: OptimalSwap<TP>,
+    /// The liquidity added to the range of the position at the pool price after the swap
+    pub position: Position<TP>,
+    /// The calldata to collect the fees, sent to the `NonfungiblePositionManager`
+    pub collect_call: MethodParameters,
+    /// The calldata of the swap, sent to the `SwapRouter`, or `None` if no swap is needed
+    pub swap_call: Option<MethodParameters>,
+    /// The calldata to increase the liquidity of the position, sent to the
+    /// `NonfungiblePositionManager`
+    pub increase_call: MethodParameters,
+}
+
+impl<TP: TickDataProvider> CompoundPlan<TP> {
+    /// Returns the calls of the plan with their targets, in the order they must be sent.
+    #[inline]
+    #[must_use]
+    pub fn calls(&self) -> Vec<(PlanTarget, &MethodParameters)> {
+        let mut calls = vec![(PlanTarget::NonfungiblePositionManager, &self.collect_call)];
+        if let Some(swap_call) = &self.swap_call {
+            calls.push((PlanTarget::SwapRouter, swap_call));
+        }
+        calls.push((PlanTarget::NonfungiblePositionManager, &self.increase_call));
+        calls
+    }
+}
+
+/// Plans reinvesting the collectable fees of `position` into its own range: collecting the fees,
+/// swapping them into the ratio of the range and increasing the liquidity of the position.
+///
+/// ## Arguments
+///
+/// * `position`: The position to compound, with the current state and tick data of its pool
+/// * `collectable_amounts`: The fees of token0 and token1 collectable from the position, e.g. from
+///   `get_collectable_token_amounts`
+/// * `options`: Additional information necessary for generating the calldata
+#[inline]
+pub fn auto_compound<TP: Clone + TickDataProvider>(
+    position: &Position<TP>,
+    collectable_amounts: (U256, U256),
+    options: CompoundOptions,
+) -> Result<CompoundPlan<TP>, Error> {
+    let CompoundOptions {
+        token_id,
+        recipient,
+        slippage_tolerance,
+        deadline,
+    } = options;
+    validate_slippage(&slippage_tolerance)?;
+    let pool = &position.pool;
+    let collect_call = collect_call_parameters(&CollectOptions {
+        token_id,
+        expected_currency_owed0: CurrencyAmount::from_raw_amount(
+            pool.token0.clone(),
+            collectable_amounts.0.to_big_int(),
+        )?,
+        expected_currency_owed1: CurrencyAmount::from_raw_amount(
+            pool.token1.clone(),
+            collectable_amounts.1.to_big_int(),
+        )?,
+        recipient,
+    });
+    let (swap, swap_call, (amount0, amount1)) = plan_swap(
+        pool,
+        position.tick_lower,
+        position.tick_upper,
+        collectable_amounts,
+        recipient,
+        &slippage_tolerance,
+        deadline,
+    )?;
+    let mut added = Position::from_amounts(
+        swap.pool.clone(),
+        position.tick_lower,
+        position.tick_upper,
+        amount0,
+        amount1,
+        true,
+    )?;
+    let increase_call = add_call_parameters(
+        &mut added,
+        AddLiquidityOptions {
+            slippage_tolerance,
+            deadline,
+            use_native: None,
+            token0_permit: None,
+            token1_permit: None,
+            specific_opts: AddLiquiditySpecificOptions::Increase(IncreaseSpecificOptions {
+                token_id,
+            }),
+        },
+    )?;
+    Ok(CompoundPlan {
+        collect_amounts: collectable_amounts,
+        swap,
+        position: added,
+        collect_call,
+        swap_call,
+        increase_call,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::{address, hex};
+
+    const RECIPIENT: Address = address!("0000000000000000000000000000000000000003");
+
+    /// A pool with the liquidity of a position in `[tick_lower, tick_upper)` on top of the full
+    /// range liquidity of [`make_pool`].
+    fn make_pool_with_position(
+        liquidity: u128,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Pool<TickListDataProvider> {
+        let tick_spacing = FEE_AMOUNT.tick_spacing();
+        let min_tick = nearest_usable_tick(MIN_TICK, tick_spacing).as_i32();
+        let max_tick = nearest_usable_tick(MAX_TICK, tick_spacing).as_i32();
+        let active_liquidity = if tick_lower <= 0 && 0 < tick_upper {
+            LIQUIDITY + liquidity
+        } else {
+            LIQUIDITY
+        };
+        Pool::new_with_tick_data_provider(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FEE_AMOUNT,
+            SQRT_RATIO_X96,
+            active_liquidity,
+            TickListDataProvider::new(
+                vec![
+                    Tick::new(min_tick, LIQUIDITY, LIQUIDITY as i128),
+                    Tick::new(tick_lower, liquidity, liquidity as i128),
+                    Tick::new(tick_upper, liquidity, -(liquidity as i128)),
+                    Tick::new(max_tick, LIQUIDITY, -(LIQUIDITY as i128)),
+                ],
+                tick_spacing.as_i32(),
+            ),
+        )
+        .unwrap()
+    }
+
+    fn options() -> RebalanceOptions {
+        RebalanceOptions {
+            token_id: U256::from(1),
+            recipient: RECIPIENT,
+            slippage_tolerance: Percent::new(1, 100),
+            deadline: U256::from(123),
+            burn_token: true,
+        }
+    }
+
+    #[test]
+    fn test_get_rebalance_plan_out_of_range() {
+        let pool = make_pool_with_position(100_000, 600, 1200);
+        // only token0 is held above the current price
+        let position = Position::new(pool, 100_000, 600, 1200);
+        let tokens_owed = (U256::from(10), U256::from(20));
+        let plan = get_rebalance_plan(&position, -600, 600, tokens_owed, options()).unwrap();
+        assert_eq!(plan.burn_amounts.1, U256::ZERO);
+        assert_eq!(
+            plan.collect_amounts,
+            (plan.burn_amounts.0 + U256::from(10), U256::from(20))
+        );
+        assert!(plan.swap.zero_for_one);
+        assert!(plan.swap.amount_in > U256::ZERO);
+        assert!(plan.swap_call.is_some());
+        assert!(plan.position.liquidity > 0);
+        assert_eq!(plan.position.tick_lower, -600);
+        assert_eq!(plan.position.tick_upper, 600);
+        assert_eq!(
+            plan.position.pool.sqrt_ratio_x96,
+            plan.swap.pool.sqrt_ratio_x96
+        );
+        // the new position doesn't require more than the collected amounts after the swap
+        let (amount0, amount1) = plan
+            .swap
+            .balances_after(plan.collect_amounts.0, plan.collect_amounts.1);
+        let mint_amounts = plan.position.mint_amounts().unwrap();
+        assert!(mint_amounts.amount0 <= amount0);
+        assert!(mint_amounts.amount1 <= amount1);
+        // decreaseLiquidity, collect and burn
+        assert_eq!(plan.remove_call.calldata[..4], hex!("ac9650d8"));
+        assert_eq!(plan.mint_call.value, U256::ZERO);
+        let calls = plan.calls();
+        assert_eq!(
+            calls.iter().map(|(target, _)| *target).collect::<Vec<_>>(),
+            vec![
+                PlanTarget::NonfungiblePositionManager,
+                PlanTarget::SwapRouter,
+                PlanTarget::NonfungiblePositionManager
+            ]
+        );
+        assert_eq!(calls[2].1, &plan.mint_call);
+    }
+
+    #[test]
+    fn test_pool_after_burn_in_range() {
+        let pool = make_pool_with_position(900_000, -600, 600);
+        let position = Position::new(pool, 900_000, -600, 600);
+        let pool = pool_after_burn(&position).unwrap();
+        assert_eq!(pool.liquidity, LIQUIDITY);
+        assert_eq!(pool.sqrt_ratio_x96, position.pool.sqrt_ratio_x96);
+        for tick in [-600, 600] {
+            let tick = pool.tick_data_provider.get_tick(tick).unwrap();
+            assert_eq!(tick.liquidity_gross, 0);
+            assert_eq!(tick.liquidity_net, 0);
+        }
+        // a swap through the burned pool moves the price further than through the original pool
+        let amount_in = CurrencyAmount::from_raw_amount(TOKEN0.clone(), 10_000).unwrap();
+        let (_, before) = position.pool.get_output_amount(&amount_in, None).unwrap();
+        let (_, after) = pool.get_output_amount(&amount_in, None).unwrap();
+        assert!(after.sqrt_ratio_x96 < before.sqrt_ratio_x96);
+    }
+
+    #[test]
+    fn test_pool_after_burn_out_of_range() {
+        let pool = make_pool_with_position(100_000, 600, 1200);
+        let position = Position::new(pool, 100_000, 600, 1200);
+        let pool = pool_after_burn(&position).unwrap();
+        assert_eq!(pool.liquidity, LIQUIDITY);
+        assert_eq!(
+            pool.tick_data_provider
+                .get_tick(600)
+                .unwrap()
+                .liquidity_gross,
+            0
+        );
+        assert_eq!(
+            pool.tick_data_provider
+                .get_tick(1200)
+                .unwrap()
+                .liquidity_net,
+            0
+        );
+    }
+
+    #[test]
+    fn test_get_rebalance_plan_in_range() {
+        let pool = make_pool_with_position(900_000, -600, 600);
+        let position = Position::new(pool, 900_000, -600, 600);
+        // the new range above the current price only holds token0
+        let plan =
+            get_rebalance_plan(&position, 600, 1200, (U256::ZERO, U256::ZERO), options()).unwrap();
+        assert!(!plan.swap.zero_for_one);
+        // the swap runs against the liquidity left after the burn
+        let burned = pool_after_burn(&position).unwrap();
+        let amount_in =
+            CurrencyAmount::from_raw_amount(TOKEN1.clone(), plan.swap.amount_in.to_big_int())
+                .unwrap();
+        let (amount_out, pool_after) = burned.get_output_amount(&amount_in, None).unwrap();
+        assert_eq!(
+            plan.swap.amount_out,
+            U256::from_big_int(amount_out.quotient())
+        );
+        assert_eq!(plan.swap.pool.sqrt_ratio_x96, pool_after.sqrt_ratio_x96);
+        assert_eq!(plan.position.pool.liquidity, pool_after.liquidity);
+    }
+
+    #[test]
+    fn test_get_rebalance_plan_no_swap() {
+        let pool = make_pool_with_position(100_000, -600, 600);
+        let position = Position::new(pool, 100_000, -600, 600);
+        let plan = get_rebalance_plan(&position, -1200, 1200, (U256::ZERO, U256::ZERO), options())
+            .unwrap();
+        assert_eq!(plan.swap.amount_in, U256::ZERO);
+        assert!(plan.swap_call.is_none());
+        assert!(plan.position.liquidity > 0);
+        assert!(plan.position.liquidity < position.liquidity);
+        assert_eq!(plan.position.pool.liquidity, LIQUIDITY);
+        assert_eq!(plan.calls().len(), 2);
+    }
+
+    #[test]
+    fn test_auto_compound() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let position = Position::new(pool, 100_000, -600, 600);
+        let fees = (U256::from(1000), U256::from(10));
+        let plan = auto_compound(
+            &position,
+            fees,
+            CompoundOptions {
+                token_id: U256::from(1),
+                recipient: RECIPIENT,
+                slippage_tolerance: Percent::new(1, 100),
+                deadline: U256::from(123),
+            },
+        )
+        .unwrap();
+        assert_eq!(plan.collect_amounts, fees);
+        assert!(plan.swap.zero_for_one);
+        assert!(plan.swap_call.is_some());
+        assert_eq!(plan.position.tick_lower, position.tick_lower);
+        assert_eq!(plan.position.tick_upper, position.tick_upper);
+        assert!(plan.position.liquidity > 0);
+        let (amount0, amount1) = plan.swap.balances_after(fees.0, fees.1);
+        let mint_amounts = plan.position.mint_amounts().unwrap();
+        assert!(mint_amounts.amount0 <= amount0);
+        assert!(mint_amounts.amount1 <= amount1);
+        // increaseLiquidity
+        assert_eq!(plan.increase_call.calldata[..4], hex!("219f5d17"));
+    }
+}
@@ -1,5 +1,5 @@
 use super::abi::ISelfPermit;
-use alloy_primitives::{Bytes, PrimitiveSignature, U256};
+use alloy_primitives::{Bytes, PrimitiveSignature, B256, U256};
 use alloy_sol_types::{eip712_domain, Eip712Domain, SolCall, SolStruct};
 use uniswap_sdk_core::prelude::*;
 
@@ -9,6 +9,15 @@ pub struct ERC20PermitData<P: SolStruct> {
     pub values: P,
 }
 
+impl<P: SolStruct> ERC20PermitData<P> {
+    /// Returns the EIP-712 digest of the permit to sign.
+    #[inline]
+    #[must_use]
+    pub fn eip712_signing_hash(&self) -> B256 {
+        self.values.eip712_signing_hash(&self.domain)
+    }
+}
+
 /// Get the EIP-2612 domain and values to sign for an ERC20 permit.
 ///
 /// ## Arguments
@@ -65,7 +74,7 @@ pub struct ERC20PermitData<P: SolStruct> {
 /// );
 ///
 /// // Derive the EIP-712 signing hash.
-/// let hash: B256 = data.values.eip712_signing_hash(&data.domain);
+/// let hash: B256 = data.eip712_signing_hash();
 ///
 /// let signature: PrimitiveSignature = signer.sign_hash_sync(&hash).unwrap();
 /// assert_eq!(
@@ -138,6 +147,13 @@ impl AllowedPermitArguments {
     }
 }
 
+/// Encodes a `selfPermit` or `selfPermitAllowed` call so that the router or position manager can
+/// spend `token` without a separate approval transaction.
+///
+/// ## Arguments
+///
+/// * `token`: The token to permit
+/// * `options`: The signed ERC-2612 or DAI-style permit
 #[inline]
 #[must_use]
 pub fn encode_permit(token: &Token, options: PermitOptions) -> Bytes {
@@ -160,6 +160,12 @@ pub fn withdraw_token<TP: TickDataProvider>(
     }
 }
 
+/// Encodes the data to pass to `safeTransferFrom` when depositing a position NFT into the staker,
+/// staking it in one or more incentives at once.
+///
+/// ## Arguments
+///
+/// * `incentive_keys`: The incentives to stake the position in.
 #[inline]
 pub fn encode_deposit<TP: TickDataProvider>(incentive_keys: &[IncentiveKey<TP>]) -> Bytes {
     if incentive_keys.len() == 1 {
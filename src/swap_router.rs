@@ -45,6 +45,7 @@ where
         sqrt_price_limit_x96,
         fee,
     } = options;
+    validate_slippage(&slippage_tolerance)?;
     let sample_trade = &trades[0];
     let input_currency = sample_trade.input_currency();
     let token_in = input_currency.wrapped();
@@ -0,0 +1,605 @@
+//! ## SwapRouter02
+//! Produces calldata for [SwapRouter02](https://github.com/Uniswap/swap-router-contracts/blob/main/contracts/SwapRouter02.sol),
+//! ported from `SwapRouter.swapCallParameters` in the TypeScript [router-sdk](https://github.com/Uniswap/router-sdk).
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{Bytes, U160, U256};
+use alloy_sol_types::SolCall;
+use uniswap_sdk_core::prelude::*;
+
+/// Options for producing the arguments to send calls to SwapRouter02.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapRouter02Options {
+    /// How much the execution price is allowed to move unfavorably for the trade execution price.
+    pub slippage_tolerance: Percent,
+    /// The account that should receive the output.
+    pub recipient: Address,
+    /// When the transaction expires, in epoch seconds. If `None`, the calls are not wrapped in a
+    /// deadline-checked multicall.
+    pub deadline: Option<U256>,
+    /// The optional permit parameters for spending the input.
+    pub input_token_permit: Option<PermitOptions>,
+    /// The optional price limit for the trade.
+    pub sqrt_price_limit_x96: Option<U160>,
+    /// Optional information for taking a fee on output.
+    pub fee: Option<FeeOptions>,
+    /// The transfer tax of the output token if it is a fee-on-transfer token.
+    ///
+    /// The output is taxed when the router sweeps it to the recipient, so the aggregated minimum
+    /// output is [`Trade::minimum_amount_out_after_tax`] of each trade. The per-swap minimums are
+    /// unaffected since the pools check the amount they send, so the trades must not be reduced by
+    /// the tax beforehand.
+    pub output_tax: Option<Percent>,
+}
+
+/// Produces the calldata and value to send to SwapRouter02 for the given trades.
+///
+/// ## Arguments
+///
+/// * `trades`: trades to produce call parameters for
+/// * `options`: options for the call parameters
+#[inline]
+pub fn swap_router02_call_parameters<TInput, TOutput, TP>(
+    trades: &mut [Trade<TInput, TOutput, TP>],
+    options: SwapRouter02Options,
+) -> Result<MethodParameters, Error>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    let SwapRouter02Options {
+        slippage_tolerance,
+        recipient,
+        deadline,
+        input_token_permit,
+        sqrt_price_limit_x96,
+        fee,
+        output_tax,
+    } = options;
+    validate_slippage(&slippage_tolerance)?;
+    let sample_trade = &trades[0];
+    let input_currency = sample_trade.input_currency();
+    let token_in = input_currency.wrapped();
+    let input_is_native = input_currency.is_native();
+    let output_currency = sample_trade.output_currency();
+    let token_out = output_currency.wrapped();
+    let output_is_native = output_currency.is_native();
+    let trade_type = sample_trade.trade_type;
+
+    // All trades should have the same starting and ending token.
+    for trade in trades.iter() {
+        assert!(
+            trade.input_currency().wrapped().equals(token_in),
+            "TOKEN_IN_DIFF"
+        );
+        assert!(
+            trade.output_currency().wrapped().equals(token_out),
+            "TOKEN_OUT_DIFF"
+        );
+    }
+    let token_out_address = token_out.address();
+
+    let num_swaps = trades.iter().map(|trade| trade.swaps.len()).sum::<usize>();
+
+    let mut calldatas: Vec<Bytes> = Vec::with_capacity(num_swaps + 3);
+
+    // encode permit if necessary
+    if let Some(input_token_permit) = input_token_permit {
+        assert!(!input_is_native, "NON_TOKEN_PERMIT");
+        calldatas.push(encode_permit(token_in, input_token_permit));
+    }
+
+    let mut total_amount_out = BigInt::zero();
+    for trade in trades.iter_mut() {
+        let minimum_amount_out = match &output_tax {
+            Some(output_tax) => {
+                trade.minimum_amount_out_after_tax(slippage_tolerance.clone(), output_tax)?
+            }
+            None => trade.minimum_amount_out_cached(slippage_tolerance.clone(), None)?,
+        };
+        total_amount_out += minimum_amount_out.quotient();
+    }
+    let total_amount_out = U256::from_big_int(total_amount_out);
+
+    // when splitting an exact input across more than two swaps, check the slippage on the total
+    // output rather than on each swap
+    let perform_aggregated_slippage_check = trade_type == TradeType::ExactInput && num_swaps > 2;
+    // flag for whether a refund needs to happen
+    let must_refund = input_is_native && trade_type == TradeType::ExactOutput;
+    // flags for whether funds should be sent first to the router
+    let router_must_custody =
+        output_is_native || fee.is_some() || perform_aggregated_slippage_check;
+    let swap_recipient = if router_must_custody {
+        ADDRESS_THIS
+    } else {
+        recipient
+    };
+
+    let mut total_value = BigInt::zero();
+    if input_is_native {
+        for trade in trades.iter_mut() {
+            total_value += trade
+                .maximum_amount_in_cached(slippage_tolerance.clone(), None)?
+                .quotient();
+        }
+    }
+
+    for trade in trades.iter() {
+        for Swap {
+            route,
+            input_amount,
+            output_amount,
+        } in &trade.swaps
+        {
+            let amount_in = U256::from_big_int(
+                trade
+                    .maximum_amount_in(slippage_tolerance.clone(), Some(input_amount.clone()))?
+                    .quotient(),
+            );
+            let amount_out = if perform_aggregated_slippage_check {
+                U256::ZERO
+            } else {
+                U256::from_big_int(
+                    trade
+                        .minimum_amount_out(
+                            slippage_tolerance.clone(),
+                            Some(output_amount.clone()),
+                        )?
+                        .quotient(),
+                )
+            };
+
+            if route.pools.len() == 1 {
+                calldatas.push(match trade.trade_type {
+                    TradeType::ExactInput => IV3SwapRouter::exactInputSingleCall {
+                        params: IV3SwapRouter::ExactInputSingleParams {
+                            tokenIn: route.input.wrapped().address(),
+                            tokenOut: route.output.wrapped().address(),
+                            fee: route.pools[0].fee.into(),
+                            recipient: swap_recipient,
+                            amountIn: amount_in,
+                            amountOutMinimum: amount_out,
+                            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+                        },
+                    }
+                    .abi_encode()
+                    .into(),
+                    TradeType::ExactOutput => IV3SwapRouter::exactOutputSingleCall {
+                        params: IV3SwapRouter::ExactOutputSingleParams {
+                            tokenIn: route.input.wrapped().address(),
+                            tokenOut: route.output.wrapped().address(),
+                            fee: route.pools[0].fee.into(),
+                            recipient: swap_recipient,
+                            amountOut: amount_out,
+                            amountInMaximum: amount_in,
+                            sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or_default(),
+                        },
+                    }
+                    .abi_encode()
+                    .into(),
+                });
+            } else {
+                assert!(sqrt_price_limit_x96.is_none(), "MULTIHOP_PRICE_LIMIT");
+
+                let path = encode_route_to_path(route, trade.trade_type == TradeType::ExactOutput);
+
+                calldatas.push(match trade.trade_type {
+                    TradeType::ExactInput => IV3SwapRouter::exactInputCall {
+                        params: IV3SwapRouter::ExactInputParams {
+                            path,
+                            recipient: swap_recipient,
+                            amountIn: amount_in,
+                            amountOutMinimum: amount_out,
+                        },
+                    }
+                    .abi_encode()
+                    .into(),
+                    TradeType::ExactOutput => IV3SwapRouter::exactOutputCall {
+                        params: IV3SwapRouter::ExactOutputParams {
+                            path,
+                            recipient: swap_recipient,
+                            amountOut: amount_out,
+                            amountInMaximum: amount_in,
+                        },
+                    }
+                    .abi_encode()
+                    .into(),
+                });
+            }
+        }
+    }
+
+    // unwrap or sweep
+    if router_must_custody {
+        if output_is_native {
+            calldatas.push(encode_unwrap_weth9(total_amount_out, recipient, fee));
+        } else {
+            calldatas.push(encode_sweep_token(
+                token_out_address,
+                total_amount_out,
+                recipient,
+                fee,
+            ));
+        }
+    }
+
+    // refund
+    if must_refund {
+        calldatas.push(encode_refund_eth());
+    }
+
+    Ok(MethodParameters {
+        calldata: match deadline {
+            Some(deadline) => encode_multicall_with_deadline(deadline, calldatas),
+            None => encode_multicall(calldatas),
+        },
+        value: U256::from_big_int(total_value),
+    })
+}
+
+/// Produces the calldata and value to wrap ether into WETH through SwapRouter02, sending the
+/// WETH to `recipient`.
+///
+/// ## Arguments
+///
+/// * `amount`: The amount of ether to wrap
+/// * `recipient`: The account that should receive the WETH
+/// * `deadline`: When the transaction expires, in epoch seconds, if any
+#[inline]
+#[must_use]
+pub fn wrap_eth_call_parameters(
+    amount: &CurrencyAmount<Ether>,
+    recipient: Address,
+    deadline: Option<U256>,
+) -> MethodParameters {
+    let value = U256::from_big_int(amount.quotient());
+    let calldatas = vec![
+        encode_wrap_eth(value),
+        encode_sweep_token(amount.currency.wrapped().address(), value, recipient, None),
+    ];
+    MethodParameters {
+        calldata: match deadline {
+            Some(deadline) => encode_multicall_with_deadline(deadline, calldatas),
+            None => encode_multicall(calldatas),
+        },
+        value,
+    }
+}
+
+/// Produces the calldata to unwrap WETH into ether through SwapRouter02, sending the ether to
+/// `recipient`. The router must be approved to spend the WETH.
+///
+/// ## Arguments
+///
+/// * `amount`: The amount of WETH to unwrap
+/// * `recipient`: The account that should receive the ether
+/// * `deadline`: When the transaction expires, in epoch seconds, if any
+#[inline]
+#[must_use]
+pub fn unwrap_weth9_call_parameters(
+    amount: &CurrencyAmount<Token>,
+    recipient: Address,
+    deadline: Option<U256>,
+) -> MethodParameters {
+    let value = U256::from_big_int(amount.quotient());
+    let calldatas = vec![
+        encode_pull(amount.currency.address(), value),
+        encode_unwrap_weth9(value, recipient, None),
+    ];
+    MethodParameters {
+        calldata: match deadline {
+            Some(deadline) => encode_multicall_with_deadline(deadline, calldatas),
+            None => encode_multicall(calldatas),
+        },
+        value: U256::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::uint;
+    use once_cell::sync::Lazy;
+
+    static POOL_0_1: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN0.clone(), TOKEN1.clone()));
+    static POOL_1_WETH: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN1.clone(), WETH.clone()));
+    static POOL_0_2: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN0.clone(), TOKEN2.clone()));
+    static POOL_2_1: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN2.clone(), TOKEN1.clone()));
+    static POOL_0_3: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN0.clone(), TOKEN3.clone()));
+    static POOL_3_1: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN3.clone(), TOKEN1.clone()));
+
+    const RECIPIENT: Address = address!("0000000000000000000000000000000000000003");
+    const DEADLINE: U256 = uint!(123_U256);
+    static SWAP_OPTIONS: Lazy<SwapRouter02Options> = Lazy::new(|| SwapRouter02Options {
+        slippage_tolerance: Percent::new(1, 100),
+        recipient: RECIPIENT,
+        deadline: Some(DEADLINE),
+        input_token_permit: None,
+        sqrt_price_limit_x96: None,
+        fee: None,
+        output_tax: None,
+    });
+
+    fn decode(calldata: &Bytes) -> Vec<Bytes> {
+        let decoded = IMulticallExtended::multicallCall::abi_decode(calldata, true).unwrap();
+        assert_eq!(decoded.deadline, DEADLINE);
+        decoded.data
+    }
+
+    #[test]
+    fn single_hop_exact_input() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            swap_router02_call_parameters(&mut [trade], SWAP_OPTIONS.clone()).unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 1);
+        let params = IV3SwapRouter::exactInputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.tokenIn, TOKEN0.address());
+        assert_eq!(params.tokenOut, TOKEN1.address());
+        assert_eq!(params.recipient, RECIPIENT);
+        assert_eq!(params.amountIn, uint!(100_U256));
+        assert_eq!(params.amountOutMinimum, uint!(97_U256));
+        assert_eq!(value, U256::ZERO);
+    }
+
+    #[test]
+    fn single_hop_exact_input_without_deadline() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, .. } = swap_router02_call_parameters(
+            &mut [trade],
+            SwapRouter02Options {
+                deadline: None,
+                ..SWAP_OPTIONS.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(calldata[..4], IV3SwapRouter::exactInputSingleCall::SELECTOR);
+    }
+
+    #[test]
+    fn multi_hop_exact_output() {
+        let trade = Trade::from_route(
+            Route::new(
+                vec![POOL_0_1.clone(), POOL_1_WETH.clone()],
+                TOKEN0.clone(),
+                WETH.clone(),
+            ),
+            CurrencyAmount::from_raw_amount(WETH.clone(), 100).unwrap(),
+            TradeType::ExactOutput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            swap_router02_call_parameters(&mut [trade], SWAP_OPTIONS.clone()).unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 1);
+        let params = IV3SwapRouter::exactOutputCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.recipient, RECIPIENT);
+        assert_eq!(params.amountOut, uint!(100_U256));
+        assert_eq!(params.amountInMaximum, uint!(105_U256));
+        assert_eq!(value, U256::ZERO);
+    }
+
+    #[test]
+    fn eth_in_exact_output() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_1_WETH.clone()], ETHER.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN1.clone(), 100).unwrap(),
+            TradeType::ExactOutput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            swap_router02_call_parameters(&mut [trade], SWAP_OPTIONS.clone()).unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 2);
+        let params = IV3SwapRouter::exactOutputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.tokenIn, WETH.address());
+        assert_eq!(params.recipient, RECIPIENT);
+        assert_eq!(calldatas[1], encode_refund_eth());
+        assert_eq!(value, uint!(103_U256));
+    }
+
+    #[test]
+    fn eth_out_exact_input() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_1_WETH.clone()], TOKEN1.clone(), ETHER.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN1.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            swap_router02_call_parameters(&mut [trade], SWAP_OPTIONS.clone()).unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 2);
+        let params = IV3SwapRouter::exactInputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.recipient, ADDRESS_THIS);
+        assert_eq!(
+            calldatas[1],
+            encode_unwrap_weth9(uint!(97_U256), RECIPIENT, None)
+        );
+        assert_eq!(value, U256::ZERO);
+    }
+
+    #[test]
+    fn aggregated_slippage_check_for_three_swaps() {
+        let amount = |raw| CurrencyAmount::from_raw_amount(TOKEN0.clone(), raw).unwrap();
+        let trade = Trade::from_routes(
+            vec![
+                (
+                    amount(100),
+                    Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+                ),
+                (
+                    amount(100),
+                    Route::new(
+                        vec![POOL_0_2.clone(), POOL_2_1.clone()],
+                        TOKEN0.clone(),
+                        TOKEN1.clone(),
+                    ),
+                ),
+                (
+                    amount(100),
+                    Route::new(
+                        vec![POOL_0_3.clone(), POOL_3_1.clone()],
+                        TOKEN0.clone(),
+                        TOKEN1.clone(),
+                    ),
+                ),
+            ],
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, .. } =
+            swap_router02_call_parameters(&mut [trade], SWAP_OPTIONS.clone()).unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 4);
+        let params = IV3SwapRouter::exactInputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.recipient, ADDRESS_THIS);
+        assert_eq!(params.amountOutMinimum, U256::ZERO);
+        assert_eq!(
+            calldatas[3][..4],
+            IPeripheryPaymentsWithFee::sweepTokenCall::SELECTOR
+        );
+    }
+
+    #[test]
+    fn output_tax_reduces_sweep_minimum() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let fee_recipient = address!("0000000000000000000000000000000000000009");
+        let fee = FeeOptions {
+            fee: Percent::new(1, 100),
+            recipient: fee_recipient,
+        };
+        let MethodParameters { calldata, .. } = swap_router02_call_parameters(
+            &mut [trade],
+            SwapRouter02Options {
+                fee: Some(fee.clone()),
+                output_tax: Some(Percent::new(5, 100)),
+                ..SWAP_OPTIONS.clone()
+            },
+        )
+        .unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 2);
+        let params = IV3SwapRouter::exactInputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        assert_eq!(params.recipient, ADDRESS_THIS);
+        assert_eq!(params.amountOutMinimum, uint!(97_U256));
+        // the output of 98 after a 5% tax and 1% slippage is 98 * 95% / 101% = 92.18
+        assert_eq!(
+            calldatas[1],
+            encode_sweep_token(TOKEN1.address(), uint!(92_U256), RECIPIENT, Some(fee))
+        );
+    }
+
+    #[test]
+    fn output_tax_leaves_swap_minimum() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, .. } = swap_router02_call_parameters(
+            &mut [trade],
+            SwapRouter02Options {
+                output_tax: Some(Percent::new(5, 100)),
+                ..SWAP_OPTIONS.clone()
+            },
+        )
+        .unwrap();
+        let calldatas = decode(&calldata);
+        assert_eq!(calldatas.len(), 1);
+        let params = IV3SwapRouter::exactInputSingleCall::abi_decode(&calldatas[0], true)
+            .unwrap()
+            .params;
+        // the pool sends the output to the recipient directly, before the tax
+        assert_eq!(params.recipient, RECIPIENT);
+        assert_eq!(params.amountOutMinimum, uint!(97_U256));
+    }
+
+    #[test]
+    fn rejects_invalid_output_tax() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        assert!(matches!(
+            swap_router02_call_parameters(
+                &mut [trade],
+                SwapRouter02Options {
+                    output_tax: Some(Percent::new(101, 100)),
+                    ..SWAP_OPTIONS.clone()
+                },
+            ),
+            Err(Error::InvalidTax)
+        ));
+    }
+
+    #[test]
+    fn test_wrap_eth_call_parameters() {
+        let amount = CurrencyAmount::from_raw_amount(ETHER.clone(), 100).unwrap();
+        let MethodParameters { calldata, value } =
+            wrap_eth_call_parameters(&amount, RECIPIENT, Some(DEADLINE));
+        let calldatas = decode(&calldata);
+        assert_eq!(
+            calldatas,
+            vec![
+                encode_wrap_eth(uint!(100_U256)),
+                encode_sweep_token(WETH.address(), uint!(100_U256), RECIPIENT, None)
+            ]
+        );
+        assert_eq!(value, uint!(100_U256));
+    }
+
+    #[test]
+    fn test_unwrap_weth9_call_parameters() {
+        let amount = CurrencyAmount::from_raw_amount(WETH.clone(), 100).unwrap();
+        let MethodParameters { calldata, value } =
+            unwrap_weth9_call_parameters(&amount, RECIPIENT, Some(DEADLINE));
+        let calldatas = decode(&calldata);
+        assert_eq!(
+            calldatas,
+            vec![
+                encode_pull(WETH.address(), uint!(100_U256)),
+                encode_unwrap_weth9(uint!(100_U256), RECIPIENT, None)
+            ]
+        );
+        assert_eq!(value, U256::ZERO);
+    }
+}
@@ -0,0 +1,269 @@
+//! ## Test Utilities
+//! An [`AnvilFork`] harness that spins up an [anvil](https://book.getfoundry.sh/anvil/) fork of
+//! mainnet at a pinned block, funds accounts and sends the calldata produced by this crate, for
+//! deterministic integration tests. Requires `anvil` to be installed.
+
+use crate::prelude::{Error, *};
+use alloy::{
+    eips::BlockId,
+    network::TransactionBuilder,
+    node_bindings::{Anvil, AnvilInstance},
+    providers::{Provider, ProviderBuilder, ReqwestProvider},
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use alloy_primitives::{address, Address, B256, U256};
+use alloy_sol_types::SolCall;
+use uniswap_lens::bindings::ierc20metadata::IERC20Metadata;
+
+/// The block the fork is pinned at by default, the same block the tests of this crate query.
+pub const FORK_BLOCK_NUMBER: u64 = 17_000_000;
+
+/// The mainnet address of the nonfungible position manager.
+pub const NONFUNGIBLE_POSITION_MANAGER_ADDRESS: Address =
+    address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+
+/// The mainnet address of SwapRouter.
+pub const SWAP_ROUTER_ADDRESS: Address = address!("E592427A0AEce92De3Edee1F18E0157C05861564");
+
+/// The mainnet address of SwapRouter02.
+pub const SWAP_ROUTER_02_ADDRESS: Address = address!("68b3465833fb72A70ecDF485E0e4C7bD8665Fc45");
+
+/// The mainnet address of QuoterV2.
+pub const QUOTER_V2_ADDRESS: Address = address!("61fFE014bA17989E743c5F6cB21bF9697530B21e");
+
+/// A running anvil fork with a provider connected to it. The anvil process is killed on drop.
+#[derive(Debug)]
+pub struct AnvilFork {
+    /// The anvil process
+    pub anvil: AnvilInstance,
+    /// A provider connected to the fork
+    pub provider: ReqwestProvider,
+    /// The block the fork is pinned at
+    pub block_number: u64,
+}
+
+impl AnvilFork {
+    /// Spawns an anvil fork of a chain at a pinned block.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fork_url`: The RPC URL of the chain to fork
+    /// * `block_number`: The block to fork at
+    #[inline]
+    #[must_use]
+    pub fn new(fork_url: impl Into<String>, block_number: u64) -> Self {
+        let anvil = Anvil::new()
+            .fork(fork_url)
+            .fork_block_number(block_number)
+            .spawn();
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+        Self {
+            anvil,
+            provider,
+            block_number,
+        }
+    }
+
+    /// Spawns an anvil fork of mainnet at [`FORK_BLOCK_NUMBER`], using the RPC URL in the
+    /// `MAINNET_RPC_URL` environment variable.
+    #[inline]
+    #[must_use]
+    pub fn mainnet() -> Self {
+        let fork_url = std::env::var("MAINNET_RPC_URL").expect("MAINNET_RPC_URL");
+        Self::new(fork_url, FORK_BLOCK_NUMBER)
+    }
+
+    /// The unlocked, prefunded accounts of the fork.
+    #[inline]
+    #[must_use]
+    pub fn accounts(&self) -> &[Address] {
+        self.anvil.addresses()
+    }
+
+    /// The block id of the fork block, for querying the state before any test transaction.
+    #[inline]
+    #[must_use]
+    pub fn block_id(&self) -> BlockId {
+        BlockId::from(self.block_number)
+    }
+
+    /// Sets the ether balance of an account.
+    ///
+    /// ## Arguments
+    ///
+    /// * `account`: The account to fund
+    /// * `amount`: The balance in wei
+    #[inline]
+    pub async fn fund_eth(&self, account: Address, amount: U256) -> Result<(), Error> {
+        self.provider
+            .raw_request::<_, ()>("anvil_setBalance".into(), (account, amount))
+            .await
+            .map_err(|e| Error::ContractError(e.into()))
+    }
+
+    /// Sets the balance of an account in an ERC20 token by writing to the storage of the token.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token`: The token address
+    /// * `account`: The account to fund
+    /// * `amount`: The balance to set
+    #[inline]
+    pub async fn fund_erc20(
+        &self,
+        token: Address,
+        account: Address,
+        amount: U256,
+    ) -> Result<(), Error> {
+        let slots = find_erc20_slots(token, self.provider.clone(), None).await?;
+        self.provider
+            .raw_request::<_, ()>(
+                "anvil_setStorageAt".into(),
+                (
+                    token,
+                    erc20_balance_slot(account, slots.balance),
+                    B256::from(amount),
+                ),
+            )
+            .await
+            .map_err(|e| Error::ContractError(e.into()))
+    }
+
+    /// Approves `spender` to spend the tokens of an unlocked account.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token`: The token address
+    /// * `owner`: The unlocked account
+    /// * `spender`: The approved spender
+    /// * `amount`: The allowance
+    #[inline]
+    pub async fn approve(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+        amount: U256,
+    ) -> Result<TransactionReceipt, Error> {
+        let call = IERC20Metadata::approveCall {
+            spender,
+            value: amount,
+        };
+        self.send(
+            owner,
+            token,
+            MethodParameters {
+                calldata: call.abi_encode().into(),
+                value: U256::ZERO,
+            },
+        )
+        .await
+    }
+
+    /// Unlocks an arbitrary account so transactions can be sent from it.
+    ///
+    /// ## Arguments
+    ///
+    /// * `account`: The account to impersonate
+    #[inline]
+    pub async fn impersonate(&self, account: Address) -> Result<(), Error> {
+        self.provider
+            .raw_request::<_, ()>("anvil_impersonateAccount".into(), (account,))
+            .await
+            .map_err(|e| Error::ContractError(e.into()))
+    }
+
+    /// Sends the calldata produced by one of the calldata builders of this crate from an unlocked
+    /// account and waits for the receipt.
+    ///
+    /// ## Arguments
+    ///
+    /// * `from`: The unlocked sender
+    /// * `to`: The contract to call
+    /// * `params`: The calldata and value to send
+    #[inline]
+    pub async fn send(
+        &self,
+        from: Address,
+        to: Address,
+        params: MethodParameters,
+    ) -> Result<TransactionReceipt, Error> {
+        let tx = TransactionRequest::default()
+            .with_from(from)
+            .with_to(to)
+            .with_input(params.calldata)
+            .with_value(params.value);
+        self.provider
+            .send_transaction(tx)
+            .await
+            .map_err(|e| Error::ContractError(e.into()))?
+            .get_receipt()
+            .await
+            .map_err(|e| Error::ContractError(e.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::{BaseCurrency, Percent};
+
+    #[tokio::test]
+    async fn test_mint_on_fork() {
+        let fork = AnvilFork::new(RPC_URL.to_string(), FORK_BLOCK_NUMBER);
+        let account = fork.accounts()[0];
+        let pool = Pool::from_pool_key(
+            1,
+            FACTORY_ADDRESS,
+            USDC.address(),
+            WETH.address(),
+            FeeAmount::LOW,
+            fork.provider.clone(),
+            Some(fork.block_id()),
+        )
+        .await
+        .unwrap();
+        let tick_spacing = pool.tick_spacing();
+        let tick_lower = nearest_usable_tick(pool.tick_current, tick_spacing) - tick_spacing * 10;
+        let tick_upper = tick_lower + tick_spacing * 20;
+        let mut position = Position::new(pool, 10_u128.pow(15), tick_lower, tick_upper);
+        let MintAmounts { amount0, amount1 } = position.mint_amounts().unwrap();
+        for (token, amount) in [(USDC.address(), amount0), (WETH.address(), amount1)] {
+            fork.fund_erc20(token, account, amount).await.unwrap();
+            fork.approve(token, account, NONFUNGIBLE_POSITION_MANAGER_ADDRESS, amount)
+                .await
+                .unwrap();
+        }
+        let params = add_call_parameters(
+            &mut position,
+            AddLiquidityOptions {
+                slippage_tolerance: Percent::new(1, 100),
+                deadline: U256::MAX,
+                use_native: None,
+                token0_permit: None,
+                token1_permit: None,
+                specific_opts: AddLiquiditySpecificOptions::Mint(MintSpecificOptions {
+                    recipient: account,
+                    create_pool: false,
+                }),
+            },
+        )
+        .unwrap();
+        let receipt = fork
+            .send(account, NONFUNGIBLE_POSITION_MANAGER_ADDRESS, params)
+            .await
+            .unwrap();
+        assert!(receipt.status());
+        let balance = get_nonfungible_position_manager_contract(
+            NONFUNGIBLE_POSITION_MANAGER_ADDRESS,
+            fork.provider.clone(),
+        )
+        .balanceOf(account)
+        .call()
+        .await
+        .unwrap()
+        .balance;
+        assert_eq!(balance, U256::from(1));
+    }
+}
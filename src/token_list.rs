@@ -0,0 +1,246 @@
+//! ## Token List
+//! Parses token lists in the [Uniswap Token List](https://github.com/Uniswap/token-lists) format
+//! into [`Token`]s, so that pools and routes can be searched over a curated set of tokens.
+
+use crate::prelude::*;
+use alloy_primitives::{Address, ChainId};
+use core::str::FromStr;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use uniswap_sdk_core::{prelude::Token, token};
+
+/// The version of a token list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// A token in a token list.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    /// The chain id of the chain the token is deployed on
+    pub chain_id: ChainId,
+    /// The checksummed address of the token
+    pub address: Address,
+    /// The name of the token
+    pub name: String,
+    /// The number of decimals of the token
+    pub decimals: u8,
+    /// The symbol of the token
+    pub symbol: String,
+    /// A URI to the token logo
+    #[serde(default, rename = "logoURI")]
+    pub logo_uri: Option<String>,
+    /// The ids of the tags of the token
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Arbitrary extension fields, e.g. `bridgeInfo`
+    #[serde(default)]
+    pub extensions: Option<Map<String, Value>>,
+}
+
+/// A token list.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenList {
+    /// The name of the list
+    pub name: String,
+    /// When the list was last updated, as an ISO 8601 timestamp
+    pub timestamp: String,
+    /// The version of the list
+    pub version: Version,
+    /// The tokens in the list
+    pub tokens: Vec<TokenInfo>,
+    /// Keywords describing the list
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// A URI to the list logo
+    #[serde(default, rename = "logoURI")]
+    pub logo_uri: Option<String>,
+}
+
+impl TokenInfo {
+    /// Builds the [`Token`] of this entry.
+    #[inline]
+    #[must_use]
+    pub fn to_token(&self) -> Token {
+        token!(
+            self.chain_id,
+            self.address,
+            self.decimals,
+            self.symbol.clone(),
+            self.name.clone()
+        )
+    }
+
+    /// Returns the value of an extension field, if present.
+    ///
+    /// ## Arguments
+    ///
+    /// * `key`: The name of the extension field
+    #[inline]
+    #[must_use]
+    pub fn extension(&self, key: &str) -> Option<&Value> {
+        self.extensions.as_ref()?.get(key)
+    }
+
+    /// Returns the address of the bridged token on another chain from the `bridgeInfo`
+    /// extension, if present.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id of the other chain
+    #[inline]
+    #[must_use]
+    pub fn bridged_address(&self, chain_id: ChainId) -> Option<Address> {
+        let address = self
+            .extension("bridgeInfo")?
+            .get(chain_id.to_string())?
+            .get("tokenAddress")?
+            .as_str()?;
+        Address::from_str(address).ok()
+    }
+}
+
+impl TokenList {
+    /// Parses a token list from JSON.
+    ///
+    /// ## Arguments
+    ///
+    /// * `json`: The token list JSON
+    #[inline]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// The entries of the list on a chain.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    #[inline]
+    pub fn tokens_on_chain(&self, chain_id: ChainId) -> impl Iterator<Item = &TokenInfo> + '_ {
+        self.tokens
+            .iter()
+            .filter(move |token| token.chain_id == chain_id)
+    }
+
+    /// Builds the [`Token`]s of the list on a chain, skipping duplicate addresses.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    #[inline]
+    #[must_use]
+    pub fn to_tokens(&self, chain_id: ChainId) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        for info in self.tokens_on_chain(chain_id) {
+            if tokens.iter().all(|token| token.address != info.address) {
+                tokens.push(info.to_token());
+            }
+        }
+        tokens
+    }
+
+    /// Returns the entry of a token on a chain, if listed.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain id
+    /// * `address`: The token address
+    #[inline]
+    #[must_use]
+    pub fn find(&self, chain_id: ChainId, address: Address) -> Option<&TokenInfo> {
+        self.tokens_on_chain(chain_id)
+            .find(|token| token.address == address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const TOKEN_LIST: &str = r#"{
+        "name": "Test List",
+        "timestamp": "2024-01-01T00:00:00.000Z",
+        "version": { "major": 1, "minor": 2, "patch": 3 },
+        "keywords": ["test"],
+        "tokens": [
+            {
+                "chainId": 1,
+                "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                "name": "USD Coin",
+                "symbol": "USDC",
+                "decimals": 6,
+                "logoURI": "https://example.com/usdc.png",
+                "extensions": {
+                    "bridgeInfo": {
+                        "10": { "tokenAddress": "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85" }
+                    }
+                }
+            },
+            {
+                "chainId": 1,
+                "address": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                "name": "Wrapped Ether",
+                "symbol": "WETH",
+                "decimals": 18,
+                "tags": ["wrapped"]
+            },
+            {
+                "chainId": 10,
+                "address": "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85",
+                "name": "USD Coin",
+                "symbol": "USDC",
+                "decimals": 6
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_from_json() {
+        let list = TokenList::from_json(TOKEN_LIST).unwrap();
+        assert_eq!(list.name, "Test List");
+        assert_eq!(
+            list.version,
+            Version {
+                major: 1,
+                minor: 2,
+                patch: 3
+            }
+        );
+        assert_eq!(list.tokens.len(), 3);
+        assert_eq!(list.tokens[1].tags, vec!["wrapped".to_string()]);
+        assert!(TokenList::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_to_tokens() {
+        let list = TokenList::from_json(TOKEN_LIST).unwrap();
+        let tokens = list.to_tokens(1);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].decimals, 6);
+        assert_eq!(tokens[0].symbol.as_deref(), Some("USDC"));
+        assert_eq!(tokens[1].name.as_deref(), Some("Wrapped Ether"));
+        assert_eq!(list.to_tokens(10).len(), 1);
+        assert!(list.to_tokens(137).is_empty());
+    }
+
+    #[test]
+    fn test_bridged_address() {
+        let list = TokenList::from_json(TOKEN_LIST).unwrap();
+        let usdc = list
+            .find(1, address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"))
+            .unwrap();
+        assert_eq!(
+            usdc.bridged_address(10),
+            Some(address!("0b2C639c533813f4Aa9D7837CAf62653d097Ff85"))
+        );
+        assert_eq!(usdc.bridged_address(137), None);
+        assert_eq!(list.tokens[1].bridged_address(10), None);
+    }
+}
@@ -0,0 +1,618 @@
+//! ## Universal Router
+//! Encodes V3 swaps into [Universal Router](https://github.com/Uniswap/universal-router) commands,
+//! ported from the TypeScript [universal-router-sdk](https://github.com/Uniswap/sdks/tree/main/sdks/universal-router-sdk).
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{uint, Bytes, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use uniswap_sdk_core::prelude::*;
+
+/// Flag that allows a command to revert without reverting the whole execution.
+pub const ALLOW_REVERT_FLAG: u8 = 0x80;
+
+/// Used as an amount to make the router spend its entire balance of a token.
+pub const CONTRACT_BALANCE: U256 =
+    uint!(0x8000000000000000000000000000000000000000000000000000000000000000_U256);
+
+/// The Universal Router command types, see `Commands.sol`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum CommandType {
+    V3SwapExactIn = 0x00,
+    V3SwapExactOut = 0x01,
+    Permit2PermitBatch = 0x03,
+    Sweep = 0x04,
+    Transfer = 0x05,
+    PayPortion = 0x06,
+    Permit2Permit = 0x0a,
+    WrapEth = 0x0b,
+    UnwrapWeth = 0x0c,
+}
+
+/// A Universal Router command with its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Swaps an exact amount in along a V3 path.
+    V3SwapExactIn {
+        recipient: Address,
+        amount_in: U256,
+        amount_out_min: U256,
+        path: Bytes,
+        payer_is_user: bool,
+    },
+    /// Swaps for an exact amount out along a reversed V3 path.
+    V3SwapExactOut {
+        recipient: Address,
+        amount_out: U256,
+        amount_in_max: U256,
+        path: Bytes,
+        payer_is_user: bool,
+    },
+    /// Sets Permit2 allowances for the router using a signed batch permit.
+    Permit2PermitBatch {
+        permit_batch: IAllowanceTransfer::PermitBatch,
+        signature: Bytes,
+    },
+    /// Sends the router's entire balance of `token` to `recipient`.
+    Sweep {
+        token: Address,
+        recipient: Address,
+        amount_min: U256,
+    },
+    /// Sends `value` of the router's `token` to `recipient`.
+    Transfer {
+        token: Address,
+        recipient: Address,
+        value: U256,
+    },
+    /// Sends a portion, in bips, of the router's balance of `token` to `recipient`.
+    PayPortion {
+        token: Address,
+        recipient: Address,
+        bips: U256,
+    },
+    /// Sets a Permit2 allowance for the router using a signed permit.
+    Permit2Permit {
+        permit_single: IAllowanceTransfer::PermitSingle,
+        signature: Bytes,
+    },
+    /// Wraps the router's ETH into WETH.
+    WrapEth {
+        recipient: Address,
+        amount_min: U256,
+    },
+    /// Unwraps the router's WETH into ETH.
+    UnwrapWeth {
+        recipient: Address,
+        amount_min: U256,
+    },
+}
+
+impl Command {
+    /// Returns the type of the command.
+    #[inline]
+    #[must_use]
+    pub const fn command_type(&self) -> CommandType {
+        match self {
+            Self::V3SwapExactIn { .. } => CommandType::V3SwapExactIn,
+            Self::V3SwapExactOut { .. } => CommandType::V3SwapExactOut,
+            Self::Permit2PermitBatch { .. } => CommandType::Permit2PermitBatch,
+            Self::Sweep { .. } => CommandType::Sweep,
+            Self::Transfer { .. } => CommandType::Transfer,
+            Self::PayPortion { .. } => CommandType::PayPortion,
+            Self::Permit2Permit { .. } => CommandType::Permit2Permit,
+            Self::WrapEth { .. } => CommandType::WrapEth,
+            Self::UnwrapWeth { .. } => CommandType::UnwrapWeth,
+        }
+    }
+
+    /// Returns the ABI encoded input of the command.
+    #[inline]
+    #[must_use]
+    pub fn encode_input(&self) -> Bytes {
+        match self.clone() {
+            Self::V3SwapExactIn {
+                recipient,
+                amount_in,
+                amount_out_min,
+                path,
+                payer_is_user,
+            } => (recipient, amount_in, amount_out_min, path, payer_is_user).abi_encode_params(),
+            Self::V3SwapExactOut {
+                recipient,
+                amount_out,
+                amount_in_max,
+                path,
+                payer_is_user,
+            } => (recipient, amount_out, amount_in_max, path, payer_is_user).abi_encode_params(),
+            Self::Permit2PermitBatch {
+                permit_batch,
+                signature,
+            } => (permit_batch, signature).abi_encode_params(),
+            Self::Sweep {
+                token,
+                recipient,
+                amount_min,
+            } => (token, recipient, amount_min).abi_encode_params(),
+            Self::Transfer {
+                token,
+                recipient,
+                value,
+            } => (token, recipient, value).abi_encode_params(),
+            Self::PayPortion {
+                token,
+                recipient,
+                bips,
+            } => (token, recipient, bips).abi_encode_params(),
+            Self::Permit2Permit {
+                permit_single,
+                signature,
+            } => (permit_single, signature).abi_encode_params(),
+            Self::WrapEth {
+                recipient,
+                amount_min,
+            }
+            | Self::UnwrapWeth {
+                recipient,
+                amount_min,
+            } => (recipient, amount_min).abi_encode_params(),
+        }
+        .into()
+    }
+}
+
+/// Accumulates Universal Router commands and their inputs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutePlanner {
+    /// The command bytes, one per command
+    pub commands: Vec<u8>,
+    /// The encoded inputs, one per command
+    pub inputs: Vec<Bytes>,
+}
+
+impl RoutePlanner {
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Appends a command to the plan.
+    ///
+    /// ## Arguments
+    ///
+    /// * `command`: The command to append
+    /// * `allow_revert`: Whether the execution may continue if the command reverts
+    #[inline]
+    pub fn add_command(&mut self, command: Command, allow_revert: bool) -> &mut Self {
+        let mut command_byte = command.command_type() as u8;
+        if allow_revert {
+            command_byte |= ALLOW_REVERT_FLAG;
+        }
+        self.commands.push(command_byte);
+        self.inputs.push(command.encode_input());
+        self
+    }
+
+    /// Encodes the plan as `execute(bytes,bytes[],uint256)` calldata.
+    ///
+    /// ## Arguments
+    ///
+    /// * `deadline`: When the transaction expires, in epoch seconds
+    #[inline]
+    #[must_use]
+    pub fn encode_execute(self, deadline: U256) -> Bytes {
+        IUniversalRouter::executeCall {
+            commands: self.commands.into(),
+            inputs: self.inputs,
+            deadline,
+        }
+        .abi_encode()
+        .into()
+    }
+}
+
+/// Options for producing the arguments to send calls to the Universal Router.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UniversalRouterOptions {
+    /// How much the execution price is allowed to move unfavorably for the trade execution price.
+    pub slippage_tolerance: Percent,
+    /// The account that should receive the output.
+    pub recipient: Address,
+    /// When the transaction expires, in epoch seconds.
+    pub deadline: U256,
+    /// The optional Permit2 permit for spending the input.
+    pub input_token_permit: Option<Permit2Permit>,
+    /// Optional information for taking a fee on output.
+    pub fee: Option<FeeOptions>,
+}
+
+/// Produces the calldata and value to send to the Universal Router for the given trades.
+///
+/// ## Arguments
+///
+/// * `trades`: trades to produce call parameters for
+/// * `options`: options for the call parameters
+#[inline]
+pub fn universal_router_call_parameters<TInput, TOutput, TP>(
+    trades: &mut [Trade<TInput, TOutput, TP>],
+    options: UniversalRouterOptions,
+) -> Result<MethodParameters, Error>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    let UniversalRouterOptions {
+        slippage_tolerance,
+        recipient,
+        deadline,
+        input_token_permit,
+        fee,
+    } = options;
+    validate_slippage(&slippage_tolerance)?;
+    let sample_trade = &trades[0];
+    let input_currency = sample_trade.input_currency();
+    let token_in = input_currency.wrapped();
+    let input_is_native = input_currency.is_native();
+    let output_currency = sample_trade.output_currency();
+    let token_out = output_currency.wrapped();
+    let output_is_native = output_currency.is_native();
+    let trade_type = sample_trade.trade_type;
+
+    // All trades should have the same starting and ending token.
+    for trade in trades.iter() {
+        assert!(
+            trade.input_currency().wrapped().equals(token_in),
+            "TOKEN_IN_DIFF"
+        );
+        assert!(
+            trade.output_currency().wrapped().equals(token_out),
+            "TOKEN_OUT_DIFF"
+        );
+    }
+    let token_out_address = token_out.address();
+
+    let num_swaps = trades.iter().map(|trade| trade.swaps.len()).sum::<usize>();
+
+    let mut planner = RoutePlanner::new();
+
+    // encode permit if necessary
+    if let Some(input_token_permit) = input_token_permit {
+        assert!(!input_is_native, "NON_TOKEN_PERMIT");
+        planner.add_command(input_token_permit.into(), false);
+    }
+
+    let mut total_amount_out = BigInt::zero();
+    for trade in trades.iter_mut() {
+        total_amount_out += trade
+            .minimum_amount_out_cached(slippage_tolerance.clone(), None)?
+            .quotient();
+    }
+    let mut total_amount_out = U256::from_big_int(total_amount_out);
+
+    let mut total_value = BigInt::zero();
+    if input_is_native {
+        for trade in trades.iter_mut() {
+            total_value += trade
+                .maximum_amount_in_cached(slippage_tolerance.clone(), None)?
+                .quotient();
+        }
+    }
+    let total_value = U256::from_big_int(total_value);
+
+    // wrap the ETH sent with the call, the swaps then pay from the router's balance
+    if input_is_native {
+        planner.add_command(
+            Command::WrapEth {
+                recipient: ADDRESS_THIS,
+                amount_min: total_value,
+            },
+            false,
+        );
+    }
+
+    let perform_aggregated_slippage_check = trade_type == TradeType::ExactInput && num_swaps > 2;
+    // flags for whether funds should be sent first to the router
+    let router_must_custody =
+        output_is_native || fee.is_some() || perform_aggregated_slippage_check;
+    let swap_recipient = if router_must_custody {
+        ADDRESS_THIS
+    } else {
+        recipient
+    };
+    let payer_is_user = !input_is_native;
+
+    for trade in trades.iter() {
+        for Swap {
+            route,
+            input_amount,
+            output_amount,
+        } in &trade.swaps
+        {
+            let amount_in = U256::from_big_int(
+                trade
+                    .maximum_amount_in(slippage_tolerance.clone(), Some(input_amount.clone()))?
+                    .quotient(),
+            );
+            let amount_out = if perform_aggregated_slippage_check {
+                U256::ZERO
+            } else {
+                U256::from_big_int(
+                    trade
+                        .minimum_amount_out(
+                            slippage_tolerance.clone(),
+                            Some(output_amount.clone()),
+                        )?
+                        .quotient(),
+                )
+            };
+            let path = encode_route_to_path(route, trade.trade_type == TradeType::ExactOutput);
+
+            planner.add_command(
+                match trade.trade_type {
+                    TradeType::ExactInput => Command::V3SwapExactIn {
+                        recipient: swap_recipient,
+                        amount_in,
+                        amount_out_min: amount_out,
+                        path,
+                        payer_is_user,
+                    },
+                    TradeType::ExactOutput => Command::V3SwapExactOut {
+                        recipient: swap_recipient,
+                        amount_out,
+                        amount_in_max: amount_in,
+                        path,
+                        payer_is_user,
+                    },
+                },
+                false,
+            );
+        }
+    }
+
+    if router_must_custody {
+        if let Some(FeeOptions {
+            fee,
+            recipient: fee_recipient,
+        }) = fee
+        {
+            let bips = encode_fee_bips(fee);
+            planner.add_command(
+                Command::PayPortion {
+                    token: token_out_address,
+                    recipient: fee_recipient,
+                    bips,
+                },
+                false,
+            );
+            total_amount_out -= total_amount_out * bips / uint!(10000_U256);
+        }
+        planner.add_command(
+            if output_is_native {
+                Command::UnwrapWeth {
+                    recipient,
+                    amount_min: total_amount_out,
+                }
+            } else {
+                Command::Sweep {
+                    token: token_out_address,
+                    recipient,
+                    amount_min: total_amount_out,
+                }
+            },
+            false,
+        );
+    }
+
+    // refund the unspent ETH
+    if input_is_native && trade_type == TradeType::ExactOutput {
+        planner.add_command(
+            Command::UnwrapWeth {
+                recipient,
+                amount_min: U256::ZERO,
+            },
+            false,
+        );
+    }
+
+    Ok(MethodParameters {
+        calldata: planner.encode_execute(deadline),
+        value: total_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::U160;
+    use once_cell::sync::Lazy;
+
+    static POOL_0_1: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN0.clone(), TOKEN1.clone()));
+    static POOL_1_WETH: Lazy<Pool<TickListDataProvider>> =
+        Lazy::new(|| make_pool(TOKEN1.clone(), WETH.clone()));
+
+    const RECIPIENT: Address = address!("0000000000000000000000000000000000000003");
+    const DEADLINE: U256 = uint!(123_U256);
+    static OPTIONS: Lazy<UniversalRouterOptions> = Lazy::new(|| UniversalRouterOptions {
+        slippage_tolerance: Percent::new(1, 100),
+        recipient: RECIPIENT,
+        deadline: DEADLINE,
+        input_token_permit: None,
+        fee: None,
+    });
+
+    fn decode(calldata: &Bytes) -> (Bytes, Vec<Bytes>) {
+        let decoded = IUniversalRouter::executeCall::abi_decode(calldata, true).unwrap();
+        assert_eq!(decoded.deadline, DEADLINE);
+        (decoded.commands, decoded.inputs)
+    }
+
+    #[test]
+    fn test_add_command_allow_revert() {
+        let mut planner = RoutePlanner::new();
+        planner
+            .add_command(
+                Command::WrapEth {
+                    recipient: ADDRESS_THIS,
+                    amount_min: U256::ZERO,
+                },
+                false,
+            )
+            .add_command(
+                Command::Sweep {
+                    token: TOKEN0.address(),
+                    recipient: RECIPIENT,
+                    amount_min: U256::ZERO,
+                },
+                true,
+            );
+        assert_eq!(planner.commands, vec![0x0b, 0x84]);
+        assert_eq!(planner.inputs.len(), 2);
+    }
+
+    #[test]
+    fn test_single_hop_exact_input() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            universal_router_call_parameters(&mut [trade], OPTIONS.clone()).unwrap();
+        let (commands, inputs) = decode(&calldata);
+        assert_eq!(commands.to_vec(), vec![0x00]);
+        let (recipient, amount_in, amount_out_min, path, payer_is_user) =
+            <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&inputs[0], true).unwrap();
+        assert_eq!(recipient, RECIPIENT);
+        assert_eq!(amount_in, uint!(100_U256));
+        assert_eq!(amount_out_min, uint!(97_U256));
+        assert_eq!(
+            path,
+            encode_route_to_path(
+                &Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+                false
+            )
+        );
+        assert!(payer_is_user);
+        assert_eq!(value, U256::ZERO);
+    }
+
+    #[test]
+    fn test_eth_in_exact_output() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_1_WETH.clone()], ETHER.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN1.clone(), 100).unwrap(),
+            TradeType::ExactOutput,
+        )
+        .unwrap();
+        let MethodParameters { calldata, value } =
+            universal_router_call_parameters(&mut [trade], OPTIONS.clone()).unwrap();
+        let (commands, inputs) = decode(&calldata);
+        assert_eq!(commands.to_vec(), vec![0x0b, 0x01, 0x0c]);
+        assert_eq!(
+            inputs[0],
+            Command::WrapEth {
+                recipient: ADDRESS_THIS,
+                amount_min: uint!(103_U256),
+            }
+            .encode_input()
+        );
+        let (_, amount_out, amount_in_max, _, payer_is_user) =
+            <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&inputs[1], true).unwrap();
+        assert_eq!(amount_out, uint!(100_U256));
+        assert_eq!(amount_in_max, uint!(103_U256));
+        assert!(!payer_is_user);
+        assert_eq!(value, uint!(103_U256));
+    }
+
+    #[test]
+    fn test_eth_out_with_fee() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_1_WETH.clone()], TOKEN1.clone(), ETHER.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN1.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let fee_recipient = address!("0000000000000000000000000000000000000009");
+        let MethodParameters { calldata, value } = universal_router_call_parameters(
+            &mut [trade],
+            UniversalRouterOptions {
+                fee: Some(FeeOptions {
+                    fee: Percent::new(5, 1000),
+                    recipient: fee_recipient,
+                }),
+                ..OPTIONS.clone()
+            },
+        )
+        .unwrap();
+        let (commands, inputs) = decode(&calldata);
+        assert_eq!(commands.to_vec(), vec![0x00, 0x06, 0x0c]);
+        let (recipient, ..) =
+            <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&inputs[0], true).unwrap();
+        assert_eq!(recipient, ADDRESS_THIS);
+        assert_eq!(
+            inputs[1],
+            Command::PayPortion {
+                token: WETH.address(),
+                recipient: fee_recipient,
+                bips: uint!(50_U256),
+            }
+            .encode_input()
+        );
+        assert_eq!(
+            inputs[2],
+            Command::UnwrapWeth {
+                recipient: RECIPIENT,
+                amount_min: uint!(97_U256),
+            }
+            .encode_input()
+        );
+        assert_eq!(value, U256::ZERO);
+    }
+
+    #[test]
+    fn test_permit2_permit() {
+        let trade = Trade::from_route(
+            Route::new(vec![POOL_0_1.clone()], TOKEN0.clone(), TOKEN1.clone()),
+            CurrencyAmount::from_raw_amount(TOKEN0.clone(), 100).unwrap(),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let permit = IAllowanceTransfer::PermitSingle {
+            details: IAllowanceTransfer::PermitDetails {
+                token: TOKEN0.address(),
+                amount: U160::MAX,
+                expiration: Default::default(),
+                nonce: Default::default(),
+            },
+            spender: RECIPIENT,
+            sigDeadline: DEADLINE,
+        };
+        let signature = Bytes::from(vec![1; 65]);
+        let MethodParameters { calldata, .. } = universal_router_call_parameters(
+            &mut [trade],
+            UniversalRouterOptions {
+                input_token_permit: Some(Permit2Permit {
+                    permit: permit.clone(),
+                    signature: signature.clone(),
+                }),
+                ..OPTIONS.clone()
+            },
+        )
+        .unwrap();
+        let (commands, inputs) = decode(&calldata);
+        assert_eq!(commands.to_vec(), vec![0x0a, 0x00]);
+        let (decoded_permit, decoded_signature) =
+            <(IAllowanceTransfer::PermitSingle, Bytes)>::abi_decode_params(&inputs[0], true)
+                .unwrap();
+        assert_eq!(decoded_permit, permit);
+        assert_eq!(decoded_signature, signature);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::constants::{FeeAmount, POOL_INIT_CODE_HASH};
-use alloy_primitives::{b256, keccak256, Address, B256};
+use crate::constants::{FeeAmount, FACTORY_ADDRESS, POOL_INIT_CODE_HASH};
+use alloy_primitives::{address, b256, keccak256, Address, B256};
 use alloy_sol_types::SolValue;
 use uniswap_sdk_core::prelude::{
     compute_zksync_create2_address::compute_zksync_create2_address, ChainId,
@@ -15,6 +15,10 @@ use uniswap_sdk_core::prelude::{
 /// * `fee`: The fee tier of the pool
 /// * `init_code_hash_manual_override`: Override the init code hash used to compute the pool address
 ///   if necessary
+/// * `chain_id`: The chain id, to use the CREATE2 scheme of zkSync on zkSync
+///
+/// Forks whose pools are deployed by a contract other than the factory are supported through
+/// [`PoolDeployment::pool_address`].
 ///
 /// ## Returns
 ///
@@ -60,29 +64,171 @@ pub fn compute_pool_address(
     init_code_hash_manual_override: Option<B256>,
     chain_id: Option<alloy_primitives::ChainId>,
 ) -> Address {
-    assert_ne!(token_a, token_b, "ADDRESSES");
-    let (token_0, token_1) = if token_a < token_b {
-        (token_a, token_b)
-    } else {
-        (token_b, token_a)
+    let deployment = match chain_id {
+        Some(ZKSYNC_CHAIN_ID) => PoolDeployment {
+            factory,
+            deployer: factory,
+            init_code_hash: ZKSYNC_POOL_INIT_CODE_HASH,
+            zksync: true,
+        },
+        _ => PoolDeployment::uniswap_v3(factory),
+    };
+    PoolDeployment {
+        init_code_hash: init_code_hash_manual_override.unwrap_or(deployment.init_code_hash),
+        ..deployment
+    }
+    .pool_address(token_a, token_b, fee)
+}
+
+const ZKSYNC_CHAIN_ID: u64 = ChainId::ZKSYNC as u64;
+
+/// The init code hash of the pools of Uniswap V3 on zkSync.
+const ZKSYNC_POOL_INIT_CODE_HASH: B256 =
+    b256!("010013f177ea1fcbc4520f9a3ca7cd2d1d77959e05aa66484027cb38e712aeed");
+
+/// Describes how the pools of a Uniswap V3 deployment or fork are deployed, so that their
+/// addresses can be computed. A factory [`Address`] converts into the deployment of the canonical
+/// Uniswap V3 pool bytecode by that factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoolDeployment {
+    /// The factory the pools are created through
+    pub factory: Address,
+    /// The contract deploying the pools with CREATE2, the factory itself for Uniswap V3 but a
+    /// separate pool deployer for forks such as PancakeSwap V3
+    pub deployer: Address,
+    /// The hash of the creation code of the pools
+    pub init_code_hash: B256,
+    /// Whether the pools are deployed with the CREATE2 scheme of zkSync
+    pub zksync: bool,
+}
+
+impl PoolDeployment {
+    /// Uniswap V3 on Ethereum and the chains sharing its factory address.
+    pub const UNISWAP_V3: Self = Self::uniswap_v3(FACTORY_ADDRESS);
+
+    /// Uniswap V3 on zkSync Era.
+    pub const UNISWAP_V3_ZKSYNC: Self = Self {
+        factory: address!("8FdA5a7a8dCA67BBcDd10F02Fa0649A937215422"),
+        deployer: address!("8FdA5a7a8dCA67BBcDd10F02Fa0649A937215422"),
+        init_code_hash: ZKSYNC_POOL_INIT_CODE_HASH,
+        zksync: true,
+    };
+
+    /// PancakeSwap V3 on the chains sharing its factory address.
+    pub const PANCAKESWAP_V3: Self = Self {
+        factory: address!("0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865"),
+        deployer: address!("41ff9AA7e16B8B1a8a8dc4f0eFacd93D02d071c9"),
+        init_code_hash: b256!("6ce8eb472fa82df5469c6ab6d485f17c3ad13c8cd7af59b3d4a8026c5ce0f7e2"),
+        zksync: false,
     };
-    let salt = keccak256((token_0, token_1, fee as i32).abi_encode());
-    const ZKSYNC_CHAIN_ID: u64 = ChainId::ZKSYNC as u64;
 
-    // ZKSync uses a different create2 address computation
-    // Most likely all ZKEVM chains will use the different computation from standard create2
-    match chain_id {
-        Some(ZKSYNC_CHAIN_ID) => compute_zksync_create2_address(
+    /// The deployment of the canonical Uniswap V3 pool bytecode by a factory, which also covers
+    /// unmodified forks such as SushiSwap V3.
+    ///
+    /// ## Arguments
+    ///
+    /// * `factory`: The factory address
+    #[inline]
+    #[must_use]
+    pub const fn uniswap_v3(factory: Address) -> Self {
+        Self {
             factory,
-            init_code_hash_manual_override.unwrap_or(b256!(
-                "010013f177ea1fcbc4520f9a3ca7cd2d1d77959e05aa66484027cb38e712aeed"
-            )),
-            salt,
-            None,
-        ),
-        _ => factory.create2(
-            salt,
-            init_code_hash_manual_override.unwrap_or(POOL_INIT_CODE_HASH),
-        ),
+            deployer: factory,
+            init_code_hash: POOL_INIT_CODE_HASH,
+            zksync: false,
+        }
+    }
+
+    /// Computes the address of a pool of the deployment.
+    ///
+    /// ## Arguments
+    ///
+    /// * `token_a`: The first token of the pair, irrespective of sort order
+    /// * `token_b`: The second token of the pair, irrespective of sort order
+    /// * `fee`: The fee tier of the pool
+    #[inline]
+    #[must_use]
+    pub fn pool_address(&self, token_a: Address, token_b: Address, fee: FeeAmount) -> Address {
+        assert_ne!(token_a, token_b, "ADDRESSES");
+        let (token_0, token_1) = if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        let salt = keccak256((token_0, token_1, fee.fee_pips().get()).abi_encode());
+        if self.zksync {
+            compute_zksync_create2_address(self.deployer, self.init_code_hash, salt, None)
+        } else {
+            self.deployer.create2(salt, self.init_code_hash)
+        }
+    }
+}
+
+impl Default for PoolDeployment {
+    #[inline]
+    fn default() -> Self {
+        Self::UNISWAP_V3
+    }
+}
+
+impl From<Address> for PoolDeployment {
+    #[inline]
+    fn from(factory: Address) -> Self {
+        Self::uniswap_v3(factory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const DAI: Address = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+
+    #[test]
+    fn test_pool_deployment() {
+        assert_eq!(
+            PoolDeployment::UNISWAP_V3.pool_address(USDC, DAI, FeeAmount::LOW),
+            address!("6c6Bc977E13Df9b0de53b251522280BB72383700")
+        );
+        assert_eq!(
+            PoolDeployment::from(FACTORY_ADDRESS),
+            PoolDeployment::UNISWAP_V3
+        );
+        let fork = PoolDeployment {
+            init_code_hash: B256::repeat_byte(1),
+            ..PoolDeployment::UNISWAP_V3
+        };
+        assert_eq!(
+            fork.pool_address(USDC, DAI, FeeAmount::LOW),
+            compute_pool_address(
+                FACTORY_ADDRESS,
+                USDC,
+                DAI,
+                FeeAmount::LOW,
+                Some(B256::repeat_byte(1)),
+                None
+            )
+        );
+        assert_eq!(
+            PoolDeployment::UNISWAP_V3_ZKSYNC.pool_address(USDC, DAI, FeeAmount::LOW),
+            compute_pool_address(
+                PoolDeployment::UNISWAP_V3_ZKSYNC.factory,
+                USDC,
+                DAI,
+                FeeAmount::LOW,
+                None,
+                Some(ZKSYNC_CHAIN_ID)
+            )
+        );
+        // the pools of PancakeSwap V3 are deployed by a separate deployer
+        assert_ne!(
+            PoolDeployment::PANCAKESWAP_V3.pool_address(USDC, DAI, FeeAmount::LOW),
+            PoolDeployment::uniswap_v3(PoolDeployment::PANCAKESWAP_V3.factory).pool_address(
+                USDC,
+                DAI,
+                FeeAmount::LOW
+            )
+        );
     }
 }
@@ -0,0 +1,108 @@
+//! ## Decode Pool Event
+//! Decodes the raw logs emitted by a `UniswapV3Pool` into typed [`PoolEvent`]s, e.g. to replay
+//! the swaps, mints and burns of a pool from `eth_getLogs`.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::Log;
+use alloy_sol_types::SolEventInterface;
+
+/// An event emitted by a `UniswapV3Pool`.
+pub type PoolEvent = IUniswapV3PoolEvents::IUniswapV3PoolEventsEvents;
+
+/// Decodes a raw log emitted by a pool into a [`PoolEvent`].
+///
+/// ## Arguments
+///
+/// * `log`: The log to decode, e.g. the `inner` log of an RPC log
+#[inline]
+pub fn decode_pool_event(log: &Log) -> Result<PoolEvent, Error> {
+    PoolEvent::decode_log(log, true)
+        .map(|log| log.data)
+        .map_err(|_| Error::InvalidLog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, aliases::I24, uint, Address, I256};
+    use alloy_sol_types::SolEvent;
+    use IUniswapV3PoolEvents::*;
+
+    const POOL: Address = address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+
+    fn log<E: SolEvent>(event: &E) -> Log {
+        Log {
+            address: POOL,
+            data: event.encode_log_data(),
+        }
+    }
+
+    #[test]
+    fn test_decode_swap() {
+        let swap = IUniswapV3PoolEvents::Swap {
+            sender: address!("0000000000000000000000000000000000000001"),
+            recipient: address!("0000000000000000000000000000000000000002"),
+            amount0: I256::try_from(-100).unwrap(),
+            amount1: I256::try_from(200).unwrap(),
+            sqrtPriceX96: uint!(79228162514264337593543950336_U160),
+            liquidity: 1_000_000,
+            tick: I24::ZERO,
+        };
+        assert_eq!(
+            decode_pool_event(&log(&swap)).unwrap(),
+            PoolEvent::Swap(swap)
+        );
+    }
+
+    #[test]
+    fn test_decode_mint_and_burn() {
+        let mint = Mint {
+            sender: address!("0000000000000000000000000000000000000001"),
+            owner: address!("0000000000000000000000000000000000000002"),
+            tickLower: I24::try_from(-60).unwrap(),
+            tickUpper: I24::try_from(60).unwrap(),
+            amount: 1000,
+            amount0: uint!(3_U256),
+            amount1: uint!(3_U256),
+        };
+        assert_eq!(
+            decode_pool_event(&log(&mint)).unwrap(),
+            PoolEvent::Mint(mint)
+        );
+        let burn = Burn {
+            owner: address!("0000000000000000000000000000000000000002"),
+            tickLower: I24::try_from(-60).unwrap(),
+            tickUpper: I24::try_from(60).unwrap(),
+            amount: 1000,
+            amount0: uint!(2_U256),
+            amount1: uint!(2_U256),
+        };
+        assert_eq!(
+            decode_pool_event(&log(&burn)).unwrap(),
+            PoolEvent::Burn(burn)
+        );
+    }
+
+    #[test]
+    fn test_decode_increase_observation_cardinality_next() {
+        let event = IncreaseObservationCardinalityNext {
+            observationCardinalityNextOld: 1,
+            observationCardinalityNextNew: 10,
+        };
+        assert_eq!(
+            decode_pool_event(&log(&event)).unwrap(),
+            PoolEvent::IncreaseObservationCardinalityNext(event)
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_log() {
+        let mut log = log(&Initialize {
+            sqrtPriceX96: uint!(79228162514264337593543950336_U160),
+            tick: I24::ZERO,
+        });
+        // an unknown topic
+        log.data = alloy_primitives::LogData::new_unchecked(vec![POOL.into_word()], log.data.data);
+        assert!(matches!(decode_pool_event(&log), Err(Error::InvalidLog)));
+    }
+}
@@ -0,0 +1,90 @@
+//! ## Decode Position Event
+//! Decodes the raw logs emitted by the `NonfungiblePositionManager` into typed [`PositionEvent`]s
+//! with the token id of the position they belong to.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{Log, U256};
+use alloy_sol_types::SolEventInterface;
+
+/// An event emitted by the `NonfungiblePositionManager` for a position.
+pub type PositionEvent = INonfungiblePositionManagerEvents::INonfungiblePositionManagerEventsEvents;
+
+impl PositionEvent {
+    /// The token id of the position the event was emitted for.
+    #[inline]
+    #[must_use]
+    pub const fn token_id(&self) -> U256 {
+        match self {
+            Self::IncreaseLiquidity(event) => event.tokenId,
+            Self::DecreaseLiquidity(event) => event.tokenId,
+            Self::Collect(event) => event.tokenId,
+            Self::Transfer(event) => event.tokenId,
+        }
+    }
+}
+
+/// Decodes a raw log emitted by the `NonfungiblePositionManager` into a [`PositionEvent`].
+///
+/// ## Arguments
+///
+/// * `log`: The log to decode, e.g. the `inner` log of an RPC log
+#[inline]
+pub fn decode_position_event(log: &Log) -> Result<PositionEvent, Error> {
+    PositionEvent::decode_log(log, true)
+        .map(|log| log.data)
+        .map_err(|_| Error::InvalidLog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, uint, Address};
+    use alloy_sol_types::SolEvent;
+    use INonfungiblePositionManagerEvents::*;
+
+    const NPM: Address = address!("C36442b4a4522E871399CD717aBDD847Ab11FE88");
+
+    fn log<E: SolEvent>(event: &E) -> Log {
+        Log {
+            address: NPM,
+            data: event.encode_log_data(),
+        }
+    }
+
+    #[test]
+    fn test_decode_increase_liquidity() {
+        let event = IncreaseLiquidity {
+            tokenId: uint!(4_U256),
+            liquidity: 1000,
+            amount0: uint!(3_U256),
+            amount1: uint!(5_U256),
+        };
+        let decoded = decode_position_event(&log(&event)).unwrap();
+        assert_eq!(decoded.token_id(), uint!(4_U256));
+        assert_eq!(decoded, PositionEvent::IncreaseLiquidity(event));
+    }
+
+    #[test]
+    fn test_decode_transfer() {
+        let event = Transfer {
+            from: Address::ZERO,
+            to: address!("4bD047CA72fa05F0B89ad08FE5Ba5ccdC07DFFBF"),
+            tokenId: uint!(4_U256),
+        };
+        let decoded = decode_position_event(&log(&event)).unwrap();
+        assert_eq!(decoded.token_id(), uint!(4_U256));
+        assert_eq!(decoded, PositionEvent::Transfer(event));
+    }
+
+    #[test]
+    fn test_decode_invalid_log() {
+        let event = IUniswapV3PoolEvents::IncreaseObservationCardinalityNext {
+            observationCardinalityNextOld: 1,
+            observationCardinalityNextNew: 10,
+        };
+        assert!(matches!(
+            decode_position_event(&log(&event)),
+            Err(Error::InvalidLog)
+        ));
+    }
+}
@@ -0,0 +1,280 @@
+use crate::prelude::*;
+use alloy_primitives::{Bytes, U256};
+use alloy_sol_types::{Panic, Revert, SolError, SolInterface};
+
+/// A custom error of the Universal Router.
+pub type UniversalRouterError = IUniversalRouter::IUniversalRouterErrors;
+
+/// A custom error of Permit2.
+pub type Permit2Error = IAllowanceTransfer::IAllowanceTransferErrors;
+
+/// A revert reason string of the core and periphery contracts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum UniswapRevert {
+    /// `LOK`
+    #[error("The pool is locked")]
+    Locked,
+    /// `TLU`
+    #[error("The lower tick must be below the upper tick")]
+    TickLowerNotBelowUpper,
+    /// `TLM`
+    #[error("The lower tick is below the minimum tick")]
+    TickLowerTooLow,
+    /// `TUM`
+    #[error("The upper tick is above the maximum tick")]
+    TickUpperTooHigh,
+    /// `AI`
+    #[error("The pool is already initialized")]
+    AlreadyInitialized,
+    /// `M0`
+    #[error("Insufficient token0 paid for the mint")]
+    InsufficientMintPayment0,
+    /// `M1`
+    #[error("Insufficient token1 paid for the mint")]
+    InsufficientMintPayment1,
+    /// `AS`
+    #[error("The amount specified is zero")]
+    ZeroAmountSpecified,
+    /// `SPL`
+    #[error("The sqrt price limit is out of bounds")]
+    InvalidSqrtPriceLimit,
+    /// `IIA`
+    #[error("Insufficient input amount paid for the swap")]
+    InsufficientInputAmount,
+    /// `L`
+    #[error("The pool has no liquidity")]
+    ZeroLiquidity,
+    /// `F0`
+    #[error("Insufficient token0 repaid for the flash loan")]
+    InsufficientFlashRepayment0,
+    /// `F1`
+    #[error("Insufficient token1 repaid for the flash loan")]
+    InsufficientFlashRepayment1,
+    /// `LO`
+    #[error("The liquidity of the tick exceeds the maximum")]
+    TickLiquidityOverflow,
+    /// `LS`
+    #[error("The liquidity delta underflows")]
+    LiquidityUnderflow,
+    /// `LA`
+    #[error("The liquidity delta overflows")]
+    LiquidityOverflow,
+    /// `STF`
+    #[error("Token transferFrom failed, e.g. insufficient balance or allowance")]
+    TransferFromFailed,
+    /// `ST`
+    #[error("Token transfer failed")]
+    TransferFailed,
+    /// `SA`
+    #[error("Token approve failed")]
+    ApproveFailed,
+    /// `STE`
+    #[error("Ether transfer failed")]
+    EthTransferFailed,
+    /// `Too little received`
+    #[error("Too little received")]
+    TooLittleReceived,
+    /// `Too much requested`
+    #[error("Too much requested")]
+    TooMuchRequested,
+    /// `Transaction too old`
+    #[error("The deadline has passed")]
+    TransactionTooOld,
+    /// `Price slippage check`
+    #[error("The amounts added are below the minimums")]
+    PriceSlippageCheck,
+    /// `Not approved`
+    #[error("The sender is not approved for the position")]
+    NotApproved,
+    /// `Not cleared`
+    #[error("The position still has liquidity or tokens owed")]
+    NotCleared,
+    /// `Insufficient WETH9`
+    #[error("Insufficient WETH9")]
+    InsufficientWeth9,
+    /// `Insufficient token`
+    #[error("Insufficient token")]
+    InsufficientToken,
+}
+
+impl UniswapRevert {
+    const REASONS: [(&'static str, Self); 28] = [
+        ("LOK", Self::Locked),
+        ("TLU", Self::TickLowerNotBelowUpper),
+        ("TLM", Self::TickLowerTooLow),
+        ("TUM", Self::TickUpperTooHigh),
+        ("AI", Self::AlreadyInitialized),
+        ("M0", Self::InsufficientMintPayment0),
+        ("M1", Self::InsufficientMintPayment1),
+        ("AS", Self::ZeroAmountSpecified),
+        ("SPL", Self::InvalidSqrtPriceLimit),
+        ("IIA", Self::InsufficientInputAmount),
+        ("L", Self::ZeroLiquidity),
+        ("F0", Self::InsufficientFlashRepayment0),
+        ("F1", Self::InsufficientFlashRepayment1),
+        ("LO", Self::TickLiquidityOverflow),
+        ("LS", Self::LiquidityUnderflow),
+        ("LA", Self::LiquidityOverflow),
+        ("STF", Self::TransferFromFailed),
+        ("ST", Self::TransferFailed),
+        ("SA", Self::ApproveFailed),
+        ("STE", Self::EthTransferFailed),
+        ("Too little received", Self::TooLittleReceived),
+        ("Too much requested", Self::TooMuchRequested),
+        ("Transaction too old", Self::TransactionTooOld),
+        ("Price slippage check", Self::PriceSlippageCheck),
+        ("Not approved", Self::NotApproved),
+        ("Not cleared", Self::NotCleared),
+        ("Insufficient WETH9", Self::InsufficientWeth9),
+        ("Insufficient token", Self::InsufficientToken),
+    ];
+
+    /// Parses the revert reason string of a contract, if it is one of the known reasons.
+    ///
+    /// ## Arguments
+    ///
+    /// * `reason`: The revert reason string, e.g. `LOK`
+    #[inline]
+    #[must_use]
+    pub fn from_reason(reason: &str) -> Option<Self> {
+        Self::REASONS
+            .iter()
+            .find(|(r, _)| *r == reason)
+            .map(|&(_, revert)| revert)
+    }
+
+    /// The revert reason string the contracts revert with.
+    #[inline]
+    #[must_use]
+    pub fn reason(&self) -> &'static str {
+        Self::REASONS
+            .iter()
+            .find(|(_, revert)| revert == self)
+            .map(|&(reason, _)| reason)
+            .unwrap()
+    }
+}
+
+/// The decoded revert data of a failed call.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RevertError {
+    /// A known revert reason string of the core and periphery contracts
+    #[error("{0}")]
+    Uniswap(UniswapRevert),
+    /// Any other revert reason string
+    #[error("{0}")]
+    Message(String),
+    /// A Solidity panic, e.g. `0x11` for an arithmetic overflow
+    #[error("Panic: {0:#x}")]
+    Panic(U256),
+    /// A custom error of the Universal Router
+    #[error("{0:?}")]
+    UniversalRouter(UniversalRouterError),
+    /// A custom error of Permit2
+    #[error("{0:?}")]
+    Permit2(Permit2Error),
+    /// Revert data that couldn't be decoded, empty if the call reverted without a reason
+    #[error("Unknown revert: {0}")]
+    Unknown(Bytes),
+}
+
+/// Decodes the revert data of a call to a pool, a router, the position manager or Permit2.
+///
+/// ## Arguments
+///
+/// * `data`: The revert data, e.g. returned by a failed `eth_call`
+#[inline]
+#[must_use]
+pub fn decode_revert(data: &[u8]) -> RevertError {
+    if let Ok(revert) = Revert::abi_decode(data, true) {
+        return UniswapRevert::from_reason(&revert.reason)
+            .map_or(RevertError::Message(revert.reason), RevertError::Uniswap);
+    }
+    if let Ok(panic) = Panic::abi_decode(data, true) {
+        return RevertError::Panic(panic.code);
+    }
+    if let Ok(error) = UniversalRouterError::abi_decode(data, true) {
+        return RevertError::UniversalRouter(error);
+    }
+    if let Ok(error) = Permit2Error::abi_decode(data, true) {
+        return RevertError::Permit2(error);
+    }
+    RevertError::Unknown(Bytes::copy_from_slice(data))
+}
+
+#[cfg(feature = "extensions")]
+impl Error {
+    /// Decodes the revert data of a failed `eth_call`, if any.
+    #[inline]
+    #[must_use]
+    pub fn revert_reason(&self) -> Option<RevertError> {
+        let Self::ContractError(alloy::contract::Error::TransportError(error)) = self else {
+            return None;
+        };
+        let data = error.as_error_resp()?.as_revert_data()?;
+        Some(decode_revert(&data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::uint;
+
+    #[test]
+    fn test_decode_reason() {
+        let data = Revert {
+            reason: "LOK".into(),
+        }
+        .abi_encode();
+        assert_eq!(
+            decode_revert(&data),
+            RevertError::Uniswap(UniswapRevert::Locked)
+        );
+        let data = Revert {
+            reason: "Too little received".into(),
+        }
+        .abi_encode();
+        assert_eq!(
+            decode_revert(&data).to_string(),
+            UniswapRevert::TooLittleReceived.to_string()
+        );
+        let data = Revert {
+            reason: "custom".into(),
+        }
+        .abi_encode();
+        assert_eq!(decode_revert(&data), RevertError::Message("custom".into()));
+        for (reason, revert) in UniswapRevert::REASONS {
+            assert_eq!(revert.reason(), reason);
+        }
+    }
+
+    #[test]
+    fn test_decode_panic() {
+        let data = Panic {
+            code: uint!(0x11_U256),
+        }
+        .abi_encode();
+        assert_eq!(decode_revert(&data), RevertError::Panic(uint!(0x11_U256)));
+    }
+
+    #[test]
+    fn test_decode_custom_error() {
+        let data = IUniversalRouter::V3TooLittleReceived {}.abi_encode();
+        assert_eq!(
+            decode_revert(&data),
+            RevertError::UniversalRouter(UniversalRouterError::V3TooLittleReceived(
+                IUniversalRouter::V3TooLittleReceived {}
+            ))
+        );
+        let data = IAllowanceTransfer::AllowanceExpired {
+            deadline: uint!(1_U256),
+        }
+        .abi_encode();
+        assert!(matches!(
+            decode_revert(&data),
+            RevertError::Permit2(Permit2Error::AllowanceExpired(_))
+        ));
+        assert_eq!(decode_revert(&[]), RevertError::Unknown(Bytes::new()));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::prelude::{Error, *};
 use alloy_primitives::{aliases::U24, Bytes};
 use alloy_sol_types::SolValue;
 use uniswap_sdk_core::prelude::*;
@@ -56,6 +56,35 @@ where
     path.into()
 }
 
+/// Decodes a path encoded by [`encode_route_to_path`] into its tokens and fees.
+///
+/// ## Arguments
+///
+/// * `path`: the encoded path of token addresses separated by 3-byte fees
+///
+/// ## Returns
+///
+/// The token addresses in the order of the path and the fee of each pool between them
+#[inline]
+pub fn decode_path(path: &[u8]) -> Result<(Vec<Address>, Vec<U24>), Error> {
+    const ADDR_SIZE: usize = 20;
+    const LEG_SIZE: usize = ADDR_SIZE + 3;
+    if path.len() < ADDR_SIZE + LEG_SIZE || (path.len() - ADDR_SIZE) % LEG_SIZE != 0 {
+        return Err(Error::InvalidPath);
+    }
+    let num_pools = (path.len() - ADDR_SIZE) / LEG_SIZE;
+    let mut tokens = Vec::with_capacity(num_pools + 1);
+    let mut fees = Vec::with_capacity(num_pools);
+    tokens.push(Address::from_slice(&path[..ADDR_SIZE]));
+    for leg in path[ADDR_SIZE..].chunks_exact(LEG_SIZE) {
+        let fee =
+            FeePips::try_from(U24::from_be_slice(&leg[..3])).map_err(|_| Error::InvalidPath)?;
+        fees.push(fee.get());
+        tokens.push(Address::from_slice(&leg[3..]));
+    }
+    Ok((tokens, fees))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,4 +227,41 @@ mod tests {
             hex!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2000bb80000000000000000000000000000000000000002000bb80000000000000000000000000000000000000001")
         );
     }
+
+    #[test]
+    fn decode_exact_input_multihop() {
+        let (tokens, fees) = decode_path(&encode_route_to_path(&ROUTE_0_1_2, false)).unwrap();
+        assert_eq!(
+            tokens,
+            vec![TOKEN0.address(), TOKEN1.address(), TOKEN2.address()]
+        );
+        assert_eq!(fees, vec![U24::from(3000), U24::from(500)]);
+    }
+
+    #[test]
+    fn decode_exact_output_multihop() {
+        let (tokens, fees) = decode_path(&encode_route_to_path(&ROUTE_0_1_WETH, true)).unwrap();
+        assert_eq!(
+            tokens,
+            vec![WETH.address(), TOKEN1.address(), TOKEN0.address()]
+        );
+        assert_eq!(fees, vec![U24::from(3000), U24::from(3000)]);
+    }
+
+    #[test]
+    fn decode_invalid_length() {
+        let path = encode_route_to_path(&ROUTE_0_1, false);
+        assert!(matches!(
+            decode_path(&path[..path.len() - 1]),
+            Err(Error::InvalidPath)
+        ));
+        assert!(matches!(decode_path(&path[..20]), Err(Error::InvalidPath)));
+    }
+
+    #[test]
+    fn decode_invalid_fee() {
+        let mut path = encode_route_to_path(&ROUTE_0_1, false).to_vec();
+        path[20..23].copy_from_slice(&[0xff; 3]);
+        assert!(matches!(decode_path(&path), Err(Error::InvalidPath)));
+    }
 }
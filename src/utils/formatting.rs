@@ -0,0 +1,304 @@
+//! ## Formatting
+//! Human-readable formatting of currency amounts and prices with a number of significant digits or
+//! decimal places, mirroring `toSignificant` and `toFixed` of the TypeScript SDK, and parsing of
+//! decimal strings into raw amounts.
+
+use crate::prelude::Error;
+use alloc::{format, string::String};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+use uniswap_sdk_core::prelude::*;
+
+/// How to format a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// With at most the given number of significant digits and no trailing zeros, like
+    /// `toSignificant`
+    Significant(u8),
+    /// With exactly the given number of decimal places, like `toFixed`
+    Fixed(u8),
+}
+
+/// Divides `numerator` by the positive `denominator`, rounding the quotient of the magnitudes.
+fn div_round(numerator: &BigInt, denominator: &BigInt, rounding: Rounding) -> BigInt {
+    let (quotient, remainder) = numerator.div_rem(denominator);
+    let round_up = match rounding {
+        Rounding::RoundDown => false,
+        Rounding::RoundHalfUp => remainder.abs() * 2 >= *denominator,
+        Rounding::RoundUp => !remainder.is_zero(),
+    };
+    if round_up {
+        quotient + numerator.signum()
+    } else {
+        quotient
+    }
+}
+
+/// Formats `digits * 10^-decimal_places`, where `decimal_places` may be negative.
+fn format_digits(digits: &BigInt, decimal_places: i64) -> String {
+    let sign = if digits.is_negative() { "-" } else { "" };
+    let mut digits = digits.abs().to_string();
+    if decimal_places <= 0 {
+        digits.extend(core::iter::repeat('0').take(decimal_places.unsigned_abs() as usize));
+        return format!("{sign}{digits}");
+    }
+    let decimal_places = decimal_places as usize;
+    if digits.len() <= decimal_places {
+        let zeros = "0".repeat(decimal_places + 1 - digits.len());
+        digits.insert_str(0, &zeros);
+    }
+    let (whole, fraction) = digits.split_at(digits.len() - decimal_places);
+    format!("{sign}{whole}.{fraction}")
+}
+
+/// Formats the fraction `numerator / denominator`.
+///
+/// ## Arguments
+///
+/// * `numerator`: The numerator of the fraction
+/// * `denominator`: The denominator of the fraction, which must not be zero
+/// * `format`: The number of significant digits or decimal places
+/// * `rounding`: The rounding mode, [`Rounding::RoundHalfUp`] by default
+#[inline]
+#[must_use]
+pub fn format_fraction(
+    numerator: &BigInt,
+    denominator: &BigInt,
+    format: NumberFormat,
+    rounding: Option<Rounding>,
+) -> String {
+    let rounding = rounding.unwrap_or(Rounding::RoundHalfUp);
+    let (numerator, denominator) = if denominator.is_negative() {
+        (-numerator, -denominator)
+    } else {
+        (numerator.clone(), denominator.clone())
+    };
+    let ten = BigInt::from(10);
+    let scale = |decimal_places: i64| {
+        let factor = ten.pow(decimal_places.unsigned_abs() as u32);
+        if decimal_places >= 0 {
+            div_round(&(&numerator * factor), &denominator, rounding)
+        } else {
+            div_round(&numerator, &(&denominator * factor), rounding)
+        }
+    };
+    match format {
+        NumberFormat::Fixed(decimal_places) => {
+            format_digits(&scale(i64::from(decimal_places)), i64::from(decimal_places))
+        }
+        NumberFormat::Significant(significant_digits) => {
+            assert!(significant_digits > 0, "SIGNIFICANT_DIGITS");
+            if numerator.is_zero() {
+                return "0".into();
+            }
+            // 10^(exponent - 1) <= |numerator / denominator| < 10^exponent, where the exponent is
+            // estimated from the number of digits of the numerator and denominator
+            let integer_digits = |value: &BigInt| value.abs().to_string().len() as i64;
+            let estimate = integer_digits(&numerator) - integer_digits(&denominator);
+            let factor = ten.pow(estimate.unsigned_abs() as u32);
+            let exponent = if (estimate >= 0 && numerator.abs() >= &denominator * &factor)
+                || (estimate < 0 && numerator.abs() * &factor >= denominator)
+            {
+                estimate + 1
+            } else {
+                estimate
+            };
+            let mut decimal_places = i64::from(significant_digits) - exponent;
+            let mut digits = scale(decimal_places);
+            // rounding may carry into a new digit, e.g. 9.99 to 10.0
+            if integer_digits(&digits) > i64::from(significant_digits) {
+                decimal_places -= 1;
+                digits = scale(decimal_places);
+            }
+            // strip the trailing zeros of the fraction
+            while decimal_places > 0 && (&digits % &ten).is_zero() {
+                digits /= &ten;
+                decimal_places -= 1;
+            }
+            format_digits(&digits, decimal_places)
+        }
+    }
+}
+
+/// Formats a currency amount in units of the currency, e.g. `"1.5"` for 1.5 WETH.
+///
+/// ## Arguments
+///
+/// * `amount`: The currency amount
+/// * `format`: The number of significant digits or decimal places
+/// * `rounding`: The rounding mode, [`Rounding::RoundHalfUp`] by default
+#[inline]
+#[must_use]
+pub fn format_currency_amount<T: BaseCurrency>(
+    amount: &CurrencyAmount<T>,
+    format: NumberFormat,
+    rounding: Option<Rounding>,
+) -> String {
+    let scalar = BigInt::from(10).pow(u32::from(amount.meta.currency.decimals()));
+    format_fraction(
+        &amount.numerator,
+        &(&amount.denominator * scalar),
+        format,
+        rounding,
+    )
+}
+
+/// Formats a price in units of the quote currency per unit of the base currency, e.g. `"2000"` for
+/// 2000 USDC per WETH.
+///
+/// ## Arguments
+///
+/// * `price`: The price
+/// * `format`: The number of significant digits or decimal places
+/// * `rounding`: The rounding mode, [`Rounding::RoundHalfUp`] by default
+#[inline]
+#[must_use]
+pub fn format_price<TBase: BaseCurrency, TQuote: BaseCurrency>(
+    price: &Price<TBase, TQuote>,
+    format: NumberFormat,
+    rounding: Option<Rounding>,
+) -> String {
+    let ten = BigInt::from(10);
+    format_fraction(
+        &(&price.numerator * ten.pow(u32::from(price.base_currency.decimals()))),
+        &(&price.denominator * ten.pow(u32::from(price.quote_currency.decimals()))),
+        format,
+        rounding,
+    )
+}
+
+/// Parses a decimal string in units of a currency into a currency amount, e.g. `"1.5"` into
+/// 1.5 * 10^18 wei of WETH.
+///
+/// ## Arguments
+///
+/// * `currency`: The currency of the amount
+/// * `value`: The decimal string, with an optional leading `-` and at most as many decimal places
+///   as the decimals of the currency
+///
+/// ## Returns
+///
+/// The currency amount, or [`Error::InvalidAmount`] if `value` is not a valid decimal string
+#[inline]
+pub fn parse_currency_amount<T: BaseCurrency>(
+    currency: T,
+    value: &str,
+) -> Result<CurrencyAmount<T>, Error> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value),
+    };
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let decimals = usize::from(currency.decimals());
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > decimals
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(Error::InvalidAmount);
+    }
+    let digits = format!("{whole}{fraction:0<decimals$}");
+    let mut raw_amount: BigInt = digits.parse().map_err(|_| Error::InvalidAmount)?;
+    if negative {
+        raw_amount = -raw_amount;
+    }
+    Ok(CurrencyAmount::from_raw_amount(currency, raw_amount)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    fn format(numerator: i64, denominator: i64, format: NumberFormat) -> String {
+        format_fraction(&numerator.into(), &denominator.into(), format, None)
+    }
+
+    #[test]
+    fn test_format_significant() {
+        assert_eq!(format(1, 3, NumberFormat::Significant(4)), "0.3333");
+        assert_eq!(format(2, 3, NumberFormat::Significant(4)), "0.6667");
+        assert_eq!(format(-2, 3, NumberFormat::Significant(2)), "-0.67");
+        assert_eq!(format(2000, 1, NumberFormat::Significant(6)), "2000");
+        assert_eq!(format(123456, 1, NumberFormat::Significant(3)), "123000");
+        assert_eq!(format(999, 1000, NumberFormat::Significant(2)), "1");
+        assert_eq!(format(15, 10000, NumberFormat::Significant(5)), "0.0015");
+        assert_eq!(format(0, 1, NumberFormat::Significant(5)), "0");
+    }
+
+    #[test]
+    fn test_format_fixed() {
+        assert_eq!(format(1, 3, NumberFormat::Fixed(2)), "0.33");
+        assert_eq!(format(-1, 3, NumberFormat::Fixed(2)), "-0.33");
+        assert_eq!(format(2, 3, NumberFormat::Fixed(0)), "1");
+        assert_eq!(format(5, 1, NumberFormat::Fixed(3)), "5.000");
+        assert_eq!(
+            format_fraction(
+                &2.into(),
+                &3.into(),
+                NumberFormat::Fixed(0),
+                Some(Rounding::RoundDown)
+            ),
+            "0"
+        );
+        assert_eq!(
+            format_fraction(
+                &1.into(),
+                &3.into(),
+                NumberFormat::Fixed(1),
+                Some(Rounding::RoundUp)
+            ),
+            "0.4"
+        );
+    }
+
+    #[test]
+    fn test_currency_amount() {
+        let amount = parse_currency_amount(WETH.clone(), "1.5").unwrap();
+        assert_eq!(
+            amount.quotient(),
+            BigInt::from(1_500_000_000_000_000_000_u64)
+        );
+        assert_eq!(
+            format_currency_amount(&amount, NumberFormat::Significant(4), None),
+            "1.5"
+        );
+        assert_eq!(
+            format_currency_amount(&amount, NumberFormat::Fixed(2), None),
+            "1.50"
+        );
+        let amount = parse_currency_amount(USDC.clone(), ".000001").unwrap();
+        assert_eq!(amount.quotient(), BigInt::from(1));
+        for invalid in ["", ".", "-", "1.0000001", "1e6", "1,5", "0x10"] {
+            assert!(matches!(
+                parse_currency_amount(USDC.clone(), invalid),
+                Err(Error::InvalidAmount)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_format_price() {
+        let price = Price::new(
+            WETH.clone(),
+            USDC.clone(),
+            1_000_000_000_000_000_000_u128,
+            2_000_000_000,
+        );
+        assert_eq!(
+            format_price(&price, NumberFormat::Significant(6), None),
+            "2000"
+        );
+        assert_eq!(
+            format_price(&price, NumberFormat::Fixed(2), None),
+            "2000.00"
+        );
+        assert_eq!(
+            format_price(&price.invert(), NumberFormat::Significant(3), None),
+            "0.0005"
+        );
+    }
+}
@@ -138,6 +138,27 @@ pub fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Result<U256,
     }
 }
 
+/// Calculates floor(a×b÷denominator) with full precision, like [`mul_div`], but distinguishes a
+/// zero denominator from an overflowing result for use with untrusted input.
+///
+/// ## Arguments
+///
+/// * `a`: The multiplicand
+/// * `b`: The multiplier
+/// * `denominator`: The divisor
+///
+/// ## Returns
+///
+/// The result, [`Error::DivisionByZero`] if `denominator` is zero, or [`Error::MulDivOverflow`] if
+/// the result doesn't fit in a uint256
+#[inline]
+pub fn try_mul_div(a: U256, b: U256, denominator: U256) -> Result<U256, Error> {
+    if denominator.is_zero() {
+        return Err(Error::DivisionByZero);
+    }
+    mul_div(a, b, denominator)
+}
+
 /// Calculates a * b / 2^96 with full precision.
 #[inline]
 pub fn mul_div_q96(a: U256, b: U256) -> Result<U256, Error> {
@@ -149,3 +170,24 @@ pub fn mul_div_q96(a: U256, b: U256) -> Result<U256, Error> {
     }
     Ok((prod0 >> 96) | (prod1 << 160))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_mul_div() {
+        assert_eq!(
+            try_mul_div(U256::MAX, U256::MAX, U256::MAX).unwrap(),
+            U256::MAX
+        );
+        assert!(matches!(
+            try_mul_div(U256::from(1), U256::from(1), U256::ZERO),
+            Err(Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            try_mul_div(U256::MAX, U256::from(2), U256::from(1)),
+            Err(Error::MulDivOverflow)
+        ));
+    }
+}
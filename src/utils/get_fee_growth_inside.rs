@@ -6,7 +6,27 @@ pub struct FeeGrowthOutside<const BITS: usize, const LIMBS: usize> {
     pub fee_growth_outside1_x128: Uint<BITS, LIMBS>,
 }
 
+/// Computes the fee growth inside a tick range from the fee growth outside its ticks, like
+/// `Tick.getFeeGrowthInside`.
+///
+/// The fee growth accumulators are allowed to overflow on chain, so the differences wrap around
+/// like in Solidity < 0.8 instead of panicking.
+///
+/// ## Arguments
+///
+/// * `lower`: The fee growth outside the lower tick
+/// * `upper`: The fee growth outside the upper tick
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `tick_current`: The current tick of the pool
+/// * `fee_growth_global0_x128`: The all-time global fee growth of token0
+/// * `fee_growth_global1_x128`: The all-time global fee growth of token1
+///
+/// ## Returns
+///
+/// The fee growth inside the range of token0 and token1
 #[inline]
+#[must_use]
 #[allow(clippy::needless_pass_by_value)]
 pub fn get_fee_growth_inside<const BITS: usize, const LIMBS: usize, T: PartialOrd>(
     lower: FeeGrowthOutside<BITS, LIMBS>,
@@ -20,18 +40,26 @@ pub fn get_fee_growth_inside<const BITS: usize, const LIMBS: usize, T: PartialOr
     let fee_growth_inside0_x128;
     let fee_growth_inside1_x128;
     if tick_current < tick_lower {
-        fee_growth_inside0_x128 = lower.fee_growth_outside0_x128 - upper.fee_growth_outside0_x128;
-        fee_growth_inside1_x128 = lower.fee_growth_outside1_x128 - upper.fee_growth_outside1_x128;
+        fee_growth_inside0_x128 = lower
+            .fee_growth_outside0_x128
+            .wrapping_sub(upper.fee_growth_outside0_x128);
+        fee_growth_inside1_x128 = lower
+            .fee_growth_outside1_x128
+            .wrapping_sub(upper.fee_growth_outside1_x128);
     } else if tick_current >= tick_upper {
-        fee_growth_inside0_x128 = upper.fee_growth_outside0_x128 - lower.fee_growth_outside0_x128;
-        fee_growth_inside1_x128 = upper.fee_growth_outside1_x128 - lower.fee_growth_outside1_x128;
+        fee_growth_inside0_x128 = upper
+            .fee_growth_outside0_x128
+            .wrapping_sub(lower.fee_growth_outside0_x128);
+        fee_growth_inside1_x128 = upper
+            .fee_growth_outside1_x128
+            .wrapping_sub(lower.fee_growth_outside1_x128);
     } else {
         fee_growth_inside0_x128 = fee_growth_global0_x128
-            - lower.fee_growth_outside0_x128
-            - upper.fee_growth_outside0_x128;
+            .wrapping_sub(lower.fee_growth_outside0_x128)
+            .wrapping_sub(upper.fee_growth_outside0_x128);
         fee_growth_inside1_x128 = fee_growth_global1_x128
-            - lower.fee_growth_outside1_x128
-            - upper.fee_growth_outside1_x128;
+            .wrapping_sub(lower.fee_growth_outside1_x128)
+            .wrapping_sub(upper.fee_growth_outside1_x128);
     }
     (fee_growth_inside0_x128, fee_growth_inside1_x128)
 }
@@ -85,4 +113,40 @@ mod tests {
         assert_eq!(fee_growth_inside0_x128, q127);
         assert_eq!(fee_growth_inside1_x128, q127);
     }
+
+    #[test]
+    fn test_overflow_inside() {
+        // the global fee growth wrapped around after the ticks were initialized
+        let lower = FeeGrowthOutside {
+            fee_growth_outside0_x128: U256::MAX - Q128 + U256::from(1),
+            fee_growth_outside1_x128: U256::MAX,
+        };
+        let upper = FeeGrowthOutside::default();
+        let (fee_growth_inside0_x128, fee_growth_inside1_x128) =
+            get_fee_growth_inside(lower, upper, -1, 1, 0, Q128, U256::ZERO);
+        assert_eq!(fee_growth_inside0_x128, Q128 << 1);
+        assert_eq!(fee_growth_inside1_x128, U256::from(1));
+    }
+
+    #[test]
+    fn test_overflow_outside() {
+        let lower = FeeGrowthOutside {
+            fee_growth_outside0_x128: U256::from(1),
+            fee_growth_outside1_x128: Q128,
+        };
+        let upper = FeeGrowthOutside {
+            fee_growth_outside0_x128: U256::from(2),
+            fee_growth_outside1_x128: Q128 << 1,
+        };
+        // below the range
+        let (fee_growth_inside0_x128, fee_growth_inside1_x128) =
+            get_fee_growth_inside(lower, upper, -1, 1, -2, U256::ZERO, U256::ZERO);
+        assert_eq!(fee_growth_inside0_x128, U256::MAX);
+        assert_eq!(fee_growth_inside1_x128, Q128.wrapping_neg());
+        // above the range
+        let (fee_growth_inside0_x128, fee_growth_inside1_x128) =
+            get_fee_growth_inside(upper, lower, -1, 1, 1, U256::ZERO, U256::ZERO);
+        assert_eq!(fee_growth_inside0_x128, U256::MAX);
+        assert_eq!(fee_growth_inside1_x128, Q128.wrapping_neg());
+    }
 }
@@ -1,7 +1,22 @@
-use super::Q128;
-use alloy_primitives::Uint;
+use alloy_primitives::{Uint, U512};
 
-/// Computes the amount of fees owed to a position
+/// Computes the amount of fees owed to a position, like `Position.update`.
+///
+/// The fee growth accumulators are allowed to overflow on chain, so the growth since the last
+/// update wraps around instead of panicking. The product with the liquidity is computed with full
+/// precision, like `FullMath.mulDiv`, and the result wraps around at `BITS` bits.
+///
+/// ## Arguments
+///
+/// * `fee_growth_inside_0_last_x128`: The fee growth inside of token0 at the last update
+/// * `fee_growth_inside_1_last_x128`: The fee growth inside of token1 at the last update
+/// * `liquidity`: The liquidity of the position
+/// * `fee_growth_inside_0_x128`: The current fee growth inside of token0
+/// * `fee_growth_inside_1_x128`: The current fee growth inside of token1
+///
+/// ## Returns
+///
+/// The amounts of token0 and token1 owed to the position
 #[inline]
 #[must_use]
 pub fn get_tokens_owed<const BITS: usize, const LIMBS: usize>(
@@ -11,18 +26,23 @@ pub fn get_tokens_owed<const BITS: usize, const LIMBS: usize>(
     fee_growth_inside_0_x128: Uint<BITS, LIMBS>,
     fee_growth_inside_1_x128: Uint<BITS, LIMBS>,
 ) -> (Uint<BITS, LIMBS>, Uint<BITS, LIMBS>) {
-    let liquidity = Uint::from(liquidity);
-    let q128 = Uint::from(Q128);
-    let tokens_owed_0 =
-        (fee_growth_inside_0_x128 - fee_growth_inside_0_last_x128) * liquidity / q128;
-    let tokens_owed_1 =
-        (fee_growth_inside_1_x128 - fee_growth_inside_1_last_x128) * liquidity / q128;
-    (tokens_owed_0, tokens_owed_1)
+    let liquidity = U512::from(liquidity);
+    let tokens_owed = |fee_growth_inside_x128: Uint<BITS, LIMBS>,
+                       fee_growth_inside_last_x128: Uint<BITS, LIMBS>| {
+        let delta =
+            U512::saturating_from(fee_growth_inside_x128.wrapping_sub(fee_growth_inside_last_x128));
+        Uint::<BITS, LIMBS>::wrapping_from((delta * liquidity) >> 128)
+    };
+    (
+        tokens_owed(fee_growth_inside_0_x128, fee_growth_inside_0_last_x128),
+        tokens_owed(fee_growth_inside_1_x128, fee_growth_inside_1_last_x128),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::Q128;
     use alloy_primitives::U256;
 
     #[test]
@@ -31,4 +51,27 @@ mod tests {
         assert_eq!(tokens_owed_0, U256::from(1));
         assert_eq!(tokens_owed_1, U256::from(1));
     }
+
+    #[test]
+    fn test_get_tokens_owed_overflow() {
+        // the fee growth inside wrapped around since the last update
+        let (tokens_owed_0, tokens_owed_1) = get_tokens_owed(
+            U256::MAX - Q128 + U256::from(1),
+            U256::MAX,
+            3,
+            Q128,
+            U256::ZERO,
+        );
+        assert_eq!(tokens_owed_0, U256::from(6));
+        assert_eq!(tokens_owed_1, U256::ZERO);
+    }
+
+    #[test]
+    fn test_get_tokens_owed_large_product() {
+        // the product of the fee growth and the liquidity exceeds 256 bits
+        let (tokens_owed_0, tokens_owed_1) =
+            get_tokens_owed(U256::ZERO, U256::ZERO, u128::MAX, Q128 << 64, Q128);
+        assert_eq!(tokens_owed_0, U256::from(u128::MAX) << 64);
+        assert_eq!(tokens_owed_1, U256::from(u128::MAX));
+    }
 }
@@ -1,4 +1,17 @@
+//! ## Liquidity Math
+//! Conversions between liquidity and token amounts for downstream accounting, porting
+//! [LiquidityAmounts](https://github.com/Uniswap/v3-periphery/blob/main/contracts/libraries/LiquidityAmounts.sol)
+//! in the v3 periphery, and re-exporting [`max_liquidity_for_amounts`], [`get_amount_0_delta`]
+//! and [`get_amount_1_delta`].
+
+pub use super::{
+    max_liquidity_for_amounts::max_liquidity_for_amounts,
+    sqrt_price_math::{get_amount_0_delta, get_amount_1_delta},
+};
+
+use super::{FullMath, Q96};
 use crate::error::Error;
+use alloy_primitives::{Uint, U256};
 
 /// Add a signed liquidity delta to liquidity and revert if it overflows or underflows
 ///
@@ -14,3 +27,205 @@ use crate::error::Error;
 pub fn add_delta(x: u128, y: i128) -> Result<u128, Error> {
     x.checked_add_signed(y).ok_or(Error::AddDeltaOverflow)
 }
+
+#[inline]
+fn sort2<const BITS: usize, const LIMBS: usize>(
+    a: Uint<BITS, LIMBS>,
+    b: Uint<BITS, LIMBS>,
+) -> (U256, U256) {
+    if a > b {
+        (U256::from(b), U256::from(a))
+    } else {
+        (U256::from(a), U256::from(b))
+    }
+}
+
+#[inline]
+fn to_u128(liquidity: U256) -> Result<u128, Error> {
+    u128::try_from(liquidity).map_err(|_| Error::SafeCastToU128Overflow)
+}
+
+/// Computes the amount of liquidity received for a given amount of token0 and price range,
+/// rounded down like the periphery contracts
+///
+/// ## Arguments
+///
+/// * `sqrt_ratio_a_x96`: A sqrt price representing the first tick boundary
+/// * `sqrt_ratio_b_x96`: A sqrt price representing the second tick boundary
+/// * `amount0`: The amount0 being sent in
+///
+/// ## Returns
+///
+/// The amount of returned liquidity
+#[inline]
+pub fn get_liquidity_for_amount0<const BITS: usize, const LIMBS: usize>(
+    sqrt_ratio_a_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_b_x96: Uint<BITS, LIMBS>,
+    amount0: U256,
+) -> Result<u128, Error> {
+    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = sort2(sqrt_ratio_a_x96, sqrt_ratio_b_x96);
+    let intermediate = sqrt_ratio_a_x96.mul_div(sqrt_ratio_b_x96, Q96)?;
+    to_u128(amount0.mul_div(intermediate, sqrt_ratio_b_x96 - sqrt_ratio_a_x96)?)
+}
+
+/// Computes the amount of liquidity received for a given amount of token1 and price range,
+/// rounded down like the periphery contracts
+///
+/// ## Arguments
+///
+/// * `sqrt_ratio_a_x96`: A sqrt price representing the first tick boundary
+/// * `sqrt_ratio_b_x96`: A sqrt price representing the second tick boundary
+/// * `amount1`: The amount1 being sent in
+///
+/// ## Returns
+///
+/// The amount of returned liquidity
+#[inline]
+pub fn get_liquidity_for_amount1<const BITS: usize, const LIMBS: usize>(
+    sqrt_ratio_a_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_b_x96: Uint<BITS, LIMBS>,
+    amount1: U256,
+) -> Result<u128, Error> {
+    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = sort2(sqrt_ratio_a_x96, sqrt_ratio_b_x96);
+    to_u128(amount1.mul_div(Q96, sqrt_ratio_b_x96 - sqrt_ratio_a_x96)?)
+}
+
+/// Computes the maximum amount of liquidity received for a given amount of token0, token1, the
+/// current pool prices and the prices at the tick boundaries
+///
+/// ## Arguments
+///
+/// * `sqrt_ratio_x96`: A sqrt price representing the current pool prices
+/// * `sqrt_ratio_a_x96`: A sqrt price representing the first tick boundary
+/// * `sqrt_ratio_b_x96`: A sqrt price representing the second tick boundary
+/// * `amount0`: The amount of token0 being sent in
+/// * `amount1`: The amount of token1 being sent in
+///
+/// ## Returns
+///
+/// The maximum amount of liquidity received
+#[inline]
+pub fn get_liquidity_for_amounts<const BITS: usize, const LIMBS: usize>(
+    sqrt_ratio_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_a_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_b_x96: Uint<BITS, LIMBS>,
+    amount0: U256,
+    amount1: U256,
+) -> Result<u128, Error> {
+    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
+    } else {
+        (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
+    };
+    if sqrt_ratio_x96 <= sqrt_ratio_a_x96 {
+        get_liquidity_for_amount0(sqrt_ratio_a_x96, sqrt_ratio_b_x96, amount0)
+    } else if sqrt_ratio_x96 < sqrt_ratio_b_x96 {
+        let liquidity0 = get_liquidity_for_amount0(sqrt_ratio_x96, sqrt_ratio_b_x96, amount0)?;
+        let liquidity1 = get_liquidity_for_amount1(sqrt_ratio_a_x96, sqrt_ratio_x96, amount1)?;
+        Ok(liquidity0.min(liquidity1))
+    } else {
+        get_liquidity_for_amount1(sqrt_ratio_a_x96, sqrt_ratio_b_x96, amount1)
+    }
+}
+
+/// Computes the token0 and token1 value for a given amount of liquidity, the current pool prices
+/// and the prices at the tick boundaries, rounded down like the periphery contracts
+///
+/// ## Arguments
+///
+/// * `sqrt_ratio_x96`: A sqrt price representing the current pool prices
+/// * `sqrt_ratio_a_x96`: A sqrt price representing the first tick boundary
+/// * `sqrt_ratio_b_x96`: A sqrt price representing the second tick boundary
+/// * `liquidity`: The liquidity being valued
+///
+/// ## Returns
+///
+/// The amount of token0 and token1
+#[inline]
+pub fn get_amounts_for_liquidity<const BITS: usize, const LIMBS: usize>(
+    sqrt_ratio_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_a_x96: Uint<BITS, LIMBS>,
+    sqrt_ratio_b_x96: Uint<BITS, LIMBS>,
+    liquidity: u128,
+) -> Result<(U256, U256), Error> {
+    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
+    } else {
+        (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
+    };
+    if sqrt_ratio_x96 <= sqrt_ratio_a_x96 {
+        Ok((
+            get_amount_0_delta(sqrt_ratio_a_x96, sqrt_ratio_b_x96, liquidity, false)?,
+            U256::ZERO,
+        ))
+    } else if sqrt_ratio_x96 < sqrt_ratio_b_x96 {
+        Ok((
+            get_amount_0_delta(sqrt_ratio_x96, sqrt_ratio_b_x96, liquidity, false)?,
+            get_amount_1_delta(sqrt_ratio_a_x96, sqrt_ratio_x96, liquidity, false)?,
+        ))
+    } else {
+        Ok((
+            U256::ZERO,
+            get_amount_1_delta(sqrt_ratio_a_x96, sqrt_ratio_b_x96, liquidity, false)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_sqrt_ratio_x96;
+    use alloy_primitives::U160;
+
+    #[test]
+    fn test_get_liquidity_for_amounts() {
+        let current: U160 = encode_sqrt_ratio_x96(1, 1);
+        let lower: U160 = encode_sqrt_ratio_x96(100, 110);
+        let upper: U160 = encode_sqrt_ratio_x96(110, 100);
+        let cases = [
+            (current, U256::from(100), U256::from(200), 2148),
+            (
+                encode_sqrt_ratio_x96(99, 110),
+                U256::from(100),
+                U256::from(200),
+                1048,
+            ),
+            (
+                encode_sqrt_ratio_x96(111, 100),
+                U256::from(100),
+                U256::from(200),
+                2097,
+            ),
+        ];
+        for (sqrt_ratio_x96, amount0, amount1, expected) in cases {
+            let liquidity =
+                get_liquidity_for_amounts(sqrt_ratio_x96, lower, upper, amount0, amount1).unwrap();
+            assert_eq!(liquidity, expected);
+            // agrees with the imprecise calculation the router uses
+            assert_eq!(
+                liquidity,
+                u128::try_from(max_liquidity_for_amounts(
+                    sqrt_ratio_x96,
+                    lower,
+                    upper,
+                    amount0,
+                    amount1,
+                    false
+                ))
+                .unwrap()
+            );
+            // get the amounts back, rounded down
+            let (amount0_out, amount1_out) =
+                get_amounts_for_liquidity(sqrt_ratio_x96, lower, upper, liquidity).unwrap();
+            assert!(amount0_out <= amount0 && amount1_out <= amount1);
+        }
+    }
+
+    #[test]
+    fn test_get_liquidity_overflow() {
+        assert!(matches!(
+            get_liquidity_for_amount1(U160::from(1), U160::from(2), U256::from(u128::MAX)),
+            Err(Error::SafeCastToU128Overflow)
+        ));
+    }
+}
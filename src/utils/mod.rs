@@ -1,32 +1,53 @@
 pub mod bit_math;
 pub mod compute_pool_address;
+pub mod decode_pool_event;
+pub mod decode_position_event;
+pub mod decode_revert;
 pub mod encode_route_to_path;
 pub mod encode_sqrt_ratio_x96;
+pub mod formatting;
 pub mod full_math;
 pub mod get_fee_growth_inside;
 pub mod get_tokens_owed;
 pub mod liquidity_math;
 pub mod max_liquidity_for_amounts;
 pub mod nearest_usable_tick;
+pub mod newtypes;
 pub mod price_tick_conversions;
+pub mod range_order;
+#[cfg(feature = "serde")]
+pub mod serde_utils;
+pub mod slippage;
 pub mod sqrt_price_math;
+pub mod sqrt_price_x96;
 pub mod swap_math;
 pub mod tick_list;
 pub mod tick_math;
 mod types;
 
 pub use bit_math::*;
-pub use compute_pool_address::compute_pool_address;
-pub use encode_route_to_path::encode_route_to_path;
+pub use compute_pool_address::{compute_pool_address, PoolDeployment};
+pub use decode_pool_event::*;
+pub use decode_position_event::*;
+pub use decode_revert::*;
+pub use encode_route_to_path::{decode_path, encode_route_to_path};
 pub use encode_sqrt_ratio_x96::encode_sqrt_ratio_x96;
+pub use formatting::*;
 pub use full_math::*;
 pub use get_fee_growth_inside::*;
 pub use get_tokens_owed::get_tokens_owed;
-pub use liquidity_math::add_delta;
+pub use liquidity_math::{
+    add_delta, get_amounts_for_liquidity, get_liquidity_for_amount0, get_liquidity_for_amount1,
+    get_liquidity_for_amounts,
+};
 pub use max_liquidity_for_amounts::*;
 pub use nearest_usable_tick::nearest_usable_tick;
+pub use newtypes::{CheckedTick, FeePips};
 pub use price_tick_conversions::*;
+pub use range_order::{range_order, RangeOrder};
+pub use slippage::*;
 pub use sqrt_price_math::*;
+pub use sqrt_price_x96::SqrtPriceX96;
 pub use swap_math::*;
 pub use tick_list::TickList;
 pub use tick_math::*;
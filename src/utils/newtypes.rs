@@ -0,0 +1,303 @@
+//! Range-checked newtypes for tick indexes and fees, to validate raw integers from untrusted input
+//! once instead of carrying out-of-range values into `CREATE2` or ABI encoding.
+//!
+//! The newtypes are deliberately limited to the boundaries where raw integers enter the crate:
+//! decoding serialized pools, tick lists and paths, pool creation events, and
+//! [`FeeAmount::Custom`], whose fee is a [`FeePips`] so that every fee reaching
+//! [`compute_pool_address`] is in range. They are not threaded through the public constructors of
+//! [`Pool`] and [`Position`], which keep taking [`TickIndex`] and [`FeeAmount`]: ticks there are
+//! generic over the tick data provider's index, [`FeeAmount`] already carries a [`FeePips`], and
+//! the ticks of positions are range-checked by [`Position::try_new`].
+//!
+//! The tick newtype is named [`CheckedTick`] because [`TickIndex`] is the trait for tick index
+//! types, and the fee newtype is named [`FeePips`] because pool fees are in hundredths of a bip,
+//! not in bips.
+
+#[cfg(doc)]
+use crate::prelude::*;
+
+use super::{
+    get_sqrt_ratio_at_tick, tick_math::saturating_i24, MAX_TICK, MAX_TICK_I32, MIN_TICK,
+    MIN_TICK_I32,
+};
+use crate::error::Error;
+use alloy_primitives::{
+    aliases::{I24, U24},
+    U160,
+};
+use core::fmt;
+
+/// A tick index between [`MIN_TICK`] and [`MAX_TICK`], inclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CheckedTick(I24);
+
+impl CheckedTick {
+    /// The minimum tick, [`MIN_TICK`].
+    pub const MIN: Self = Self(MIN_TICK);
+    /// The maximum tick, [`MAX_TICK`].
+    pub const MAX: Self = Self(MAX_TICK);
+    /// The tick at a price of 1.
+    pub const ZERO: Self = Self(I24::ZERO);
+
+    /// Validates a tick index.
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick`: The tick index
+    ///
+    /// ## Returns
+    ///
+    /// The tick, or [`Error::InvalidTick`] with the tick saturated to [`I24`] if it is out of range
+    #[inline]
+    pub fn new(tick: i32) -> Result<Self, Error> {
+        if (MIN_TICK_I32..=MAX_TICK_I32).contains(&tick) {
+            Ok(Self(saturating_i24(tick)))
+        } else {
+            Err(Error::InvalidTick(saturating_i24(tick)))
+        }
+    }
+
+    /// Validates a tick index of type [`I24`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick`: The tick index
+    #[inline]
+    pub fn from_i24(tick: I24) -> Result<Self, Error> {
+        if (MIN_TICK..=MAX_TICK).contains(&tick) {
+            Ok(Self(tick))
+        } else {
+            Err(Error::InvalidTick(tick))
+        }
+    }
+
+    /// The tick index as an [`I24`].
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> I24 {
+        self.0
+    }
+
+    /// The tick index as an `i32`.
+    #[inline]
+    #[must_use]
+    pub fn as_i32(self) -> i32 {
+        self.0.as_i32()
+    }
+
+    /// The sqrt ratio as a Q64.96 at the tick, which can't fail since the tick is in range.
+    #[inline]
+    #[must_use]
+    pub fn sqrt_ratio_x96(self) -> U160 {
+        get_sqrt_ratio_at_tick(self.0).expect("tick in range")
+    }
+}
+
+impl TryFrom<i32> for CheckedTick {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(tick: i32) -> Result<Self, Self::Error> {
+        Self::new(tick)
+    }
+}
+
+impl TryFrom<I24> for CheckedTick {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(tick: I24) -> Result<Self, Self::Error> {
+        Self::from_i24(tick)
+    }
+}
+
+impl From<CheckedTick> for I24 {
+    #[inline]
+    fn from(tick: CheckedTick) -> Self {
+        tick.0
+    }
+}
+
+impl From<CheckedTick> for i32 {
+    #[inline]
+    fn from(tick: CheckedTick) -> Self {
+        tick.as_i32()
+    }
+}
+
+impl fmt::Display for CheckedTick {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A pool fee in pips, i.e. hundredths of a bip or millionths, below 100% as required by the
+/// factory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeePips(U24);
+
+impl FeePips {
+    /// The maximum fee, just below 100%.
+    pub const MAX: Self = Self(U24::from_limbs([999_999]));
+
+    /// Validates a fee.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fee`: The fee in hundredths of bips
+    ///
+    /// ## Returns
+    ///
+    /// The fee, or [`Error::InvalidFee`] if it is not below 1,000,000
+    #[inline]
+    pub fn new(fee: u32) -> Result<Self, Error> {
+        if fee <= Self::MAX.as_u32() {
+            Ok(Self(U24::from_limbs([u64::from(fee)])))
+        } else {
+            Err(Error::InvalidFee(fee))
+        }
+    }
+
+    /// The fee as a [`U24`], ready for ABI encoding.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> U24 {
+        self.0
+    }
+
+    /// The fee as a `u32`.
+    #[inline]
+    #[must_use]
+    pub const fn as_u32(self) -> u32 {
+        self.0.as_limbs()[0] as u32
+    }
+}
+
+impl TryFrom<u32> for FeePips {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(fee: u32) -> Result<Self, Self::Error> {
+        Self::new(fee)
+    }
+}
+
+impl TryFrom<U24> for FeePips {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(fee: U24) -> Result<Self, Self::Error> {
+        Self::new(fee.as_limbs()[0] as u32)
+    }
+}
+
+impl From<FeePips> for U24 {
+    #[inline]
+    fn from(fee: FeePips) -> Self {
+        fee.0
+    }
+}
+
+impl From<FeePips> for u32 {
+    #[inline]
+    fn from(fee: FeePips) -> Self {
+        fee.as_u32()
+    }
+}
+
+impl fmt::Display for FeePips {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_u32(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::FeeAmount,
+        utils::{MAX_SQRT_RATIO, MAX_TICK_I32, MIN_TICK_I32},
+    };
+
+    #[test]
+    fn test_checked_tick() {
+        assert_eq!(CheckedTick::new(MIN_TICK_I32).unwrap(), CheckedTick::MIN);
+        assert_eq!(CheckedTick::new(MAX_TICK_I32).unwrap(), CheckedTick::MAX);
+        assert!(matches!(
+            CheckedTick::new(MAX_TICK_I32 + 1),
+            Err(Error::InvalidTick(_))
+        ));
+        assert!(matches!(
+            CheckedTick::new(i32::MIN),
+            Err(Error::InvalidTick(tick)) if tick == I24::MIN
+        ));
+        assert_eq!(CheckedTick::try_from(60).unwrap().as_i32(), 60);
+        assert_eq!(CheckedTick::MAX.sqrt_ratio_x96(), MAX_SQRT_RATIO);
+        assert_eq!(I24::from(CheckedTick::MIN), MIN_TICK);
+    }
+
+    #[test]
+    fn test_fee_pips() {
+        assert_eq!(FeePips::new(3000).unwrap().get(), U24::from(3000));
+        assert_eq!(FeePips::new(999_999).unwrap(), FeePips::MAX);
+        assert!(matches!(
+            FeePips::new(1_000_000),
+            Err(Error::InvalidFee(1_000_000))
+        ));
+        assert_eq!(FeePips::try_from(U24::from(500)).unwrap().as_u32(), 500);
+    }
+
+    #[test]
+    fn test_fee_amount_try_new() {
+        let one = I24::ONE;
+        assert_eq!(
+            FeeAmount::try_new(3000, I24::from_limbs([60])).unwrap(),
+            FeeAmount::MEDIUM
+        );
+        assert!(matches!(
+            FeeAmount::try_new(1_000_000, one),
+            Err(Error::InvalidFee(_))
+        ));
+        assert!(matches!(
+            FeeAmount::try_new(3000, I24::ZERO),
+            Err(Error::InvalidTickSpacing(_))
+        ));
+        assert!(matches!(
+            FeeAmount::try_new(3000, I24::from_limbs([16384])),
+            Err(Error::InvalidTickSpacing(_))
+        ));
+        assert_eq!(
+            FeeAmount::try_from(U24::from(3000)).unwrap(),
+            FeeAmount::MEDIUM
+        );
+        assert!(matches!(
+            FeeAmount::try_from(U24::from(2500)),
+            Err(Error::InvalidFee(2500))
+        ));
+        assert!(matches!(
+            FeeAmount::try_from(U24::MAX),
+            Err(Error::InvalidFee(_))
+        ));
+        let fee: U24 = FeeAmount::try_new(2500, one).unwrap().into();
+        assert_eq!(fee, U24::from(2500));
+    }
+
+    #[test]
+    fn test_fee_amount_eq() {
+        let medium = FeeAmount::Custom {
+            fee: FeePips::new(3000).unwrap(),
+            tick_spacing: I24::from_limbs([60]),
+        };
+        assert_eq!(medium, FeeAmount::MEDIUM);
+        assert_eq!(
+            FeeAmount::new(FeePips::new(3000).unwrap(), I24::from_limbs([60])),
+            FeeAmount::MEDIUM
+        );
+        assert_ne!(
+            FeeAmount::try_new(3000, I24::ONE).unwrap(),
+            FeeAmount::MEDIUM
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! ## Range Order
+//! A [`range_order`] utility for single-sided positions built with
+//! [`Position::from_amount0_above_current`] or [`Position::from_amount1_below_current`], which are
+//! filled like limit orders once the price crosses the whole range.
+
+use crate::prelude::{Error, *};
+use uniswap_sdk_core::prelude::*;
+
+/// The outcome of a range order when the price fully crosses its range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeOrder {
+    /// The amount of the token sold, which the position currently holds
+    pub amount_in: CurrencyAmount<Token>,
+    /// The amount of the token received once the range is fully crossed
+    pub amount_out: CurrencyAmount<Token>,
+    /// The average fill price in the token received per token sold, the geometric mean of the
+    /// prices at the tick boundaries
+    pub fill_price: Price<Token, Token>,
+}
+
+/// Computes the amounts and the fill price of a single-sided position when the price fully crosses
+/// its range, excluding the swap fees earned along the way.
+///
+/// ## Arguments
+///
+/// * `position`: A position entirely above or below the current tick
+///
+/// ## Returns
+///
+/// The range order, [`Error::InvalidRange`] if the position is in range or
+/// [`Error::ZeroLiquidity`] if it has no liquidity
+#[inline]
+pub fn range_order<TP: TickDataProvider>(position: &Position<TP>) -> Result<RangeOrder, Error> {
+    if position.liquidity == 0 {
+        return Err(Error::ZeroLiquidity);
+    }
+    let pool = &position.pool;
+    let sqrt_ratio_lower_x96 = get_sqrt_ratio_at_tick(position.tick_lower.to_i24())?;
+    let sqrt_ratio_upper_x96 = get_sqrt_ratio_at_tick(position.tick_upper.to_i24())?;
+    let amount0 = get_amount_0_delta(
+        sqrt_ratio_lower_x96,
+        sqrt_ratio_upper_x96,
+        position.liquidity,
+        false,
+    )?
+    .to_big_int();
+    let amount1 = get_amount_1_delta(
+        sqrt_ratio_lower_x96,
+        sqrt_ratio_upper_x96,
+        position.liquidity,
+        false,
+    )?
+    .to_big_int();
+    let (token_in, amount_in, token_out, amount_out) = if pool.tick_current < position.tick_lower {
+        (&pool.token0, amount0, &pool.token1, amount1)
+    } else if pool.tick_current >= position.tick_upper {
+        (&pool.token1, amount1, &pool.token0, amount0)
+    } else {
+        return Err(Error::InvalidRange);
+    };
+    Ok(RangeOrder {
+        fill_price: Price::new(
+            token_in.clone(),
+            token_out.clone(),
+            amount_in.clone(),
+            amount_out.clone(),
+        ),
+        amount_in: CurrencyAmount::from_raw_amount(token_in.clone(), amount_in)?,
+        amount_out: CurrencyAmount::from_raw_amount(token_out.clone(), amount_out)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::U256;
+
+    fn pool() -> Pool {
+        Pool::new(
+            TOKEN0.clone(),
+            TOKEN1.clone(),
+            FeeAmount::MEDIUM,
+            encode_sqrt_ratio_x96(1, 1),
+            0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sell_token0() {
+        let amount0 = U256::from(1_000_000_000_000_000_000_u128);
+        let position = Position::from_amount0_above_current(pool(), 60, 120, amount0).unwrap();
+        assert!(position.amount1().unwrap().quotient().is_zero());
+        let order = range_order(&position).unwrap();
+        assert!(order.amount_in.currency.equals(&*TOKEN0));
+        assert!(order.amount_out.currency.equals(&*TOKEN1));
+        // liquidity is rounded down, so at most the amount is sold
+        assert!(order.amount_in.quotient() <= amount0.to_big_int());
+        // filled at about 1.0001^90 token1 per token0
+        assert_eq!(order.fill_price.to_significant(4, None).unwrap(), "1.009");
+    }
+
+    #[test]
+    fn test_buy_token0() {
+        let amount1 = U256::from(1_000_000_000_000_000_000_u128);
+        let position = Position::from_amount1_below_current(pool(), -120, -60, amount1).unwrap();
+        assert!(position.amount0().unwrap().quotient().is_zero());
+        let order = range_order(&position).unwrap();
+        assert!(order.amount_in.currency.equals(&*TOKEN1));
+        assert_eq!(order.fill_price.to_significant(4, None).unwrap(), "1.009");
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        let amount = U256::from(1_000_000);
+        assert!(matches!(
+            Position::from_amount0_above_current(pool(), -60, 60, amount),
+            Err(Error::InvalidRange)
+        ));
+        assert!(matches!(
+            Position::from_amount1_below_current(pool(), -60, 60, amount),
+            Err(Error::InvalidRange)
+        ));
+        let position = Position::new(pool(), 1_000_000, -60, 60);
+        assert!(matches!(range_order(&position), Err(Error::InvalidRange)));
+    }
+}
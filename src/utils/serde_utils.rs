@@ -0,0 +1,188 @@
+//! Helpers for the `serde` feature, for use with `#[serde(with = "...")]` on fields of types the
+//! crate doesn't own. Big integers are (de)serialized as decimal strings, so that they survive JSON
+//! parsers limited to 64-bit floats.
+
+use alloc::string::String;
+use alloy_primitives::{Address, ChainId};
+use serde::{Deserialize, Serialize};
+
+/// (De)serializes an integer as a decimal string, e.g. a `u128` liquidity or a `U160` price.
+pub mod decimal {
+    use super::*;
+    use core::{fmt::Display, str::FromStr};
+    use serde::{de::Error, Deserializer, Serializer};
+
+    #[inline]
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    #[inline]
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr<Err: Display>,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenRepr {
+    chain_id: ChainId,
+    address: Address,
+    decimals: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+/// (De)serializes a [`Token`](uniswap_sdk_core::prelude::Token) as its chain id, address, decimals,
+/// symbol and name.
+pub mod token {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+    use uniswap_sdk_core::prelude::*;
+
+    #[inline]
+    pub fn serialize<S: Serializer>(token: &Token, serializer: S) -> Result<S::Ok, S::Error> {
+        TokenRepr {
+            chain_id: token.chain_id(),
+            address: token.address(),
+            decimals: token.decimals,
+            symbol: token.symbol.clone(),
+            name: token.name.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    #[inline]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Token, D::Error> {
+        let repr = TokenRepr::deserialize(deserializer)?;
+        if repr.chain_id == 0 {
+            return Err(Error::custom("chain id can't be zero"));
+        }
+        Ok(Token::new(
+            repr.chain_id,
+            repr.address,
+            repr.decimals,
+            repr.symbol,
+            repr.name,
+            None,
+            None,
+        ))
+    }
+}
+
+/// (De)serializes a [`Price`](uniswap_sdk_core::prelude::Price) of two tokens as its base and
+/// quote tokens and the numerator and denominator of the raw price as decimal strings.
+pub mod price {
+    use super::*;
+    use num_bigint::BigInt;
+    use num_traits::Zero;
+    use serde::{Deserializer, Serializer};
+    use uniswap_sdk_core::prelude::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct PriceRepr {
+        #[serde(with = "super::token")]
+        base_currency: Token,
+        #[serde(with = "super::token")]
+        quote_currency: Token,
+        #[serde(with = "super::decimal")]
+        numerator: BigInt,
+        #[serde(with = "super::decimal")]
+        denominator: BigInt,
+    }
+
+    #[inline]
+    pub fn serialize<S: Serializer>(
+        price: &Price<Token, Token>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        PriceRepr {
+            base_currency: price.base_currency.clone(),
+            quote_currency: price.quote_currency.clone(),
+            numerator: price.numerator.clone(),
+            denominator: price.denominator.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    #[inline]
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Price<Token, Token>, D::Error> {
+        let repr = PriceRepr::deserialize(deserializer)?;
+        if repr.denominator.is_zero() {
+            return Err(serde::de::Error::custom("zero denominator"));
+        }
+        Ok(Price::new(
+            repr.base_currency,
+            repr.quote_currency,
+            repr.denominator,
+            repr.numerator,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::*;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrapper {
+        #[serde(with = "super::token")]
+        token: Token,
+        #[serde(with = "super::price")]
+        price: Price<Token, Token>,
+        #[serde(with = "super::decimal")]
+        liquidity: u128,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let wrapper = Wrapper {
+            token: USDC.clone(),
+            price: Price::new(
+                WETH.clone(),
+                USDC.clone(),
+                1_000_000_000_000_000_000_u128,
+                2_000_000_000,
+            ),
+            liquidity: u128::MAX,
+        };
+        let json = serde_json::to_string(&wrapper).unwrap();
+        assert!(json.contains(&format!("\"liquidity\":\"{}\"", u128::MAX)));
+        assert!(json.contains("\"numerator\":\"2000000000\""));
+        let decoded: Wrapper = serde_json::from_str(&json).unwrap();
+        assert!(decoded.token.equals(&USDC.clone()));
+        assert_eq!(decoded.token.symbol, USDC.symbol);
+        assert!(decoded.price.base_currency.equals(&WETH.clone()));
+        assert_eq!(decoded.price.numerator, wrapper.price.numerator);
+        assert_eq!(decoded.price.denominator, wrapper.price.denominator);
+        assert_eq!(decoded.liquidity, u128::MAX);
+    }
+
+    #[test]
+    fn test_zero_chain_id() {
+        #[derive(serde::Deserialize)]
+        struct TokenWrapper {
+            #[serde(with = "super::token", rename = "token")]
+            _token: Token,
+        }
+
+        let json = serde_json::to_string(&serde_json::json!({
+            "token": { "chain_id": 0, "address": USDC.address(), "decimals": 6 }
+        }))
+        .unwrap();
+        assert!(serde_json::from_str::<TokenWrapper>(&json).is_err());
+    }
+}
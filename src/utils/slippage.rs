@@ -0,0 +1,141 @@
+//! ## Slippage
+//! A [`Slippage`] tolerance validated to be between 0 and 100%, with constructors from basis points
+//! and percents, and [`validate_slippage`] and [`validate_tax`] used by the calldata builders.
+
+use crate::error::Error;
+use uniswap_sdk_core::prelude::*;
+
+/// Validates a slippage tolerance, which must be between 0 and 100%, inclusive.
+///
+/// ## Arguments
+///
+/// * `slippage_tolerance`: The slippage tolerance
+///
+/// ## Returns
+///
+/// [`Error::InvalidSlippage`] if the tolerance is negative or above 100%
+#[inline]
+pub fn validate_slippage(slippage_tolerance: &Percent) -> Result<(), Error> {
+    if slippage_tolerance < &Percent::default() || slippage_tolerance > &Percent::new(1, 1) {
+        return Err(Error::InvalidSlippage);
+    }
+    Ok(())
+}
+
+/// Validates the transfer tax of a fee-on-transfer token, which must be between 0 and 100%,
+/// inclusive.
+///
+/// ## Arguments
+///
+/// * `tax`: The share of each transfer taken by the token
+///
+/// ## Returns
+///
+/// [`Error::InvalidTax`] if the tax is negative or above 100%
+#[inline]
+pub fn validate_tax(tax: &Percent) -> Result<(), Error> {
+    if tax < &Percent::default() || tax > &Percent::new(1, 1) {
+        return Err(Error::InvalidTax);
+    }
+    Ok(())
+}
+
+/// A slippage tolerance between 0 and 100%, inclusive, which converts into the [`Percent`] taken by
+/// the calldata builders.
+///
+/// ## Examples
+///
+/// ```
+/// use uniswap_sdk_core::prelude::*;
+/// use uniswap_v3_sdk::prelude::*;
+///
+/// let slippage = Slippage::bps(50).unwrap();
+/// assert_eq!(Percent::from(slippage), Percent::new(1, 200));
+/// assert!(Slippage::percent(101).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slippage(Percent);
+
+impl Slippage {
+    /// Creates a slippage tolerance from a [`Percent`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `percent`: The slippage tolerance
+    #[inline]
+    pub fn new(percent: Percent) -> Result<Self, Error> {
+        validate_slippage(&percent)?;
+        Ok(Self(percent))
+    }
+
+    /// Creates a slippage tolerance from basis points, e.g. 50 for 0.5%.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bps`: The slippage tolerance in basis points, at most 10,000
+    #[inline]
+    pub fn bps(bps: u32) -> Result<Self, Error> {
+        Self::new(Percent::new(bps, 10_000))
+    }
+
+    /// Creates a slippage tolerance from whole percents, e.g. 1 for 1%.
+    ///
+    /// ## Arguments
+    ///
+    /// * `percent`: The slippage tolerance in percents, at most 100
+    #[inline]
+    pub fn percent(percent: u32) -> Result<Self, Error> {
+        Self::new(Percent::new(percent, 100))
+    }
+
+    /// No slippage.
+    #[inline]
+    #[must_use]
+    pub fn zero() -> Self {
+        Self(Percent::default())
+    }
+
+    /// The slippage tolerance as a [`Percent`].
+    #[inline]
+    #[must_use]
+    pub const fn as_percent(&self) -> &Percent {
+        &self.0
+    }
+}
+
+impl TryFrom<Percent> for Slippage {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(percent: Percent) -> Result<Self, Self::Error> {
+        Self::new(percent)
+    }
+}
+
+impl From<Slippage> for Percent {
+    #[inline]
+    fn from(slippage: Slippage) -> Self {
+        slippage.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slippage() {
+        assert_eq!(
+            Slippage::bps(50).unwrap().as_percent(),
+            &Percent::new(5, 1000)
+        );
+        assert_eq!(Slippage::percent(1).unwrap(), Slippage::bps(100).unwrap());
+        assert_eq!(Slippage::bps(0).unwrap(), Slippage::zero());
+        assert!(Slippage::bps(10_000).is_ok());
+        assert!(matches!(Slippage::bps(10_001), Err(Error::InvalidSlippage)));
+        assert!(matches!(
+            Slippage::new(Percent::new(-1, 100)),
+            Err(Error::InvalidSlippage)
+        ));
+    }
+}
@@ -0,0 +1,321 @@
+//! ## Sqrt Price X96
+//! A [`SqrtPriceX96`] newtype for the Q64.96 square root prices of pools, with conversions to and
+//! from SDK Core [`Price`] prices, ticks and floating point numbers.
+
+use crate::prelude::{Error, *};
+use alloc::string::String;
+use alloy_primitives::{ruint::UintTryFrom, U160, U256};
+use core::fmt;
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use uniswap_sdk_core::{prelude::*, utils::sqrt::sqrt};
+
+/// 2^96 as a float, the scale of a Q64.96 number.
+const Q96_F64: f64 = 79228162514264337593543950336.0;
+
+/// A Q64.96 square root price between [`MIN_SQRT_RATIO`] and [`MAX_SQRT_RATIO`], inclusive, i.e.
+/// `sqrt(amount1 / amount0) * 2^96` of the raw amounts of the sorted tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SqrtPriceX96(U160);
+
+impl SqrtPriceX96 {
+    pub const MIN: Self = Self(MIN_SQRT_RATIO);
+    pub const MAX: Self = Self(MAX_SQRT_RATIO);
+    /// The price of 1, i.e. 2^96.
+    pub const ONE: Self = Self(U160::from_limbs([0, 1 << 32, 0]));
+
+    /// Validates a sqrt price.
+    ///
+    /// ## Arguments
+    ///
+    /// * `sqrt_price_x96`: The sqrt price as a Q64.96
+    ///
+    /// ## Returns
+    ///
+    /// The sqrt price, or [`Error::InvalidSqrtPrice`] if it is out of range
+    #[inline]
+    pub fn new(sqrt_price_x96: U160) -> Result<Self, Error> {
+        if (MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(&sqrt_price_x96) {
+            Ok(Self(sqrt_price_x96))
+        } else {
+            Err(Error::InvalidSqrtPrice(sqrt_price_x96))
+        }
+    }
+
+    /// The sqrt price as a [`U160`].
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> U160 {
+        self.0
+    }
+
+    /// Returns the sqrt price at a tick.
+    ///
+    /// ## Arguments
+    ///
+    /// * `tick`: The tick
+    #[inline]
+    #[must_use]
+    pub fn from_tick(tick: CheckedTick) -> Self {
+        Self(tick.sqrt_ratio_x96())
+    }
+
+    /// Returns the greatest tick whose sqrt price is less than or equal to this one.
+    ///
+    /// ## Returns
+    ///
+    /// The tick, or [`Error::InvalidSqrtPrice`] for [`SqrtPriceX96::MAX`], which is not below the
+    /// sqrt price of any tick
+    #[inline]
+    pub fn to_tick(self) -> Result<CheckedTick, Error> {
+        CheckedTick::from_i24(get_tick_at_sqrt_ratio(self.0)?)
+    }
+
+    /// Returns the sqrt price of a [`Price`] of two tokens, rounded down. The decimals of the tokens
+    /// are accounted for, since the price is a ratio of raw amounts.
+    ///
+    /// ## Arguments
+    ///
+    /// * `price`: The price of either token in terms of the other
+    #[inline]
+    pub fn from_price(price: &Price<Token, Token>) -> Result<Self, Error> {
+        let (amount1, amount0) = if price.base_currency.sorts_before(&price.quote_currency)? {
+            (&price.numerator, &price.denominator)
+        } else {
+            (&price.denominator, &price.numerator)
+        };
+        if amount0.is_zero() {
+            return Err(Error::InvalidPrice);
+        }
+        let sqrt_price_x96 = sqrt(&((amount1 << 192) / amount0))?;
+        if sqrt_price_x96.bits() > 160 {
+            return Err(Error::InvalidSqrtPrice(U160::MAX));
+        }
+        Self::new(U160::from_big_int(sqrt_price_x96))
+    }
+
+    /// Returns the price of `base_token` in terms of `quote_token` at this sqrt price.
+    ///
+    /// ## Arguments
+    ///
+    /// * `base_token`: The base token of the price
+    /// * `quote_token`: The quote token of the price
+    #[inline]
+    pub fn to_price(
+        self,
+        base_token: Token,
+        quote_token: Token,
+    ) -> Result<Price<Token, Token>, Error> {
+        let ratio_x192 = BigInt::from(self.0.to_big_uint().pow(2));
+        let q192 = Q192.to_big_int();
+        Ok(if base_token.sorts_before(&quote_token)? {
+            Price::new(base_token, quote_token, q192, ratio_x192)
+        } else {
+            Price::new(base_token, quote_token, ratio_x192, q192)
+        })
+    }
+
+    /// Formats the price of `base_token` in terms of `quote_token` with the given number of
+    /// significant digits, adjusted for the decimals of the tokens.
+    ///
+    /// ## Arguments
+    ///
+    /// * `base_token`: The base token of the price
+    /// * `quote_token`: The quote token of the price
+    /// * `significant_digits`: The number of significant digits
+    #[inline]
+    pub fn to_significant(
+        self,
+        base_token: Token,
+        quote_token: Token,
+        significant_digits: u8,
+    ) -> Result<String, Error> {
+        Ok(self
+            .to_price(base_token, quote_token)?
+            .to_significant(significant_digits, None)?)
+    }
+
+    /// The raw price of token0 in terms of token1 as a float, i.e. `(sqrt_price_x96 / 2^96)^2`,
+    /// not adjusted for decimals. This is lossy and meant for display and analytics only.
+    #[inline]
+    #[must_use]
+    pub fn to_f64(self) -> f64 {
+        let sqrt_price = self.0.to_big_uint().to_f64().unwrap() / Q96_F64;
+        sqrt_price * sqrt_price
+    }
+
+    /// The price of token0 in terms of token1 as a float, adjusted for the decimals of the tokens.
+    /// This is lossy and meant for display and analytics only.
+    ///
+    /// ## Arguments
+    ///
+    /// * `decimals0`: The decimals of token0
+    /// * `decimals1`: The decimals of token1
+    #[cfg(feature = "std")]
+    #[inline]
+    #[must_use]
+    pub fn to_adjusted_f64(self, decimals0: u8, decimals1: u8) -> f64 {
+        self.to_f64() * 10_f64.powi(i32::from(decimals0) - i32::from(decimals1))
+    }
+
+    /// Returns the sqrt price closest to a raw price of token0 in terms of token1, not adjusted for
+    /// decimals. This is lossy, since a float has 53 bits of precision.
+    ///
+    /// ## Arguments
+    ///
+    /// * `price`: The raw price of token0 in terms of token1
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn from_f64(price: f64) -> Result<Self, Error> {
+        use num_bigint::BigUint;
+        use num_traits::FromPrimitive;
+
+        if !price.is_finite() || price <= 0.0 {
+            return Err(Error::InvalidPrice);
+        }
+        let sqrt_price_x96 =
+            BigUint::from_f64(price.sqrt() * Q96_F64).ok_or(Error::InvalidPrice)?;
+        if sqrt_price_x96.bits() > 160 {
+            return Err(Error::InvalidSqrtPrice(U160::MAX));
+        }
+        Self::new(U160::from_big_uint(sqrt_price_x96))
+    }
+
+    /// Returns the sqrt price closest to a price of token0 in terms of token1 adjusted for the
+    /// decimals of the tokens, e.g. 3000 if token0 is WETH and token1 is USDC. This is lossy, since
+    /// a float has 53 bits of precision.
+    ///
+    /// ## Arguments
+    ///
+    /// * `price`: The price of token0 in terms of token1, adjusted for decimals
+    /// * `decimals0`: The decimals of token0
+    /// * `decimals1`: The decimals of token1
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn from_adjusted_f64(price: f64, decimals0: u8, decimals1: u8) -> Result<Self, Error> {
+        Self::from_f64(price * 10_f64.powi(i32::from(decimals1) - i32::from(decimals0)))
+    }
+}
+
+impl TryFrom<U160> for SqrtPriceX96 {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(sqrt_price_x96: U160) -> Result<Self, Self::Error> {
+        Self::new(sqrt_price_x96)
+    }
+}
+
+impl TryFrom<U256> for SqrtPriceX96 {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(sqrt_price_x96: U256) -> Result<Self, Self::Error> {
+        Self::new(
+            U160::uint_try_from(sqrt_price_x96).map_err(|_| Error::InvalidSqrtPrice(U160::MAX))?,
+        )
+    }
+}
+
+impl From<SqrtPriceX96> for U160 {
+    #[inline]
+    fn from(sqrt_price_x96: SqrtPriceX96) -> Self {
+        sqrt_price_x96.0
+    }
+}
+
+impl From<SqrtPriceX96> for U256 {
+    #[inline]
+    fn from(sqrt_price_x96: SqrtPriceX96) -> Self {
+        Self::from(sqrt_price_x96.0)
+    }
+}
+
+impl From<CheckedTick> for SqrtPriceX96 {
+    #[inline]
+    fn from(tick: CheckedTick) -> Self {
+        Self::from_tick(tick)
+    }
+}
+
+impl fmt::Display for SqrtPriceX96 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(
+            SqrtPriceX96::new(MIN_SQRT_RATIO).unwrap(),
+            SqrtPriceX96::MIN
+        );
+        assert!(SqrtPriceX96::new(MIN_SQRT_RATIO - U160::from(1)).is_err());
+        assert!(SqrtPriceX96::new(MAX_SQRT_RATIO + U160::from(1)).is_err());
+        assert!(SqrtPriceX96::try_from(U256::MAX).is_err());
+        let one: U256 = SqrtPriceX96::ONE.into();
+        assert_eq!(one, Q96);
+        assert_eq!(SqrtPriceX96::ONE.to_string(), Q96.to_string());
+    }
+
+    #[test]
+    fn test_tick_round_trip() {
+        for tick in [MIN_TICK_I32, -60, 0, 1, 887271] {
+            let tick = CheckedTick::new(tick).unwrap();
+            assert_eq!(SqrtPriceX96::from_tick(tick).to_tick().unwrap(), tick);
+        }
+        assert!(SqrtPriceX96::MAX.to_tick().is_err());
+    }
+
+    #[test]
+    fn test_price_round_trip() {
+        // 1 WETH = 2000 USDC, where USDC sorts before WETH
+        let price = Price::new(
+            WETH.clone(),
+            USDC.clone(),
+            1_000_000_000_000_000_000_u128,
+            2_000_000_000,
+        );
+        let sqrt_price = SqrtPriceX96::from_price(&price).unwrap();
+        assert_eq!(
+            SqrtPriceX96::from_price(&price.invert()).unwrap(),
+            sqrt_price
+        );
+        let significant = |base: &Token, quote: &Token| -> f64 {
+            sqrt_price
+                .to_significant(base.clone(), quote.clone(), 6)
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        assert!((significant(&WETH, &USDC) - 2000.0).abs() < 1e-6);
+        assert!((significant(&USDC, &WETH) - 0.0005).abs() < 1e-12);
+        let zero = Price::new(WETH.clone(), USDC.clone(), 1, 0);
+        assert!(SqrtPriceX96::from_price(&zero).is_err());
+    }
+
+    #[test]
+    fn test_f64() {
+        assert_eq!(SqrtPriceX96::ONE.to_f64(), 1.0);
+        let sqrt_price = SqrtPriceX96::from_tick(CheckedTick::new(10000).unwrap());
+        assert!((sqrt_price.to_f64() - 1.0001_f64.powi(10000)).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(SqrtPriceX96::from_f64(1.0).unwrap(), SqrtPriceX96::ONE);
+        assert!(SqrtPriceX96::from_f64(-1.0).is_err());
+        assert!(SqrtPriceX96::from_f64(f64::NAN).is_err());
+        assert!(SqrtPriceX96::from_f64(1e100).is_err());
+        // 2000 USDC per WETH, where WETH is token1
+        let sqrt_price = SqrtPriceX96::from_adjusted_f64(1.0 / 2000.0, 6, 18).unwrap();
+        assert!((sqrt_price.to_adjusted_f64(6, 18) * 2000.0 - 1.0).abs() < 1e-9);
+    }
+}
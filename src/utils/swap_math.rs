@@ -1,13 +1,16 @@
 use crate::prelude::*;
 use alloy_primitives::{aliases::U24, Uint, I256, U160, U256};
 
+/// The state of a swap simulated by [`v3_swap`], exposed publicly through [`SwapResult`].
 #[derive(Clone, Copy, Debug, Default)]
-pub struct SwapState<I = i32> {
-    pub amount_specified_remaining: I256,
-    pub amount_calculated: I256,
-    pub sqrt_price_x96: U160,
-    pub tick_current: I,
-    pub liquidity: u128,
+pub(crate) struct SwapState<I = i32> {
+    pub(crate) amount_specified_remaining: I256,
+    pub(crate) amount_calculated: I256,
+    pub(crate) sqrt_price_x96: U160,
+    pub(crate) tick_current: I,
+    pub(crate) liquidity: u128,
+    /// The total fees paid in the input token
+    pub(crate) fee_amount: U256,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -155,7 +158,7 @@ pub fn compute_swap_step<const BITS: usize, const LIMBS: usize>(
 
 #[inline]
 #[allow(clippy::too_many_arguments)]
-pub fn v3_swap<TP: TickDataProvider>(
+pub(crate) fn v3_swap<TP: TickDataProvider>(
     fee: U24,
     sqrt_price_x96: U160,
     tick_current: TP::Index,
@@ -166,23 +169,7 @@ pub fn v3_swap<TP: TickDataProvider>(
     amount_specified: I256,
     sqrt_price_limit_x96: Option<U160>,
 ) -> Result<SwapState<TP::Index>, Error> {
-    let sqrt_price_limit_x96 = sqrt_price_limit_x96.unwrap_or_else(|| {
-        if zero_for_one {
-            MIN_SQRT_RATIO + ONE
-        } else {
-            MAX_SQRT_RATIO - ONE
-        }
-    });
-
-    if zero_for_one {
-        assert!(sqrt_price_limit_x96 > MIN_SQRT_RATIO, "RATIO_MIN");
-        assert!(sqrt_price_limit_x96 < sqrt_price_x96, "RATIO_CURRENT");
-    } else {
-        assert!(sqrt_price_limit_x96 < MAX_SQRT_RATIO, "RATIO_MAX");
-        assert!(sqrt_price_limit_x96 > sqrt_price_x96, "RATIO_CURRENT");
-    }
-
-    let exact_input = amount_specified >= I256::ZERO;
+    let sqrt_price_limit_x96 = price_limit(sqrt_price_x96, zero_for_one, sqrt_price_limit_x96);
 
     // keep track of swap state
     let mut state = SwapState {
@@ -191,86 +178,205 @@ pub fn v3_swap<TP: TickDataProvider>(
         sqrt_price_x96,
         tick_current,
         liquidity,
+        fee_amount: U256::ZERO,
     };
 
     // start swap while loop
     while !state.amount_specified_remaining.is_zero()
         && state.sqrt_price_x96 != sqrt_price_limit_x96
     {
-        let mut step = StepComputations {
-            sqrt_price_start_x96: state.sqrt_price_x96,
-            ..Default::default()
-        };
-
         // because each iteration of the while loop rounds, we can't optimize this code
         // (relative to the smart contract) by simply traversing to the next available tick, we
         // instead need to exactly replicate
-        (step.tick_next, step.initialized) = tick_data_provider
-            .next_initialized_tick_within_one_word(
-                state.tick_current,
-                zero_for_one,
-                tick_spacing,
-            )?;
+        let (tick_next, initialized) = tick_data_provider.next_initialized_tick_within_one_word(
+            state.tick_current,
+            zero_for_one,
+            tick_spacing,
+        )?;
+        let step = swap_step(
+            &mut state,
+            tick_next,
+            initialized,
+            zero_for_one,
+            sqrt_price_limit_x96,
+            fee,
+        )?;
+        let liquidity_net = if state.sqrt_price_x96 == step.sqrt_price_next_x96 && step.initialized
+        {
+            Some(tick_data_provider.get_tick(step.tick_next)?.liquidity_net)
+        } else {
+            None
+        };
+        finish_step(&mut state, &step, liquidity_net, zero_for_one)?;
+    }
 
-        step.tick_next = TP::Index::from_i24(step.tick_next.to_i24().clamp(MIN_TICK, MAX_TICK));
-        step.sqrt_price_next_x96 = get_sqrt_ratio_at_tick(step.tick_next.to_i24())?;
+    Ok(state)
+}
 
-        (
-            state.sqrt_price_x96,
-            step.amount_in,
-            step.amount_out,
-            step.fee_amount,
-        ) = compute_swap_step(
-            state.sqrt_price_x96,
-            if zero_for_one {
-                step.sqrt_price_next_x96.max(sqrt_price_limit_x96)
-            } else {
-                step.sqrt_price_next_x96.min(sqrt_price_limit_x96)
-            },
-            state.liquidity,
-            state.amount_specified_remaining,
+/// Simulates a swap like [`v3_swap`] with an [`AsyncTickDataProvider`], which may fetch the ticks
+/// crossed by the swap on demand.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn v3_swap_async<TP: AsyncTickDataProvider>(
+    fee: U24,
+    sqrt_price_x96: U160,
+    tick_current: TP::Index,
+    liquidity: u128,
+    tick_spacing: TP::Index,
+    tick_data_provider: &mut TP,
+    zero_for_one: bool,
+    amount_specified: I256,
+    sqrt_price_limit_x96: Option<U160>,
+) -> Result<SwapState<TP::Index>, Error> {
+    let sqrt_price_limit_x96 = price_limit(sqrt_price_x96, zero_for_one, sqrt_price_limit_x96);
+
+    let mut state = SwapState {
+        amount_specified_remaining: amount_specified,
+        amount_calculated: I256::ZERO,
+        sqrt_price_x96,
+        tick_current,
+        liquidity,
+        fee_amount: U256::ZERO,
+    };
+
+    while !state.amount_specified_remaining.is_zero()
+        && state.sqrt_price_x96 != sqrt_price_limit_x96
+    {
+        let (tick_next, initialized) = tick_data_provider
+            .next_initialized_tick_within_one_word(state.tick_current, zero_for_one, tick_spacing)
+            .await?;
+        let step = swap_step(
+            &mut state,
+            tick_next,
+            initialized,
+            zero_for_one,
+            sqrt_price_limit_x96,
             fee,
         )?;
+        let liquidity_net = if state.sqrt_price_x96 == step.sqrt_price_next_x96 && step.initialized
+        {
+            Some(
+                tick_data_provider
+                    .get_tick(step.tick_next)
+                    .await?
+                    .liquidity_net,
+            )
+        } else {
+            None
+        };
+        finish_step(&mut state, &step, liquidity_net, zero_for_one)?;
+    }
+
+    Ok(state)
+}
 
-        if exact_input {
-            state.amount_specified_remaining = I256::from_raw(
-                state.amount_specified_remaining.into_raw() - step.amount_in - step.fee_amount,
-            );
-            state.amount_calculated =
-                I256::from_raw(state.amount_calculated.into_raw() - step.amount_out);
+/// Returns the price limit of a swap, defaulting to the min or max price, and checks it against
+/// the current price.
+fn price_limit(
+    sqrt_price_x96: U160,
+    zero_for_one: bool,
+    sqrt_price_limit_x96: Option<U160>,
+) -> U160 {
+    let sqrt_price_limit_x96 = sqrt_price_limit_x96.unwrap_or_else(|| {
+        if zero_for_one {
+            MIN_SQRT_RATIO + ONE
         } else {
-            state.amount_specified_remaining =
-                I256::from_raw(state.amount_specified_remaining.into_raw() + step.amount_out);
-            state.amount_calculated = I256::from_raw(
-                state.amount_calculated.into_raw() + step.amount_in + step.fee_amount,
-            );
+            MAX_SQRT_RATIO - ONE
         }
+    });
+
+    if zero_for_one {
+        assert!(sqrt_price_limit_x96 > MIN_SQRT_RATIO, "RATIO_MIN");
+        assert!(sqrt_price_limit_x96 < sqrt_price_x96, "RATIO_CURRENT");
+    } else {
+        assert!(sqrt_price_limit_x96 < MAX_SQRT_RATIO, "RATIO_MAX");
+        assert!(sqrt_price_limit_x96 > sqrt_price_x96, "RATIO_CURRENT");
+    }
+    sqrt_price_limit_x96
+}
+
+/// Swaps up to the next initialized tick within one word or the price limit, updating the
+/// amounts of the swap state.
+fn swap_step<I: TickIndex>(
+    state: &mut SwapState<I>,
+    tick_next: I,
+    initialized: bool,
+    zero_for_one: bool,
+    sqrt_price_limit_x96: U160,
+    fee: U24,
+) -> Result<StepComputations<I>, Error> {
+    let exact_input = state.amount_specified_remaining >= I256::ZERO;
+    let mut step = StepComputations {
+        sqrt_price_start_x96: state.sqrt_price_x96,
+        tick_next: I::from_i24(tick_next.to_i24().clamp(MIN_TICK, MAX_TICK)),
+        initialized,
+        ..Default::default()
+    };
+    step.sqrt_price_next_x96 = get_sqrt_ratio_at_tick(step.tick_next.to_i24())?;
+
+    (
+        state.sqrt_price_x96,
+        step.amount_in,
+        step.amount_out,
+        step.fee_amount,
+    ) = compute_swap_step(
+        state.sqrt_price_x96,
+        if zero_for_one {
+            step.sqrt_price_next_x96.max(sqrt_price_limit_x96)
+        } else {
+            step.sqrt_price_next_x96.min(sqrt_price_limit_x96)
+        },
+        state.liquidity,
+        state.amount_specified_remaining,
+        fee,
+    )?;
 
-        if state.sqrt_price_x96 == step.sqrt_price_next_x96 {
-            // if the tick is initialized, run the tick transition
-            if step.initialized {
-                let mut liquidity_net = tick_data_provider.get_tick(step.tick_next)?.liquidity_net;
-                // if we're moving leftward, we interpret liquidityNet as the opposite sign
-                // safe because liquidityNet cannot be type(int128).min
-                if zero_for_one {
-                    liquidity_net = -liquidity_net;
-                }
-                state.liquidity = add_delta(state.liquidity, liquidity_net)?;
+    state.fee_amount += step.fee_amount;
+
+    if exact_input {
+        state.amount_specified_remaining = I256::from_raw(
+            state.amount_specified_remaining.into_raw() - step.amount_in - step.fee_amount,
+        );
+        state.amount_calculated =
+            I256::from_raw(state.amount_calculated.into_raw() - step.amount_out);
+    } else {
+        state.amount_specified_remaining =
+            I256::from_raw(state.amount_specified_remaining.into_raw() + step.amount_out);
+        state.amount_calculated =
+            I256::from_raw(state.amount_calculated.into_raw() + step.amount_in + step.fee_amount);
+    }
+    Ok(step)
+}
+
+/// Updates the tick and liquidity of the swap state after a step, crossing the next tick with its
+/// `liquidity_net` if it was reached and is initialized.
+fn finish_step<I: TickIndex>(
+    state: &mut SwapState<I>,
+    step: &StepComputations<I>,
+    liquidity_net: Option<i128>,
+    zero_for_one: bool,
+) -> Result<(), Error> {
+    if state.sqrt_price_x96 == step.sqrt_price_next_x96 {
+        // if the tick is initialized, run the tick transition
+        if let Some(mut liquidity_net) = liquidity_net {
+            // if we're moving leftward, we interpret liquidityNet as the opposite sign
+            // safe because liquidityNet cannot be type(int128).min
+            if zero_for_one {
+                liquidity_net = -liquidity_net;
             }
-            state.tick_current = if zero_for_one {
-                step.tick_next - TP::Index::ONE
-            } else {
-                step.tick_next
-            };
-        } else if state.sqrt_price_x96 != step.sqrt_price_start_x96 {
-            // recompute unless we're on a lower tick boundary (i.e. already transitioned
-            // ticks), and haven't moved
-            state.tick_current =
-                TP::Index::from_i24(state.sqrt_price_x96.get_tick_at_sqrt_ratio()?);
+            state.liquidity = add_delta(state.liquidity, liquidity_net)?;
         }
+        state.tick_current = if zero_for_one {
+            step.tick_next - I::ONE
+        } else {
+            step.tick_next
+        };
+    } else if state.sqrt_price_x96 != step.sqrt_price_start_x96 {
+        // recompute unless we're on a lower tick boundary (i.e. already transitioned
+        // ticks), and haven't moved
+        state.tick_current = I::from_i24(state.sqrt_price_x96.get_tick_at_sqrt_ratio()?);
     }
-
-    Ok(state)
+    Ok(())
 }
 
 #[cfg(test)]
@@ -2,9 +2,9 @@
 //! This library is a Rust port of the [TickMath library](https://github.com/uniswap/v3-core/blob/main/contracts/libraries/TickMath.sol) in Solidity,
 //! with custom optimizations presented in [uni-v3-lib](https://github.com/Aperture-Finance/uni-v3-lib/blob/main/src/TickMath.sol).
 
-use super::most_significant_bit;
+use super::{most_significant_bit, CheckedTick};
 use crate::error::Error;
-use alloy_primitives::{aliases::I24, uint, Uint, U160, U256};
+use alloy_primitives::{aliases::I24, ruint::UintTryFrom, uint, Uint, U160, U256};
 use core::ops::{Shl, Shr, Sub};
 
 /// The maximum tick that can be passed to `get_sqrt_ratio_at_tick`.
@@ -55,7 +55,7 @@ impl<const BITS: usize, const LIMBS: usize> TickMath for Uint<BITS, LIMBS> {
 /// The sqrt ratio as a Q64.96
 #[inline]
 pub fn get_sqrt_ratio_at_tick(tick: I24) -> Result<U160, Error> {
-    let abs_tick = tick.abs().as_i32();
+    let abs_tick = tick.as_i32().abs();
 
     if abs_tick > MAX_TICK.as_i32() {
         return Err(Error::InvalidTick(tick));
@@ -279,6 +279,50 @@ pub fn get_tick_at_sqrt_ratio<const BITS: usize, const LIMBS: usize>(
     Ok(I24::try_from(tick).unwrap())
 }
 
+/// Returns the sqrt ratio as a Q64.96 for the given tick, like [`get_sqrt_ratio_at_tick`], but
+/// accepts any `i32` from untrusted input and never panics.
+///
+/// ## Arguments
+///
+/// * `tick`: the tick for which to compute the sqrt ratio
+///
+/// ## Returns
+///
+/// The sqrt ratio as a Q64.96, or [`Error::InvalidTick`] with the tick saturated to [`I24`] if it
+/// is not between [`MIN_TICK`] and [`MAX_TICK`]
+#[inline]
+pub fn try_get_sqrt_ratio_at_tick(tick: i32) -> Result<U160, Error> {
+    CheckedTick::new(tick).map(CheckedTick::sqrt_ratio_x96)
+}
+
+/// Converts an `i32` to [`I24`], saturating at the bounds of [`I24`]. The bounds are checked before
+/// converting since `I24::try_from` panics on some out of range values.
+#[inline]
+pub(crate) fn saturating_i24(value: i32) -> I24 {
+    I24::try_from(value.clamp(-(1 << 23), (1 << 23) - 1)).unwrap()
+}
+
+/// Returns the tick corresponding to a given sqrt ratio, like [`get_tick_at_sqrt_ratio`], but
+/// accepts any [`Uint`] from untrusted input and never panics.
+///
+/// ## Arguments
+///
+/// * `sqrt_ratio_x96`: the sqrt ratio as a Q64.96 for which to compute the tick
+///
+/// ## Returns
+///
+/// The tick corresponding to the given sqrt ratio, or [`Error::InvalidSqrtPrice`] with the sqrt
+/// ratio saturated to [`U160`] if it is not between [`MIN_SQRT_RATIO`] and [`MAX_SQRT_RATIO`]
+#[inline]
+pub fn try_get_tick_at_sqrt_ratio<const BITS: usize, const LIMBS: usize>(
+    sqrt_ratio_x96: Uint<BITS, LIMBS>,
+) -> Result<I24, Error> {
+    let Ok(sqrt_ratio_x96) = U160::uint_try_from(sqrt_ratio_x96) else {
+        return Err(Error::InvalidSqrtPrice(U160::MAX));
+    };
+    get_tick_at_sqrt_ratio(sqrt_ratio_x96)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -335,4 +379,52 @@ mod tests {
             MAX_TICK - I24::ONE
         );
     }
+
+    #[test]
+    fn test_try_get_sqrt_ratio_at_tick() {
+        assert_eq!(
+            try_get_sqrt_ratio_at_tick(MIN_TICK_I32).unwrap(),
+            MIN_SQRT_RATIO
+        );
+        assert_eq!(
+            try_get_sqrt_ratio_at_tick(MAX_TICK_I32).unwrap(),
+            MAX_SQRT_RATIO
+        );
+        assert!(matches!(
+            try_get_sqrt_ratio_at_tick(MAX_TICK_I32 + 1),
+            Err(Error::InvalidTick(tick)) if tick == MAX_TICK + I24::ONE
+        ));
+        assert!(matches!(
+            try_get_sqrt_ratio_at_tick(i32::MIN),
+            Err(Error::InvalidTick(tick)) if tick == I24::MIN
+        ));
+        assert!(matches!(
+            try_get_sqrt_ratio_at_tick(i32::MAX),
+            Err(Error::InvalidTick(tick)) if tick == I24::MAX
+        ));
+        assert!(matches!(
+            get_sqrt_ratio_at_tick(I24::MIN),
+            Err(Error::InvalidTick(tick)) if tick == I24::MIN
+        ));
+    }
+
+    #[test]
+    fn test_try_get_tick_at_sqrt_ratio() {
+        assert_eq!(
+            try_get_tick_at_sqrt_ratio(U256::from(MIN_SQRT_RATIO)).unwrap(),
+            MIN_TICK
+        );
+        assert!(matches!(
+            try_get_tick_at_sqrt_ratio(U256::from(MAX_SQRT_RATIO)),
+            Err(Error::InvalidSqrtPrice(price)) if price == MAX_SQRT_RATIO
+        ));
+        assert!(matches!(
+            try_get_tick_at_sqrt_ratio(U256::MAX),
+            Err(Error::InvalidSqrtPrice(price)) if price == U160::MAX
+        ));
+        assert!(matches!(
+            try_get_tick_at_sqrt_ratio(U256::ZERO),
+            Err(Error::InvalidSqrtPrice(price)) if price.is_zero()
+        ));
+    }
 }
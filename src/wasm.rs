@@ -0,0 +1,20 @@
+//! Conversions of the errors of the SDK into JavaScript errors, so that functions exported with
+//! `wasm-bindgen` can return them with `?`.
+
+use crate::error::{Error, TickListError};
+use alloc::string::ToString;
+use wasm_bindgen::{JsError, JsValue};
+
+impl From<Error> for JsValue {
+    #[inline]
+    fn from(error: Error) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<TickListError> for JsValue {
+    #[inline]
+    fn from(error: TickListError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
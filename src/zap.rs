@@ -0,0 +1,211 @@
+//! ## Zap
+//! Computes the swap through a pool that converts arbitrary token balances into the ratio required
+//! to add liquidity to a range of the same pool.
+
+use crate::prelude::{Error, *};
+use alloy_primitives::{U160, U256};
+use uniswap_sdk_core::prelude::*;
+
+/// The swap that converts token balances into the ratio of a range, see
+/// [`get_optimal_swap_amount`].
+#[derive(Clone, Debug)]
+pub struct OptimalSwap<TP: TickDataProvider> {
+    /// Whether token0 is swapped for token1
+    pub zero_for_one: bool,
+    /// The amount of the input token to swap
+    pub amount_in: U256,
+    /// The amount of the output token received
+    pub amount_out: U256,
+    /// The pool with state updated after the swap
+    pub pool: Pool<TP>,
+}
+
+impl<TP: TickDataProvider> OptimalSwap<TP> {
+    /// Returns the balances of token0 and token1 after the swap.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount0`: The balance of token0 before the swap
+    /// * `amount1`: The balance of token1 before the swap
+    #[inline]
+    #[must_use]
+    pub fn balances_after(&self, amount0: U256, amount1: U256) -> (U256, U256) {
+        if self.zero_for_one {
+            (amount0 - self.amount_in, amount1 + self.amount_out)
+        } else {
+            (amount0 + self.amount_out, amount1 - self.amount_in)
+        }
+    }
+}
+
+/// Computes how much of one token to swap through `pool` so that the remaining balances fit the
+/// ratio of the range `[tick_lower, tick_upper)` at the price after the swap, accounting for the
+/// price movement and fee of the swap itself.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool with tick data to swap through and add liquidity to
+/// * `tick_lower`: The lower tick of the range
+/// * `tick_upper`: The upper tick of the range
+/// * `amount0_desired`: The balance of token0 to add
+/// * `amount1_desired`: The balance of token1 to add
+///
+/// ## Returns
+///
+/// The largest swap that doesn't overshoot the ratio of the range, and the pool after the swap, or
+/// [`Error::InvalidRange`] if `tick_lower` is not below `tick_upper`.
+#[inline]
+pub fn get_optimal_swap_amount<TP: Clone + TickDataProvider>(
+    pool: &Pool<TP>,
+    tick_lower: TP::Index,
+    tick_upper: TP::Index,
+    amount0_desired: U256,
+    amount1_desired: U256,
+) -> Result<OptimalSwap<TP>, Error> {
+    if tick_lower >= tick_upper {
+        return Err(Error::InvalidRange);
+    }
+    let sqrt_ratio_lower_x96 = get_sqrt_ratio_at_tick(tick_lower.to_i24())?;
+    let sqrt_ratio_upper_x96 = get_sqrt_ratio_at_tick(tick_upper.to_i24())?;
+    // The token amounts of a fixed liquidity in the range at a price.
+    let unit_amounts = |sqrt_ratio_x96: U160| -> Result<(BigInt, BigInt), Error> {
+        let sqrt_ratio_x96 = sqrt_ratio_x96.clamp(sqrt_ratio_lower_x96, sqrt_ratio_upper_x96);
+        let liquidity = 1_u128 << 96;
+        Ok((
+            get_amount_0_delta(sqrt_ratio_x96, sqrt_ratio_upper_x96, liquidity, false)?
+                .to_big_int(),
+            get_amount_1_delta(sqrt_ratio_lower_x96, sqrt_ratio_x96, liquidity, false)?
+                .to_big_int(),
+        ))
+    };
+    let (unit0, unit1) = unit_amounts(pool.sqrt_ratio_x96)?;
+    // Swap token0 if there is more token0 than the range requires at the current price.
+    let zero_for_one =
+        amount0_desired.to_big_int() * &unit1 > amount1_desired.to_big_int() * &unit0;
+    let (token_in, balance_in) = if zero_for_one {
+        (&pool.token0, amount0_desired)
+    } else {
+        (&pool.token1, amount1_desired)
+    };
+
+    // Simulates swapping `amount_in`, returning `None` if it overshoots the ratio of the range or
+    // exceeds the liquidity of the pool.
+    let simulate = |amount_in: U256| -> Result<Option<(U256, Pool<TP>)>, Error> {
+        let (amount_out, pool_after) = match pool.get_output_amount(
+            &CurrencyAmount::from_raw_amount(token_in.clone(), amount_in.to_big_int())?,
+            None,
+        ) {
+            Ok((amount_out, pool_after)) => (U256::from_big_int(amount_out.quotient()), pool_after),
+            Err(Error::InsufficientLiquidity) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let (unit0, unit1) = unit_amounts(pool_after.sqrt_ratio_x96)?;
+        let (amount0, amount1) = if zero_for_one {
+            (amount0_desired - amount_in, amount1_desired + amount_out)
+        } else {
+            (amount0_desired + amount_out, amount1_desired - amount_in)
+        };
+        let excess0 = amount0.to_big_int() * &unit1 - amount1.to_big_int() * &unit0;
+        let overshoots = if zero_for_one {
+            excess0 < BigInt::zero()
+        } else {
+            excess0 > BigInt::zero()
+        };
+        Ok((!overshoots).then_some((amount_out, pool_after)))
+    };
+
+    // Binary search for the largest amount that doesn't overshoot, as the excess of the input
+    // token decreases monotonically with the amount swapped.
+    let mut amount_in = U256::ZERO;
+    let mut result = (U256::ZERO, pool.clone());
+    let mut high = balance_in;
+    while amount_in < high {
+        let mid = amount_in + (high - amount_in + U256::from(1)) / U256::from(2);
+        match simulate(mid)? {
+            Some(swap) => {
+                amount_in = mid;
+                result = swap;
+            }
+            None => high = mid - U256::from(1),
+        }
+    }
+    Ok(OptimalSwap {
+        zero_for_one,
+        amount_in,
+        amount_out: result.0,
+        pool: result.1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    /// Asserts that the balances after the swap fit the range up to the rounding of a single unit
+    /// swapped.
+    fn assert_fits(swap: &OptimalSwap<TickListDataProvider>, amount0: U256, amount1: U256) {
+        let (amount0, amount1) = swap.balances_after(amount0, amount1);
+        let position =
+            Position::from_amounts(swap.pool.clone(), -600, 600, amount0, amount1, true).unwrap();
+        let used0 = U256::from_big_int(position.amount0().unwrap().quotient());
+        let used1 = U256::from_big_int(position.amount1().unwrap().quotient());
+        assert!(amount0 - used0 <= U256::from(3));
+        assert!(amount1 - used1 <= U256::from(3));
+    }
+
+    #[test]
+    fn test_get_optimal_swap_amount_zero_for_one() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let amount0 = U256::from(10000);
+        let swap = get_optimal_swap_amount(&pool, -600, 600, amount0, U256::ZERO).unwrap();
+        assert!(swap.zero_for_one);
+        // less than half is swapped as the price moves down, where the range requires more token0
+        assert!(swap.amount_in > U256::from(4000) && swap.amount_in < U256::from(5000));
+        assert_fits(&swap, amount0, U256::ZERO);
+    }
+
+    #[test]
+    fn test_get_optimal_swap_amount_one_for_zero() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let (amount0, amount1) = (U256::from(1000), U256::from(9000));
+        let swap = get_optimal_swap_amount(&pool, -600, 600, amount0, amount1).unwrap();
+        assert!(!swap.zero_for_one);
+        assert!(swap.amount_in > U256::ZERO && swap.amount_in < U256::from(4000));
+        assert_fits(&swap, amount0, amount1);
+    }
+
+    #[test]
+    fn test_get_optimal_swap_amount_out_of_range() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        // only token0 is required above the current price
+        let swap = get_optimal_swap_amount(&pool, 600, 1200, U256::ZERO, U256::from(1000)).unwrap();
+        assert!(!swap.zero_for_one);
+        assert_eq!(swap.amount_in, U256::from(1000));
+        // only token1 is required below the current price
+        let swap =
+            get_optimal_swap_amount(&pool, -1200, -600, U256::from(1000), U256::ZERO).unwrap();
+        assert!(swap.zero_for_one);
+        assert_eq!(swap.amount_in, U256::from(1000));
+    }
+
+    #[test]
+    fn test_get_optimal_swap_amount_already_balanced() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        let swap =
+            get_optimal_swap_amount(&pool, -600, 600, U256::from(1000), U256::from(1000)).unwrap();
+        assert_eq!(swap.amount_in, U256::ZERO);
+        assert_eq!(swap.amount_out, U256::ZERO);
+    }
+
+    #[test]
+    fn test_get_optimal_swap_amount_invalid_range() {
+        let pool = make_pool(TOKEN0.clone(), TOKEN1.clone());
+        for (tick_lower, tick_upper) in [(600, -600), (600, 600)] {
+            assert!(matches!(
+                get_optimal_swap_amount(&pool, tick_lower, tick_upper, U256::ZERO, U256::ZERO),
+                Err(Error::InvalidRange)
+            ));
+        }
+    }
+}